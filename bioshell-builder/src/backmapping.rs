@@ -0,0 +1,209 @@
+//! Restores backbone and CB atoms from a coarse-grained CA trace.
+//!
+//! This is not a full-atom back-mapping: side chains beyond the CB atom and hydrogens are not rebuilt.
+//!
+//! Positions of N, C and CB atoms of every residue are taken from a table of their average coordinates,
+//! expressed in the local system of the alpha carbon. That system is defined by the three consecutive CA atoms:
+//! the x axis is the bisector of the CA(i-1)-CA(i)-CA(i+1) angle, the z axis is parallel to CA(i+1)-CA(i-1) and
+//! y = z × x. The positions depend on the planar CA(i-1)-CA(i)-CA(i+1) angle, which is tabulated in 10 degree bins
+//! in the `bioshell-builder/data/backmapping/ca_trace.cif` file. The local frame follows the method of
+//! Milik, Kolinski & Skolnick (J. Comput. Chem. 18:80-85, 1997). Finally, the carbonyl oxygen of each residue
+//! is placed in the peptide plane, defined by the C atom of that residue and the N atom of the next one.
+//!
+//! ```
+//! # use bioshell_builder::BuilderError;
+//! # fn main() -> Result<(), BuilderError> {
+//! use bioshell_pdb::{Deposit, Structure};
+//! use bioshell_builder::backmapping::CaTraceBackmapping;
+//! let strctr = Deposit::from_file("../bioshell-pdb/tests/test_files/2gb1.cif").unwrap().structure().unwrap();
+//! // --- reduce the structure to its CA trace ...
+//! let ca_trace = Structure::from_iterator("2gb1", strctr.atoms().iter().filter(|a| a.name == " CA ").cloned());
+//! // --- and rebuild the backbone
+//! let backmapping = CaTraceBackmapping::load()?;
+//! let backbone = backmapping.rebuild_backbone(&ca_trace);
+//! assert_eq!(backbone.atoms().iter().filter(|a| a.name == " O  ").count(), 56);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only protein CA traces may be back-mapped; the other coarse-grained models provided by
+//! the `bioshell_pdb::coarse_grained` module are not supported.
+
+use bioshell_cif::{read_cif_file, CifTable};
+use bioshell_core::io::find_bioshell_path;
+use bioshell_core::{planar_angle3, Vec3};
+use bioshell_pdb::{PdbAtom, Structure};
+use crate::nerf::restore_atom;
+use crate::BuilderError;
+
+/// CA atoms more distant than that are not considered to be bonded
+const MAX_CA_CA_DISTANCE: f64 = 4.2;
+/// Length of the C=O bond
+const C_O_BOND: f64 = 1.231;
+/// Planar CA-C=O angle in degrees
+const CA_C_O_ANGLE: f64 = 121.0;
+
+/// Average local positions of backbone atoms for a single bin of CA(i-1)-CA(i)-CA(i+1) angle
+#[derive(Clone, Debug)]
+struct LocalPositions {
+    angle_min: f64,
+    n: Vec3,
+    c: Vec3,
+    cb: Vec3,
+}
+
+/// Rebuilds N, C, O and CB atoms of a protein from positions of its alpha carbons.
+///
+/// See the [module documentation](crate::backmapping) for the method and an example.
+#[derive(Clone, Debug)]
+pub struct CaTraceBackmapping {
+    bins: Vec<LocalPositions>,
+}
+
+impl CaTraceBackmapping {
+
+    /// Loads the back-mapping parameters distributed with BioShell
+    pub fn load() -> Result<CaTraceBackmapping, BuilderError> {
+        let fname = "bioshell-builder/data/backmapping/ca_trace.cif";
+        let path = find_bioshell_path().map(|p| p.join(fname))
+            .ok_or(BuilderError::MissingBioShellFile { fname: fname.to_string() })?;
+        return CaTraceBackmapping::from_cif_file(path.to_str().unwrap());
+    }
+
+    /// Loads the back-mapping parameters from a `_ca_trace_atom` table of a CIF file
+    pub fn from_cif_file(fname: &str) -> Result<CaTraceBackmapping, BuilderError> {
+        let mut bins: Vec<LocalPositions> = vec![];
+        for block in read_cif_file(fname)? {
+            let table = CifTable::new(&block, "_ca_trace_atom", ["angle_min", "atom_name", "x", "y", "z"])?;
+            for [angle, name, x, y, z] in table.iter() {
+                let parse = |s: &str| s.parse::<f64>().map_err(|_| BuilderError::InternalAtomDefinitionError {
+                    error: format!("can't parse a real value: {}", s) });
+                let angle_min = parse(angle)?;
+                let v = Vec3::new(parse(x)?, parse(y)?, parse(z)?);
+                let idx = match bins.iter().position(|b| b.angle_min == angle_min) {
+                    Some(i) => i,
+                    None => {
+                        let zero = Vec3::from_float(0.0);
+                        bins.push(LocalPositions { angle_min, n: zero, c: zero, cb: zero });
+                        bins.len() - 1
+                    }
+                };
+                match name {
+                    "N" => bins[idx].n = v,
+                    "C" => bins[idx].c = v,
+                    "CB" => bins[idx].cb = v,
+                    _ => return Err(BuilderError::InternalAtomDefinitionError {
+                        error: format!("unexpected atom name in a CA-trace back-mapping table: {}", name) }),
+                }
+            }
+        }
+        bins.sort_by(|a, b| a.angle_min.total_cmp(&b.angle_min));
+
+        return Ok(CaTraceBackmapping { bins });
+    }
+
+    /// Rebuilds backbone and CB atoms for every CA atom of a given structure.
+    ///
+    /// Other atoms of the input structure are ignored. A chain is split into separate fragments wherever
+    /// the distance between consecutive CA atoms is larger than 4.2 Å; the missing neighbors of the terminal
+    /// residues of each fragment are extrapolated from the trace. Fragments shorter than three residues
+    /// can't be rebuilt and are copied to the output as they are. No CB atom is created for glycine.
+    pub fn rebuild_backbone(&self, ca_trace: &Structure) -> Structure {
+        let mut atoms: Vec<PdbAtom> = vec![];
+        for fragment in ca_fragments(ca_trace) {
+            if fragment.len() < 3 {
+                atoms.extend(fragment.iter().map(|&a| a.clone()));
+                continue;
+            }
+            self.rebuild_fragment(&fragment, &mut atoms);
+        }
+        for (i, a) in atoms.iter_mut().enumerate() { a.serial = i as i32 + 1; }
+
+        return Structure::from_iterator(&ca_trace.id_code, atoms);
+    }
+
+    fn rebuild_fragment(&self, fragment: &[&PdbAtom], out: &mut Vec<PdbAtom>) {
+        let n = fragment.len();
+        // --- the trace extended by one virtual CA before and two after the fragment
+        let mut ext: Vec<Vec3> = Vec::with_capacity(n + 3);
+        ext.push(extrapolate(&fragment[0].pos, &fragment[1].pos, &fragment[2].pos));
+        ext.extend(fragment.iter().map(|a| a.pos));
+        ext.push(extrapolate(&ext[n], &ext[n - 1], &ext[n - 2]));
+        ext.push(extrapolate(&ext[n + 1], &ext[n], &ext[n - 1]));
+
+        // --- N, C and CB for every real residue and N for the virtual one that follows the fragment
+        let mut local: Vec<[Vec3; 3]> = Vec::with_capacity(n + 1);
+        for i in 1..=n + 1 {
+            let bin = self.bin(planar_angle3(&ext[i - 1], &ext[i], &ext[i + 1]).to_degrees());
+            let (x, y, z) = local_frame(&ext[i - 1], &ext[i], &ext[i + 1]);
+            let place = |v: &Vec3| {
+                let mut p = ext[i];
+                for (axis, coord) in [(x, v.x), (y, v.y), (z, v.z)] {
+                    let mut a = axis;
+                    a *= coord;
+                    p += &a;
+                }
+                p
+            };
+            local.push([place(&bin.n), place(&bin.c), place(&bin.cb)]);
+        }
+
+        for (i, ca) in fragment.iter().enumerate() {
+            let [n_pos, c_pos, cb_pos] = local[i];
+            let mut o_pos = Vec3::from_float(0.0);
+            restore_atom(&local[i + 1][0], &ca.pos, &c_pos, C_O_BOND, CA_C_O_ANGLE.to_radians(),
+                         180.0_f64.to_radians(), &mut o_pos);
+            out.push(atom_like(ca, " N  ", "N", n_pos));
+            out.push((*ca).clone());
+            out.push(atom_like(ca, " C  ", "C", c_pos));
+            out.push(atom_like(ca, " O  ", "O", o_pos));
+            if ca.res_name != "GLY" { out.push(atom_like(ca, " CB ", "C", cb_pos)); }
+        }
+    }
+
+    /// Local positions for the bin a given angle (in degrees) falls into; angles outside the tabulated range
+    /// are assigned to the first or the last bin
+    fn bin(&self, angle: f64) -> &LocalPositions {
+        return self.bins.iter().rev().find(|b| b.angle_min <= angle).unwrap_or(&self.bins[0]);
+    }
+}
+
+/// Splits CA atoms of a structure into fragments of bonded residues
+fn ca_fragments(strctr: &Structure) -> Vec<Vec<&PdbAtom>> {
+    let mut fragments: Vec<Vec<&PdbAtom>> = vec![];
+    for a in strctr.atoms().iter().filter(|a| a.name == " CA ") {
+        match fragments.last_mut() {
+            Some(last) if last.last().is_some_and(|p| p.chain_id == a.chain_id
+                && p.pos.distance_to(&a.pos) <= MAX_CA_CA_DISTANCE) => last.push(a),
+            _ => fragments.push(vec![a]),
+        }
+    }
+
+    return fragments;
+}
+
+/// Virtual atom that continues a chain beyond `end`: `end + (next - next_next)`
+fn extrapolate(end: &Vec3, next: &Vec3, next_next: &Vec3) -> Vec3 {
+    let mut v = Vec3::sub_s(next, next_next);
+    v += end;
+    return v;
+}
+
+/// Local coordinate system of the CA atom `b` defined by its neighbors `a` and `c`
+fn local_frame(a: &Vec3, b: &Vec3, c: &Vec3) -> (Vec3, Vec3, Vec3) {
+    let u1 = Vec3::sub_s(a, b).normalized();
+    let u2 = Vec3::sub_s(c, b).normalized();
+    let x = Vec3::add_s(&u1, &u2).normalized();
+    let z = Vec3::sub_s(&u2, &u1).normalized();
+    let y = Vec3::cross(&z, &x);
+
+    return (x, y, z);
+}
+
+fn atom_like(ca: &PdbAtom, name: &str, element: &str, pos: Vec3) -> PdbAtom {
+    let mut a = ca.clone();
+    a.name = name.to_string();
+    a.element = Some(element.to_string());
+    a.pos = pos;
+    return a;
+}
@@ -23,6 +23,10 @@ pub enum BuilderError {
     /// Make sure its name is properly spelled, and it has actually been defined in the respective InternalAtomDefinition entry
     DihedralAngleNotFound {residue_index: usize, dihedral_name: String},
 
+    #[error("Can't find a file distributed with BioShell: {fname}")]
+    /// Can't find a data file that should be provided by the BioShell package
+    MissingBioShellFile {fname: String},
+
     #[error("Error occurred while parsing a CIF file with topology parameters")]
     /// I/O error occurred while reading a sequence file
     ParsingError(#[from] CifError),
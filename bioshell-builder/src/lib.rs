@@ -14,6 +14,7 @@
 
 #![allow(clippy::needless_return)]
 pub mod nerf;
+pub mod backmapping;
 
 mod internal_coordinates_definitions;
 mod kinematic_atom_tree;
@@ -0,0 +1,27 @@
+use bioshell_pdb::{Deposit, PDBError, Structure};
+use bioshell_builder::backmapping::CaTraceBackmapping;
+
+#[test]
+fn rebuild_backbone() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("../bioshell-pdb/tests/test_files/2gb1.cif")?.structure()?;
+    let ca_trace = Structure::from_iterator("2gb1", strctr.atoms().iter().filter(|a| a.name == " CA ").cloned());
+    let backmapping = CaTraceBackmapping::load().unwrap();
+    let rebuilt = backmapping.rebuild_backbone(&ca_trace);
+
+    // --- carbonyl oxygens are the least accurate: orientation of a peptide plane is ambiguous in loops
+    for (name, max_rms) in [(" N  ", 0.5), (" C  ", 0.5), (" O  ", 1.2), (" CB ", 0.5)] {
+        let mut sum = 0.0;
+        let mut n = 0;
+        for a in rebuilt.atoms().iter().filter(|a| a.name == name) {
+            let native = strctr.atoms().iter()
+                .find(|b| b.name == name && b.res_seq == a.res_seq && b.chain_id == a.chain_id).unwrap();
+            sum += a.pos.distance_square_to(&native.pos);
+            n += 1;
+        }
+        let rms = (sum / n as f64).sqrt();
+        assert!(rms < max_rms, "rmsd on {} atoms too large: {}", name, rms);
+    }
+    let n_gly = ca_trace.atoms().iter().filter(|a| a.res_name == "GLY").count();
+    assert_eq!(rebuilt.count_atoms(), 56 * 5 - n_gly);
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use std::fmt::{Display, Formatter};
+use bioshell_core::Vec3;
+use bioshell_pdb::{PdbAtom, ResidueId, Structure, RESIDUE_RINGS};
+use bioshell_pdb::pdb_atom_filters::{IsAromatic, PdbAtomPredicate};
+
+/// Maximum distance between centers of two stacked aromatic rings (in Angstroms)
+pub const PI_STACKING_MAX_DISTANCE: f64 = 5.5;
+
+/// Maximum angle between normals of two rings stacked in parallel (in degrees)
+pub const PI_STACKING_PARALLEL_ANGLE: f64 = 30.0;
+
+/// Minimum angle between normals of two rings stacked in a T-shaped geometry (in degrees)
+pub const PI_STACKING_T_SHAPED_ANGLE: f64 = 60.0;
+
+/// Maximum distance between a cation and the center of an aromatic ring (in Angstroms)
+pub const CATION_PI_MAX_DISTANCE: f64 = 6.0;
+
+/// Maximum offset of a ring center or a cation from the axis of an aromatic ring (in Angstroms)
+pub const MAX_RING_OFFSET: f64 = 2.0;
+
+/// Cationic groups of standard amino acids, represented by a single atom
+const CATIONIC_GROUPS: [(&str, &str); 2] = [("LYS", " NZ "), ("ARG", " CZ ")];
+
+/// An aromatic ring of a residue, described by its geometric center and its normal vector
+#[derive(Clone, Debug)]
+pub struct AromaticRing {
+    /// residue this ring belongs to
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    /// the number of atoms in this ring: 5 or 6
+    pub size: usize,
+    /// geometric center of this ring
+    pub center: Vec3,
+    /// unit vector normal to the ring plane
+    pub normal: Vec3,
+}
+
+impl AromaticRing {
+    /// Creates a ring from positions of its atoms, given in the order they are bonded
+    pub fn from_atoms(res_id: ResidueId, res_name: &str, positions: &[Vec3]) -> AromaticRing {
+        let mut center = Vec3::from_float(0.0);
+        for p in positions { center += p; }
+        center /= positions.len() as f64;
+        let mut normal = Vec3::from_float(0.0);
+        for i in 0..positions.len() {
+            let a = Vec3::sub_s(&positions[i], &center);
+            let b = Vec3::sub_s(&positions[(i + 1) % positions.len()], &center);
+            normal += &Vec3::cross(&a, &b);
+        }
+        normal.normalize();
+
+        return AromaticRing { res_id, res_name: res_name.to_string(), size: positions.len(), center, normal };
+    }
+
+    /// Distance from a point to the axis of this ring, i.e. the line running through its center along its normal
+    pub fn offset(&self, p: &Vec3) -> f64 {
+        let d = Vec3::sub_s(p, &self.center);
+        let h = Vec3::dot(&d, &self.normal);
+
+        return (d.length_squared() - h * h).max(0.0).sqrt();
+    }
+}
+
+/// Finds all complete aromatic rings of PHE, TYR, TRP and HIS residues of a structure
+pub fn aromatic_rings(strctr: &Structure) -> Vec<AromaticRing> {
+    let mut out: Vec<AromaticRing> = vec![];
+    let mut last_residue: Option<ResidueId> = None;
+    for a in strctr.atoms().iter().filter(|a| IsAromatic.check(a)) {
+        let res_id = ResidueId::from(a);
+        if last_residue.as_ref() == Some(&res_id) { continue; }
+        last_residue = Some(res_id.clone());
+        let residue_atoms: Vec<&PdbAtom> = match strctr.atoms_in_residue(&res_id) {
+            Ok(atoms) => atoms.collect(),
+            Err(_) => continue,
+        };
+        for (_, names) in RESIDUE_RINGS.iter().filter(|(res_name, _)| *res_name == a.res_name) {
+            let positions: Option<Vec<Vec3>> = names.iter()
+                .map(|n| residue_atoms.iter().find(|r| r.name.trim() == *n).map(|r| r.pos)).collect();
+            if let Some(positions) = positions {
+                out.push(AromaticRing::from_atoms(res_id.clone(), &a.res_name, &positions));
+            }
+        }
+    }
+
+    return out;
+}
+
+/// Geometry of a π-π stacking interaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StackingGeometry {
+    /// two rings are roughly parallel, face to face
+    Parallel,
+    /// two rings are roughly perpendicular, an edge of one ring points at the face of the other
+    TShaped,
+}
+
+/// A π-π stacking interaction between two aromatic rings
+#[derive(Clone, Debug)]
+pub struct PiStacking {
+    /// residue of the first ring
+    pub first: ResidueId,
+    /// three-letter code of the first residue
+    pub first_name: String,
+    /// residue of the second ring
+    pub second: ResidueId,
+    /// three-letter code of the second residue
+    pub second_name: String,
+    /// distance between the ring centers
+    pub distance: f64,
+    /// angle between the ring planes, in the range [0, 90] degrees
+    pub angle: f64,
+    /// the smaller of the two offsets of a ring center from the axis of the other ring
+    pub offset: f64,
+    /// geometry of this interaction
+    pub geometry: StackingGeometry,
+}
+
+impl Display for PiStacking {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} - {} {} {:5.2} {:5.1} {:5.2} {:?}", self.first, self.first_name,
+               self.second, self.second_name, self.distance, self.angle, self.offset, self.geometry)
+    }
+}
+
+/// Finds π-π stacking interactions between aromatic rings of a structure.
+///
+/// Two rings interact when their centers are closer than [`PI_STACKING_MAX_DISTANCE`] and the center of
+/// one of them is found within [`MAX_RING_OFFSET`] from the axis of the other one. The interaction is
+/// parallel when the angle between ring planes is below [`PI_STACKING_PARALLEL_ANGLE`] and T-shaped
+/// when it is above [`PI_STACKING_T_SHAPED_ANGLE`]; pairs of rings at intermediate angles are not reported.
+/// The two rings of a tryptophan may each interact with the same ring; both interactions are then reported.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{find_pi_stacking, PI_STACKING_MAX_DISTANCE};
+/// let strctr = Deposit::from_file("./tests/input_files/1c5n.cif")?.structure()?;
+/// let stacking = find_pi_stacking(&strctr);
+/// assert!(stacking.iter().all(|s| s.distance <= PI_STACKING_MAX_DISTANCE && s.first != s.second));
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_pi_stacking(strctr: &Structure) -> Vec<PiStacking> {
+    let rings = aromatic_rings(strctr);
+    let mut out: Vec<PiStacking> = vec![];
+    for (i, ri) in rings.iter().enumerate() {
+        for rj in rings[i + 1..].iter().filter(|r| r.res_id != ri.res_id) {
+            let distance = ri.center.distance_to(&rj.center);
+            if distance > PI_STACKING_MAX_DISTANCE { continue; }
+            let angle = Vec3::dot(&ri.normal, &rj.normal).abs().min(1.0).acos().to_degrees();
+            let geometry = if angle <= PI_STACKING_PARALLEL_ANGLE { StackingGeometry::Parallel }
+                else if angle >= PI_STACKING_T_SHAPED_ANGLE { StackingGeometry::TShaped }
+                else { continue };
+            let offset = ri.offset(&rj.center).min(rj.offset(&ri.center));
+            if offset > MAX_RING_OFFSET { continue; }
+            out.push(PiStacking { first: ri.res_id.clone(), first_name: ri.res_name.clone(),
+                second: rj.res_id.clone(), second_name: rj.res_name.clone(), distance, angle, offset, geometry });
+        }
+    }
+
+    return out;
+}
+
+/// A cation-π interaction between a positively charged group and an aromatic ring
+#[derive(Clone, Debug)]
+pub struct CationPi {
+    /// the residue bearing the positive charge
+    pub cation: ResidueId,
+    /// three-letter code of the cationic residue
+    pub cation_name: String,
+    /// name of the atom representing the cationic group: NZ of lysine or CZ of arginine
+    pub cation_atom: String,
+    /// residue of the aromatic ring
+    pub ring: ResidueId,
+    /// three-letter code of the aromatic residue
+    pub ring_name: String,
+    /// distance between the cation and the ring center
+    pub distance: f64,
+    /// distance between the cation and the ring axis
+    pub offset: f64,
+}
+
+impl Display for CationPi {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} - {} {} {:5.2} {:5.2}", self.cation, self.cation_name, self.cation_atom.trim(),
+               self.ring, self.ring_name, self.distance, self.offset)
+    }
+}
+
+/// Finds cation-π interactions in a structure.
+///
+/// A cationic group, i.e. the NZ atom of a lysine or the CZ atom of an arginine, interacts with an aromatic ring
+/// when it is closer than [`CATION_PI_MAX_DISTANCE`] to the ring center and not further than [`MAX_RING_OFFSET`]
+/// from the ring axis.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{find_cation_pi, MAX_RING_OFFSET};
+/// let strctr = Deposit::from_file("./tests/input_files/1c5n.cif")?.structure()?;
+/// let interactions = find_cation_pi(&strctr);
+/// assert!(interactions.iter().all(|c| c.offset <= MAX_RING_OFFSET));
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_cation_pi(strctr: &Structure) -> Vec<CationPi> {
+    let rings = aromatic_rings(strctr);
+    let cations: Vec<&PdbAtom> = strctr.atoms().iter()
+        .filter(|a| CATIONIC_GROUPS.iter().any(|(r, n)| a.res_name == *r && a.name == *n)).collect();
+    let mut out: Vec<CationPi> = vec![];
+    for c in cations {
+        for ring in &rings {
+            let distance = c.pos.distance_to(&ring.center);
+            if distance > CATION_PI_MAX_DISTANCE { continue; }
+            let offset = ring.offset(&c.pos);
+            if offset > MAX_RING_OFFSET { continue; }
+            out.push(CationPi { cation: ResidueId::from(c), cation_name: c.res_name.clone(), cation_atom: c.name.clone(),
+                ring: ring.res_id.clone(), ring_name: ring.res_name.clone(), distance, offset });
+        }
+    }
+
+    return out;
+}
@@ -152,6 +152,9 @@ impl<'a> BackboneHBondMap<'a> {
         return hbonds;
     }
 
+    /// The structure whose hydrogen bonds are stored in this map.
+    pub fn structure(&self) -> &'a Structure { self.the_structure }
+
     /// The number of residues in the structure indexed by this map.
     ///
     /// Note, that not all the residues create hydrogen bonds.
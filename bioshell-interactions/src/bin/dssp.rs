@@ -1,8 +1,9 @@
 use std::env;
 use clap::Parser;
 use log::info;
-use bioshell_interactions::BackboneHBondMap;
-use bioshell_pdb::{Deposit, PDBError};
+use bioshell_interactions::{find_water_bridges, write_dssp_classic, write_dssp_mmcif, BackboneHBondMap, DsspAssignment, HBondDetector};
+use bioshell_core::io::out_writer;
+use bioshell_pdb::{write_pdb, write_secondary_cif, Deposit, PDBError};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None, arg_required_else_help = true)]
@@ -16,9 +17,24 @@ struct Args {
     /// print sequence and secondary structure in FASTA format
     #[clap(short, long, short='f')]
     out_fasta: bool,
-    /// list all hydrogen bonds
+    /// list all backbone hydrogen bonds
     #[clap(long)]
     list: bool,
+    /// write the secondary structure assignment in the classic DSSP format
+    #[clap(long)]
+    classic: bool,
+    /// write the secondary structure assignment in the mmCIF format
+    #[clap(long)]
+    mmcif: bool,
+    /// list all hydrogen bonds: between backbone, side chains, water molecules and ligands, followed by water bridges
+    #[clap(long)]
+    list_hbonds: bool,
+    /// write the structure in the PDB format, with HELIX and SHEET records created from the DSSP assignment
+    #[clap(long)]
+    out_pdb: Option<String>,
+    /// write the DSSP assignment as mmCIF _struct_conf and _struct_sheet_range loops
+    #[clap(long)]
+    out_struct_conf: bool,
     /// be more verbose and log program actions on the screen
     #[clap(short, long, short='v')]
     verbose: bool
@@ -41,7 +57,7 @@ fn main() -> Result<(), PDBError> {
 
     // ---------- INPUT section ----------
     let deposit = Deposit::from_file(&args.infile)?;
-    let strctr= deposit.structure()?;
+    let mut strctr= deposit.structure()?;
 
     // ---------- Detect H-bonds ----------
     let hbonds = BackboneHBondMap::new(&strctr);
@@ -52,5 +68,26 @@ fn main() -> Result<(), PDBError> {
         }
     }
 
+    if args.classic || args.mmcif {
+        let assignment = DsspAssignment::new(&hbonds);
+        let mut stdout = std::io::stdout();
+        if args.classic { write_dssp_classic(&mut stdout, &assignment)?; }
+        if args.mmcif { write_dssp_mmcif(&mut stdout, &assignment)?; }
+    }
+
+    if args.list_hbonds {
+        let all_hbonds = HBondDetector::default().find(&strctr);
+        info!("{} hydrogen bonds found", all_hbonds.len());
+        for hbond in &all_hbonds { println!("{}", hbond); }
+        for bridge in find_water_bridges(&all_hbonds) { println!("{}", bridge); }
+    }
+
+    if args.out_pdb.is_some() || args.out_struct_conf {
+        let assignment = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+        assignment.assign_to(&mut strctr)?;
+        if let Some(fname) = &args.out_pdb { write_pdb(&strctr, out_writer(fname, false)); }
+        if args.out_struct_conf { write_secondary_cif(&mut std::io::stdout(), &strctr)?; }
+    }
+
     Ok(())
 }
\ No newline at end of file
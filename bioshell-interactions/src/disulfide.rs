@@ -0,0 +1,165 @@
+use std::fmt::{Display, Formatter};
+use bioshell_core::{dihedral_angle4, Vec3};
+use bioshell_pdb::{ResidueId, Structure};
+use bioshell_pdb::calc::NeighborGrid;
+
+/// Maximum distance between the two SG atoms of a disulfide bridge (in Angstroms)
+pub const MAX_SG_SG_DISTANCE: f64 = 2.5;
+
+/// Handedness of a disulfide bridge, given by the sign of its χ3 dihedral angle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DisulfideHandedness {
+    /// χ3 is negative
+    Left,
+    /// χ3 is positive
+    Right,
+}
+
+/// Conformation of a disulfide bridge, given by the signs of its χ2 and χ2' dihedral angles
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DisulfideMotif {
+    /// both χ2 and χ2' have the same sign as χ3
+    Spiral,
+    /// one of χ2 and χ2' has the sign opposite to χ3
+    Hook,
+    /// both χ2 and χ2' have the sign opposite to χ3
+    Staple,
+}
+
+/// Conformational class of a disulfide bridge, following [Schmidt *et al.*](https://doi.org/10.1021/bi061262h).
+///
+/// The twenty classes are defined by the signs of the five dihedral angles of a bridge. The class name
+/// is composed of the signs of χ1 and χ1', the handedness and the motif, e.g. `-LHSpiral` or `+/-RHHook`.
+///
+/// ```
+/// use bioshell_interactions::{DisulfideClass, DisulfideHandedness, DisulfideMotif};
+/// let class = DisulfideClass::from_dihedrals(-60.0, -60.0, -90.0, -60.0, -60.0);
+/// assert_eq!(class.handedness, DisulfideHandedness::Left);
+/// assert_eq!(class.motif, DisulfideMotif::Spiral);
+/// assert_eq!(class.to_string(), "-LHSpiral");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DisulfideClass {
+    /// handedness of the bridge
+    pub handedness: DisulfideHandedness,
+    /// conformation of the bridge
+    pub motif: DisulfideMotif,
+    /// signs of χ1 and χ1' dihedral angles: `'+'` or `'-'`
+    pub chi1_signs: [char; 2],
+}
+
+impl DisulfideClass {
+    /// Classifies a disulfide bridge by its χ1, χ2, χ3, χ2' and χ1' dihedral angles, given in degrees
+    pub fn from_dihedrals(chi1: f64, chi2: f64, chi3: f64, chi2_prime: f64, chi1_prime: f64) -> DisulfideClass {
+        let sign = |a: f64| if a < 0.0 { '-' } else { '+' };
+        let handedness = if chi3 < 0.0 { DisulfideHandedness::Left } else { DisulfideHandedness::Right };
+        let n_opposite = [chi2, chi2_prime].iter().filter(|&&c| sign(c) != sign(chi3)).count();
+        let motif = match n_opposite {
+            0 => DisulfideMotif::Spiral,
+            1 => DisulfideMotif::Hook,
+            _ => DisulfideMotif::Staple,
+        };
+
+        return DisulfideClass { handedness, motif, chi1_signs: [sign(chi1), sign(chi1_prime)] };
+    }
+}
+
+impl Display for DisulfideClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let chi1 = if self.chi1_signs[0] == self.chi1_signs[1] { self.chi1_signs[0].to_string() } else { "+/-".to_string() };
+        let hand = match self.handedness { DisulfideHandedness::Left => "LH", DisulfideHandedness::Right => "RH" };
+        write!(f, "{}{}{:?}", chi1, hand, self.motif)
+    }
+}
+
+/// A disulfide bridge between two cysteine residues.
+///
+/// All the dihedral angles are given in degrees:
+///
+///  - χ1: N-CA-CB-SG of the first cysteine
+///  - χ2: CA-CB-SG-SG'
+///  - χ3: CB-SG-SG'-CB'
+///  - χ2': SG-SG'-CB'-CA'
+///  - χ1': SG'-CB'-CA'-N' of the second cysteine
+#[derive(Clone, Debug)]
+pub struct DisulfideBond {
+    /// the first cysteine, which comes first in a structure
+    pub first: ResidueId,
+    /// the second cysteine
+    pub second: ResidueId,
+    /// distance between the two SG atoms
+    pub distance_sg: f64,
+    /// distance between the CA atoms of the two cysteines
+    pub distance_ca: f64,
+    /// the five dihedral angles: χ1, χ2, χ3, χ2' and χ1'
+    pub dihedrals: [f64; 5],
+    /// conformational class of this bridge
+    pub class: DisulfideClass,
+}
+
+impl DisulfideBond {
+    /// Torsional strain energy of this bridge (in kJ/mol).
+    ///
+    /// The energy is computed from the dihedral angles with the empirical formula
+    /// of [Katz and Kossiakoff](https://doi.org/10.1016/S0021-9258(18)67421-2).
+    pub fn strain_energy(&self) -> f64 {
+        let [chi1, chi2, chi3, chi2p, chi1p] = self.dihedrals.map(|a| a.to_radians());
+        return 8.37 * (1.0 + (3.0 * chi1).cos()) + 8.37 * (1.0 + (3.0 * chi1p).cos())
+            + 4.18 * (1.0 + (3.0 * chi2).cos()) + 4.18 * (1.0 + (3.0 * chi2p).cos())
+            + 14.64 * (1.0 + (2.0 * chi3).cos()) + 2.51 * (1.0 + (3.0 * chi3).cos());
+    }
+}
+
+impl Display for DisulfideBond {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {} {:5.3} {:6.2} {:7.1} {:7.1} {:7.1} {:7.1} {:7.1} {}", self.first, self.second,
+               self.distance_sg, self.distance_ca, self.dihedrals[0], self.dihedrals[1], self.dihedrals[2],
+               self.dihedrals[3], self.dihedrals[4], self.class)
+    }
+}
+
+/// Finds disulfide bridges in a given structure.
+///
+/// Two cysteine residues form a bridge when their SG atoms are closer than [`MAX_SG_SG_DISTANCE`];
+/// residues missing any of their N, CA, CB or SG atoms are skipped.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::find_disulfides;
+/// let strctr = Deposit::from_file("./tests/input_files/1c5n.cif")?.structure()?;
+/// let bridges = find_disulfides(&strctr);
+/// for b in &bridges { println!("{} {:.1}", b, b.strain_energy()); }
+/// assert_eq!(bridges.len(), 4);
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_disulfides(strctr: &Structure) -> Vec<DisulfideBond> {
+    // --- N, CA, CB and SG positions of every complete cysteine
+    let mut cysteines: Vec<(ResidueId, [Vec3; 4])> = vec![];
+    for sg in strctr.atoms().iter().filter(|a| a.res_name == "CYS" && a.name == " SG ") {
+        let res_id = ResidueId::from(sg);
+        let atoms: Option<Vec<Vec3>> = [" N  ", " CA ", " CB "].iter()
+            .map(|name| strctr.atom(&res_id, name).ok().map(|a| a.pos)).collect();
+        if let Some(atoms) = atoms { cysteines.push((res_id, [atoms[0], atoms[1], atoms[2], sg.pos])); }
+    }
+
+    let sg: Vec<Vec3> = cysteines.iter().map(|c| c.1[3]).collect();
+    let grid = NeighborGrid::new(&sg, MAX_SG_SG_DISTANCE);
+    let mut out: Vec<DisulfideBond> = vec![];
+    for (i, (res_i, [n, ca, cb, sg_i])) in cysteines.iter().enumerate() {
+        let mut partners = grid.neighbors(sg_i, MAX_SG_SG_DISTANCE);
+        partners.sort();
+        for j in partners.into_iter().filter(|&j| j > i) {
+            let (res_j, [n_j, ca_j, cb_j, sg_j]) = &cysteines[j];
+            let dihedrals = [dihedral_angle4(n, ca, cb, sg_i), dihedral_angle4(ca, cb, sg_i, sg_j),
+                dihedral_angle4(cb, sg_i, sg_j, cb_j), dihedral_angle4(sg_i, sg_j, cb_j, ca_j),
+                dihedral_angle4(sg_j, cb_j, ca_j, n_j)].map(|a| a.to_degrees());
+            let class = DisulfideClass::from_dihedrals(dihedrals[0], dihedrals[1], dihedrals[2], dihedrals[3], dihedrals[4]);
+            out.push(DisulfideBond { first: res_i.clone(), second: res_j.clone(), distance_sg: sg_i.distance_to(sg_j),
+                distance_ca: ca.distance_to(ca_j), dihedrals, class });
+        }
+    }
+
+    return out;
+}
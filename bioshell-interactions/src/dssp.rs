@@ -1,79 +1,572 @@
-use bioshell_pdb::ResidueId;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use bioshell_core::{dihedral_angle4, planar_angle3, Vec3};
+use bioshell_pdb::{is_hydrogen, PdbAtom, PdbSheet, PDBError, ResidueId, SecondaryStructureTypes, Structure};
+use bioshell_pdb::calc::{solvent_accessible_area, SASA_SPHERE_POINTS};
+use bioshell_seq::chemical::ResidueTypeProperties;
 use crate::{BackboneHBondMap};
 
+/// The largest C-N distance between two consecutive residues; when exceeded, a chain break is detected
+const MAX_PEPTIDE_BOND: f64 = 2.5;
 
-/// Implements the DSSP algorithm to detect the secondary structure of a protein.
+/// Virtual CA bond angle (in degrees) above which a residue is considered a bend
+const MIN_BEND_KAPPA: f64 = 70.0;
+
+/// Center of the Phi and Psi ranges that define a polyproline II helix (in degrees)
+const PPII_PHI_PSI: (f64, f64) = (-75.0, 145.0);
+
+/// Allowed deviation of Phi and Psi angles from [`PPII_PHI_PSI`] (in degrees)
+const PPII_EPSILON: f64 = 29.0;
+
+/// The minimum number of consecutive residues of a polyproline II helix
+const PPII_MIN_LENGTH: usize = 3;
+
+/// Orientation of the two strands of a beta bridge or a beta ladder
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BridgeType {
+    /// both strands run in the same direction
+    Parallel,
+    /// the two strands run in opposite directions
+    Antiparallel,
+}
+
+/// A residue paired with a given residue in a beta bridge
+#[derive(Clone, Debug)]
+pub struct BridgePartner {
+    /// index of the partner residue in [`DsspAssignment::residues()`]
+    pub index: usize,
+    /// identifier of the partner residue
+    pub res_id: ResidueId,
+    /// label of the ladder this bridge belongs to; lower case for parallel ladders, upper case for antiparallel ones
+    pub ladder: char,
+    /// orientation of the bridge
+    pub bridge_type: BridgeType,
+}
+
+/// A ladder: a set of consecutive beta bridges of the same type, possibly interrupted by a beta bulge
+#[derive(Clone, Debug)]
+pub struct Ladder {
+    /// label of this ladder; lower case for parallel ladders, upper case for antiparallel ones
+    pub label: char,
+    /// label of the sheet this ladder belongs to
+    pub sheet: char,
+    /// orientation of the two strands
+    pub bridge_type: BridgeType,
+    /// bridged residues of the first strand, in the ascending order
+    pub first_strand: Vec<ResidueId>,
+    /// bridged residues of the second strand; `second_strand[k]` is paired with `first_strand[k]`, which defines the register
+    pub second_strand: Vec<ResidueId>,
+}
+
+/// DSSP assignment for a single residue
+#[derive(Clone, Debug)]
+pub struct DsspResidue {
+    /// residue identifier
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    /// one-letter code of the residue
+    pub code1: char,
+    /// `true` if there is a chain break between this residue and the previous one, or this residue starts a chain
+    pub chain_break: bool,
+    /// secondary structure: one of `H`, `B`, `E`, `G`, `I`, `P`, `T`, `S`, or space for a loop
+    pub ss: char,
+    /// flags of 3-, 4- and 5-turns: `>` marks a residue accepting a turn H-bond, `<` the donor, `X` both
+    /// and a digit a residue located inside a turn
+    pub helix_flags: [char; 3],
+    /// `true` if the virtual CA bond angle at this residue exceeds 70 degrees
+    pub bend: bool,
+    /// sign of the `alpha` dihedral angle: `+`, `-` or space when undefined
+    pub chirality: char,
+    /// up to two beta bridge partners of this residue
+    pub bridge_partners: [Option<BridgePartner>; 2],
+    /// label of the beta sheet this residue belongs to
+    pub sheet: Option<char>,
+    /// solvent accessible surface area of this residue (in square Angstroms)
+    pub accessibility: f64,
+    /// two strongest H-bonds donated by this residue: offset of the acceptor residue and the energy (kcal/mol)
+    pub nh_o: [(isize, f64); 2],
+    /// two strongest H-bonds accepted by this residue: offset of the donor residue and the energy (kcal/mol)
+    pub o_hn: [(isize, f64); 2],
+    /// cosine of the angle between C=O of this residue and C=O of the previous one
+    pub tco: Option<f64>,
+    /// virtual bond angle defined by CA atoms of residues `i-2`, `i` and `i+2` (in degrees)
+    pub kappa: Option<f64>,
+    /// virtual dihedral angle defined by CA atoms of residues `i-1`, `i`, `i+1` and `i+2` (in degrees)
+    pub alpha: Option<f64>,
+    /// Phi dihedral angle (in degrees)
+    pub phi: Option<f64>,
+    /// Psi dihedral angle (in degrees)
+    pub psi: Option<f64>,
+    /// position of the CA atom
+    pub ca: Option<Vec3>,
+}
+
+impl Display for DsspResidue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let partner = |k: usize| self.bridge_partners[k].as_ref()
+            .map_or(" ".repeat(8), |p| format!("{}{:>7}", p.ladder, p.res_id.to_string().trim()));
+        write!(f, "{} {} {} {} {}{}{} {}{} {} {} {:4.0}", self.res_id, self.res_name, self.code1, self.ss,
+               self.helix_flags[0], self.helix_flags[1], self.helix_flags[2], if self.bend { 'S' } else { ' ' },
+               self.chirality, partner(0), partner(1), self.accessibility)
+    }
+}
+
+/// Secondary structure assignment computed by the DSSP algorithm.
 ///
-/// # Arguments
-/// * `n_residues` - Total number of residues in the protein.
+/// This struct implements the method of [Kabsch and Sander](https://doi.org/10.1002/bip.360221211),
+/// extended by polyproline II helices as in DSSP 4. Hydrogen bonds are taken from a [`BackboneHBondMap`].
+/// Each residue is assigned one of the following states, given in the order of their priority:
 ///
-/// # Returns
-/// A String representing the secondary structure, where each character corresponds to a residue:
-/// * 'H' - Alpha-helix
-/// * 'G' - 3-10 helix
-/// * 'I' - Pi-helix
-/// * 'E' - Beta-strand
-/// * 'C' - Coil (not part of a helix or a sheet)
-pub fn dssp(bb_h_bonds: &BackboneHBondMap) -> String {
+///  - `H`: alpha helix, `B`: isolated beta bridge, `E`: extended strand participating in a ladder
+///  - `G`: 3-10 helix, `I`: pi helix, `P`: polyproline II helix
+///  - `T`: hydrogen bonded turn, `S`: bend, and space for a loop
+///
+/// Beta bridges are grouped into ladders and ladders sharing residues form sheets. Each residue record, available
+/// as [`DsspResidue`], provides also its bridge partners, backbone angles and solvent accessibility.
+/// The accessibility is computed for heavy atoms of amino acid residues with the
+/// [`solvent_accessible_area()`] function, thus it may slightly differ from values reported by DSSP.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{BackboneHBondMap, BridgeType, DsspAssignment};
+/// let strctr = Deposit::from_file("./tests/input_files/2gb1.cif")?.structure()?;
+/// let hbonds = BackboneHBondMap::new(&strctr);
+/// let dssp = DsspAssignment::new(&hbonds);
+/// // --- the two N-terminal strands form an antiparallel hairpin
+/// let ladder = dssp.ladders().iter().find(|l| l.first_strand.contains(&ResidueId::new("A", 5, ' '))).unwrap();
+/// assert_eq!(ladder.bridge_type, BridgeType::Antiparallel);
+/// assert_eq!(dssp.sheets().len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct DsspAssignment {
+    id_code: String,
+    residues: Vec<DsspResidue>,
+    ladders: Vec<Ladder>,
+}
+
+/// A ladder under construction, holding residue indexes
+struct LadderIndexes {
+    bridge_type: BridgeType,
+    i: VecDeque<usize>,
+    j: VecDeque<usize>,
+}
+
+impl DsspAssignment {
 
-    // count the residues in the structure
-    let n_residues = bb_h_bonds.n_residues();
+    /// Computes the DSSP assignment for residues indexed by a given [`BackboneHBondMap`]
+    pub fn new(hbonds: &BackboneHBondMap) -> DsspAssignment {
+        let strctr = hbonds.structure();
+        let res_ids: Vec<ResidueId> = hbonds.residue_ids().cloned().collect();
+        let n = res_ids.len();
 
-    // Initialize the secondary structure for each residue as 'C' (coil) by default
-    let mut secondary_structure: Vec<char> = vec!['C'; n_residues];
+        // ---------- backbone atoms and chain breaks
+        let bb: Vec<[Option<Vec3>; 4]> = res_ids.iter()
+            .map(|r| [" N  ", " CA ", " C  ", " O  "].map(|name| strctr.atom(r, name).ok().map(|a| a.pos))).collect();
+        let chain_break: Vec<bool> = (0..n).map(|i| {
+            if i == 0 || res_ids[i].chain_id != res_ids[i - 1].chain_id { return true; }
+            match (bb[i - 1][2], bb[i][0]) {
+                (Some(c), Some(n)) => c.distance_to(&n) > MAX_PEPTIDE_BOND,
+                _ => true,
+            }
+        }).collect();
+        let no_break = |from: usize, to: usize| (from + 1..=to).all(|k| !chain_break[k]);
+        let is_hb = |d: usize, a: usize| hbonds.h_bond_for_indexes(d, a).is_some();
 
-    let res_ids: Vec<&ResidueId> = bb_h_bonds.residue_ids().collect();
+        // ---------- n-turns and helix flags
+        let mut helix_flags = vec![[' '; 3]; n];
+        let mut turns = vec![[false; 3]; n];
+        for (k, stride) in (3..=5).enumerate() {
+            for i in 0..n.saturating_sub(stride) {
+                if !no_break(i, i + stride) || !is_hb(i + stride, i) { continue; }
+                turns[i][k] = true;
+                helix_flags[i][k] = if helix_flags[i][k] == '<' { 'X' } else { '>' };
+                helix_flags[i + stride][k] = if helix_flags[i + stride][k] == '>' { 'X' } else { '<' };
+                for flags in helix_flags[i + 1..i + stride].iter_mut() {
+                    if flags[k] == ' ' { flags[k] = char::from_digit(stride as u32, 10).unwrap(); }
+                }
+            }
+        }
 
-    // Identify 3-10 helices using the i, i+3 hydrogen bond pattern
-    for i in 1..n_residues.saturating_sub(3) {
-        if bb_h_bonds.accepts_n_turn(res_ids[i-1], 3)&& bb_h_bonds.accepts_n_turn(res_ids[i], 3) {
-            secondary_structure[i] = 'G';
-            secondary_structure[i + 1] = 'G';
-            secondary_structure[i + 2] = 'G';
+        // ---------- beta bridges and ladders
+        let mut ladders: Vec<LadderIndexes> = vec![];
+        for i in 1..n.saturating_sub(4) {
+            for j in i + 3..n - 1 {
+                if !no_break(i - 1, i + 1) || !no_break(j - 1, j + 1) { continue; }
+                let bridge_type = if (is_hb(i + 1, j) && is_hb(j, i - 1)) || (is_hb(j + 1, i) && is_hb(i, j - 1)) {
+                    BridgeType::Parallel
+                } else if (is_hb(i + 1, j - 1) && is_hb(j + 1, i - 1)) || (is_hb(j, i) && is_hb(i, j)) {
+                    BridgeType::Antiparallel
+                } else { continue };
+                let extended = ladders.iter_mut().any(|l| {
+                    if l.bridge_type != bridge_type || *l.i.back().unwrap() + 1 != i || !no_break(i - 1, i) { return false; }
+                    match bridge_type {
+                        BridgeType::Parallel if *l.j.back().unwrap() + 1 == j && no_break(j - 1, j) => {
+                            l.i.push_back(i);
+                            l.j.push_back(j);
+                            true
+                        }
+                        BridgeType::Antiparallel if *l.j.front().unwrap() == j + 1 && no_break(j, j + 1) => {
+                            l.i.push_back(i);
+                            l.j.push_front(j);
+                            true
+                        }
+                        _ => false,
+                    }
+                });
+                if !extended { ladders.push(LadderIndexes { bridge_type, i: VecDeque::from([i]), j: VecDeque::from([j]) }); }
+            }
+        }
+        merge_bulges(&mut ladders, &no_break);
+
+        // ---------- sheets: ladders sharing a residue
+        let mut sheet_of = vec![usize::MAX; ladders.len()];
+        let mut n_sheets = 0;
+        for start in 0..ladders.len() {
+            if sheet_of[start] != usize::MAX { continue; }
+            sheet_of[start] = n_sheets;
+            let mut stack = vec![start];
+            while let Some(l) = stack.pop() {
+                for other in 0..ladders.len() {
+                    if sheet_of[other] == usize::MAX && linked(&ladders[l], &ladders[other]) {
+                        sheet_of[other] = n_sheets;
+                        stack.push(other);
+                    }
+                }
+            }
+            n_sheets += 1;
+        }
+        // --- ladders are labelled in the order of sheets
+        let mut order: Vec<usize> = (0..ladders.len()).collect();
+        order.sort_by_key(|&l| (sheet_of[l], ladders[l].i[0]));
+
+        let mut ss = vec![' '; n];
+        let mut partners: Vec<[Option<BridgePartner>; 2]> = vec![[None, None]; n];
+        let mut sheet: Vec<Option<char>> = vec![None; n];
+        let mut out_ladders: Vec<Ladder> = vec![];
+        for (nr, &l) in order.iter().enumerate() {
+            let ladder = &ladders[l];
+            let upper = (b'A' + (nr % 26) as u8) as char;
+            let label = if ladder.bridge_type == BridgeType::Parallel { upper.to_ascii_lowercase() } else { upper };
+            let sheet_label = (b'A' + (sheet_of[l] % 26) as u8) as char;
+            let len = ladder.i.len();
+            let pair = |k: usize| if ladder.bridge_type == BridgeType::Parallel { ladder.j[k] } else { ladder.j[len - 1 - k] };
+            for k in 0..len {
+                let (i, j) = (ladder.i[k], pair(k));
+                for (a, b) in [(i, j), (j, i)] {
+                    let slot = if partners[a][0].is_none() { 0 } else { 1 };
+                    partners[a][slot] = Some(BridgePartner { index: b, res_id: res_ids[b].clone(), ladder: label,
+                        bridge_type: ladder.bridge_type });
+                }
+            }
+            let state = if len > 1 { 'E' } else { 'B' };
+            for range in [ladder.i[0]..=ladder.i[len - 1], ladder.j[0]..=ladder.j[len - 1]] {
+                for r in range {
+                    if ss[r] != 'E' { ss[r] = state; }
+                    sheet[r] = Some(sheet_label);
+                }
+            }
+            out_ladders.push(Ladder { label, sheet: sheet_label, bridge_type: ladder.bridge_type,
+                first_strand: (0..len).map(|k| res_ids[ladder.i[k]].clone()).collect(),
+                second_strand: (0..len).map(|k| res_ids[pair(k)].clone()).collect() });
+        }
+
+        // ---------- helices: alpha first, then 3-10 and pi where these do not overlap other states
+        for i in 1..n.saturating_sub(4) {
+            if turns[i - 1][1] && turns[i][1] { ss[i..i + 4].iter_mut().for_each(|s| *s = 'H'); }
+        }
+        for (k, stride, code) in [(0, 3, 'G'), (2, 5, 'I')] {
+            for i in 1..n.saturating_sub(stride) {
+                if turns[i - 1][k] && turns[i][k] && ss[i..i + stride].iter().all(|&s| s == ' ' || s == code) {
+                    ss[i..i + stride].iter_mut().for_each(|s| *s = code);
+                }
+            }
+        }
+
+        // ---------- backbone geometry
+        let ca: Vec<Option<Vec3>> = bb.iter().map(|b| b[1]).collect();
+        let mut phi = vec![None; n];
+        let mut psi = vec![None; n];
+        let mut tco = vec![None; n];
+        let mut kappa = vec![None; n];
+        let mut alpha = vec![None; n];
+        for i in 0..n {
+            if i > 0 && no_break(i - 1, i) {
+                if let (Some(c_prev), Some(n_at), Some(ca_at), Some(c)) = (bb[i - 1][2], bb[i][0], bb[i][1], bb[i][2]) {
+                    phi[i] = Some(dihedral_angle4(&c_prev, &n_at, &ca_at, &c).to_degrees());
+                }
+                if let (Some(c_prev), Some(o_prev), Some(c), Some(o)) = (bb[i - 1][2], bb[i - 1][3], bb[i][2], bb[i][3]) {
+                    let (u, v) = (Vec3::sub_s(&o, &c), Vec3::sub_s(&o_prev, &c_prev));
+                    tco[i] = Some(Vec3::dot(&u, &v) / (u.length() * v.length()));
+                }
+            }
+            if i + 1 < n && no_break(i, i + 1) {
+                if let (Some(n_at), Some(ca_at), Some(c), Some(n_next)) = (bb[i][0], bb[i][1], bb[i][2], bb[i + 1][0]) {
+                    psi[i] = Some(dihedral_angle4(&n_at, &ca_at, &c, &n_next).to_degrees());
+                }
+            }
+            if i >= 2 && i + 2 < n && no_break(i - 2, i + 2) {
+                if let (Some(a), Some(b), Some(c)) = (ca[i - 2], ca[i], ca[i + 2]) {
+                    kappa[i] = Some(180.0 - planar_angle3(&a, &b, &c).to_degrees());
+                }
+            }
+            if i >= 1 && i + 2 < n && no_break(i - 1, i + 2) {
+                if let (Some(a), Some(b), Some(c), Some(d)) = (ca[i - 1], ca[i], ca[i + 1], ca[i + 2]) {
+                    alpha[i] = Some(dihedral_angle4(&a, &b, &c, &d).to_degrees());
+                }
+            }
+        }
+        let bend: Vec<bool> = kappa.iter().map(|k| k.is_some_and(|k| k > MIN_BEND_KAPPA)).collect();
+
+        // ---------- polyproline II helices, turns and bends fill the remaining loops
+        let is_ppii = |i: usize| matches!((phi[i], psi[i]), (Some(f), Some(p))
+            if (f - PPII_PHI_PSI.0).abs() <= PPII_EPSILON && (p - PPII_PHI_PSI.1).abs() <= PPII_EPSILON);
+        for i in 0..n.saturating_sub(PPII_MIN_LENGTH - 1) {
+            let last = i + PPII_MIN_LENGTH - 1;
+            if no_break(i, last) && (i..=last).all(is_ppii) {
+                for s in ss[i..=last].iter_mut() { if *s == ' ' { *s = 'P'; } }
+            }
+        }
+        for i in 1..n {
+            if ss[i] != ' ' { continue; }
+            let in_turn = (0..3).any(|k| (1..k + 3).any(|back| i >= back && turns[i - back][k]));
+            if in_turn { ss[i] = 'T'; } else if bend[i] { ss[i] = 'S'; }
         }
-    }
 
-    // Identify pi-helices using the i, i+5 hydrogen bond pattern
-    for i in 1..n_residues.saturating_sub(5) {
-        if bb_h_bonds.accepts_n_turn(res_ids[i-1], 5) && bb_h_bonds.accepts_n_turn(res_ids[i], 5) {
-            secondary_structure[i] = 'I';
-            secondary_structure[i + 1] = 'I';
-            secondary_structure[i + 2] = 'I';
-            secondary_structure[i + 3] = 'I';
-            secondary_structure[i + 4] = 'I';
+        // ---------- the two strongest hydrogen bonds donated and accepted by each residue
+        let mut nh_o = vec![[(0isize, 0.0); 2]; n];
+        let mut o_hn = vec![[(0isize, 0.0); 2]; n];
+        for ((d, a), hb) in hbonds.h_bonds() {
+            let e = hb.dssp_energy();
+            insert_bond(&mut nh_o[*d], (*a as isize - *d as isize, e));
+            insert_bond(&mut o_hn[*a], (*d as isize - *a as isize, e));
         }
+
+        let accessibility = residue_accessibility(strctr, &res_ids);
+
+        let residues = (0..n).map(|i| {
+            let res_name = strctr.atoms_in_residue(&res_ids[i]).ok().and_then(|mut it| it.next())
+                .map_or("UNK".to_string(), |a| a.res_name.clone());
+            let code1 = strctr.residue_type(&res_ids[i]).map_or('X', |rt| rt.parent_type.code1());
+            DsspResidue { res_id: res_ids[i].clone(), res_name, code1, chain_break: chain_break[i], ss: ss[i],
+                helix_flags: helix_flags[i], bend: bend[i],
+                chirality: alpha[i].map_or(' ', |a| if a < 0.0 { '-' } else { '+' }),
+                bridge_partners: partners[i].clone(), sheet: sheet[i], accessibility: accessibility[i],
+                nh_o: nh_o[i], o_hn: o_hn[i], tco: tco[i], kappa: kappa[i], alpha: alpha[i], phi: phi[i], psi: psi[i],
+                ca: ca[i] }
+        }).collect();
+
+        return DsspAssignment { id_code: strctr.id_code.clone(), residues, ladders: out_ladders };
+    }
+
+    /// ID code of the structure this assignment was computed for
+    pub fn id_code(&self) -> &str { &self.id_code }
+
+    /// Per-residue records of this assignment, in the order of [`BackboneHBondMap::residue_ids()`]
+    pub fn residues(&self) -> &[DsspResidue] { &self.residues }
+
+    /// Beta ladders, ordered by the sheets they belong to
+    pub fn ladders(&self) -> &[Ladder] { &self.ladders }
+
+    /// Labels of beta sheets found in a structure
+    pub fn sheets(&self) -> Vec<char> {
+        let mut out: Vec<char> = self.ladders.iter().map(|l| l.sheet).collect();
+        out.dedup();
+        return out;
+    }
+
+    /// Secondary structure as a string, where loop residues are marked by `C`
+    pub fn secondary_structure(&self) -> String {
+        self.residues.iter().map(|r| if r.ss == ' ' { 'C' } else { r.ss }).collect()
     }
 
-    // Identify alpha-helices using the i, i+4 hydrogen bond pattern
-    for i in 1..n_residues.saturating_sub(4) {
-        if bb_h_bonds.accepts_n_turn(res_ids[i-1], 4) && bb_h_bonds.accepts_n_turn(res_ids[i], 4) {
-            secondary_structure[i] = 'H';
-            secondary_structure[i + 1] = 'H';
-            secondary_structure[i + 2] = 'H';
-            secondary_structure[i + 3] = 'H';
+    /// Stores this assignment as the secondary structure annotation of a given structure.
+    ///
+    /// Every residue of this assignment is annotated with a [`SecondaryStructureTypes`] variant:
+    /// `H`, `G`, `I` and `P` residues become alpha, 3-10, pi and polyproline helices, respectively,
+    /// `E` residues become strands and all the other residues, including isolated bridges (`B`), are annotated as coil.
+    /// Consecutive residues of the same state form a single secondary structure element. The annotation
+    /// loaded from the input file is replaced, so [`Structure::secondary()`], [`SecondaryView`](bioshell_pdb::SecondaryView)
+    /// and the writers of `HELIX` / `SHEET` records report the DSSP assignment afterwards.
+    ///
+    /// Strands are also grouped into sheets with [`Structure::set_sheets()`]: every DSSP sheet gives its label
+    /// to the respective `SHEET` records. Strands of a sheet are listed starting from an edge strand, so that every
+    /// strand is followed by one of its partners when possible; the sense of a strand is given by the type
+    /// of the ladder that pairs it with the strand listed before.
+    ///
+    /// Results in a [`PDBError`] if a residue of this assignment can't be found in the given structure.
+    ///
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_interactions::{BackboneHBondMap, DsspAssignment};
+    /// let mut strctr = Deposit::from_file("./tests/input_files/2gb1.cif")?.structure()?;
+    /// let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+    /// dssp.assign_to(&mut strctr)?;
+    /// assert_eq!(&strctr.secondary("A").to_string()[22..35], "HHHHHHHHHHHHH");
+    /// // --- the four strands of protein G form a single sheet, with one parallel pair of strands
+    /// let sheets = strctr.sheets();
+    /// assert_eq!(sheets.len(), 4);
+    /// assert!(sheets.iter().all(|s| s.sheet_id == "A" && s.num_strands == 4));
+    /// assert_eq!(sheets.iter().filter(|s| s.sense == 1).count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn assign_to(&self, strctr: &mut Structure) -> Result<(), PDBError> {
+        let mut sse_index: usize = 0;
+        for (i, r) in self.residues.iter().enumerate() {
+            if i > 0 && (r.chain_break || r.ss != self.residues[i - 1].ss) { sse_index += 1; }
+            let index = (sse_index % 255) as u8;
+            let sse = match r.ss {
+                'H' => SecondaryStructureTypes::RightAlphaHelix(index),
+                'G' => SecondaryStructureTypes::Right3_10Helix(index),
+                'I' => SecondaryStructureTypes::RightPiHelix(index),
+                'P' => SecondaryStructureTypes::Polyproline(index),
+                'E' => SecondaryStructureTypes::Strand(index),
+                _ => SecondaryStructureTypes::Coil,
+            };
+            strctr.set_residue_secondary(&r.res_id, sse)?;
         }
+        strctr.set_sheets(self.sheet_records());
+
+        return Ok(());
     }
 
-    // Step 4: Identify beta-sheets using hydrogen bonds between distant residues
-    for i in 0..n_residues {
-        if secondary_structure[i] == 'H' || secondary_structure[i] == 'G' || secondary_structure[i] == 'I' { continue; }
-        let res_i = res_ids[i];
-        for j in 0..n_residues {
-            if secondary_structure[j] == 'H' || secondary_structure[j] == 'G' || secondary_structure[j] == 'I' { continue; }
-            let res_j = res_ids[j];
-            if bb_h_bonds.is_antiparallel_bridge(res_i, res_j) {
-                secondary_structure[i] = 'E';
-                secondary_structure[j] = 'E';
+    /// Creates a `SHEET` record for every strand of this assignment, as described in [`DsspAssignment::assign_to()`]
+    fn sheet_records(&self) -> Vec<PdbSheet> {
+        // --- strands: runs of consecutive E residues
+        let mut strands: Vec<Range<usize>> = vec![];
+        for (i, r) in self.residues.iter().enumerate() {
+            if r.ss != 'E' { continue }
+            match strands.last_mut() {
+                Some(last) if last.end == i && !r.chain_break => last.end = i + 1,
+                _ => strands.push(i..i + 1),
+            }
+        }
+        let strand_of: HashMap<&ResidueId, usize> = strands.iter().enumerate()
+            .flat_map(|(s, range)| range.clone().map(move |i| (s, i)))
+            .map(|(s, i)| (&self.residues[i].res_id, s)).collect();
+        // --- pairs of strands bridged by a ladder
+        let mut partners: Vec<Vec<(usize, BridgeType)>> = vec![vec![]; strands.len()];
+        for ladder in &self.ladders {
+            let (Some(&a), Some(&b)) = (strand_of.get(&ladder.first_strand[0]), strand_of.get(&ladder.second_strand[0])) else { continue };
+            if a == b || partners[a].iter().any(|&(p, _)| p == b) { continue }
+            partners[a].push((b, ladder.bridge_type));
+            partners[b].push((a, ladder.bridge_type));
+        }
+
+        let mut out: Vec<PdbSheet> = vec![];
+        for sheet in self.sheets() {
+            let mut left: Vec<usize> = (0..strands.len())
+                .filter(|&s| self.residues[strands[s].start].sheet == Some(sheet)).collect();
+            // --- the order of strands: each one paired with the previous one if possible, starting from an edge
+            let mut order: Vec<(usize, i32)> = vec![];
+            while !left.is_empty() {
+                let prev = order.last().map(|&(s, _)| s);
+                let next = prev.and_then(|p| partners[p].iter().find(|(s, _)| left.contains(s)).cloned())
+                    .or_else(|| order.iter().rev().find_map(|&(o, _)| partners[o].iter().find(|(s, _)| left.contains(s)).cloned()));
+                let (strand, sense) = match next {
+                    Some((s, BridgeType::Parallel)) => (s, 1),
+                    Some((s, BridgeType::Antiparallel)) => (s, -1),
+                    None => (*left.iter().min_by_key(|&&s| partners[s].len()).unwrap(), 0),
+                };
+                left.retain(|&s| s != strand);
+                order.push((strand, sense));
             }
-            if bb_h_bonds.is_parallel_bridge(res_i, res_j) {
-                secondary_structure[i] = 'E';
-                secondary_structure[j] = 'E';
+            for (k, &(s, sense)) in order.iter().enumerate() {
+                let (first, last) = (&self.residues[strands[s].start], &self.residues[strands[s].end - 1]);
+                out.push(PdbSheet { strand: k as i32 + 1, sheet_id: sheet.to_string(), num_strands: order.len() as i32,
+                    sense, init_res_name: first.res_name.clone(), init_chain_id: first.res_id.chain_id.clone(),
+                    init_seq_num: first.res_id.res_seq, init_i_code: first.res_id.i_code,
+                    end_res_name: last.res_name.clone(), end_chain_id: last.res_id.chain_id.clone(),
+                    end_seq_num: last.res_id.res_seq, end_i_code: last.res_id.i_code });
             }
         }
+
+        return out;
     }
+}
+
+/// Links ladders separated by a beta bulge, following the DSSP rules
+fn merge_bulges(ladders: &mut Vec<LadderIndexes>, no_break: &dyn Fn(usize, usize) -> bool) {
+    let mut i = 0;
+    while i < ladders.len() {
+        let mut j = i + 1;
+        while j < ladders.len() {
+            let (a, b) = (&ladders[i], &ladders[j]);
+            let (ibi, iei, jbi, jei) = (a.i[0], *a.i.back().unwrap(), a.j[0], *a.j.back().unwrap());
+            let (ibj, iej, jbj, jej) = (b.i[0], *b.i.back().unwrap(), b.j[0], *b.j.back().unwrap());
+            let gap = |from: usize, to: usize| to.checked_sub(from);
+            let i_gap = gap(iei, ibj);
+            let mergeable = a.bridge_type == b.bridge_type
+                && no_break(ibi.min(ibj), iei.max(iej)) && no_break(jbi.min(jbj), jei.max(jej))
+                && i_gap.is_some_and(|g| g < 6) && !(iei >= ibj && ibi <= iej);
+            let j_gap = if a.bridge_type == BridgeType::Parallel { gap(jei, jbj) } else { gap(jej, jbi) };
+            let bulge = mergeable && j_gap.is_some_and(|jg| (jg < 6 && i_gap.unwrap() < 3) || jg < 3);
+            if bulge {
+                let b = ladders.remove(j);
+                let a = &mut ladders[i];
+                a.i.extend(b.i);
+                if a.bridge_type == BridgeType::Parallel {
+                    a.j.extend(b.j);
+                } else {
+                    for r in b.j.into_iter().rev() { a.j.push_front(r); }
+                }
+            } else { j += 1; }
+        }
+        i += 1;
+    }
+}
+
+/// Two ladders are linked when they share a residue
+fn linked(a: &LadderIndexes, b: &LadderIndexes) -> bool {
+    a.i.iter().chain(a.j.iter()).any(|r| b.i.contains(r) || b.j.contains(r))
+}
 
-    // Convert the secondary structure to a string
-    secondary_structure.into_iter().collect()
+/// Keeps the two lowest-energy bonds
+fn insert_bond(best: &mut [(isize, f64); 2], bond: (isize, f64)) {
+    if bond.1 < best[0].1 {
+        best[1] = best[0];
+        best[0] = bond;
+    } else if bond.1 < best[1].1 { best[1] = bond; }
 }
 
+/// Solvent accessibility of the given residues, computed for their heavy atoms only
+fn residue_accessibility(strctr: &Structure, res_ids: &[ResidueId]) -> Vec<f64> {
+    let mut atoms: Vec<&PdbAtom> = vec![];
+    let mut residue_of: Vec<usize> = vec![];
+    for (i, res_id) in res_ids.iter().enumerate() {
+        if let Ok(it) = strctr.atoms_in_residue(res_id) {
+            for a in it.filter(|a| !is_hydrogen(a)) {
+                atoms.push(a);
+                residue_of.push(i);
+            }
+        }
+    }
+    let mut out = vec![0.0; res_ids.len()];
+    for (area, i) in solvent_accessible_area(&atoms, SASA_SPHERE_POINTS).iter().zip(residue_of) { out[i] += area; }
+
+    return out;
+}
 
+/// Implements the DSSP algorithm to detect the secondary structure of a protein.
+///
+/// This function provides a simplified, five-letter summary of a [`DsspAssignment`]:
+///
+/// # Returns
+/// A String representing the secondary structure, where each character corresponds to a residue:
+/// * 'H' - Alpha-helix
+/// * 'G' - 3-10 helix
+/// * 'I' - Pi-helix
+/// * 'E' - Beta-strand or an isolated beta bridge
+/// * 'C' - Coil (not part of a helix or a sheet)
+pub fn dssp(bb_h_bonds: &BackboneHBondMap) -> String {
+    DsspAssignment::new(bb_h_bonds).residues().iter().map(|r| match r.ss {
+        'H' | 'G' | 'I' | 'E' => r.ss,
+        'B' => 'E',
+        _ => 'C',
+    }).collect()
+}
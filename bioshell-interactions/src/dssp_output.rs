@@ -0,0 +1,165 @@
+use std::io::Write;
+use bioshell_cif::CifLoop;
+use bioshell_pdb::{PDBError, ResidueId};
+use crate::{BridgeType, DsspAssignment, DsspResidue};
+
+const CLASSIC_HEADER: &str = "  #  RESIDUE AA STRUCTURE BP1 BP2  ACC     N-H-->O    O-->H-N    N-H-->O    O-->H-N    TCO  KAPPA ALPHA  PHI   PSI    X-CA   Y-CA   Z-CA";
+
+/// Writes a DSSP assignment in the classic DSSP format.
+///
+/// Residues are numbered sequentially; a chain break is marked by an extra line with the `!` character,
+/// followed by `*` when a new chain starts. Bridge partners and H-bond offsets refer to this numbering.
+/// Angles that can't be computed are reported as 360.0, as in the original program.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{write_dssp_classic, BackboneHBondMap, DsspAssignment};
+/// let strctr = Deposit::from_file("./tests/input_files/2gb1.cif")?.structure()?;
+/// let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+/// let mut out: Vec<u8> = vec![];
+/// write_dssp_classic(&mut out, &dssp)?;
+/// let text = String::from_utf8(out).unwrap();
+/// assert!(text.lines().any(|l| l.starts_with("  #  RESIDUE AA STRUCTURE")));
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_dssp_classic<W: Write>(out: &mut W, dssp: &DsspAssignment) -> Result<(), PDBError> {
+    let residues = dssp.residues();
+    let numbers = dssp_numbers(residues);
+    let n_chains = residues.iter().enumerate()
+        .filter(|(i, r)| *i == 0 || r.res_id.chain_id != residues[i - 1].res_id.chain_id).count();
+    let area: f64 = residues.iter().map(|r| r.accessibility).sum();
+
+    writeln!(out, "==== Secondary Structure Definition by the program DSSP, BioShell implementation ====")?;
+    writeln!(out, "REFERENCE W. KABSCH AND C.SANDER, BIOPOLYMERS 22 (1983) 2577-2637")?;
+    writeln!(out, "HEADER    {}", dssp.id_code())?;
+    writeln!(out, "{:5}{:3} TOTAL NUMBER OF RESIDUES, NUMBER OF CHAINS", residues.len(), n_chains)?;
+    writeln!(out, "{:8.1}   ACCESSIBLE SURFACE OF PROTEIN (ANGSTROM**2)", area)?;
+    writeln!(out, "{}", CLASSIC_HEADER)?;
+
+    for (i, r) in residues.iter().enumerate() {
+        if r.chain_break && i > 0 {
+            let new_chain = if r.res_id.chain_id != residues[i - 1].res_id.chain_id { '*' } else { ' ' };
+            writeln!(out, "{:5}        !{}             0   0    0      0, 0.0     0, 0.0     0, 0.0     0, 0.0   0.000 360.0 360.0 360.0 360.0    0.0    0.0    0.0",
+                     numbers[i] - 1, new_chain)?;
+        }
+        let hbond = |(offset, energy): (isize, f64)| {
+            let offset = if offset == 0 { 0 } else { numbers[(i as isize + offset) as usize] as isize - numbers[i] as isize };
+            format!("{:6},{:4.1}", offset, energy)
+        };
+        let partner = |k: usize| r.bridge_partners[k].as_ref().map_or((' ', 0), |p| (p.ladder, numbers[p.index]));
+        let ((l1, bp1), (l2, bp2)) = (partner(0), partner(1));
+        let angle = |v: Option<f64>| v.unwrap_or(360.0);
+        let ca = r.ca.map_or([0.0; 3], |v| [v.x, v.y, v.z]);
+        writeln!(out, "{:5}{:5}{}{} {}  {} {}{}{}{}{}{}{}{:4}{:4}{}{:4} {:>11}{:>11}{:>11}{:>11}  {:6.3}{:6.1}{:6.1}{:6.1}{:6.1} {:6.1} {:6.1} {:6.1}",
+                 numbers[i], r.res_id.res_seq, r.res_id.i_code, r.res_id.chain_id.chars().next().unwrap_or(' '),
+                 r.code1.to_ascii_uppercase(), r.ss, r.helix_flags[0], r.helix_flags[1], r.helix_flags[2],
+                 if r.bend { 'S' } else { ' ' }, r.chirality, l1, l2, bp1, bp2, r.sheet.unwrap_or(' '),
+                 r.accessibility.round() as i32, hbond(r.nh_o[0]), hbond(r.o_hn[0]), hbond(r.nh_o[1]), hbond(r.o_hn[1]),
+                 r.tco.unwrap_or(0.0), angle(r.kappa), angle(r.alpha), angle(r.phi), angle(r.psi), ca[0], ca[1], ca[2])?;
+    }
+
+    return Ok(());
+}
+
+/// Writes a DSSP assignment in the mmCIF format, as data items of the DSSP extension dictionary.
+///
+/// Three loops are written: `_dssp_struct_summary` with per-residue records, `_dssp_struct_bridge_pairs`
+/// listing bridge partners of every bridged residue, and `_dssp_struct_ladder` describing each ladder.
+/// The register of a ladder is given by its ends: the `beg_2` residue is paired with `beg_1`
+/// and `end_2` with `end_1`. Residues are identified by their author-defined chain ID, residue number
+/// and insertion code.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{write_dssp_mmcif, BackboneHBondMap, DsspAssignment};
+/// let strctr = Deposit::from_file("./tests/input_files/2gb1.cif")?.structure()?;
+/// let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+/// let mut out: Vec<u8> = vec![];
+/// write_dssp_mmcif(&mut out, &dssp)?;
+/// let text = String::from_utf8(out).unwrap();
+/// assert!(text.starts_with("data_2GB1"));
+/// assert!(text.contains("_dssp_struct_ladder.sheet_id"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_dssp_mmcif<W: Write>(out: &mut W, dssp: &DsspAssignment) -> Result<(), PDBError> {
+    let or_dot = |c: char| if c == ' ' { ".".to_string() } else { c.to_string() };
+    let value = |v: Option<f64>, precision: usize| v.map_or(".".to_string(), |v| format!("{:.*}", precision, v));
+
+    let mut summary = cif_loop("_dssp_struct_summary", &["entry_id", "label_comp_id", "auth_asym_id", "auth_seq_id",
+        "pdbx_PDB_ins_code", "secondary_structure", "helix_3_10", "helix_alpha", "helix_pi", "helix_pp", "bend",
+        "chirality", "sheet", "ladder_1", "ladder_2", "accessibility", "TCO", "kappa", "alpha", "phi", "psi",
+        "x_ca", "y_ca", "z_ca"]);
+    let mut pairs = cif_loop("_dssp_struct_bridge_pairs", &["id", "auth_asym_id", "auth_seq_id", "pdbx_PDB_ins_code",
+        "ladder_1", "partner_1_auth_asym_id", "partner_1_auth_seq_id", "partner_1_PDB_ins_code",
+        "ladder_2", "partner_2_auth_asym_id", "partner_2_auth_seq_id", "partner_2_PDB_ins_code"]);
+    for r in dssp.residues() {
+        let ladder = |k: usize| r.bridge_partners[k].as_ref().map_or(".".to_string(), |p| p.ladder.to_string());
+        let mut row = vec![dssp.id_code().to_string(), r.res_name.clone()];
+        row.extend(residue_columns(&r.res_id));
+        row.extend([or_dot(r.ss), or_dot(r.helix_flags[0]), or_dot(r.helix_flags[1]), or_dot(r.helix_flags[2]),
+            if r.ss == 'P' { "P".to_string() } else { ".".to_string() }, if r.bend { "S".to_string() } else { ".".to_string() },
+            or_dot(r.chirality), r.sheet.map_or(".".to_string(), |s| s.to_string()), ladder(0), ladder(1),
+            format!("{:.0}", r.accessibility), value(r.tco, 3), value(r.kappa, 1), value(r.alpha, 1),
+            value(r.phi, 1), value(r.psi, 1), value(r.ca.map(|v| v.x), 3), value(r.ca.map(|v| v.y), 3),
+            value(r.ca.map(|v| v.z), 3)]);
+        summary.add_data_row(row)?;
+
+        if r.bridge_partners[0].is_none() { continue; }
+        let mut row = vec![(pairs.count_rows() + 1).to_string()];
+        row.extend(residue_columns(&r.res_id));
+        for p in &r.bridge_partners {
+            match p {
+                Some(p) => {
+                    row.push(p.ladder.to_string());
+                    row.extend(residue_columns(&p.res_id));
+                }
+                None => row.extend(vec![".".to_string(); 4]),
+            }
+        }
+        pairs.add_data_row(row)?;
+    }
+
+    let mut ladders = cif_loop("_dssp_struct_ladder", &["id", "sheet_id", "type", "beg_1_auth_asym_id", "beg_1_auth_seq_id",
+        "beg_1_PDB_ins_code", "end_1_auth_asym_id", "end_1_auth_seq_id", "end_1_PDB_ins_code", "beg_2_auth_asym_id",
+        "beg_2_auth_seq_id", "beg_2_PDB_ins_code", "end_2_auth_asym_id", "end_2_auth_seq_id", "end_2_PDB_ins_code"]);
+    for l in dssp.ladders() {
+        let bridge_type = if l.bridge_type == BridgeType::Parallel { "parallel" } else { "anti-parallel" };
+        let mut row = vec![l.label.to_string(), l.sheet.to_string(), bridge_type.to_string()];
+        for res_id in [&l.first_strand[0], l.first_strand.last().unwrap(), &l.second_strand[0], l.second_strand.last().unwrap()] {
+            row.extend(residue_columns(res_id));
+        }
+        ladders.add_data_row(row)?;
+    }
+
+    writeln!(out, "data_{}", dssp.id_code())?;
+    for a_loop in [summary, pairs, ladders] {
+        writeln!(out, "#")?;
+        write!(out, "{}", a_loop)?;
+    }
+
+    return Ok(());
+}
+
+/// Sequential residue numbers used by the classic DSSP format, where each chain break takes one number
+fn dssp_numbers(residues: &[DsspResidue]) -> Vec<usize> {
+    let mut number = 0;
+    residues.iter().enumerate().map(|(i, r)| {
+        number += if r.chain_break && i > 0 { 2 } else { 1 };
+        number
+    }).collect()
+}
+
+fn cif_loop(category: &str, items: &[&str]) -> CifLoop {
+    let names: Vec<String> = items.iter().map(|i| format!("{}.{}", category, i)).collect();
+    return CifLoop::new(&names.iter().map(|n| n.as_str()).collect::<Vec<&str>>());
+}
+
+/// Chain ID, residue number and insertion code of a residue; a missing insertion code is given as `?`
+fn residue_columns(res_id: &ResidueId) -> Vec<String> {
+    let i_code = if res_id.i_code == ' ' { "?".to_string() } else { res_id.i_code.to_string() };
+    return vec![res_id.chain_id.clone(), res_id.res_seq.to_string(), i_code];
+}
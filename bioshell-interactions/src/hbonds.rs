@@ -0,0 +1,394 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use bioshell_core::{planar_angle3, Vec3};
+use bioshell_pdb::{is_hydrogen, PdbAtom, ResidueId, Structure};
+use bioshell_pdb::calc::NeighborGrid;
+use bioshell_pdb::pdb_atom_filters::{KeepProtein, PdbAtomPredicate};
+
+/// Role an atom may play in a hydrogen bond
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HBondRole {
+    /// the atom carries a hydrogen it may donate
+    Donor,
+    /// the atom may accept a hydrogen
+    Acceptor,
+    /// the atom may either donate or accept a hydrogen, e.g. a hydroxyl oxygen
+    Both,
+}
+
+impl HBondRole {
+    /// Returns `true` if this role allows donating a hydrogen
+    pub fn can_donate(&self) -> bool { *self != HBondRole::Acceptor }
+
+    /// Returns `true` if this role allows accepting a hydrogen
+    pub fn can_accept(&self) -> bool { *self != HBondRole::Donor }
+}
+
+/// Donors and acceptors of standard amino acids and water.
+///
+/// Each entry provides a residue name, an atom name, the role of that atom and its antecedent, i.e. the heavy atom
+/// it is covalently bonded to, which defines the direction of a hydrogen bond. The `*` residue name
+/// matches any amino acid; residue-specific entries take precedence.
+pub const HBOND_ATOMS: [(&str, &str, HBondRole, &str); 26] = [
+    ("*", "N", HBondRole::Donor, "CA"),
+    ("*", "O", HBondRole::Acceptor, "C"),
+    ("*", "OXT", HBondRole::Acceptor, "C"),
+    ("ARG", "NE", HBondRole::Donor, "CD"),
+    ("ARG", "NH1", HBondRole::Donor, "CZ"),
+    ("ARG", "NH2", HBondRole::Donor, "CZ"),
+    ("ASN", "OD1", HBondRole::Acceptor, "CG"),
+    ("ASN", "ND2", HBondRole::Donor, "CG"),
+    ("ASP", "OD1", HBondRole::Acceptor, "CG"),
+    ("ASP", "OD2", HBondRole::Acceptor, "CG"),
+    ("CYS", "SG", HBondRole::Both, "CB"),
+    ("GLN", "OE1", HBondRole::Acceptor, "CD"),
+    ("GLN", "NE2", HBondRole::Donor, "CD"),
+    ("GLU", "OE1", HBondRole::Acceptor, "CD"),
+    ("GLU", "OE2", HBondRole::Acceptor, "CD"),
+    ("HIS", "ND1", HBondRole::Both, "CG"),
+    ("HIS", "NE2", HBondRole::Both, "CE1"),
+    ("LYS", "NZ", HBondRole::Donor, "CE"),
+    ("MET", "SD", HBondRole::Acceptor, "CG"),
+    ("SER", "OG", HBondRole::Both, "CB"),
+    ("THR", "OG1", HBondRole::Both, "CB"),
+    ("TRP", "NE1", HBondRole::Donor, "CD1"),
+    ("TYR", "OH", HBondRole::Both, "CZ"),
+    ("HOH", "O", HBondRole::Both, ""),
+    ("WAT", "O", HBondRole::Both, ""),
+    ("DOD", "O", HBondRole::Both, ""),
+];
+
+/// Atoms matched by a `*` entry of [`HBOND_ATOMS`] that nevertheless form no hydrogen bonds: proline nitrogen has no hydrogen
+const NON_POLAR_ATOMS: [(&str, &str); 1] = [("PRO", "N")];
+
+/// Names of water residues
+const WATERS: [&str; 3] = ["HOH", "WAT", "DOD"];
+
+/// Names of protein backbone atoms that may form a hydrogen bond
+const BACKBONE_ATOMS: [&str; 3] = ["N", "O", "OXT"];
+
+/// Largest distance between a heavy atom and a hydrogen bonded to it
+const MAX_X_H_BOND: f64 = 1.3;
+
+/// Largest length of a covalent bond between two heavy atoms; used to find antecedents of ligand atoms
+const MAX_COVALENT_BOND: f64 = 2.0;
+
+/// Location of an atom involved in a hydrogen bond
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HBondSite {
+    /// protein backbone: amide nitrogen or carbonyl oxygen
+    Backbone,
+    /// side chain of an amino acid
+    SideChain,
+    /// water molecule
+    Water,
+    /// any other molecule, e.g. a ligand or a nucleotide
+    Ligand,
+}
+
+/// An atom that donates or accepts a hydrogen bond
+#[derive(Clone, Debug)]
+pub struct HBondAtom {
+    /// residue the atom belongs to
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    /// name of the atom
+    pub atom_name: String,
+    /// location of the atom
+    pub site: HBondSite,
+}
+
+impl Display for HBondAtom {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {:4}", self.res_id, self.res_name, self.atom_name.trim())
+    }
+}
+
+/// A hydrogen bond between any two atoms of a structure.
+///
+/// All the angles are given in degrees. The hydrogen-based quantities: `distance_AH` and `angle_AHD`
+/// are available only when the donated hydrogen is present in the structure.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug)]
+pub struct HBond {
+    /// the donor atom
+    pub donor: HBondAtom,
+    /// the acceptor atom
+    pub acceptor: HBondAtom,
+    /// distance between the donor and the acceptor
+    pub distance_DA: f64,
+    /// distance between the donated hydrogen and the acceptor
+    pub distance_AH: Option<f64>,
+    /// planar angle between the acceptor, the donated hydrogen and the donor
+    pub angle_AHD: Option<f64>,
+    /// planar angle between the donor antecedent, the donor and the acceptor
+    pub angle_XDA: Option<f64>,
+    /// planar angle between the acceptor antecedent, the acceptor and the donor
+    pub angle_PAD: Option<f64>,
+}
+
+impl HBond {
+    /// Returns `true` if a water molecule takes part in this hydrogen bond
+    pub fn involves_water(&self) -> bool {
+        self.donor.site == HBondSite::Water || self.acceptor.site == HBondSite::Water
+    }
+
+    /// Returns `true` if a ligand takes part in this hydrogen bond
+    pub fn involves_ligand(&self) -> bool {
+        self.donor.site == HBondSite::Ligand || self.acceptor.site == HBondSite::Ligand
+    }
+}
+
+impl Display for HBond {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fmt_opt = |v: Option<f64>, precision: usize| v.map_or("    -".to_string(), |v| format!("{:5.*}", precision, v));
+        write!(f, "{} -> {} {:?}-{:?} {:5.2} {} {} {} {}", self.donor, self.acceptor, self.donor.site,
+               self.acceptor.site, self.distance_DA, fmt_opt(self.distance_AH, 2),
+               fmt_opt(self.angle_AHD, 1), fmt_opt(self.angle_XDA, 1), fmt_opt(self.angle_PAD, 1))
+    }
+}
+
+/// Two atoms linked by hydrogen bonds to the same water molecule
+#[derive(Clone, Debug)]
+pub struct WaterBridge {
+    /// the first bridged atom
+    pub first: HBondAtom,
+    /// the water molecule
+    pub water: ResidueId,
+    /// the second bridged atom
+    pub second: HBondAtom,
+}
+
+impl Display for WaterBridge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ~ {} HOH ~ {}", self.first, self.water, self.second)
+    }
+}
+
+/// Detects hydrogen bonds between any atoms of a structure: protein backbone, side chains, water and ligands.
+///
+/// Donors and acceptors of standard amino acids and water are defined by the [`HBOND_ATOMS`] table.
+/// Protonation of ligand atoms is unknown; every nitrogen and oxygen of a ligand is therefore
+/// considered both a donor and an acceptor, its antecedent being the closest heavy atom of the same residue.
+///
+/// A donor D and an acceptor A form a hydrogen bond when:
+///
+///  - their distance `distance_DA` is between [`min_distance_DA`](HBondDetector::min_distance_DA) and
+///    [`max_distance_DA`](HBondDetector::max_distance_DA)
+///  - the angles X-D..A and P-A..D, where X and P are the antecedents of D and A, respectively, are
+///    not smaller than [`min_antecedent_angle`](HBondDetector::min_antecedent_angle)
+///  - when hydrogens of the donor's residue are given explicitly, at least one hydrogen H of the donor satisfies both
+///    `distance_AH` <= [`max_distance_AH`](HBondDetector::max_distance_AH)
+///    and `angle_AHD` >= [`min_angle_AHD`](HBondDetector::min_angle_AHD); donors lacking hydrogens in such
+///    a residue, e.g. the SG atom of a cysteine forming a disulfide bond, are skipped. Residues deposited without
+///    hydrogens, such as ligands or water molecules of an otherwise protonated structure, donate without that test
+///
+/// Atoms of the same residue are never H-bonded; backbone atoms of neighbouring residues are not H-bonded either.
+/// A pair of atoms that may both donate and accept is reported only once.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{HBondDetector, HBondSite};
+/// let strctr = Deposit::from_file("./tests/input_files/2gb1.cif")?.structure()?;
+/// let hbonds = HBondDetector::default().find(&strctr);
+/// let side_chain = hbonds.iter()
+///     .filter(|h| h.donor.site == HBondSite::SideChain || h.acceptor.site == HBondSite::SideChain).count();
+/// assert!(side_chain > 0);
+/// # Ok(())
+/// # }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Clone, Debug)]
+pub struct HBondDetector {
+    /// the smallest donor-acceptor distance (in Angstroms)
+    pub min_distance_DA: f64,
+    /// the largest donor-acceptor distance (in Angstroms)
+    pub max_distance_DA: f64,
+    /// the largest hydrogen-acceptor distance (in Angstroms), used when hydrogens are present
+    pub max_distance_AH: f64,
+    /// the smallest acceptor-hydrogen-donor angle (in degrees), used when hydrogens are present
+    pub min_angle_AHD: f64,
+    /// the smallest angle between an antecedent, the donor or acceptor atom and its partner (in degrees)
+    pub min_antecedent_angle: f64,
+}
+
+impl Default for HBondDetector {
+    fn default() -> Self {
+        HBondDetector { min_distance_DA: 2.4, max_distance_DA: 3.5, max_distance_AH: 2.5, min_angle_AHD: 120.0,
+            min_antecedent_angle: 90.0 }
+    }
+}
+
+/// Finds hydrogen bonds in a structure with the default [`HBondDetector`] criteria
+pub fn find_hbonds(strctr: &Structure) -> Vec<HBond> { HBondDetector::default().find(strctr) }
+
+/// A polar atom of a structure, annotated for hydrogen bond detection
+struct PolarAtom {
+    index: usize,
+    residue: usize,
+    role: HBondRole,
+    site: HBondSite,
+    antecedent: Option<usize>,
+}
+
+impl HBondDetector {
+
+    /// Finds all hydrogen bonds in a given structure.
+    ///
+    /// H-bonds are sorted by their donor residue, in the order of atoms of the structure.
+    pub fn find(&self, strctr: &Structure) -> Vec<HBond> {
+        let atoms = strctr.atoms();
+        let residues = residue_ranges(atoms);
+        let polar = polar_atoms(atoms, &residues);
+        let acceptors: Vec<&PolarAtom> = polar.iter().filter(|p| p.role.can_accept()).collect();
+        let positions: Vec<&Vec3> = acceptors.iter().map(|p| &atoms[p.index].pos).collect();
+        let grid = NeighborGrid::new(&positions, self.max_distance_DA);
+
+        let has_hydrogens: Vec<bool> = residues.iter().map(|r| atoms[r.clone()].iter().any(is_hydrogen)).collect();
+        let mut reported: HashSet<(usize, usize)> = HashSet::new();
+        let mut out: Vec<HBond> = vec![];
+        for d in polar.iter().filter(|p| p.role.can_donate()) {
+            let d_atom = &atoms[d.index];
+            let hydrogens: Vec<&Vec3> = atoms[residues[d.residue].clone()].iter()
+                .filter(|a| is_hydrogen(a) && a.pos.distance_to(&d_atom.pos) <= MAX_X_H_BOND).map(|a| &a.pos).collect();
+            // --- when hydrogens of a residue are given, only a donor that has one can donate
+            if has_hydrogens[d.residue] && hydrogens.is_empty() { continue; }
+            let mut partners = grid.neighbors(&d_atom.pos, self.max_distance_DA);
+            partners.sort();
+            for a in partners.into_iter().map(|i| acceptors[i]) {
+                if a.residue == d.residue { continue; }
+                if d.site == HBondSite::Backbone && a.site == HBondSite::Backbone
+                    && a.residue.abs_diff(d.residue) == 1 && atoms[a.index].chain_id == d_atom.chain_id { continue; }
+                let pair = (d.index.min(a.index), d.index.max(a.index));
+                if reported.contains(&pair) { continue; }
+                let a_atom = &atoms[a.index];
+                let distance_da = d_atom.pos.distance_to(&a_atom.pos);
+                if distance_da < self.min_distance_DA { continue; }
+
+                let angle_xda = d.antecedent.map(|x| planar_angle3(&atoms[x].pos, &d_atom.pos, &a_atom.pos).to_degrees());
+                let angle_pad = a.antecedent.map(|p| planar_angle3(&atoms[p].pos, &a_atom.pos, &d_atom.pos).to_degrees());
+                if angle_xda.is_some_and(|v| v < self.min_antecedent_angle) { continue; }
+                if angle_pad.is_some_and(|v| v < self.min_antecedent_angle) { continue; }
+
+                // --- the best of explicit hydrogens, if any
+                let (mut distance_ah, mut angle_ahd) = (None, None);
+                if !hydrogens.is_empty() {
+                    let best = hydrogens.iter()
+                        .map(|h| (h.distance_to(&a_atom.pos), planar_angle3(&a_atom.pos, h, &d_atom.pos).to_degrees()))
+                        .filter(|(dist, angle)| *dist <= self.max_distance_AH && *angle >= self.min_angle_AHD)
+                        .min_by(|x, y| x.0.total_cmp(&y.0));
+                    match best {
+                        Some((dist, angle)) => { distance_ah = Some(dist); angle_ahd = Some(angle); }
+                        None => continue,
+                    }
+                }
+
+                reported.insert(pair);
+                out.push(HBond { donor: hbond_atom(d_atom, d.site), acceptor: hbond_atom(a_atom, a.site),
+                    distance_DA: distance_da, distance_AH: distance_ah, angle_AHD: angle_ahd,
+                    angle_XDA: angle_xda, angle_PAD: angle_pad });
+            }
+        }
+
+        return out;
+    }
+}
+
+/// Finds pairs of atoms bridged by a water molecule.
+///
+/// A water bridge is recorded for every two non-water atoms of different residues, that are hydrogen bonded
+/// to the same water molecule, regardless the direction of these bonds.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{find_hbonds, find_water_bridges};
+/// let strctr = Deposit::from_file("./tests/input_files/1c5n.cif")?.structure()?;
+/// let bridges = find_water_bridges(&find_hbonds(&strctr));
+/// assert!(bridges.iter().all(|b| b.first.res_id != b.second.res_id));
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_water_bridges(hbonds: &[HBond]) -> Vec<WaterBridge> {
+    let mut partners: HashMap<&ResidueId, Vec<&HBondAtom>> = HashMap::new();
+    let mut waters: Vec<&ResidueId> = vec![];
+    for hb in hbonds {
+        let (water, other) = match (hb.donor.site, hb.acceptor.site) {
+            (HBondSite::Water, HBondSite::Water) => continue,
+            (HBondSite::Water, _) => (&hb.donor, &hb.acceptor),
+            (_, HBondSite::Water) => (&hb.acceptor, &hb.donor),
+            _ => continue,
+        };
+        if !partners.contains_key(&water.res_id) { waters.push(&water.res_id); }
+        partners.entry(&water.res_id).or_default().push(other);
+    }
+
+    let mut out: Vec<WaterBridge> = vec![];
+    for water in waters {
+        let atoms = &partners[water];
+        for i in 0..atoms.len() {
+            for j in i + 1..atoms.len() {
+                if atoms[i].res_id == atoms[j].res_id { continue; }
+                out.push(WaterBridge { first: atoms[i].clone(), water: water.clone(), second: atoms[j].clone() });
+            }
+        }
+    }
+
+    return out;
+}
+
+fn hbond_atom(a: &PdbAtom, site: HBondSite) -> HBondAtom {
+    HBondAtom { res_id: ResidueId::from(a), res_name: a.res_name.clone(), atom_name: a.name.clone(), site }
+}
+
+/// Splits atoms of a structure into ranges, each holding atoms of a single residue
+fn residue_ranges(atoms: &[PdbAtom]) -> Vec<Range<usize>> {
+    let mut out: Vec<Range<usize>> = vec![];
+    let mut first = 0;
+    for i in 1..=atoms.len() {
+        if i == atoms.len() || ResidueId::from(&atoms[i]) != ResidueId::from(&atoms[first]) {
+            out.push(first..i);
+            first = i;
+        }
+    }
+
+    return out;
+}
+
+/// Finds donors and acceptors among atoms of a structure, together with their antecedents
+fn polar_atoms(atoms: &[PdbAtom], residues: &[Range<usize>]) -> Vec<PolarAtom> {
+    let mut out: Vec<PolarAtom> = vec![];
+    for (ires, range) in residues.iter().enumerate() {
+        let res_atoms = &atoms[range.clone()];
+        let res_name = res_atoms[0].res_name.as_str();
+        let is_water = WATERS.contains(&res_name);
+        let is_protein = !is_water && KeepProtein.check(&res_atoms[0]);
+        let find = |name: &str| res_atoms.iter().position(|a| a.name.trim() == name).map(|i| i + range.start);
+        for (i, a) in res_atoms.iter().enumerate() {
+            let name = a.name.trim();
+            if is_water || is_protein {
+                if NON_POLAR_ATOMS.contains(&(res_name, name)) { continue; }
+                let entry = HBOND_ATOMS.iter().find(|e| e.0 == res_name && e.1 == name)
+                    .or_else(|| if is_protein { HBOND_ATOMS.iter().find(|e| e.0 == "*" && e.1 == name) } else { None });
+                let Some((_, _, role, antecedent)) = entry else { continue };
+                let site = if is_water { HBondSite::Water }
+                    else if BACKBONE_ATOMS.contains(&name) { HBondSite::Backbone } else { HBondSite::SideChain };
+                out.push(PolarAtom { index: range.start + i, residue: ires, role: *role, site, antecedent: find(antecedent) });
+            } else {
+                if is_hydrogen(a) || !(name.starts_with('N') || name.starts_with('O')) { continue; }
+                if a.element.as_ref().is_some_and(|e| e != "N" && e != "O") { continue; }
+                let antecedent = res_atoms.iter().enumerate()
+                    .filter(|(j, b)| *j != i && !is_hydrogen(b))
+                    .map(|(j, b)| (j, b.pos.distance_to(&a.pos)))
+                    .filter(|(_, d)| *d <= MAX_COVALENT_BOND)
+                    .min_by(|x, y| x.1.total_cmp(&y.1)).map(|(j, _)| j + range.start);
+                out.push(PolarAtom { index: range.start + i, residue: ires, role: HBondRole::Both, site: HBondSite::Ligand, antecedent });
+            }
+        }
+    }
+
+    return out;
+}
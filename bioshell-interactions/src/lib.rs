@@ -1,10 +1,22 @@
-//! Discovers and describes hydrogen bonds in protein molecules.
+//! Discovers and describes hydrogen bonds and other non-covalent interactions in protein molecules.
 
 mod backbone_hb_map;
 mod dssp;
+mod dssp_output;
+mod disulfide;
+mod salt_bridges;
+mod aromatic;
+mod metal_coordination;
+mod hbonds;
 
 pub use backbone_hb_map::*;
 pub use dssp::*;
+pub use dssp_output::*;
+pub use disulfide::*;
+pub use salt_bridges::*;
+pub use aromatic::*;
+pub use metal_coordination::*;
+pub use hbonds::*;
 
 /// Define the maximum allowed distance between a hydrogen atom and its acceptor to still record a hydrogen bond
 const MAX_AH_DISTANCE: f64 = 3.0;
@@ -0,0 +1,217 @@
+use std::fmt::{Display, Formatter};
+use bioshell_core::{planar_angle3, Vec3};
+use bioshell_pdb::{atom_element, PdbAtom, ResidueId, Structure, METAL_ELEMENTS};
+use bioshell_pdb::calc::NeighborGrid;
+
+/// Maximum distance between a metal ion and a coordinating nitrogen, oxygen or sulfur atom (in Angstroms)
+pub const METAL_COORDINATION_CUTOFF: f64 = 2.8;
+
+/// Maximum distance between a metal ion and a coordinating carbon atom, e.g. of a carbonyl or cyanide ligand (in Angstroms)
+pub const METAL_CARBON_COORDINATION_CUTOFF: f64 = 2.2;
+
+/// Maximum RMSD between observed and ideal ligand-metal-ligand angles (in degrees); sites deviating more are irregular
+pub const MAX_COORDINATION_ANGLE_RMSD: f64 = 20.0;
+
+/// Geometry of a metal coordination site
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CoordinationGeometry {
+    /// two ligands at 180 degrees
+    Linear,
+    /// three ligands in a plane around the metal
+    TrigonalPlanar,
+    /// three ligands forming a tetrahedron with the metal, one of its vertices being vacant
+    TrigonalPyramidal,
+    /// four ligands at vertices of a tetrahedron
+    Tetrahedral,
+    /// four ligands at corners of a square
+    SquarePlanar,
+    /// five ligands: three in the equatorial plane and two axial
+    TrigonalBipyramidal,
+    /// five ligands: four at corners of a square and one axial
+    SquarePyramidal,
+    /// six ligands at vertices of an octahedron
+    Octahedral,
+    /// six ligands at vertices of a trigonal prism
+    TrigonalPrismatic,
+    /// coordination that matches none of the ideal geometries
+    Irregular,
+}
+
+impl CoordinationGeometry {
+    /// Unit vectors pointing from a metal towards its ligands in this ideal geometry
+    fn ideal_vectors(&self) -> Vec<Vec3> {
+        let (s3, r7) = (3.0f64.sqrt(), 7.0f64.sqrt());
+        let t = 1.0 / s3;
+        let v = |x: f64, y: f64, z: f64| Vec3::new(x, y, z);
+        let v = match self {
+            CoordinationGeometry::Linear => vec![v(1.0, 0.0, 0.0), v(-1.0, 0.0, 0.0)],
+            CoordinationGeometry::TrigonalPlanar => vec![v(1.0, 0.0, 0.0), v(-0.5, s3 / 2.0, 0.0), v(-0.5, -s3 / 2.0, 0.0)],
+            CoordinationGeometry::TrigonalPyramidal => vec![v(t, t, t), v(t, -t, -t), v(-t, t, -t)],
+            CoordinationGeometry::Tetrahedral => vec![v(t, t, t), v(t, -t, -t), v(-t, t, -t), v(-t, -t, t)],
+            CoordinationGeometry::SquarePlanar => vec![v(1.0, 0.0, 0.0), v(0.0, 1.0, 0.0), v(-1.0, 0.0, 0.0), v(0.0, -1.0, 0.0)],
+            CoordinationGeometry::TrigonalBipyramidal => vec![v(1.0, 0.0, 0.0), v(-0.5, s3 / 2.0, 0.0),
+                v(-0.5, -s3 / 2.0, 0.0), v(0.0, 0.0, 1.0), v(0.0, 0.0, -1.0)],
+            CoordinationGeometry::SquarePyramidal => vec![v(1.0, 0.0, 0.0), v(0.0, 1.0, 0.0), v(-1.0, 0.0, 0.0),
+                v(0.0, -1.0, 0.0), v(0.0, 0.0, 1.0)],
+            CoordinationGeometry::Octahedral => vec![v(1.0, 0.0, 0.0), v(-1.0, 0.0, 0.0), v(0.0, 1.0, 0.0),
+                v(0.0, -1.0, 0.0), v(0.0, 0.0, 1.0), v(0.0, 0.0, -1.0)],
+            CoordinationGeometry::TrigonalPrismatic => {
+                // --- radius and half-height chosen so that all the vertices lie on a unit sphere
+                let (r, h) = (2.0 / r7, s3 / r7);
+                (0..6).map(|i| {
+                    let phi = (i % 3) as f64 * 2.0 * std::f64::consts::PI / 3.0;
+                    v(r * phi.cos(), r * phi.sin(), if i < 3 { h } else { -h })
+                }).collect()
+            }
+            CoordinationGeometry::Irregular => vec![],
+        };
+
+        return v;
+    }
+
+    /// Ideal geometries possible for a given number of ligands
+    fn candidates(n_ligands: usize) -> Vec<CoordinationGeometry> {
+        return match n_ligands {
+            2 => vec![CoordinationGeometry::Linear],
+            3 => vec![CoordinationGeometry::TrigonalPlanar, CoordinationGeometry::TrigonalPyramidal],
+            4 => vec![CoordinationGeometry::Tetrahedral, CoordinationGeometry::SquarePlanar],
+            5 => vec![CoordinationGeometry::TrigonalBipyramidal, CoordinationGeometry::SquarePyramidal],
+            6 => vec![CoordinationGeometry::Octahedral, CoordinationGeometry::TrigonalPrismatic],
+            _ => vec![],
+        };
+    }
+}
+
+/// An atom coordinating a metal ion
+#[derive(Clone, Debug)]
+pub struct CoordinatingAtom {
+    /// residue the atom belongs to
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    /// name of the coordinating atom
+    pub atom_name: String,
+    /// chemical element of the coordinating atom
+    pub element: String,
+    /// distance from the metal ion
+    pub distance: f64,
+}
+
+/// A metal ion together with the atoms coordinating it
+#[derive(Clone, Debug)]
+pub struct MetalSite {
+    /// residue of the metal ion
+    pub metal: ResidueId,
+    /// three-letter code of the residue the metal belongs to, e.g. `ZN` or `HEM`
+    pub metal_name: String,
+    /// name of the metal atom
+    pub metal_atom: String,
+    /// chemical element of the metal
+    pub element: String,
+    /// atoms coordinating the metal, sorted by their distance from the metal
+    pub ligands: Vec<CoordinatingAtom>,
+    /// geometry of this site
+    pub geometry: CoordinationGeometry,
+    /// RMSD between observed and ideal ligand-metal-ligand angles (in degrees); 0.0 for irregular sites
+    pub angle_rmsd: f64,
+}
+
+impl MetalSite {
+    /// Coordination number of this metal, i.e. the number of its ligand atoms
+    pub fn coordination_number(&self) -> usize { self.ligands.len() }
+}
+
+impl Display for MetalSite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} {} {:?} {:.1}", self.metal, self.metal_name, self.metal_atom.trim(),
+               self.coordination_number(), self.geometry, self.angle_rmsd)?;
+        for l in &self.ligands {
+            write!(f, "\n  {} {} {} {:4.2}", l.res_id, l.res_name, l.atom_name.trim(), l.distance)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds metal ions in a structure and describes their coordination.
+///
+/// Metals are atoms whose element is listed in [`METAL_ELEMENTS`].
+/// Nitrogen, oxygen and sulfur atoms closer than [`METAL_COORDINATION_CUTOFF`] to a metal ion are considered
+/// its ligands, as well as carbon atoms closer than [`METAL_CARBON_COORDINATION_CUTOFF`]. Hydrogen atoms and
+/// other metal ions are never counted as ligands. The geometry of a site is the ideal polyhedron
+/// whose sorted ligand-metal-ligand angles are the closest to the observed ones; when the angular RMSD
+/// exceeds [`MAX_COORDINATION_ANGLE_RMSD`] or the coordination number is larger than six, the site
+/// is [`Irregular`](CoordinationGeometry::Irregular).
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::find_metal_sites;
+/// let strctr = Deposit::from_file("./tests/input_files/1c5n.cif")?.structure()?;
+/// let sites = find_metal_sites(&strctr);
+/// assert!(sites.iter().any(|s| s.element == "NA"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_metal_sites(strctr: &Structure) -> Vec<MetalSite> {
+    let atoms: Vec<&PdbAtom> = strctr.atoms().iter().collect();
+    let elements: Vec<String> = atoms.iter().map(|a| atom_element(a)).collect();
+    let positions: Vec<&Vec3> = atoms.iter().map(|a| &a.pos).collect();
+    let grid = NeighborGrid::new(&positions, METAL_COORDINATION_CUTOFF);
+
+    let mut out: Vec<MetalSite> = vec![];
+    for (i, metal) in atoms.iter().enumerate() {
+        if !METAL_ELEMENTS.contains(&elements[i].as_str()) { continue; }
+        let mut ligands: Vec<(CoordinatingAtom, Vec3)> = vec![];
+        for j in grid.neighbors(&metal.pos, METAL_COORDINATION_CUTOFF) {
+            let cutoff = match elements[j].as_str() {
+                "N" | "O" | "S" => METAL_COORDINATION_CUTOFF,
+                "C" => METAL_CARBON_COORDINATION_CUTOFF,
+                _ => continue,
+            };
+            let distance = metal.pos.distance_to(&atoms[j].pos);
+            if distance > cutoff { continue; }
+            ligands.push((CoordinatingAtom { res_id: ResidueId::from(atoms[j]), res_name: atoms[j].res_name.clone(),
+                atom_name: atoms[j].name.clone(), element: elements[j].clone(), distance }, atoms[j].pos));
+        }
+        ligands.sort_by(|a, b| a.0.distance.total_cmp(&b.0.distance));
+
+        let ligand_positions: Vec<Vec3> = ligands.iter().map(|l| l.1).collect();
+        let (geometry, angle_rmsd) = classify_geometry(&metal.pos, &ligand_positions);
+        out.push(MetalSite { metal: ResidueId::from(*metal), metal_name: metal.res_name.clone(),
+            metal_atom: metal.name.clone(), element: elements[i].clone(),
+            ligands: ligands.into_iter().map(|l| l.0).collect(), geometry, angle_rmsd });
+    }
+
+    return out;
+}
+
+/// Selects the ideal geometry that best matches the given ligand positions
+fn classify_geometry(metal: &Vec3, ligands: &[Vec3]) -> (CoordinationGeometry, f64) {
+    let observed = sorted_angles(metal, ligands);
+    let mut best = (CoordinationGeometry::Irregular, 0.0);
+    let mut best_rmsd = MAX_COORDINATION_ANGLE_RMSD;
+    for geometry in CoordinationGeometry::candidates(ligands.len()) {
+        let ideal = sorted_angles(&Vec3::from_float(0.0), &geometry.ideal_vectors());
+        let msd = observed.iter().zip(ideal.iter()).map(|(o, i)| (o - i) * (o - i)).sum::<f64>() / observed.len() as f64;
+        let rmsd = msd.sqrt();
+        if rmsd <= best_rmsd {
+            best_rmsd = rmsd;
+            best = (geometry, rmsd);
+        }
+    }
+
+    return best;
+}
+
+/// All ligand-metal-ligand angles, in degrees, sorted in the ascending order
+fn sorted_angles(metal: &Vec3, ligands: &[Vec3]) -> Vec<f64> {
+    let mut angles: Vec<f64> = vec![];
+    for i in 0..ligands.len() {
+        for j in i + 1..ligands.len() {
+            angles.push(planar_angle3(&ligands[i], metal, &ligands[j]).to_degrees());
+        }
+    }
+    angles.sort_by(|a, b| a.total_cmp(b));
+
+    return angles;
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use bioshell_pdb::{PdbAtom, ResidueId, Structure};
+use bioshell_pdb::calc::NeighborGrid;
+use bioshell_pdb::pdb_atom_filters::{IsAnionic, IsCationic, PdbAtomPredicate};
+
+/// Maximum distance between a charged nitrogen and a charged oxygen of a salt bridge (in Angstroms)
+pub const SALT_BRIDGE_CUTOFF: f64 = 4.0;
+
+/// A salt bridge between a positively and a negatively charged residue.
+///
+/// A single record is created for a pair of residues, even when several pairs of their charged atoms
+/// are in contact; the closest of them is stored.
+#[derive(Clone, Debug)]
+pub struct SaltBridge {
+    /// the residue bearing the positive charge: LYS, ARG or HIS
+    pub cation: ResidueId,
+    /// name of the residue bearing the positive charge
+    pub cation_name: String,
+    /// the charged nitrogen of the closest contact
+    pub cation_atom: String,
+    /// the residue bearing the negative charge: ASP, GLU or a C-terminal residue
+    pub anion: ResidueId,
+    /// name of the residue bearing the negative charge
+    pub anion_name: String,
+    /// the charged oxygen of the closest contact
+    pub anion_atom: String,
+    /// distance between the closest pair of charged atoms
+    pub distance: f64,
+    /// the number of charged atom pairs closer than the cutoff distance
+    pub n_contacts: usize,
+}
+
+impl Display for SaltBridge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} - {} {} {} {:5.2} {}", self.cation, self.cation_name, self.cation_atom.trim(),
+               self.anion, self.anion_name, self.anion_atom.trim(), self.distance, self.n_contacts)
+    }
+}
+
+/// Finds salt bridges in a given structure.
+///
+/// Charged atoms are selected with the [`IsCationic`] and [`IsAnionic`] filters; a salt bridge is recorded
+/// when any cationic atom is found closer than `cutoff` to any anionic atom of a different residue.
+/// Bridges are sorted by the cationic residue and then by the anionic one.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_interactions::{find_salt_bridges, SALT_BRIDGE_CUTOFF};
+/// let strctr = Deposit::from_file("./tests/input_files/2gb1.cif")?.structure()?;
+/// let bridges = find_salt_bridges(&strctr, SALT_BRIDGE_CUTOFF);
+/// assert!(bridges.iter().all(|b| b.distance <= SALT_BRIDGE_CUTOFF));
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_salt_bridges(strctr: &Structure, cutoff: f64) -> Vec<SaltBridge> {
+    let anions: Vec<&PdbAtom> = strctr.atoms().iter().filter(|a| IsAnionic.check(a)).collect();
+    let positions: Vec<_> = anions.iter().map(|a| &a.pos).collect();
+    let grid = NeighborGrid::new(&positions, cutoff);
+
+    let mut bridges: HashMap<(ResidueId, ResidueId), SaltBridge> = HashMap::new();
+    for cation in strctr.atoms().iter().filter(|a| IsCationic.check(a)) {
+        let cation_id = ResidueId::from(cation);
+        for i in grid.neighbors(&cation.pos, cutoff) {
+            let anion = anions[i];
+            let anion_id = ResidueId::from(anion);
+            if anion_id == cation_id { continue; }
+            let d = cation.pos.distance_to(&anion.pos);
+            let bridge = bridges.entry((cation_id.clone(), anion_id.clone())).or_insert_with(|| SaltBridge {
+                cation: cation_id.clone(), cation_name: cation.res_name.clone(), cation_atom: cation.name.clone(),
+                anion: anion_id, anion_name: anion.res_name.clone(), anion_atom: anion.name.clone(),
+                distance: d, n_contacts: 0,
+            });
+            bridge.n_contacts += 1;
+            if d < bridge.distance {
+                bridge.distance = d;
+                bridge.cation_atom = cation.name.clone();
+                bridge.anion_atom = anion.name.clone();
+            }
+        }
+    }
+
+    let mut out: Vec<SaltBridge> = bridges.into_values().collect();
+    out.sort_by(|a, b| a.cation.cmp(&b.cation).then_with(|| a.anion.cmp(&b.anion)));
+
+    return out;
+}
@@ -53,4 +53,142 @@ mod test_dssp {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test_dssp_assignment {
+    use std::io::BufReader;
+    use bioshell_interactions::{BackboneHBondMap, BridgeType, DsspAssignment, write_dssp_classic, write_dssp_mmcif};
+    use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    use crate::{cif_1c5n, cif_2gb1};
+
+    #[test]
+    fn sheet_2gb1() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_2gb1.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+
+        let ss = dssp.secondary_structure();
+        assert_eq!(&ss[22..35], "HHHHHHHHHHHHH");
+        assert_eq!(&ss[1..7], "EEEEEE");
+        // --- protein G has a four-stranded sheet made of two hairpins connected by a parallel ladder
+        assert_eq!(dssp.ladders().len(), 3);
+        assert_eq!(dssp.sheets().len(), 1);
+        let types: Vec<BridgeType> = dssp.ladders().iter().map(|l| l.bridge_type).collect();
+        assert_eq!(types, vec![BridgeType::Antiparallel, BridgeType::Parallel, BridgeType::Antiparallel]);
+        let parallel = &dssp.ladders()[1];
+        assert_eq!(parallel.first_strand[0], ResidueId::new("A", 4, ' '));
+        assert_eq!(parallel.second_strand[0], ResidueId::new("A", 51, ' '));
+
+        let r5 = &dssp.residues()[4];
+        assert_eq!(r5.res_id, ResidueId::new("A", 5, ' '));
+        let partners: Vec<i32> = r5.bridge_partners.iter().flatten().map(|p| p.res_id.res_seq).collect();
+        assert_eq!(partners, vec![16, 52]);
+        assert_eq!(r5.sheet, Some('A'));
+
+        Ok(())
+    }
+
+    #[test]
+    fn states_flags_and_accessibility() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_2gb1.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+        let residue = |res_seq: i32| dssp.residues().iter().find(|r| r.res_id == ResidueId::new("A", res_seq, ' ')).unwrap();
+
+        // --- the turn of the C-terminal hairpin and the bend of the N-terminal one
+        assert_eq!(&dssp.secondary_structure()[46..50], "TTTT");
+        assert!(residue(10).bend && residue(11).bend);
+        assert_eq!(residue(10).ss, 'S');
+        // --- the alpha helix starts with a 4-turn donated by ASP 22 and ends at ASN 35
+        assert_eq!(residue(22).helix_flags, [' ', '>', ' ']);
+        assert_eq!(residue(23).ss, 'H');
+        assert_eq!(residue(35).helix_flags[1], '<');
+        assert_eq!(residue(36).ss, 'T');
+        // --- LEU 5, ALA 26 and VAL 54 are buried in the core, the N-terminal MET is exposed
+        for res_seq in [5, 26, 54] { assert!(residue(res_seq).accessibility < 5.0); }
+        assert!(residue(1).accessibility > 100.0);
+
+        let reader = BufReader::new(cif_1c5n.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+        let residue = |chain: &str, res_seq: i32, i_code: char| dssp.residues().iter()
+            .find(|r| r.res_id == ResidueId::new(chain, res_seq, i_code)).unwrap();
+
+        // --- isolated bridges, a polyproline stretch and 3-10 helices
+        assert_eq!(residue("L", 1, 'G').ss, 'B');
+        assert_eq!(residue("L", 1, 'D').ss, 'B');
+        assert!((22..=24).all(|res_seq| residue("H", res_seq, ' ').ss == 'P'));
+        assert!(dssp.secondary_structure().contains("GGG"));
+        // --- a 3-turn starts at PHE 1G, therefore GLY 1F and SER 1E are turn residues
+        assert_eq!(residue("L", 1, 'G').helix_flags[0], '>');
+        assert_eq!(residue("L", 1, 'F').ss, 'T');
+        assert_eq!(residue("L", 1, 'E').helix_flags[0], '3');
+        assert!(residue("L", 15, ' ').accessibility > 200.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn classic_and_mmcif_output() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_1c5n.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+
+        let mut out: Vec<u8> = vec![];
+        write_dssp_classic(&mut out, &dssp)?;
+        let text = String::from_utf8(out).unwrap();
+        let records: Vec<&str> = text.lines().skip_while(|l| !l.starts_with("  #  RESIDUE")).skip(1).collect();
+        let n_breaks = records.iter().filter(|l| l.chars().nth(13) == Some('!')).count();
+        assert_eq!(records.len(), dssp.residues().len() + n_breaks);
+        assert!(n_breaks > 0);
+
+        let mut out: Vec<u8> = vec![];
+        write_dssp_mmcif(&mut out, &dssp)?;
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("_dssp_struct_summary.secondary_structure"));
+        assert!(text.contains("_dssp_struct_bridge_pairs.partner_1_auth_seq_id"));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_dssp_annotation {
+    use std::io::BufReader;
+    use bioshell_interactions::{BackboneHBondMap, DsspAssignment};
+    use bioshell_pdb::{write_pdb, Deposit, PDBError, SecondaryStructureTypes, SecondaryView};
+    use crate::cif_2gb1;
+
+    #[test]
+    fn assign_and_write_2gb1() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_2gb1.as_bytes());
+        let mut strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        // --- remove the secondary structure loaded from the file, as in a predicted model
+        for res_id in strctr.residues().clone() {
+            strctr.set_residue_secondary(&res_id, SecondaryStructureTypes::Coil)?;
+        }
+        assert_eq!(SecondaryView::new(&strctr, "A").strands().count(), 0);
+
+        let dssp = DsspAssignment::new(&BackboneHBondMap::new(&strctr));
+        dssp.assign_to(&mut strctr)?;
+        // --- isolated bridges are not strands
+        let expected = dssp.secondary_structure().replace(['T', 'S', 'B'], "C");
+        assert_eq!(strctr.secondary("A").to_string(), expected);
+        let view = SecondaryView::new(&strctr, "A");
+        assert_eq!(view.helices().count(), 1);
+        assert_eq!(view.strands().count(), 4);
+
+        // --- HELIX and SHEET records written to a PDB file restore the same annotation
+        let fname = std::env::temp_dir().join("2gb1_dssp_annotation.pdb");
+        write_pdb(&strctr, Box::new(std::fs::File::create(&fname)?));
+        let reloaded = Deposit::from_file(&fname)?.structure()?;
+        assert_eq!(reloaded.secondary("A").to_string(), expected);
+        // --- strands are listed in the order of the sheet, with their sense
+        let senses: Vec<String> = std::fs::read_to_string(&fname)?.lines().filter(|l| l.starts_with("SHEET"))
+            .map(|l| format!("{}{}", &l[11..14].trim(), &l[38..40].trim())).collect();
+        assert_eq!(senses, ["A0", "A-1", "A1", "A-1"]);
+        std::fs::remove_file(&fname)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+#[allow(non_upper_case_globals)]
+const cif_1c5n:  &str = include_str!("./input_files/1c5n.cif");
+#[allow(non_upper_case_globals)]
+const cif_2gb1:  &str = include_str!("./input_files/2gb1.cif");
+#[allow(non_upper_case_globals)]
+const cif_4esa:  &str = include_str!("./input_files/4esa.cif");
+
+#[cfg(test)]
+mod test_disulfides {
+    use std::io::BufReader;
+    use bioshell_interactions::{find_disulfides, DisulfideHandedness, DisulfideMotif};
+    use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    use crate::cif_1c5n;
+
+    #[test]
+    fn disulfides_1c5n() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_1c5n.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let bridges = find_disulfides(&strctr);
+        // --- the same four bridges are listed in the _struct_conn loop of the deposit
+        let expected = [(("L", 1), ("H", 122)), (("H", 42), ("H", 58)), (("H", 168), ("H", 182)), (("H", 191), ("H", 220))];
+        assert_eq!(bridges.len(), expected.len());
+        for (b, ((c1, r1), (c2, r2))) in bridges.iter().zip(expected.iter()) {
+            assert_eq!(b.first, ResidueId::new(c1, *r1, ' '));
+            assert_eq!(b.second, ResidueId::new(c2, *r2, ' '));
+            assert!((b.distance_sg - 2.01).abs() < 0.02);
+            assert!(b.strain_energy() > 0.0);
+        }
+        assert_eq!(bridges[1].class.handedness, DisulfideHandedness::Left);
+        assert_eq!(bridges[1].class.motif, DisulfideMotif::Spiral);
+        assert_eq!(bridges[3].class.to_string(), "-RHHook");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_salt_bridges {
+    use std::io::BufReader;
+    use bioshell_interactions::{find_salt_bridges, SALT_BRIDGE_CUTOFF};
+    use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    use crate::cif_1c5n;
+
+    #[test]
+    fn salt_bridges_1c5n() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_1c5n.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let bridges = find_salt_bridges(&strctr, SALT_BRIDGE_CUTOFF);
+        assert!(bridges.iter().all(|b| b.distance <= SALT_BRIDGE_CUTOFF && b.n_contacts > 0));
+        // --- a bridge between the heavy chain and the hirudin peptide
+        let b = bridges.iter().find(|b| b.cation == ResidueId::new("H", 73, ' ')).unwrap();
+        assert_eq!(b.anion, ResidueId::new("I", 55, ' '));
+        assert_eq!((b.cation_name.as_str(), b.anion_name.as_str()), ("ARG", "ASP"));
+        // --- a single record is created for a pair of residues
+        let b = bridges.iter().find(|b| b.cation == ResidueId::new("L", 14, 'D')).unwrap();
+        assert_eq!(b.n_contacts, 6);
+        assert_eq!(bridges.iter().filter(|b| b.cation == ResidueId::new("L", 14, 'D')).count(), 1);
+        // --- a stricter cutoff gives fewer bridges
+        assert!(find_salt_bridges(&strctr, 3.0).len() < bridges.len());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_aromatic {
+    use std::io::BufReader;
+    use bioshell_interactions::{aromatic_rings, find_cation_pi, find_pi_stacking, StackingGeometry};
+    use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    use crate::cif_4esa;
+
+    #[test]
+    fn pi_stacking_4esa() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_4esa.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let rings = aromatic_rings(&strctr);
+        assert!(rings.iter().all(|r| (r.normal.length() - 1.0).abs() < 1e-6));
+        assert_eq!(rings.iter().filter(|r| r.res_id == ResidueId::new("A", 46, ' ')).count(), 2);
+
+        let stacking = find_pi_stacking(&strctr);
+        let s = stacking.iter().find(|s| s.first == ResidueId::new("C", 46, ' ')).unwrap();
+        assert_eq!(s.second, ResidueId::new("C", 55, ' '));
+        assert_eq!(s.geometry, StackingGeometry::Parallel);
+        let s = stacking.iter().find(|s| s.first == ResidueId::new("B", 42, ' ')).unwrap();
+        assert_eq!(s.second, ResidueId::new("B", 45, ' '));
+        assert_eq!(s.geometry, StackingGeometry::TShaped);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cation_pi_4esa() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_4esa.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let interactions = find_cation_pi(&strctr);
+        assert_eq!(interactions.len(), 3);
+        let c = interactions.iter().find(|c| c.cation == ResidueId::new("B", 59, ' ')).unwrap();
+        assert_eq!(c.cation_atom, " NZ ");
+        assert_eq!(c.ring, ResidueId::new("B", 45, ' '));
+        assert!(c.distance < 4.0);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_metal_sites {
+    use std::io::BufReader;
+    use bioshell_interactions::{find_metal_sites, CoordinationGeometry};
+    use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    use crate::{cif_1c5n, cif_4esa};
+
+    #[test]
+    fn heme_iron_4esa() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_4esa.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let sites = find_metal_sites(&strctr);
+        assert_eq!(sites.len(), 4);
+        for site in &sites {
+            assert_eq!(site.element, "FE");
+            assert_eq!(site.coordination_number(), 6);
+            assert_eq!(site.geometry, CoordinationGeometry::Octahedral);
+            // --- four heme nitrogens, the proximal histidine and the carbon monoxide
+            assert_eq!(site.ligands.iter().filter(|l| l.res_name == "HEM").count(), 4);
+            assert!(site.ligands.iter().any(|l| l.res_name == "HIS" && l.atom_name == " NE2"));
+            assert!(site.ligands.iter().any(|l| l.res_name == "CMO" && l.element == "C"));
+        }
+        assert_eq!(sites[0].metal, ResidueId::new("A", 202, ' '));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ions_1c5n() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_1c5n.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let sites = find_metal_sites(&strctr);
+        assert_eq!(sites.len(), 2);
+        let na = sites.iter().find(|s| s.element == "NA").unwrap();
+        assert_eq!(na.geometry, CoordinationGeometry::Octahedral);
+        assert_eq!(na.ligands.iter().filter(|l| l.res_name == "HOH").count(), 4);
+        let ca = sites.iter().find(|s| s.element == "CA").unwrap();
+        assert_eq!(ca.coordination_number(), 5);
+        assert!(ca.ligands.windows(2).all(|w| w[0].distance <= w[1].distance));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_hbonds {
+    use std::io::BufReader;
+    use bioshell_interactions::{find_hbonds, find_water_bridges, BackboneHBondMap, HBondDetector, HBondSite};
+    use bioshell_pdb::{is_hydrogen, Deposit, PDBError, ResidueId, Structure};
+    use crate::{cif_1c5n, cif_2gb1};
+
+    #[test]
+    fn backbone_hbonds_2gb1() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_2gb1.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let hbonds = find_hbonds(&strctr);
+        // --- every DSSP backbone H-bond of a good geometry is also found by the general detector
+        let bb_map = BackboneHBondMap::new(&strctr);
+        for (_, bb) in bb_map.h_bonds().filter(|(_, hb)| hb.angle_AHD().to_degrees() >= 130.0 && hb.distance_AH() <= 2.3) {
+            let found = hbonds.iter().any(|h| h.donor.site == HBondSite::Backbone && h.acceptor.site == HBondSite::Backbone
+                && (h.distance_DA - bb.distance_DA()).abs() < 1e-6);
+            assert!(found);
+        }
+        // --- 2gb1 provides hydrogens, so the AHD angle is evaluated for every donor
+        assert!(hbonds.iter().all(|h| h.distance_DA <= 3.5 && h.angle_AHD.is_some_and(|a| a >= 120.0)));
+        // --- side chain - backbone H-bonds are found as well
+        assert!(hbonds.iter().any(|h| h.donor.site == HBondSite::SideChain && h.acceptor.site == HBondSite::Backbone));
+        // --- proline nitrogen does not donate
+        assert!(hbonds.iter().all(|h| !(h.donor.res_name == "PRO" && h.donor.atom_name == " N  ")));
+        // --- stricter criteria give fewer H-bonds
+        let strict = HBondDetector { max_distance_DA: 3.0, ..Default::default() }.find(&strctr);
+        assert!(strict.len() < hbonds.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn donor_residue_without_hydrogens() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_2gb1.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let hbond = find_hbonds(&strctr).into_iter().find(|h| h.donor.site == HBondSite::SideChain).unwrap();
+        // --- hydrogens are removed from a single residue; the rest of the structure still has them
+        let donor = hbond.donor.res_id.clone();
+        let stripped = Structure::from_iterator(&strctr.id_code, strctr.atoms().iter()
+            .filter(|a| !(is_hydrogen(a) && ResidueId::from(*a) == donor)).cloned());
+        let found = find_hbonds(&stripped).into_iter().find(|h| h.donor.res_id == donor && h.donor.atom_name == hbond.donor.atom_name
+            && h.acceptor.res_id == hbond.acceptor.res_id && h.acceptor.atom_name == hbond.acceptor.atom_name).unwrap();
+        assert!(found.angle_AHD.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ligand_and_water_hbonds_1c5n() -> Result<(), PDBError> {
+        let reader = BufReader::new(cif_1c5n.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let hbonds = find_hbonds(&strctr);
+        // --- 1c5n provides hydrogens, so H-based criteria are evaluated for all donors but water
+        assert!(hbonds.iter().filter(|h| h.donor.site != HBondSite::Water)
+            .all(|h| h.distance_AH.is_some_and(|d| d <= 2.5) && h.angle_AHD.is_some_and(|a| a >= 120.0)));
+        // --- the amidine group of the inhibitor binds Asp189 at the bottom of the S1 pocket
+        let asp189 = ResidueId::new("H", 189, ' ');
+        let ligand: Vec<_> = hbonds.iter().filter(|h| h.involves_ligand()).collect();
+        assert!(ligand.iter().any(|h| h.donor.res_name == "ESI" && h.acceptor.res_id == asp189));
+        assert!(hbonds.iter().any(|h| h.involves_water()));
+
+        let bridges = find_water_bridges(&hbonds);
+        assert!(!bridges.is_empty());
+        assert!(bridges.iter().all(|b| b.first.site != HBondSite::Water && b.second.site != HBondSite::Water));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use std::env;
 use clap::Parser;
 use bioshell_pdb::{Deposit, PdbAtom, Structure};
-use bioshell_pdb::calc::distance;
+use bioshell_pdb::calc::{contact_map, distance, DistanceMatrix, ResidueRepresentation};
 use bioshell_pdb::pdb_atom_filters::{AlwaysPass, ByChain, IsBackbone, IsCA, IsCB, KeepProtein, MatchAll, PdbAtomPredicate};
 use log::info;
 
@@ -35,6 +35,12 @@ struct Args {
     /// print contact map only
     #[clap(long)]
     cmap: Option<f64>,
+    /// compute distances between residues rather than atoms; residue representation is one of: min, ca, cb, centroid
+    #[clap(long)]
+    residues: Option<String>,
+    /// minimum sequence separation between residues in contact (used with --residues and --cmap)
+    #[clap(long, default_value = "1")]
+    min_separation: i32,
     /// be more verbose and log program actions on the screen
     #[clap(short, long, short='v')]
     verbose: bool
@@ -60,6 +66,26 @@ fn print_contacts(structure: &Structure, cutoff: f64) {
     }
 }
 
+fn residue_representation(name: &str) -> ResidueRepresentation {
+    match name {
+        "min" => ResidueRepresentation::MinHeavyAtom,
+        "ca" => ResidueRepresentation::CA,
+        "cb" => ResidueRepresentation::CB,
+        "centroid" => ResidueRepresentation::SideChainCentroid,
+        _ => panic!("Unknown residue representation: {}; use one of: min, ca, cb, centroid", name),
+    }
+}
+
+fn print_residue_distance_map(structure: &Structure, representation: ResidueRepresentation) {
+
+    let dm = DistanceMatrix::new(structure, representation);
+    for i in 0..dm.len() {
+        for j in 0..i {
+            println!("{} {} : {:.3}", dm.residues()[i], dm.residues()[j], dm.distance(i, j));
+        }
+    }
+}
+
 fn main() {
     let args = Args::parse();
     unsafe {
@@ -95,7 +121,14 @@ fn main() {
     let atoms_iter = strctr.atoms().iter().filter(|a| multi_filter.check(a));
     let strctr = Structure::from_iterator(&strctr.id_code, atoms_iter.cloned());
 
-    if let Some(cutoff) = args.cmap {
+    if let Some(repr) = args.residues {
+        let representation = residue_representation(&repr);
+        if let Some(cutoff) = args.cmap {
+            for c in contact_map(&strctr, representation, cutoff, args.min_separation) { println!("{}", c); }
+        } else {
+            print_residue_distance_map(&strctr, representation);
+        }
+    } else if let Some(cutoff) = args.cmap {
         print_contacts(&strctr, cutoff);
     } else {
         print_distance_map(&strctr);
@@ -7,6 +7,11 @@ use log::info;
 use bioshell_core::io::{out_writer, markdown_to_text};
 use bioshell_pdb::{Deposit, downlad_deposit_from_rcsb, EntityType, find_cif_file_name, find_pdb_file_name, make_pdb_compatible, PDBError, Structure};
 use bioshell_pdb::pdb_atom_filters::{ByChain, ByEntity, InvertPredicate, IsBackbone, IsCA, IsHydrogen, IsNotWater, KeepNucleicAcid, KeepProtein, MatchAll, PdbAtomPredicate};
+use bioshell_pdb::calc::{nucleic_torsions, protein_torsions, NucleotideTorsions, ResidueTorsions};
+use bioshell_pdb::interfaces::find_interfaces;
+use bioshell_pdb::binding_sites::find_binding_sites;
+use bioshell_pdb::pockets::{pockets_as_structure, PocketFinder};
+use bioshell_pdb::validation::{GeometryReport, ResidueValidation, ValidationReport};
 use bioshell_seq::chemical::ResidueTypeProperties;
 
 mod deposit_info;
@@ -46,6 +51,23 @@ struct Args {
     /// print secondary structure for every chain in each input file
     #[clap(long)]
     out_secondary: bool,
+    /// print a table of dihedral angles (in degrees) for every amino acid and nucleotide residue
+    #[clap(long, action)]
+    out_torsions: bool,
+    /// validate geometry of every amino acid residue: Ramachandran and rotamer outliers, cis and non-planar peptides
+    #[clap(long, action)]
+    validate: bool,
+    /// compare bond lengths and planar angles with ideal values, check chirality and detect steric clashes
+    #[clap(long, action)]
+    validate_geometry: bool,
+    /// map the deposited sequence (SEQRES or entity) of every chain onto observed residues; print missing
+    /// segments, numbering jumps and chain breaks
+    #[clap(long, action)]
+    missing_residues: bool,
+    /// print UniProt accession and position of every residue, along with differences between the deposited
+    /// and the UniProt sequence (mmCIF input only)
+    #[clap(long, action)]
+    uniprot_mapping: bool,
     /// print basic information about a given structure
     ///
     /// the following parameters can be used to select which information to print:
@@ -83,6 +105,27 @@ struct Args {
     /// when printing entities, show also the sequence of an entity (only for polymer entities)
     #[clap(long, action)]
     entity_sequence: bool,
+    /// print interfaces between every pair of chains: number of interface residues, atom contacts,
+    /// hydrogen bonds, salt bridges and buried surface area
+    #[clap(long, action)]
+    interfaces: bool,
+    /// distance cutoff used to detect atom contacts across an interface
+    #[clap(long, default_value="4.5")]
+    interface_cutoff: f64,
+    /// print binding site of every ligand: number of surrounding residues, hydrogen bonds, hydrophobic contacts,
+    /// metal coordination bonds and stacked aromatic rings, followed by the list of these contacts
+    #[clap(long, action)]
+    binding_sites: bool,
+    /// distance cutoff used to select residues surrounding a ligand
+    #[clap(long, default_value="4.5")]
+    binding_site_cutoff: f64,
+    /// find pockets on the surface of the selected structure; print their volume, buriedness, score,
+    /// number of lining residues and center, followed by the list of lining residues
+    #[clap(long, action)]
+    pockets: bool,
+    /// write pseudo-atoms of the detected pockets to a given file in the PDB format
+    #[clap(long)]
+    out_pockets: Option<String>,
     /// keep only amino acid residues; all ligands and cofactors will be removed
     #[clap(long, action)]
     select_protein: bool,
@@ -188,6 +231,85 @@ fn print_entities(substructure: &Structure, deposit: &Deposit, print_sequences:
     }
 }
 
+/// Print interfaces between all pairs of chains of a structure.
+///
+/// A summary line is printed for every interface, followed by the list of hydrogen bonds
+/// and salt bridges formed across it
+fn print_interfaces(strctr: &Structure, cutoff: f64) {
+    let atoms = strctr.atoms();
+    let atom_str = |i: usize| format!("{}:{}{} {} {}", atoms[i].chain_id, atoms[i].res_seq,
+                                      atoms[i].i_code, atoms[i].res_name, atoms[i].name);
+    println!("#chn1 chn2 nres1 nres2 ncont  nhb   nsb  buried_A2");
+    for iface in find_interfaces(strctr, cutoff) {
+        println!("{}", iface);
+        for hb in &iface.hydrogen_bonds {
+            println!("HBOND {} - {} {:6.2}", atom_str(hb.first_atom), atom_str(hb.second_atom), hb.distance);
+        }
+        for sb in &iface.salt_bridges {
+            println!("SALTB {} - {} {:6.2}", atom_str(sb.first_atom), atom_str(sb.second_atom), sb.distance);
+        }
+    }
+}
+
+/// Print binding sites of all ligands of a deposit.
+///
+/// A summary line is printed for every ligand, followed by the list of its contacts
+fn print_binding_sites(deposit: &Deposit, cutoff: f64) -> Result<(), PDBError> {
+    let strctr = deposit.structure()?;
+    let atoms = strctr.atoms();
+    let atom_str = |i: usize| format!("{}:{}{} {} {}", atoms[i].chain_id, atoms[i].res_seq,
+                                      atoms[i].i_code, atoms[i].res_name, atoms[i].name);
+    println!("#lig  resid  nres   nhb nhpho nmetl nstck");
+    for site in find_binding_sites(deposit, cutoff)? {
+        println!("{}", site);
+        for c in &site.contacts {
+            println!("{} {} - {} {:6.2}", c.contact_type, atom_str(c.ligand_atom), atom_str(c.partner_atom), c.distance);
+        }
+    }
+
+    return Ok(());
+}
+
+/// Print geometry outliers, chirality errors and clashes found in a structure, followed by a summary line
+fn print_geometry_report(strctr: &Structure) {
+    let atoms = strctr.atoms();
+    let atom_str = |i: usize| format!("{}:{}{} {} {}", atoms[i].chain_id, atoms[i].res_seq,
+                                      atoms[i].i_code, atoms[i].res_name, atoms[i].name);
+    let report = GeometryReport::new(strctr);
+    for b in &report.bond_outliers {
+        println!("BOND   {} - {} {:6.3} {:6.3} {:6.2}", atom_str(b.atoms[0]), atom_str(b.atoms[1]), b.value, b.ideal, b.z_score);
+    }
+    for a in &report.angle_outliers {
+        println!("ANGLE  {} - {} - {} {:6.1} {:6.1} {:6.2}", atom_str(a.atoms[0]), atom_str(a.atoms[1]),
+                 atom_str(a.atoms[2]), a.value, a.ideal, a.z_score);
+    }
+    for c in &report.chirality_errors {
+        println!("CHIRAL {} {:6.2} {:6.2}", atom_str(c.atom), c.volume, c.ideal_volume);
+    }
+    for c in &report.clashes {
+        println!("CLASH  {} - {} {:6.2} {:6.2}", atom_str(c.first), atom_str(c.second), c.distance, c.overlap);
+    }
+    println!("# {}", report);
+}
+
+/// Print segments missing from every polymer chain, numbering jumps and chain breaks.
+///
+/// Chains that have no deposited sequence, e.g. those comprising ligands only, are skipped
+fn print_missing_residues(deposit: &Deposit, strctr: &Structure) {
+    println!("#kind   first -  last  len before   after    sequence");
+    for chain_id in &strctr.chain_ids() {
+        let seq_map = match deposit.chain_sequence_map(chain_id) {
+            Ok(m) => m,
+            Err(e) => { info!("{}", e); continue }
+        };
+        for segment in seq_map.missing_segments() { println!("{}", segment); }
+        for (a, b) in seq_map.numbering_jumps() { println!("JUMP   {} {}", a, b); }
+        for (a, b) in seq_map.chain_breaks(strctr) { println!("BREAK  {} {}", a, b); }
+        println!("# chain {}: {} residues deposited, {} observed, {} missing", chain_id, seq_map.len(),
+                 seq_map.count_observed(), seq_map.count_missing());
+    }
+}
+
 fn load_deposit(args: &Args) -> Result<Deposit, PDBError> {
 
     // ---------- if a file name was given, load it
@@ -305,6 +427,56 @@ fn main() -> Result<(), PDBError> {
             println!("> {}\n{}", seq.description(), strctr.secondary(chain_id).to_string());
         }
     }
+    if args.out_torsions {
+        let protein = protein_torsions(&strctr);
+        if !protein.is_empty() {
+            println!("{}", ResidueTorsions::TABLE_HEADER);
+            for row in &protein { println!("{}", row); }
+        }
+        let nucleic = nucleic_torsions(&strctr);
+        if !nucleic.is_empty() {
+            println!("{}", NucleotideTorsions::TABLE_HEADER);
+            for row in &nucleic { println!("{}", row); }
+        }
+    }
+    if args.validate {
+        let report = ValidationReport::new(&strctr);
+        println!("{}", ResidueValidation::TABLE_HEADER);
+        for row in report.residues() { println!("{}", row); }
+        println!("# Ramachandran favored: {:5.1}%  outliers: {:5.1}%  rotamer outliers: {:5.1}%  cis-nonPro: {}  non-planar: {}",
+                 100.0 * report.ramachandran_favored(), 100.0 * report.ramachandran_outliers(),
+                 100.0 * report.rotamer_outliers(), report.count_cis_nonpro(), report.count_non_planar_peptides());
+        println!("# quality score: {:.2}", report.quality_score(&GeometryReport::new(&strctr)));
+    }
+    if args.validate_geometry {
+        print_geometry_report(&strctr);
+    }
+    if args.missing_residues {
+        print_missing_residues(&deposit, &strctr);
+    }
+    if args.uniprot_mapping {
+        println!("#resid  res accession     pos U difference");
+        for r in deposit.uniprot_mapping()?.residues() { println!("{}", r); }
+    }
+    if args.interfaces {
+        print_interfaces(&strctr, args.interface_cutoff);
+    }
+    if args.binding_sites {
+        print_binding_sites(&deposit, args.binding_site_cutoff)?;
+    }
+    if args.pockets || args.out_pockets.is_some() {
+        let pockets = PocketFinder::default().find_pockets(&strctr);
+        if args.pockets {
+            println!("#  n   volume  bur.    score nres        x        y        z");
+            for (i, p) in pockets.iter().enumerate() {
+                println!("{:4} {}", i + 1, p);
+                println!("#  {}", p.residues.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(" "));
+            }
+        }
+        if let Some(fname) = &args.out_pockets {
+            write_pdb(&pockets_as_structure(&pockets), fname);
+        }
+    }
     if let Some(tokens) = args.info {
         print_info(&deposit, &tokens);
     }
@@ -0,0 +1,437 @@
+//! Describes binding sites of ligands found in a [`Deposit`](crate::Deposit).
+//!
+//! A [`BindingSite`] struct holds residues surrounding a ligand instance, atomic contacts between the ligand
+//! and its environment, classified by their type (see [`ContactType`]), and a standalone [`Structure`]
+//! of the pocket, which comprises the ligand and all the surrounding residues.
+//!
+//! Binding sites of all ligands of a deposit can be found with a single call:
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::binding_sites::{find_binding_sites, BINDING_SITE_CUTOFF, ContactType};
+//! let deposit = Deposit::from_file("./tests/test_files/4esa.cif")?;
+//! let sites = find_binding_sites(&deposit, BINDING_SITE_CUTOFF)?;
+//! for site in sites.iter().filter(|s| s.ligand_name == "HEM") {
+//!     assert_eq!(site.count_contacts(ContactType::MetalCoordination), 1);
+//!     println!("{}", site);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Every residue of a non-polymer entity, except water, is considered a ligand. When entities are not
+//! available, e.g. for some PDB files, ligands are these `HETATM` residues which are neither amino acids nor nucleotides.
+//! Hydrogen atoms and water molecules are neglected while detecting contacts.
+
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use bioshell_core::Vec3;
+use crate::{atom_element, Deposit, EntityType, is_hydrogen, is_metal, PDBError, PdbAtom, ResidueId, Structure, RESIDUE_RINGS};
+use crate::calc::NeighborGrid;
+use crate::pdb_atom_filters::{IsBackbone, IsHBondAcceptor, IsHBondDonor, KeepNucleicAcid, KeepProtein, PdbAtomPredicate};
+
+/// Default distance between a ligand atom and an atom of a residue to consider that residue a part of a binding site (in Angstroms)
+pub const BINDING_SITE_CUTOFF: f64 = 4.5;
+
+/// Maximum distance between a donor and an acceptor atom of a hydrogen bond (in Angstroms)
+pub const LIGAND_HBOND_CUTOFF: f64 = 3.5;
+
+/// Maximum distance between two carbon atoms forming a hydrophobic contact (in Angstroms)
+pub const HYDROPHOBIC_CONTACT_CUTOFF: f64 = 4.0;
+
+/// Maximum distance between a metal ion and a coordinating atom (in Angstroms)
+pub const METAL_COORDINATION_CUTOFF: f64 = 2.8;
+
+/// Maximum distance between centers of two stacked aromatic rings (in Angstroms)
+pub const PI_STACKING_CUTOFF: f64 = 5.5;
+
+/// Maximum offset between centers of two stacked aromatic rings, measured in the plane of either ring (in Angstroms)
+pub const PI_STACKING_OFFSET: f64 = 2.0;
+
+/// Two atoms of a ligand closer than that are considered covalently bonded (in Angstroms)
+const MAX_LIGAND_BOND: f64 = 1.9;
+
+/// Amino acid residues whose side chain carbons may form hydrophobic contacts
+const HYDROPHOBIC_RESIDUES: [&str; 9] = ["ALA", "VAL", "LEU", "ILE", "MET", "PHE", "TRP", "PRO", "TYR"];
+
+/// Type of interaction between a ligand and its environment
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContactType {
+    /// a hydrogen bond between polar atoms
+    HydrogenBond,
+    /// a contact between two non-polar carbon (or halogen) atoms
+    Hydrophobic,
+    /// a bond between a metal ion and a nitrogen, oxygen or sulfur atom
+    MetalCoordination,
+    /// stacking of two aromatic rings, either parallel or T-shaped
+    PiStacking,
+}
+
+impl Display for ContactType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ContactType::HydrogenBond => "HBOND",
+            ContactType::Hydrophobic => "HPHOB",
+            ContactType::MetalCoordination => "METAL",
+            ContactType::PiStacking => "STACK",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A contact between a ligand atom and an atom of its environment.
+///
+/// Atoms are given as indexes to the vector of atoms returned by [`Structure::atoms()`](crate::Structure::atoms())
+/// of the structure the binding site was detected in. In the case of [`ContactType::PiStacking`],
+/// these are the closest atoms of the two rings and `distance` is measured between the ring centers.
+#[derive(Clone, Debug)]
+pub struct LigandContact {
+    /// index of a ligand atom
+    pub ligand_atom: usize,
+    /// index of an atom interacting with the ligand
+    pub partner_atom: usize,
+    /// distance between the two atoms
+    pub distance: f64,
+    /// type of this contact
+    pub contact_type: ContactType,
+}
+
+/// Describes a binding site of a single ligand instance.
+#[derive(Clone)]
+pub struct BindingSite {
+    /// residue ID of the ligand
+    pub ligand: ResidueId,
+    /// three-letter code of the ligand
+    pub ligand_name: String,
+    /// residues that have at least one heavy atom within a cutoff distance from the ligand; other ligands and ions included
+    pub residues: Vec<ResidueId>,
+    /// typed contacts between the ligand and its environment
+    pub contacts: Vec<LigandContact>,
+    /// a standalone structure that contains the ligand and all the binding site residues
+    pub pocket: Structure,
+}
+
+impl BindingSite {
+    /// Iterates over contacts of a given type
+    pub fn contacts_of_type(&self, contact_type: ContactType) -> impl Iterator<Item = &LigandContact> {
+        self.contacts.iter().filter(move |c| c.contact_type == contact_type)
+    }
+
+    /// Counts contacts of a given type
+    pub fn count_contacts(&self, contact_type: ContactType) -> usize { self.contacts_of_type(contact_type).count() }
+}
+
+impl Display for BindingSite {
+    /// Prints a single-line summary of a binding site
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>4} {:>4} {:5} {:5} {:5} {:5} {:5}", self.ligand_name, self.ligand, self.residues.len(),
+               self.count_contacts(ContactType::HydrogenBond), self.count_contacts(ContactType::Hydrophobic),
+               self.count_contacts(ContactType::MetalCoordination), self.count_contacts(ContactType::PiStacking))
+    }
+}
+
+/// Lists ligand residues of a deposit.
+///
+/// Water molecules are excluded; residues are listed in the order of their appearance in the structure.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::binding_sites::ligand_residues;
+/// let deposit = Deposit::from_file("./tests/test_files/6ins.cif")?;
+/// let strctr = deposit.structure()?;
+/// let ligands = ligand_residues(&deposit, &strctr);
+/// assert_eq!(ligands.len(), 2);       // two zinc ions
+/// # Ok(())
+/// # }
+/// ```
+pub fn ligand_residues(deposit: &Deposit, strctr: &Structure) -> Vec<ResidueId> {
+    let mut out: Vec<ResidueId> = vec![];
+    for (i, res_id) in strctr.residue_ids.iter().enumerate() {
+        let a = &strctr.atoms[strctr.atoms_for_residue_id[i].start];
+        if a.res_name == "HOH" { continue }
+        let is_ligand = match deposit.entity(&a.entity_id) {
+            Some(entity) => entity.entity_type() == EntityType::NonPolymer,
+            None => a.is_hetero_atom && !KeepProtein.check(a) && !KeepNucleicAcid.check(a),
+        };
+        if is_ligand { out.push(res_id.clone()); }
+    }
+
+    return out;
+}
+
+/// Finds binding sites of every ligand of a given deposit.
+///
+/// Ligands are identified by [`ligand_residues()`], binding sites are detected by [`binding_site()`]
+/// with the given `cutoff` distance.
+pub fn find_binding_sites(deposit: &Deposit, cutoff: f64) -> Result<Vec<BindingSite>, PDBError> {
+    let strctr = deposit.structure()?;
+    let mut out: Vec<BindingSite> = vec![];
+    for res_id in ligand_residues(deposit, &strctr) {
+        out.push(binding_site(&strctr, &res_id, cutoff)?);
+    }
+
+    return Ok(out);
+}
+
+/// Describes the binding site of a given ligand.
+///
+/// Every residue that has a heavy atom closer than `cutoff` to a heavy atom of the ligand is included in the site.
+/// Contacts between the ligand and its environment are classified as:
+///
+///  - hydrogen bonds: a ligand nitrogen or oxygen atom and a polar atom closer than [`LIGAND_HBOND_CUTOFF`]
+///  - hydrophobic contacts: a ligand carbon atom not bonded to any nitrogen or oxygen (or a halogen) and a side chain carbon
+///    of a hydrophobic residue, closer than [`HYDROPHOBIC_CONTACT_CUTOFF`]
+///  - metal coordination: a metal atom and a nitrogen, oxygen or sulfur atom, closer than [`METAL_COORDINATION_CUTOFF`]
+///  - pi stacking: a planar five- or six-membered ring of the ligand and an aromatic ring of a residue whose centers
+///    are closer than [`PI_STACKING_CUTOFF`] and offset by no more than [`PI_STACKING_OFFSET`]; the angle between
+///    the ring planes must be below 30 degrees (parallel stacking) or above 60 degrees (T-shaped stacking)
+///
+/// Nitrogen and oxygen atoms that coordinate a metal ion are not considered hydrogen bond partners.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::binding_sites::{binding_site, BINDING_SITE_CUTOFF, ContactType};
+/// let deposit = Deposit::from_file("./tests/test_files/6ins.cif")?;
+/// let strctr = deposit.structure()?;
+/// let site = binding_site(&strctr, &ResidueId::new("E", 30, ' '), BINDING_SITE_CUTOFF)?;
+/// assert_eq!(site.ligand_name, "ZN");
+/// // --- the zinc ion is coordinated by the histidine B10 (zinc ions are assigned to separate chains in the 6ins deposit)
+/// let his: Vec<_> = site.contacts_of_type(ContactType::MetalCoordination)
+///     .map(|c| &strctr.atoms()[c.partner_atom]).collect();
+/// assert_eq!(his[0].res_name, "HIS");
+/// assert_eq!(site.pocket.count_atoms(), 1 + site.residues.iter().map(|r| strctr.atoms_in_residue(r).unwrap().count()).sum::<usize>());
+/// # Ok(())
+/// # }
+/// ```
+pub fn binding_site(strctr: &Structure, ligand: &ResidueId, cutoff: f64) -> Result<BindingSite, PDBError> {
+
+    let i_ligand = strctr.residue_pos(ligand)?;
+    let ligand_range = strctr.atoms_for_residue_id[i_ligand].clone();
+    let atoms = strctr.atoms();
+    let ligand_idx: Vec<usize> = ligand_range.clone().filter(|&i| !is_hydrogen(&atoms[i])).collect();
+    let partner_idx: Vec<usize> = (0..atoms.len())
+        .filter(|i| !ligand_range.contains(i))
+        .filter(|&i| !is_hydrogen(&atoms[i]) && atoms[i].res_name != "HOH").collect();
+
+    // ---------- residues within the cutoff and atom contacts
+    let max_cutoff = cutoff.max(HYDROPHOBIC_CONTACT_CUTOFF).max(LIGAND_HBOND_CUTOFF);
+    let grid = NeighborGrid::new(&partner_idx.iter().map(|&i| atoms[i].pos).collect::<Vec<_>>(), max_cutoff);
+    let ligand_bonds = bonded_pairs(atoms, &ligand_idx);
+    // --- atoms coordinating a metal can't form hydrogen bonds, e.g. nitrogen atoms of a heme
+    let mut metal_bound: HashSet<usize> = HashSet::new();
+    for &m in ligand_idx.iter().chain(partner_idx.iter()).filter(|&&m| is_metal(&atoms[m])) {
+        let close_partners = grid.neighbors(&atoms[m].pos, METAL_COORDINATION_CUTOFF).into_iter().map(|l| partner_idx[l]);
+        for i in ligand_idx.iter().cloned().chain(close_partners) {
+            if is_coordinating(&atom_element(&atoms[i])) && atoms[i].pos.distance_to(&atoms[m].pos) <= METAL_COORDINATION_CUTOFF {
+                metal_bound.insert(i);
+            }
+        }
+    }
+    let mut site_atoms: HashSet<usize> = HashSet::new();
+    let mut contacts: Vec<LigandContact> = vec![];
+    for (k, &i) in ligand_idx.iter().enumerate() {
+        let ai = &atoms[i];
+        let ligand_hydrophobic = is_hydrophobic_ligand_atom(atoms, &ligand_idx, &ligand_bonds[k], i);
+        let mut neighbors = grid.neighbors(&ai.pos, max_cutoff);
+        neighbors.sort();
+        for l in neighbors {
+            let j = partner_idx[l];
+            let aj = &atoms[j];
+            if ai.alt_loc != ' ' && aj.alt_loc != ' ' && ai.alt_loc != aj.alt_loc { continue; }
+            let distance = ai.pos.distance_to(&aj.pos);
+            if distance <= cutoff { site_atoms.insert(j); }
+            let (ei, ej) = (atom_element(ai), atom_element(aj));
+            let contact_type = if distance <= METAL_COORDINATION_CUTOFF
+                && ((is_metal(ai) && is_coordinating(&ej)) || (is_coordinating(&ei) && is_metal(aj))) {
+                Some(ContactType::MetalCoordination)
+            } else if distance <= LIGAND_HBOND_CUTOFF && (ei == "N" || ei == "O") && is_polar_partner(aj, &ej)
+                && !metal_bound.contains(&i) && !metal_bound.contains(&j) {
+                Some(ContactType::HydrogenBond)
+            } else if distance <= HYDROPHOBIC_CONTACT_CUTOFF && ligand_hydrophobic && is_hydrophobic_partner(aj, &ej) {
+                Some(ContactType::Hydrophobic)
+            } else { None };
+            if let Some(contact_type) = contact_type {
+                contacts.push(LigandContact { ligand_atom: i, partner_atom: j, distance, contact_type });
+            }
+        }
+    }
+
+    // ---------- residues that form the site
+    let mut site_residues: Vec<usize> = vec![];
+    for (i_res, range) in strctr.atoms_for_residue_id.iter().enumerate() {
+        if range.clone().any(|i| site_atoms.contains(&i)) { site_residues.push(i_res); }
+    }
+
+    // ---------- stacking of aromatic rings
+    let ligand_rings = ligand_aromatic_rings(atoms, &ligand_idx, &ligand_bonds);
+    if !ligand_rings.is_empty() {
+        for &i_res in &site_residues {
+            for ring in residue_aromatic_rings(strctr, i_res) {
+                for lig_ring in &ligand_rings {
+                    if let Some(contact) = stacking(atoms, lig_ring, &ring) { contacts.push(contact); }
+                }
+            }
+        }
+    }
+
+    // ---------- the pocket structure
+    let mut pocket_residues = site_residues.clone();
+    pocket_residues.push(i_ligand);
+    pocket_residues.sort();
+    let pocket = Structure::from_iterator(&strctr.id_code, pocket_residues.iter()
+        .flat_map(|&i_res| strctr.atoms_for_residue_id[i_res].clone()).map(|i| atoms[i].clone()));
+
+    return Ok(BindingSite {
+        ligand: ligand.clone(),
+        ligand_name: atoms[ligand_range.start].res_name.clone(),
+        residues: site_residues.iter().map(|&i| strctr.residue_ids[i].clone()).collect(),
+        contacts, pocket,
+    });
+}
+
+fn is_coordinating(element: &str) -> bool { element == "N" || element == "O" || element == "S" }
+
+/// An atom of a ligand environment that can form a hydrogen bond
+fn is_polar_partner(a: &PdbAtom, element: &str) -> bool {
+    if KeepProtein.check(a) { return IsHBondDonor.check(a) || IsHBondAcceptor.check(a); }
+    return element == "N" || element == "O";
+}
+
+/// A side chain carbon of a hydrophobic amino acid, or any carbon atom of a non-protein residue
+fn is_hydrophobic_partner(a: &PdbAtom, element: &str) -> bool {
+    if element != "C" { return false; }
+    if KeepProtein.check(a) {
+        return HYDROPHOBIC_RESIDUES.contains(&a.res_name.as_str()) && !IsBackbone.check(a);
+    }
+    return true;
+}
+
+/// A ligand carbon atom not bonded to any nitrogen or oxygen atom, or a halogen atom
+fn is_hydrophobic_ligand_atom(atoms: &[PdbAtom], ligand_idx: &[usize], bonded: &[usize], i: usize) -> bool {
+    let e = atom_element(&atoms[i]);
+    if e == "CL" || e == "BR" || e == "I" { return true; }
+    if e != "C" { return false; }
+    return bonded.iter().all(|&k| {
+        let ek = atom_element(&atoms[ligand_idx[k]]);
+        ek != "N" && ek != "O"
+    });
+}
+
+/// For every ligand atom lists the ligand atoms bonded to it; atoms are given as indexes to `ligand_idx`
+fn bonded_pairs(atoms: &[PdbAtom], ligand_idx: &[usize]) -> Vec<Vec<usize>> {
+    let mut bonds: Vec<Vec<usize>> = vec![vec![]; ligand_idx.len()];
+    for k in 0..ligand_idx.len() {
+        let ak = &atoms[ligand_idx[k]];
+        if is_metal(ak) { continue; }
+        for l in k + 1..ligand_idx.len() {
+            let al = &atoms[ligand_idx[l]];
+            if is_metal(al) { continue; }
+            if ak.alt_loc != ' ' && al.alt_loc != ' ' && ak.alt_loc != al.alt_loc { continue; }
+            if ak.pos.distance_to(&al.pos) < MAX_LIGAND_BOND {
+                bonds[k].push(l);
+                bonds[l].push(k);
+            }
+        }
+    }
+
+    return bonds;
+}
+
+/// Finds planar five- and six-membered rings of a ligand; returns atom indexes in the order along each ring
+fn ligand_aromatic_rings(atoms: &[PdbAtom], ligand_idx: &[usize], bonds: &[Vec<usize>]) -> Vec<Vec<usize>> {
+
+    fn extend(bonds: &[Vec<usize>], path: &mut Vec<usize>, rings: &mut Vec<Vec<usize>>) {
+        let last = *path.last().unwrap();
+        for &next in &bonds[last] {
+            if next == path[0] && path.len() >= 5 {
+                rings.push(path.clone());
+            } else if next > path[0] && !path.contains(&next) && path.len() < 6 {
+                path.push(next);
+                extend(bonds, path, rings);
+                path.pop();
+            }
+        }
+    }
+
+    let mut cycles: Vec<Vec<usize>> = vec![];
+    for start in 0..ligand_idx.len() {
+        extend(bonds, &mut vec![start], &mut cycles);
+    }
+    // --- each ring has been found twice, in both directions
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    let mut rings: Vec<Vec<usize>> = vec![];
+    for cycle in cycles {
+        let mut key = cycle.clone();
+        key.sort();
+        if !seen.insert(key) { continue; }
+        let ring: Vec<usize> = cycle.iter().map(|&k| ligand_idx[k]).collect();
+        if is_planar(atoms, &ring) { rings.push(ring); }
+    }
+
+    return rings;
+}
+
+/// Aromatic rings of a standard residue that has all the ring atoms
+fn residue_aromatic_rings(strctr: &Structure, i_res: usize) -> Vec<Vec<usize>> {
+    let range = strctr.atoms_for_residue_id[i_res].clone();
+    let atoms = strctr.atoms();
+    let res_name = atoms[range.start].res_name.as_str();
+    let mut out: Vec<Vec<usize>> = vec![];
+    for (_, names) in RESIDUE_RINGS.iter().filter(|(n, _)| *n == res_name) {
+        let ring: Vec<usize> = names.iter()
+            .filter_map(|name| range.clone().find(|&i| atoms[i].name.trim() == *name)).collect();
+        if ring.len() == names.len() { out.push(ring); }
+    }
+
+    return out;
+}
+
+/// Center of a ring and the normal vector to its plane
+fn ring_geometry(atoms: &[PdbAtom], ring: &[usize]) -> (Vec3, Vec3) {
+    let mut center = Vec3::from_float(0.0);
+    for &i in ring { center += &atoms[i].pos; }
+    center /= ring.len() as f64;
+    let mut normal = Vec3::from_float(0.0);
+    for k in 0..ring.len() {
+        let a = Vec3::sub_s(&atoms[ring[k]].pos, &center);
+        let b = Vec3::sub_s(&atoms[ring[(k + 1) % ring.len()]].pos, &center);
+        normal += &Vec3::cross(&a, &b);
+    }
+    normal.normalize();
+
+    return (center, normal);
+}
+
+/// Returns `true` when no atom of a ring deviates from its mean plane by more than 0.2 Angstrom
+fn is_planar(atoms: &[PdbAtom], ring: &[usize]) -> bool {
+    let (center, normal) = ring_geometry(atoms, ring);
+    return ring.iter().all(|&i| Vec3::dot(&Vec3::sub_s(&atoms[i].pos, &center), &normal).abs() < 0.2);
+}
+
+/// Checks whether two rings are stacked
+fn stacking(atoms: &[PdbAtom], ligand_ring: &[usize], other_ring: &[usize]) -> Option<LigandContact> {
+    let (c1, n1) = ring_geometry(atoms, ligand_ring);
+    let (c2, n2) = ring_geometry(atoms, other_ring);
+    let distance = c1.distance_to(&c2);
+    if distance > PI_STACKING_CUTOFF { return None; }
+    let angle = Vec3::dot(&n1, &n2).abs().min(1.0).acos().to_degrees();
+    if angle > 30.0 && angle < 60.0 { return None; }
+    // --- offset: distance between the two centers projected onto the plane of either ring
+    let d = Vec3::sub_s(&c2, &c1);
+    let offset = |n: &Vec3| (d.length_squared() - Vec3::dot(&d, n).powi(2)).max(0.0).sqrt();
+    if offset(&n1).min(offset(&n2)) > PI_STACKING_OFFSET { return None; }
+
+    let mut closest = (ligand_ring[0], other_ring[0], f64::MAX);
+    for &i in ligand_ring {
+        for &j in other_ring {
+            let d = atoms[i].pos.distance_to(&atoms[j].pos);
+            if d < closest.2 { closest = (i, j, d); }
+        }
+    }
+
+    return Some(LigandContact { ligand_atom: closest.0, partner_atom: closest.1, distance, contact_type: ContactType::PiStacking });
+}
@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use bioshell_core::Vec3;
+use crate::{is_hydrogen, PdbAtom, ResidueId, Structure};
+use crate::calc::NeighborGrid;
+use crate::pdb_atom_filters::{IsBackbone, PdbAtomPredicate};
+
+/// Defines how a residue is represented while computing inter-residue distances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResidueRepresentation {
+    /// the shortest distance between any two heavy atoms of the two residues
+    MinHeavyAtom,
+    /// the alpha carbon of an amino acid residue
+    CA,
+    /// the beta carbon of an amino acid residue, alpha carbon for glycine
+    CB,
+    /// the center of side chain heavy atoms (`CB` included) of an amino acid residue; alpha carbon for glycine
+    SideChainCentroid,
+}
+
+/// Distances between all pairs of residues of a [`Structure`](Structure).
+///
+/// Only these residues, for which a given [`ResidueRepresentation`] is defined, are included in the matrix,
+/// e.g. nucleotides are skipped when the [`CA`](ResidueRepresentation::CA) representation is used.
+/// Water molecules are always excluded.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::{DistanceMatrix, ResidueRepresentation};
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+/// let strctr = deposit.structure()?;
+/// let ca_map = DistanceMatrix::new(&strctr, ResidueRepresentation::CA);
+/// assert_eq!(ca_map.len(), 56);
+/// // --- two consecutive alpha carbons are 3.8 Angstroms apart
+/// assert!((ca_map.distance(0, 1) - 3.8).abs() < 0.05);
+/// // --- the shortest distance between heavy atoms is never longer than the CA-CA distance
+/// let min_map = DistanceMatrix::new(&strctr, ResidueRepresentation::MinHeavyAtom);
+/// assert!(min_map.distance(0, 10) <= ca_map.distance(0, 10));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DistanceMatrix {
+    residues: Vec<ResidueId>,
+    distances: Vec<f64>,
+}
+
+impl DistanceMatrix {
+
+    /// Computes distances between residues of a given structure
+    pub fn new(strctr: &Structure, representation: ResidueRepresentation) -> DistanceMatrix {
+        let (residues, points) = residue_points(strctr, representation);
+        let n = residues.len();
+        let mut distances = vec![0.0; n * n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let d = min_distance(&points[i], &points[j]);
+                distances[i * n + j] = d;
+                distances[j * n + i] = d;
+            }
+        }
+
+        return DistanceMatrix { residues, distances };
+    }
+
+    /// Number of residues in this matrix
+    pub fn len(&self) -> usize { self.residues.len() }
+
+    /// Returns `true` when this matrix holds no residues
+    pub fn is_empty(&self) -> bool { self.residues.is_empty() }
+
+    /// Residues of this matrix, in the order of their rows
+    pub fn residues(&self) -> &Vec<ResidueId> { &self.residues }
+
+    /// Distance between the i-th and the j-th residue of this matrix
+    pub fn distance(&self, i: usize, j: usize) -> f64 { self.distances[i * self.residues.len() + j] }
+
+    /// Lists pairs of residues closer than a given cutoff.
+    ///
+    /// See [`contact_map()`] for the meaning of the `min_separation` parameter.
+    pub fn contacts(&self, cutoff: f64, min_separation: i32) -> Vec<ResidueContact> {
+        let mut out: Vec<ResidueContact> = vec![];
+        for i in 0..self.residues.len() {
+            for j in i + 1..self.residues.len() {
+                let (ri, rj) = (&self.residues[i], &self.residues[j]);
+                if self.distance(i, j) <= cutoff && separated(ri, rj, min_separation) {
+                    out.push(ResidueContact { first: ri.clone(), second: rj.clone(), distance: self.distance(i, j) });
+                }
+            }
+        }
+
+        return out;
+    }
+}
+
+/// A pair of residues in contact.
+///
+/// The `first` residue always precedes the `second` one in a structure.
+#[derive(Clone, Debug)]
+pub struct ResidueContact {
+    pub first: ResidueId,
+    pub second: ResidueId,
+    /// distance between the two residues, as defined by a [`ResidueRepresentation`]
+    pub distance: f64,
+}
+
+impl Display for ResidueContact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {:7.3}", self.first, self.second, self.distance)
+    }
+}
+
+/// Lists pairs of residues of a structure closer than a given cutoff.
+///
+/// Contacts are computed without creating the full [`DistanceMatrix`], which makes this function suitable
+/// for large structures. Two residues of the same chain are reported only when their residue numbers
+/// differ at least by `min_separation`, e.g. contact prediction is usually evaluated for `min_separation = 6`
+/// (medium and long range contacts) or `24` (long range only). Inter-chain contacts are always reported.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::{contact_map, DistanceMatrix, ResidueRepresentation};
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+/// let strctr = deposit.structure()?;
+/// let contacts = contact_map(&strctr, ResidueRepresentation::CB, 8.0, 6);
+/// let matrix = DistanceMatrix::new(&strctr, ResidueRepresentation::CB);
+/// assert_eq!(contacts.len(), matrix.contacts(8.0, 6).len());
+/// for c in &contacts { assert!(c.second.res_seq - c.first.res_seq >= 6); }
+/// # Ok(())
+/// # }
+/// ```
+pub fn contact_map(strctr: &Structure, representation: ResidueRepresentation, cutoff: f64, min_separation: i32) -> Vec<ResidueContact> {
+
+    let (residues, points) = residue_points(strctr, representation);
+    let mut owner: Vec<usize> = vec![];
+    let mut all_points: Vec<Vec3> = vec![];
+    for (i, p) in points.iter().enumerate() {
+        owner.extend(std::iter::repeat_n(i, p.len()));
+        all_points.extend(p.iter().cloned());
+    }
+    let grid = NeighborGrid::new(&all_points, cutoff);
+
+    let mut out: Vec<ResidueContact> = vec![];
+    for i in 0..residues.len() {
+        let mut partners: HashSet<usize> = HashSet::new();
+        for p in &points[i] {
+            partners.extend(grid.neighbors(p, cutoff).iter().map(|&k| owner[k]).filter(|&j| j > i));
+        }
+        let mut partners: Vec<usize> = partners.into_iter().collect();
+        partners.sort();
+        for j in partners {
+            if !separated(&residues[i], &residues[j], min_separation) { continue; }
+            let distance = min_distance(&points[i], &points[j]);
+            if distance <= cutoff {
+                out.push(ResidueContact { first: residues[i].clone(), second: residues[j].clone(), distance });
+            }
+        }
+    }
+
+    return out;
+}
+
+/// Compares a predicted contact map with a reference one.
+///
+/// Contacts are compared as unordered pairs of residues; the distance of a contact doesn't matter.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::{contact_map, ContactMapComparison, ResidueRepresentation};
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+/// let strctr = deposit.structure()?;
+/// let reference = contact_map(&strctr, ResidueRepresentation::CB, 8.0, 6);
+/// // --- CA-based contacts used as a "prediction"
+/// let predicted = contact_map(&strctr, ResidueRepresentation::CA, 8.0, 6);
+/// let cmp = ContactMapComparison::new(&predicted, &reference);
+/// assert!(cmp.precision() > 0.7);
+/// assert!(cmp.recall() > 0.7);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ContactMapComparison {
+    /// number of predicted contacts found also in the reference map
+    pub true_positives: usize,
+    /// number of predicted contacts absent in the reference map
+    pub false_positives: usize,
+    /// number of reference contacts that have not been predicted
+    pub false_negatives: usize,
+}
+
+impl ContactMapComparison {
+
+    /// Compares two sets of contacts
+    pub fn new(predicted: &[ResidueContact], reference: &[ResidueContact]) -> ContactMapComparison {
+        Self::from_pairs(predicted.iter().map(|c| (c.first.clone(), c.second.clone())),
+                         reference.iter().map(|c| (c.first.clone(), c.second.clone())))
+    }
+
+    /// Compares two sets of contacts, each given as pairs of residues.
+    ///
+    /// This method may be used to evaluate contacts read from a prediction file, which provides no distances.
+    pub fn from_pairs<P, R>(predicted: P, reference: R) -> ContactMapComparison
+        where P: IntoIterator<Item = (ResidueId, ResidueId)>, R: IntoIterator<Item = (ResidueId, ResidueId)> {
+
+        let ordered = |(a, b): (ResidueId, ResidueId)| if a <= b { (a, b) } else { (b, a) };
+        let predicted: HashSet<(ResidueId, ResidueId)> = predicted.into_iter().map(ordered).collect();
+        let reference: HashSet<(ResidueId, ResidueId)> = reference.into_iter().map(ordered).collect();
+        let true_positives = predicted.intersection(&reference).count();
+
+        return ContactMapComparison {
+            true_positives,
+            false_positives: predicted.len() - true_positives,
+            false_negatives: reference.len() - true_positives,
+        };
+    }
+
+    /// Fraction of predicted contacts that are correct; `0.0` when nothing has been predicted
+    pub fn precision(&self) -> f64 {
+        let n = self.true_positives + self.false_positives;
+        return if n == 0 { 0.0 } else { self.true_positives as f64 / n as f64 };
+    }
+
+    /// Fraction of reference contacts that have been predicted; `0.0` for an empty reference map
+    pub fn recall(&self) -> f64 {
+        let n = self.true_positives + self.false_negatives;
+        return if n == 0 { 0.0 } else { self.true_positives as f64 / n as f64 };
+    }
+
+    /// Harmonic mean of precision and recall
+    pub fn f1_score(&self) -> f64 {
+        let (p, r) = (self.precision(), self.recall());
+        return if p + r == 0.0 { 0.0 } else { 2.0 * p * r / (p + r) };
+    }
+}
+
+/// Checks the sequence separation criterion; residues of different chains are always separated
+fn separated(ri: &ResidueId, rj: &ResidueId, min_separation: i32) -> bool {
+    ri.chain_id != rj.chain_id || (ri.res_seq - rj.res_seq).abs() >= min_separation
+}
+
+/// The shortest distance between two sets of points
+fn min_distance(pi: &[Vec3], pj: &[Vec3]) -> f64 {
+    let mut d2 = f64::MAX;
+    for a in pi {
+        for b in pj { d2 = d2.min(a.distance_square_to(b)); }
+    }
+
+    return d2.sqrt();
+}
+
+/// Points that represent each residue of a structure; residues without such points are skipped
+fn residue_points(strctr: &Structure, representation: ResidueRepresentation) -> (Vec<ResidueId>, Vec<Vec<Vec3>>) {
+    let mut residues: Vec<ResidueId> = vec![];
+    let mut points: Vec<Vec<Vec3>> = vec![];
+    let bb = IsBackbone;
+    for (res_id, range) in strctr.residue_ids.iter().zip(strctr.atoms_for_residue_id.iter()) {
+        let atoms: Vec<&PdbAtom> = strctr.atoms[range.clone()].iter().filter(|a| !is_hydrogen(a)).collect();
+        if atoms.is_empty() || atoms[0].res_name == "HOH" { continue; }
+        let find = |name: &str| atoms.iter().find(|a| a.name == name).map(|a| a.pos);
+        let p: Vec<Vec3> = match representation {
+            ResidueRepresentation::MinHeavyAtom => atoms.iter().map(|a| a.pos).collect(),
+            ResidueRepresentation::CA => find(" CA ").into_iter().collect(),
+            ResidueRepresentation::CB => find(" CB ").or_else(|| find(" CA ")).into_iter().collect(),
+            ResidueRepresentation::SideChainCentroid => {
+                if find(" CA ").is_none() { vec![] } else {
+                    let side: Vec<&&PdbAtom> = atoms.iter().filter(|a| !bb.check(a)).collect();
+                    if side.is_empty() { find(" CA ").into_iter().collect() } else {
+                        let mut c = Vec3::from_float(0.0);
+                        for a in &side { c += &a.pos; }
+                        c /= side.len() as f64;
+                        vec![c]
+                    }
+                }
+            }
+        };
+        if p.is_empty() { continue; }
+        residues.push(res_id.clone());
+        points.push(p);
+    }
+
+    return (residues, points);
+}
@@ -8,3 +8,28 @@ pub use transformation::Rototranslation;
 
 mod substructures;
 pub use substructures::SubstructureAxis;
+
+mod neighbor_grid;
+pub use neighbor_grid::NeighborGrid;
+
+mod sasa;
+pub use sasa::{atom_radius, residue_solvent_accessible_area, solvent_accessible_area, van_der_waals_radius,
+               SASA_PROBE_RADIUS, SASA_SPHERE_POINTS};
+pub(crate) use sasa::golden_spiral_points;
+
+mod shape;
+pub use shape::{atom_mass, atomic_mass, center_of_mass, radius_of_gyration, ShapeDescriptors};
+
+mod torsions;
+pub use torsions::*;
+
+mod contact_map;
+pub use contact_map::{contact_map, ContactMapComparison, DistanceMatrix, ResidueContact, ResidueRepresentation};
+
+mod superposition;
+pub use superposition::{crmsd, superimpose};
+
+mod model_quality;
+pub use model_quality::{residue_pairs_by_id, GDTScore, LDDTScore, TMScore, GDT_HA_CUTOFFS, GDT_TS_CUTOFFS,
+                        LDDT_INCLUSION_RADIUS, LDDT_THRESHOLDS};
+pub(crate) use model_quality::{superposition_search, tm_d0};
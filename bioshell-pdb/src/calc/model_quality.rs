@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use bioshell_core::Vec3;
+use crate::{is_hydrogen, ResidueId, Structure};
+use crate::calc::{superimpose, NeighborGrid, Rototranslation};
+
+/// Distance cutoffs used by GDT_TS
+pub const GDT_TS_CUTOFFS: [f64; 4] = [1.0, 2.0, 4.0, 8.0];
+/// Distance cutoffs used by GDT_HA
+pub const GDT_HA_CUTOFFS: [f64; 4] = [0.5, 1.0, 2.0, 4.0];
+/// Distance differences that are considered preserved by lDDT
+pub const LDDT_THRESHOLDS: [f64; 4] = [0.5, 1.0, 2.0, 4.0];
+/// Only distances shorter than this inclusion radius in the reference structure are scored by lDDT
+pub const LDDT_INCLUSION_RADIUS: f64 = 15.0;
+
+/// Pairs residues of a model with residues of a reference structure that have the same [`ResidueId`].
+///
+/// This is the typical correspondence between a predicted model and the reference structure it was built for.
+/// Only residues that hold a `CA` atom in both structures are paired.
+pub fn residue_pairs_by_id(model: &Structure, reference: &Structure) -> Vec<(ResidueId, ResidueId)> {
+    reference.residues().iter()
+        .filter(|r| model.atom(r, " CA ").is_ok() && reference.atom(r, " CA ").is_ok())
+        .map(|r| (r.clone(), r.clone())).collect()
+}
+
+/// TM-score of a model, computed for a given residue correspondence.
+///
+/// The TM-score (Zhang & Skolnick, Proteins 57, 702-710, 2004) is computed from the alpha carbons
+/// of paired residues. The score is normalised by the number of amino acid residues of the reference
+/// structure, so unpaired reference residues lower the score. The optimal superposition is searched
+/// by the heuristic used by the `TM-score` program: superimposing fragments of decreasing length
+/// and iteratively refining each superposition on residues closer than a search cutoff.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::{residue_pairs_by_id, TMScore};
+/// let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+/// let pairs = residue_pairs_by_id(&strctr, &strctr);
+/// let tm = TMScore::new(&strctr, &strctr, &pairs);
+/// assert!((tm.tm_score - 1.0).abs() < 1e-6);
+/// assert_eq!(tm.n_aligned, 56);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TMScore {
+    /// the TM-score value
+    pub tm_score: f64,
+    /// the distance scale `d0` derived from the reference length
+    pub d0: f64,
+    /// number of residue pairs closer than 5.0 Angstroms after the superposition
+    pub n_aligned: usize,
+    /// crmsd computed on the `n_aligned` residue pairs
+    pub rmsd: f64,
+    /// the transformation that superimposes the model onto the reference structure
+    pub transformation: Rototranslation,
+}
+
+impl TMScore {
+
+    /// Computes the TM-score for a model and a reference structure.
+    ///
+    /// The `residue_pairs` slice pairs residues of the `model` with residues of the `reference`
+    pub fn new(model: &Structure, reference: &Structure, residue_pairs: &[(ResidueId, ResidueId)]) -> TMScore {
+        let (model_ca, ref_ca) = paired_ca(model, reference, residue_pairs);
+        return TMScore::from_coordinates(&model_ca, &ref_ca, count_ca(reference));
+    }
+
+    /// Computes the TM-score for two sets of corresponding alpha carbon positions.
+    ///
+    /// The score is normalised by `reference_length`, which should not be smaller than the number of positions.
+    pub fn from_coordinates(model_ca: &[Vec3], ref_ca: &[Vec3], reference_length: usize) -> TMScore {
+        let l_norm = reference_length.max(1) as f64;
+        let d0 = tm_d0(reference_length);
+        let d0_search = d0.clamp(4.5, 8.0);
+        let (tm_score, transformation) = superposition_search(model_ca, ref_ca, |d: &[f64]| {
+            let score = d.iter().map(|x| 1.0 / (1.0 + (x / d0) * (x / d0))).sum::<f64>() / l_norm;
+            // --- the search cutoff is extended until at least three residues get selected
+            let mut cutoff = d0_search;
+            let mut selected: Vec<usize> = vec![];
+            while selected.len() < 3.min(d.len()) {
+                selected = (0..d.len()).filter(|&i| d[i] < cutoff).collect();
+                cutoff += 0.5;
+            }
+            (score, selected)
+        });
+        let (mut n_aligned, mut sum) = (0, 0.0);
+        for (m, r) in model_ca.iter().zip(ref_ca.iter()) {
+            let d2 = transformation.apply(m).distance_square_to(r);
+            if d2 < 25.0 { n_aligned += 1; sum += d2; }
+        }
+        let rmsd = if n_aligned > 0 { (sum / n_aligned as f64).sqrt() } else { 0.0 };
+
+        return TMScore { tm_score, d0, n_aligned, rmsd, transformation };
+    }
+}
+
+/// The TM-score distance scale for a protein of a given length
+pub(crate) fn tm_d0(length: usize) -> f64 {
+    if length <= 21 { return 0.5; }
+    return (1.24 * ((length - 15) as f64).cbrt() - 1.8).max(0.5);
+}
+
+/// Global distance test: GDT_TS and GDT_HA scores.
+///
+/// For every distance cutoff the largest fraction of reference residues, whose alpha carbons may be placed within
+/// that cutoff by a single superposition, is searched independently. GDT_TS is the average of these fractions
+/// for [`GDT_TS_CUTOFFS`], GDT_HA - for [`GDT_HA_CUTOFFS`]. Both scores range from 0 to 1.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::{residue_pairs_by_id, GDTScore};
+/// let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+/// let gdt = GDTScore::new(&strctr, &strctr, &residue_pairs_by_id(&strctr, &strctr));
+/// assert!((gdt.gdt_ts - 1.0).abs() < 1e-6);
+/// assert!((gdt.gdt_ha - 1.0).abs() < 1e-6);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GDTScore {
+    /// the GDT_TS score
+    pub gdt_ts: f64,
+    /// the GDT_HA score
+    pub gdt_ha: f64,
+    /// fractions of residues found within 0.5, 1.0, 2.0, 4.0 and 8.0 Angstroms, respectively
+    pub fractions: [f64; 5],
+}
+
+impl GDTScore {
+
+    /// Computes GDT scores for a model and a reference structure, given the residue correspondence
+    pub fn new(model: &Structure, reference: &Structure, residue_pairs: &[(ResidueId, ResidueId)]) -> GDTScore {
+        let (model_ca, ref_ca) = paired_ca(model, reference, residue_pairs);
+        return GDTScore::from_coordinates(&model_ca, &ref_ca, count_ca(reference));
+    }
+
+    /// Computes GDT scores for two sets of corresponding alpha carbon positions.
+    pub fn from_coordinates(model_ca: &[Vec3], ref_ca: &[Vec3], reference_length: usize) -> GDTScore {
+        let l_norm = reference_length.max(1) as f64;
+        let mut fractions = [0.0; 5];
+        for (k, cutoff) in [0.5, 1.0, 2.0, 4.0, 8.0].iter().enumerate() {
+            let (n_best, _) = superposition_search(model_ca, ref_ca, |d: &[f64]| {
+                let selected: Vec<usize> = (0..d.len()).filter(|&i| d[i] <= *cutoff).collect();
+                (selected.len() as f64, selected)
+            });
+            fractions[k] = n_best / l_norm;
+        }
+        let gdt_ts = (fractions[1] + fractions[2] + fractions[3] + fractions[4]) / 4.0;
+        let gdt_ha = (fractions[0] + fractions[1] + fractions[2] + fractions[3]) / 4.0;
+
+        return GDTScore { gdt_ts, gdt_ha, fractions };
+    }
+}
+
+/// Local distance difference test (lDDT), a superposition-free model quality score.
+///
+/// The lDDT (Mariani et al., Bioinformatics 29, 2722-2728, 2013) checks how well inter-atomic distances of the reference
+/// structure are reproduced by a model. Every distance between heavy atoms of different residues, that is shorter than
+/// [`LDDT_INCLUSION_RADIUS`] in the reference, is considered preserved at each of the [`LDDT_THRESHOLDS`] its model
+/// counterpart differs by less than. Distances involving atoms missing in the model are never preserved.
+/// Ambiguous atom names of symmetric side chains (ASP, GLU, PHE, TYR, ARG) are swapped in the model when this improves the score.
+///
+/// Amino acid residues (those with a `CA` atom) of the reference structure are scored; the `per_residue` vector
+/// holds a score for each of them, provided it has any neighbour within the inclusion radius.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::{residue_pairs_by_id, LDDTScore};
+/// let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+/// let pairs = residue_pairs_by_id(&strctr, &strctr);
+/// let lddt = LDDTScore::new(&strctr, &strctr, &pairs, false);
+/// assert!((lddt.global - 1.0).abs() < 1e-6);
+/// assert_eq!(lddt.per_residue.len(), 56);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LDDTScore {
+    /// lDDT value computed for the whole model
+    pub global: f64,
+    /// lDDT value of each reference residue
+    pub per_residue: Vec<(ResidueId, f64)>,
+}
+
+const SYMMETRIC_ATOMS: [(&str, &[(&str, &str)]); 5] = [
+    ("ASP", &[(" OD1", " OD2")]),
+    ("GLU", &[(" OE1", " OE2")]),
+    ("PHE", &[(" CD1", " CD2"), (" CE1", " CE2")]),
+    ("TYR", &[(" CD1", " CD2"), (" CE1", " CE2")]),
+    ("ARG", &[(" NH1", " NH2")]),
+];
+
+impl LDDTScore {
+
+    /// Computes lDDT of a model against a reference structure.
+    ///
+    /// When `ca_only` is `true`, only alpha carbons are used, which gives the CA-lDDT variant of the score.
+    pub fn new(model: &Structure, reference: &Structure, residue_pairs: &[(ResidueId, ResidueId)], ca_only: bool) -> LDDTScore {
+
+        // --- reference atoms and their model counterparts
+        let mut ref_residues: Vec<ResidueId> = vec![];
+        let mut atom_residue: Vec<usize> = vec![];
+        let mut ref_pos: Vec<Vec3> = vec![];
+        let mut model_pos: Vec<Option<Vec3>> = vec![];
+        let model_for: HashMap<&ResidueId, &ResidueId> = residue_pairs.iter().map(|(m, r)| (r, m)).collect();
+        let mut swappable: Vec<Vec<(usize, usize)>> = vec![];
+        for res_id in reference.residues() {
+            if reference.atom(res_id, " CA ").is_err() { continue; }
+            let ires = ref_residues.len();
+            ref_residues.push(res_id.clone());
+            let model_atoms: HashMap<&str, Vec3> = match model_for.get(res_id) {
+                Some(m) => model.atoms_in_residue(m).map(|it| it.map(|a| (a.name.as_str(), a.pos)).collect()).unwrap_or_default(),
+                None => HashMap::new(),
+            };
+            let first = ref_pos.len();
+            let mut names: Vec<&str> = vec![];
+            for a in reference.atoms_in_residue(res_id).unwrap() {
+                if is_hydrogen(a) || (ca_only && a.name != " CA ") { continue; }
+                atom_residue.push(ires);
+                ref_pos.push(a.pos);
+                model_pos.push(model_atoms.get(a.name.as_str()).copied());
+                names.push(a.name.as_str());
+            }
+            let res_name = &reference.atoms_in_residue(res_id).unwrap().next().unwrap().res_name;
+            let mut pairs: Vec<(usize, usize)> = vec![];
+            if let Some((_, sym)) = SYMMETRIC_ATOMS.iter().find(|(n, _)| n == res_name) {
+                for (a, b) in sym.iter() {
+                    let ia = names.iter().position(|n| n == a);
+                    let ib = names.iter().position(|n| n == b);
+                    if let (Some(ia), Some(ib)) = (ia, ib) { pairs.push((first + ia, first + ib)); }
+                }
+            }
+            swappable.push(pairs);
+        }
+
+        // --- distances to be checked, stored for each reference atom
+        let grid = NeighborGrid::new(&ref_pos, LDDT_INCLUSION_RADIUS);
+        let mut neighbors: Vec<Vec<(usize, f64)>> = vec![vec![]; ref_pos.len()];
+        for i in 0..ref_pos.len() {
+            for j in grid.neighbors(&ref_pos[i], LDDT_INCLUSION_RADIUS) {
+                if atom_residue[j] == atom_residue[i] { continue; }
+                let d = ref_pos[i].distance_to(&ref_pos[j]);
+                if d < LDDT_INCLUSION_RADIUS { neighbors[i].push((j, d)); }
+            }
+        }
+        let preserved = |i: usize, j: usize, d_ref: f64, pos: &Vec<Option<Vec3>>| -> f64 {
+            match (pos[i], pos[j]) {
+                (Some(pi), Some(pj)) => {
+                    let diff = (pi.distance_to(&pj) - d_ref).abs();
+                    LDDT_THRESHOLDS.iter().filter(|&&t| diff < t).count() as f64 / LDDT_THRESHOLDS.len() as f64
+                }
+                _ => 0.0,
+            }
+        };
+
+        // --- resolve ambiguous atom names in the model
+        for pairs in &swappable {
+            if pairs.is_empty() { continue; }
+            let atoms: Vec<usize> = pairs.iter().flat_map(|&(a, b)| [a, b]).collect();
+            let score = |pos: &Vec<Option<Vec3>>| -> f64 {
+                atoms.iter().map(|&i| neighbors[i].iter().map(|&(j, d)| preserved(i, j, d, pos)).sum::<f64>()).sum()
+            };
+            let mut swapped = model_pos.clone();
+            for &(a, b) in pairs { swapped.swap(a, b); }
+            if score(&swapped) > score(&model_pos) { model_pos = swapped; }
+        }
+
+        // --- the score
+        let mut res_total = vec![0.0; ref_residues.len()];
+        let mut res_preserved = vec![0.0; ref_residues.len()];
+        let (mut total, mut sum) = (0.0, 0.0);
+        for i in 0..ref_pos.len() {
+            for &(j, d) in &neighbors[i] {
+                let p = preserved(i, j, d, &model_pos);
+                res_total[atom_residue[i]] += 1.0;
+                res_preserved[atom_residue[i]] += p;
+                // --- each distance appears twice on the neighbor lists
+                if i < j { total += 1.0; sum += p; }
+            }
+        }
+        let global = if total > 0.0 { sum / total } else { 0.0 };
+        let per_residue = ref_residues.into_iter().enumerate()
+            .filter(|(i, _)| res_total[*i] > 0.0)
+            .map(|(i, r)| (r, res_preserved[i] / res_total[i])).collect();
+
+        return LDDTScore { global, per_residue };
+    }
+}
+
+/// Alpha carbons of paired residues; pairs missing a CA atom in either structure are skipped
+fn paired_ca(model: &Structure, reference: &Structure, residue_pairs: &[(ResidueId, ResidueId)]) -> (Vec<Vec3>, Vec<Vec3>) {
+    let mut model_ca: Vec<Vec3> = vec![];
+    let mut ref_ca: Vec<Vec3> = vec![];
+    for (m, r) in residue_pairs {
+        if let (Ok(am), Ok(ar)) = (model.atom(m, " CA "), reference.atom(r, " CA ")) {
+            model_ca.push(am.pos);
+            ref_ca.push(ar.pos);
+        }
+    }
+
+    return (model_ca, ref_ca);
+}
+
+fn count_ca(strctr: &Structure) -> usize {
+    strctr.residues().iter().filter(|r| strctr.atom(r, " CA ").is_ok()).count()
+}
+
+/// Searches for a superposition that maximises a score.
+///
+/// Superpositions are computed for sliding fragments of length `n`, `n/2`, `n/4`, ... (but not shorter than 4),
+/// then each superposition is iteratively refined. The `evaluate` closure receives distances between superimposed
+/// positions and returns the score and the positions the next superposition should be computed on.
+pub(crate) fn superposition_search<F>(model: &[Vec3], reference: &[Vec3], mut evaluate: F) -> (f64, Rototranslation)
+    where F: FnMut(&[f64]) -> (f64, Vec<usize>) {
+
+    let n = model.len();
+    let mut best_score = 0.0;
+    let mut best = superimpose(model, reference);
+    if n < 3 { return (best_score, best); }
+
+    let mut fragment = n;
+    loop {
+        let step = (fragment / 2).max(1);
+        let mut start = 0;
+        while start + fragment <= n {
+            let mut selected: Vec<usize> = (start..start + fragment).collect();
+            for _iter in 0..20 {
+                let m: Vec<Vec3> = selected.iter().map(|&i| model[i]).collect();
+                let r: Vec<Vec3> = selected.iter().map(|&i| reference[i]).collect();
+                let rot = superimpose(&m, &r);
+                let d: Vec<f64> = model.iter().zip(reference.iter()).map(|(m, r)| rot.apply(m).distance_to(r)).collect();
+                let (score, next) = evaluate(&d);
+                if score > best_score { best_score = score; best = rot; }
+                if next.len() < 3 || next == selected { break; }
+                selected = next;
+            }
+            start += step;
+        }
+        if fragment <= 4 { break; }
+        fragment = (fragment / 2).max(4);
+    }
+
+    return (best_score, best);
+}
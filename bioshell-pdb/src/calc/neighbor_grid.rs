@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use bioshell_core::{HasCartesians, Vec3};
+
+/// Spatial hashing of 3D points that allows fast lookup of neighbors within a given cutoff.
+///
+/// A [`NeighborGrid`] divides the space into cubic cells of a given size and stores the index
+/// of each point in the cell it falls into. A neighbor search visits only the cells that overlap
+/// with the query sphere, which makes it much faster than a brute force loop over all points.
+/// The cell size should be equal to (or slightly larger than) the typical cutoff distance.
+///
+/// # Example
+/// ```
+/// use bioshell_core::Vec3;
+/// use bioshell_pdb::calc::NeighborGrid;
+/// let points = vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(5.0, 0.0, 0.0)];
+/// let grid = NeighborGrid::new(&points, 4.0);
+/// let neighbors = grid.neighbors(&Vec3::new(0.5, 0.0, 0.0), 1.0);
+/// assert_eq!(neighbors.len(), 2);
+/// ```
+pub struct NeighborGrid {
+    cell_size: f64,
+    positions: Vec<Vec3>,
+    cells: HashMap<(i32, i32, i32), Vec<usize>>,
+}
+
+impl NeighborGrid {
+
+    /// Creates a new grid for the given points.
+    ///
+    /// Indexes returned by [`neighbors()`](NeighborGrid::neighbors()) refer to the order of the `points`
+    pub fn new<T: HasCartesians>(points: &[T], cell_size: f64) -> NeighborGrid {
+        let mut grid = NeighborGrid { cell_size, positions: Vec::with_capacity(points.len()), cells: HashMap::new() };
+        for (i, p) in points.iter().enumerate() {
+            let pos = *p.position();
+            grid.cells.entry(grid.cell_of(&pos)).or_default().push(i);
+            grid.positions.push(pos);
+        }
+
+        return grid;
+    }
+
+    /// Number of points stored in this grid
+    pub fn len(&self) -> usize { self.positions.len() }
+
+    /// Returns `true` if this grid contains no points
+    pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+    /// Returns indexes of all points located not further than `cutoff` from the `query` position.
+    ///
+    /// The returned indexes are not sorted.
+    pub fn neighbors(&self, query: &Vec3, cutoff: f64) -> Vec<usize> {
+        let mut out: Vec<usize> = vec![];
+        let cutoff2 = cutoff * cutoff;
+        let span = (cutoff / self.cell_size).ceil() as i32;
+        let (cx, cy, cz) = self.cell_of(query);
+        for ix in cx - span..=cx + span {
+            for iy in cy - span..=cy + span {
+                for iz in cz - span..=cz + span {
+                    if let Some(cell) = self.cells.get(&(ix, iy, iz)) {
+                        for &i in cell {
+                            if self.positions[i].distance_square_to(query) <= cutoff2 { out.push(i); }
+                        }
+                    }
+                }
+            }
+        }
+
+        return out;
+    }
+
+    fn cell_of(&self, v: &Vec3) -> (i32, i32, i32) {
+        ((v.x / self.cell_size).floor() as i32, (v.y / self.cell_size).floor() as i32, (v.z / self.cell_size).floor() as i32)
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use bioshell_core::Vec3;
+use crate::calc::NeighborGrid;
+use crate::{atom_element, is_hydrogen, PdbAtom, ResidueId, Structure};
+
+/// Radius of a water molecule used as a probe to roll over the molecular surface
+pub const SASA_PROBE_RADIUS: f64 = 1.4;
+
+/// Default number of points placed on each atomic sphere by the Shrake-Rupley algorithm
+pub const SASA_SPHERE_POINTS: usize = 96;
+
+/// Returns the van der Waals radius of a given chemical element (in Angstroms).
+///
+/// Values are taken from Bondi (1964); 1.8 Å is returned for an element not listed in the table.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::calc::van_der_waals_radius;
+/// assert_eq!(van_der_waals_radius("C"), 1.7);
+/// assert_eq!(van_der_waals_radius("O"), 1.52);
+/// ```
+pub fn van_der_waals_radius(element: &str) -> f64 {
+    match element.trim().to_ascii_uppercase().as_str() {
+        "H" => 1.1,
+        "C" => 1.7,
+        "N" => 1.55,
+        "O" => 1.52,
+        "F" => 1.47,
+        "P" => 1.8,
+        "S" => 1.8,
+        "CL" => 1.75,
+        "BR" => 1.85,
+        "I" => 1.98,
+        "SE" => 1.9,
+        "NA" => 2.27,
+        "MG" => 1.73,
+        "K" => 2.75,
+        "ZN" => 1.39,
+        "CU" => 1.4,
+        "FE" => 1.94,
+        _ => 1.8,
+    }
+}
+
+/// Returns the van der Waals radius of an atom.
+///
+/// The element is given by [`atom_element()`]; when it can't be deduced,
+/// e.g. for an atom name made of digits only, the default radius of 1.8 Å is returned.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::PdbAtom;
+/// use bioshell_pdb::calc::atom_radius;
+/// let mut atom = PdbAtom::from_atom_line("ATOM    320  CA  PHE A  43      16.101   9.057  19.587  1.00 18.18           C  ");
+/// assert_eq!(atom_radius(&atom), 1.7);
+/// atom.element = None;
+/// atom.name = "1".to_string();
+/// assert_eq!(atom_radius(&atom), 1.8);
+/// ```
+pub fn atom_radius(atom: &PdbAtom) -> f64 { van_der_waals_radius(&atom_element(atom)) }
+
+/// Computes the solvent accessible surface area of every atom of a given set.
+///
+/// This function implements the [Shrake-Rupley](https://doi.org/10.1016/0022-2836(73)90011-9)
+/// algorithm: `n_points` points are evenly distributed on a sphere of radius equal to
+/// the van der Waals radius of each atom enlarged by [`SASA_PROBE_RADIUS`]; the accessible area
+/// is proportional to the fraction of these points not buried by any other atom.
+///
+/// The returned vector holds area values (in square Angstroms) in the order of the given atoms.
+/// Only the given atoms are used to compute burial, e.g. to compute area of a chain in isolation
+/// simply provide atoms of that chain only.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::PdbAtom;
+/// use bioshell_pdb::calc::solvent_accessible_area;
+/// let a = PdbAtom::from_atom_line("ATOM    515  CA  ALA A  68      25.790  28.757  29.513  1.00 16.12           C");
+/// let sasa = solvent_accessible_area(&[&a], 960);
+/// // --- area of an isolated carbon: 4 pi (1.7 + 1.4)^2
+/// assert!((sasa[0] - 120.76).abs() < 0.1);
+/// ```
+pub fn solvent_accessible_area(atoms: &[&PdbAtom], n_points: usize) -> Vec<f64> {
+
+    let sphere = golden_spiral_points(n_points);
+    let radii: Vec<f64> = atoms.iter().map(|a| atom_radius(a) + SASA_PROBE_RADIUS).collect();
+    let max_radius = radii.iter().cloned().fold(0.0, f64::max);
+    let positions: Vec<Vec3> = atoms.iter().map(|a| a.pos).collect();
+    let grid = NeighborGrid::new(&positions, 2.0 * max_radius);
+
+    let mut out: Vec<f64> = Vec::with_capacity(atoms.len());
+    for (i, pos_i) in positions.iter().enumerate() {
+        let r_i = radii[i];
+        let neighbors: Vec<usize> = grid.neighbors(pos_i, r_i + max_radius).into_iter()
+            .filter(|&j| j != i && positions[j].distance_to(pos_i) < r_i + radii[j]).collect();
+        let mut n_accessible = 0;
+        for p in &sphere {
+            let test = Vec3::new(pos_i.x + p.x * r_i, pos_i.y + p.y * r_i, pos_i.z + p.z * r_i);
+            if neighbors.iter().all(|&j| positions[j].distance_square_to(&test) >= radii[j] * radii[j]) {
+                n_accessible += 1;
+            }
+        }
+        out.push(4.0 * std::f64::consts::PI * r_i * r_i * n_accessible as f64 / sphere.len() as f64);
+    }
+
+    return out;
+}
+
+/// Computes the solvent accessible surface area of every residue of a [`Structure`].
+///
+/// Hydrogen atoms are not taken into account. The returned vector is ordered as
+/// [`Structure::residues()`](Structure::residues()). Note, that all the atoms of the structure,
+/// including ligands and water molecules, bury the surface. Remove them beforehand if necessary.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::residue_solvent_accessible_area;
+/// let strctr = Deposit::from_file("./tests/test_files/2gb1.pdb")?.structure()?;
+/// let sasa = residue_solvent_accessible_area(&strctr);
+/// assert_eq!(sasa.len(), strctr.count_residues());
+/// assert!(sasa.iter().all(|&area| area >= 0.0));
+/// # Ok(())
+/// # }
+/// ```
+pub fn residue_solvent_accessible_area(strctr: &Structure) -> Vec<f64> {
+
+    let heavy: Vec<&PdbAtom> = strctr.atoms().iter().filter(|a| !is_hydrogen(a)).collect();
+    let atom_sasa = solvent_accessible_area(&heavy, SASA_SPHERE_POINTS);
+
+    let index: HashMap<&ResidueId, usize> = strctr.residue_ids.iter().enumerate().map(|(i, r)| (r, i)).collect();
+    let mut out = vec![0.0; strctr.count_residues()];
+    for (a, area) in heavy.iter().zip(atom_sasa.iter()) {
+        if let Some(&i_res) = index.get(&ResidueId::from(*a)) { out[i_res] += area; }
+    }
+
+    return out;
+}
+
+/// Returns `n` points approximately evenly distributed on a unit sphere
+pub(crate) fn golden_spiral_points(n: usize) -> Vec<Vec3> {
+    let golden_angle = std::f64::consts::PI * (3.0 - 5.0_f64.sqrt());
+    (0..n).map(|i| {
+        let z = 1.0 - (2.0 * i as f64 + 1.0) / n as f64;
+        let r = (1.0 - z * z).sqrt();
+        let phi = golden_angle * i as f64;
+        Vec3::new(r * phi.cos(), r * phi.sin(), z)
+    }).collect()
+}
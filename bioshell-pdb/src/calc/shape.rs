@@ -0,0 +1,231 @@
+use nalgebra::{Matrix3, SymmetricEigen};
+use bioshell_core::Vec3;
+use crate::{atom_element, PDBError, PdbAtom};
+
+/// Returns the standard atomic mass of a given chemical element (in Daltons).
+///
+/// Mass of a carbon atom is returned for an element that is not listed in the table.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::calc::atomic_mass;
+/// assert_eq!(atomic_mass("C"), 12.011);
+/// assert_eq!(atomic_mass("Se"), 78.971);
+/// ```
+pub fn atomic_mass(element: &str) -> f64 {
+    match element.trim().to_ascii_uppercase().as_str() {
+        "H" => 1.008,
+        "D" => 2.014,
+        "C" => 12.011,
+        "N" => 14.007,
+        "O" => 15.999,
+        "F" => 18.998,
+        "NA" => 22.990,
+        "MG" => 24.305,
+        "P" => 30.974,
+        "S" => 32.06,
+        "CL" => 35.45,
+        "K" => 39.098,
+        "CA" => 40.078,
+        "MN" => 54.938,
+        "FE" => 55.845,
+        "CO" => 58.933,
+        "NI" => 58.693,
+        "CU" => 63.546,
+        "ZN" => 65.38,
+        "SE" => 78.971,
+        "BR" => 79.904,
+        "I" => 126.904,
+        _ => 12.011,
+    }
+}
+
+/// Returns the mass of an atom.
+///
+/// The element is given by [`atom_element()`]; when it can't be deduced,
+/// e.g. for an atom name made of digits only, the mass of carbon is returned.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::PdbAtom;
+/// use bioshell_pdb::calc::atom_mass;
+/// let mut atom = PdbAtom::from_atom_line("ATOM    320  CA  PHE A  43      16.101   9.057  19.587  1.00 18.18           C  ");
+/// atom.element = Some("Se".to_string());
+/// assert_eq!(atom_mass(&atom), 78.971);
+/// atom.element = None;
+/// atom.name = "1".to_string();
+/// assert_eq!(atom_mass(&atom), 12.011);
+/// ```
+pub fn atom_mass(atom: &PdbAtom) -> f64 { atomic_mass(&atom_element(atom)) }
+
+/// Computes the center of mass of a given set of atoms.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::PdbAtom;
+/// use bioshell_pdb::calc::center_of_mass;
+/// let c = PdbAtom::from_atom_line("ATOM      1  C   ALA A   1       0.000   0.000   0.000  1.00 10.00           C");
+/// let o = PdbAtom::from_atom_line("ATOM      2  O   ALA A   1       1.200   0.000   0.000  1.00 10.00           O");
+/// let com = center_of_mass([&c, &o]);
+/// assert!((com.x - 0.6857).abs() < 0.001);
+/// ```
+pub fn center_of_mass<'a>(atoms: impl IntoIterator<Item=&'a PdbAtom>) -> Vec3 {
+    let mut com = Vec3::from_float(0.0);
+    let mut total_mass = 0.0;
+    for a in atoms {
+        let m = atom_mass(a);
+        com.x += a.pos.x * m;
+        com.y += a.pos.y * m;
+        com.z += a.pos.z * m;
+        total_mass += m;
+    }
+    com /= total_mass;
+
+    return com;
+}
+
+/// Computes the mass-weighted radius of gyration of a given set of atoms.
+///
+/// For a selection of atoms of the same element, e.g. alpha carbons only, the result
+/// is equal to the geometric radius of gyration. Returns [`PDBError::TooFewAtoms`]
+/// when less than two atoms are given.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::radius_of_gyration;
+/// use bioshell_pdb::pdb_atom_filters::{IsCA, PdbAtomPredicate};
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+/// let strctr = deposit.structure()?;
+/// let rg = radius_of_gyration(strctr.atoms().iter().filter(|a| IsCA.check(a)))?;
+/// assert!(rg > 10.0 && rg < 11.0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn radius_of_gyration<'a>(atoms: impl IntoIterator<Item=&'a PdbAtom>) -> Result<f64, PDBError> {
+    return Ok(ShapeDescriptors::from_atoms(atoms)?.radius_of_gyration());
+}
+
+/// Shape of a molecule described by its gyration tensor.
+///
+/// The mass-weighted gyration tensor of a set of atoms is diagonalized; its eigenvalues
+/// λ<sub>1</sub> ≤ λ<sub>2</sub> ≤ λ<sub>3</sub> and the respective eigenvectors (principal axes)
+/// define the following descriptors:
+///
+///  - radius of gyration: R<sub>g</sub><sup>2</sup> = λ<sub>1</sub> + λ<sub>2</sub> + λ<sub>3</sub>
+///  - asphericity: b = λ<sub>3</sub> - (λ<sub>1</sub> + λ<sub>2</sub>) / 2
+///  - acylindricity: c = λ<sub>2</sub> - λ<sub>1</sub>
+///  - relative shape anisotropy: κ<sup>2</sup> = (b<sup>2</sup> + 3/4 c<sup>2</sup>) / R<sub>g</sub><sup>4</sup>
+///
+/// The anisotropy is 0.0 for a spherically symmetric body and 1.0 for atoms placed on a straight line.
+/// Principal moments of inertia are also available.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::ShapeDescriptors;
+/// use bioshell_pdb::pdb_atom_filters::{ByChain, PdbAtomPredicate};
+/// let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+/// let strctr = deposit.structure()?;
+/// // --- the whole dimer
+/// let dimer = ShapeDescriptors::from_atoms(strctr.atoms())?;
+/// // --- chain A only
+/// let chain_a = ByChain::new("A");
+/// let monomer = ShapeDescriptors::from_atoms(strctr.atoms().iter().filter(|a| chain_a.check(a)))?;
+/// assert!(monomer.radius_of_gyration() < dimer.radius_of_gyration());
+/// assert!(dimer.anisotropy() >= 0.0 && dimer.anisotropy() <= 1.0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShapeDescriptors {
+    total_mass: f64,
+    center_of_mass: Vec3,
+    eigenvalues: [f64; 3],
+    axes: [Vec3; 3],
+}
+
+impl ShapeDescriptors {
+
+    /// Computes the gyration tensor for a given set of atoms and diagonalizes it.
+    ///
+    /// Returns [`PDBError::TooFewAtoms`] when less than two atoms are given, since the shape
+    /// of a single point is not defined.
+    pub fn from_atoms<'a>(atoms: impl IntoIterator<Item=&'a PdbAtom>) -> Result<ShapeDescriptors, PDBError> {
+        let atoms: Vec<&PdbAtom> = atoms.into_iter().collect();
+        if atoms.len() < 2 { return Err(PDBError::TooFewAtoms { required: 2, found: atoms.len() }); }
+
+        let com = center_of_mass(atoms.iter().cloned());
+        let mut tensor = Matrix3::<f64>::zeros();
+        let mut total_mass = 0.0;
+        for a in &atoms {
+            let m = atom_mass(a);
+            let d = [a.pos.x - com.x, a.pos.y - com.y, a.pos.z - com.z];
+            for i in 0..3 {
+                for j in 0..3 { tensor[(i, j)] += m * d[i] * d[j]; }
+            }
+            total_mass += m;
+        }
+        tensor /= total_mass;
+
+        // ---------- eigenvalues sorted in the ascending order
+        let eigen = SymmetricEigen::new(tensor);
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| eigen.eigenvalues[i].total_cmp(&eigen.eigenvalues[j]));
+        let eigenvalues = order.map(|i| eigen.eigenvalues[i].max(0.0));
+        let axes = order.map(|i| {
+            let v = eigen.eigenvectors.column(i);
+            Vec3::new(v[0], v[1], v[2])
+        });
+
+        return Ok(ShapeDescriptors { total_mass, center_of_mass: com, eigenvalues, axes });
+    }
+
+    /// Total mass of the atoms
+    pub fn total_mass(&self) -> f64 { self.total_mass }
+
+    /// Center of mass of the atoms
+    pub fn center_of_mass(&self) -> Vec3 { self.center_of_mass }
+
+    /// Eigenvalues of the gyration tensor, sorted in the ascending order
+    pub fn gyration_eigenvalues(&self) -> [f64; 3] { self.eigenvalues }
+
+    /// Principal axes (unit vectors), given in the order of [`gyration_eigenvalues()`](ShapeDescriptors::gyration_eigenvalues())
+    ///
+    /// The last axis is the longest dimension of a molecule
+    pub fn principal_axes(&self) -> [Vec3; 3] { self.axes }
+
+    /// Principal moments of inertia, given in the order of [`principal_axes()`](ShapeDescriptors::principal_axes())
+    ///
+    /// Since the principal axes are sorted by the gyration tensor eigenvalues, the moments are
+    /// sorted in the descending order.
+    pub fn principal_moments(&self) -> [f64; 3] {
+        let rg2 = self.radius_of_gyration_squared();
+        return self.eigenvalues.map(|l| self.total_mass * (rg2 - l));
+    }
+
+    /// Squared radius of gyration
+    pub fn radius_of_gyration_squared(&self) -> f64 { self.eigenvalues.iter().sum() }
+
+    /// Radius of gyration
+    pub fn radius_of_gyration(&self) -> f64 { self.radius_of_gyration_squared().sqrt() }
+
+    /// Asphericity: λ<sub>3</sub> - (λ<sub>1</sub> + λ<sub>2</sub>) / 2
+    pub fn asphericity(&self) -> f64 {
+        self.eigenvalues[2] - 0.5 * (self.eigenvalues[0] + self.eigenvalues[1])
+    }
+
+    /// Acylindricity: λ<sub>2</sub> - λ<sub>1</sub>
+    pub fn acylindricity(&self) -> f64 { self.eigenvalues[1] - self.eigenvalues[0] }
+
+    /// Relative shape anisotropy κ<sup>2</sup>, ranging from 0.0 (a sphere) to 1.0 (a rod)
+    pub fn anisotropy(&self) -> f64 {
+        let rg2 = self.radius_of_gyration_squared();
+        if rg2 == 0.0 { return 0.0; }
+        let b = self.asphericity();
+        let c = self.acylindricity();
+        return (b * b + 0.75 * c * c) / (rg2 * rg2);
+    }
+}
@@ -0,0 +1,88 @@
+use nalgebra::{Matrix4, SymmetricEigen};
+use bioshell_core::{Matrix3x3, Vec3};
+use crate::calc::Rototranslation;
+
+/// Finds the rototranslation that optimally superimposes `moving` points onto `reference` ones.
+///
+/// The returned transformation minimises the root-mean-square deviation between the transformed `moving`
+/// points and their `reference` counterparts; both slices must be of the same length. The optimal rotation
+/// is found with the quaternion method of Horn (J. Opt. Soc. Am. A 4, 629-642, 1987).
+///
+/// # Example
+/// ```
+/// use bioshell_core::{assert_delta, Vec3};
+/// use bioshell_pdb::calc::{crmsd, superimpose, Rototranslation};
+/// let reference = vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(3.8, 0.0, 0.0),
+///         Vec3::new(3.8, 3.8, 0.0), Vec3::new(3.8, 3.8, 3.8)];
+/// // --- rotate the points by 1 radian around an axis, then shift them
+/// let rot = Rototranslation::around_axis(&Vec3::new(1.0, 2.0, 0.0), &Vec3::new(0.0, 1.0, 3.0), 1.0);
+/// let moving: Vec<Vec3> = reference.iter().map(|v| { let mut v = rot.apply(v); v.x += 5.0; v }).collect();
+/// let back = superimpose(&moving, &reference);
+/// for (m, r) in moving.iter().zip(reference.iter()) {
+///     assert_delta!(back.apply(m).distance_to(r), 0.0, 1e-6);
+/// }
+/// assert_delta!(crmsd(&moving, &reference), 0.0, 1e-6);
+/// ```
+pub fn superimpose(moving: &[Vec3], reference: &[Vec3]) -> Rototranslation {
+
+    assert_eq!(moving.len(), reference.len(), "superimposed sets must be of the same size");
+    let cm = center(moving);
+    let cr = center(reference);
+    // --- correlation matrix: s[a][b] = sum of moving_a * reference_b
+    let mut s = [[0.0; 3]; 3];
+    for (m, r) in moving.iter().zip(reference.iter()) {
+        let m = [m.x - cm.x, m.y - cm.y, m.z - cm.z];
+        let r = [r.x - cr.x, r.y - cr.y, r.z - cr.z];
+        for a in 0..3 {
+            for b in 0..3 { s[a][b] += m[a] * r[b]; }
+        }
+    }
+    let n = [
+        [s[0][0] + s[1][1] + s[2][2], s[1][2] - s[2][1], s[2][0] - s[0][2], s[0][1] - s[1][0]],
+        [s[1][2] - s[2][1], s[0][0] - s[1][1] - s[2][2], s[0][1] + s[1][0], s[2][0] + s[0][2]],
+        [s[2][0] - s[0][2], s[0][1] + s[1][0], -s[0][0] + s[1][1] - s[2][2], s[1][2] + s[2][1]],
+        [s[0][1] - s[1][0], s[2][0] + s[0][2], s[1][2] + s[2][1], -s[0][0] - s[1][1] + s[2][2]],
+    ];
+    let q = largest_eigenvector(n);
+    let (q0, q1, q2, q3) = (q[0], q[1], q[2], q[3]);
+    let rot = Matrix3x3::from_array([
+        q0 * q0 + q1 * q1 - q2 * q2 - q3 * q3, 2.0 * (q1 * q2 - q0 * q3), 2.0 * (q1 * q3 + q0 * q2),
+        2.0 * (q1 * q2 + q0 * q3), q0 * q0 - q1 * q1 + q2 * q2 - q3 * q3, 2.0 * (q2 * q3 - q0 * q1),
+        2.0 * (q1 * q3 - q0 * q2), 2.0 * (q2 * q3 + q0 * q1), q0 * q0 - q1 * q1 - q2 * q2 + q3 * q3,
+    ]);
+    // --- t = c_ref - R c_moving
+    let mut t = cr;
+    t -= &Matrix3x3::mul_vec_s(&rot, &cm);
+
+    return Rototranslation::new(rot, t);
+}
+
+/// Computes the coordinate root-mean-square deviation between two sets of points after their optimal superposition
+///
+/// See [`superimpose()`] for an example.
+pub fn crmsd(moving: &[Vec3], reference: &[Vec3]) -> f64 {
+    if moving.is_empty() { return 0.0; }
+    let rot = superimpose(moving, reference);
+    let sum: f64 = moving.iter().zip(reference.iter())
+        .map(|(m, r)| rot.apply(m).distance_square_to(r)).sum();
+
+    return (sum / moving.len() as f64).sqrt();
+}
+
+fn center(points: &[Vec3]) -> Vec3 {
+    let mut c = Vec3::from_float(0.0);
+    if points.is_empty() { return c; }
+    for p in points { c += p; }
+    c /= points.len() as f64;
+
+    return c;
+}
+
+/// Eigenvector of a symmetric 4x4 matrix that corresponds to its largest eigenvalue
+fn largest_eigenvector(a: [[f64; 4]; 4]) -> [f64; 4] {
+    let eigen = SymmetricEigen::new(Matrix4::from_fn(|i, j| a[i][j]));
+    let best = eigen.eigenvalues.imax();
+    let v = eigen.eigenvectors.column(best);
+
+    return [v[0], v[1], v[2], v[3]];
+}
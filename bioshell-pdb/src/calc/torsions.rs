@@ -0,0 +1,349 @@
+use std::fmt::{Display, Formatter};
+use bioshell_core::dihedral_angle4;
+use crate::{PdbAtom, ResidueId, Structure};
+use crate::calc::{phi, psi};
+use crate::pdb_atom_filters::{KeepNucleicAcid, KeepProtein, PdbAtomPredicate};
+use crate::pdb_parsing_error::PDBError;
+
+/// Computes the Omega dihedral angle for a given amino acid residue.
+///
+/// The angle is defined by the `CA` and `C` atoms of the given residue and the `N` and `CA` atoms
+/// of the following one, i.e. it describes the peptide bond that follows the residue.
+/// Returns [`PDBError::ResidueAtTerminus`](PDBError::ResidueAtTerminus) if the following residue can't be found.
+///
+/// # Example
+/// ```
+/// use bioshell_core::assert_delta;
+/// use bioshell_pdb::{PdbAtom, ResidueId, Structure};
+/// use bioshell_pdb::calc::omega;
+/// let pdb_lines = ["ATOM    350  CA  ALA A  23      -9.237   2.226  -4.777  1.00  0.18           C",
+/// "ATOM    351  C   ALA A  23      -8.287   3.342  -5.231  1.00  0.19           C",
+/// "ATOM    359  N   ALA A  24      -8.828   4.348  -5.860  1.00  0.22           N",
+/// "ATOM    360  CA  ALA A  24      -8.012   5.492  -6.277  1.00  0.22           C"];
+/// let atoms: Vec<PdbAtom> = pdb_lines.iter().map(|l| PdbAtom::from_atom_line(l)).collect();
+/// let strctr = Structure::from_atoms("1xyz", atoms);
+/// let omega_val = omega(&strctr, &ResidueId::new("A", 23, ' ')).unwrap();
+/// assert!(omega_val.to_degrees().abs() > 150.0);
+/// ```
+pub fn omega(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    return torsion(strctr, which_res, &[(0, " CA "), (0, " C  "), (1, " N  "), (1, " CA ")]);
+}
+
+/// Returns the number of side chain chi dihedral angles defined for a given amino acid.
+///
+/// Zero is returned for `GLY`, `ALA` and for any residue type not known to this function.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::calc::count_chi_angles;
+/// assert_eq!(count_chi_angles("ARG"), 4);
+/// assert_eq!(count_chi_angles("PHE"), 2);
+/// assert_eq!(count_chi_angles("GLY"), 0);
+/// ```
+pub fn count_chi_angles(res_name: &str) -> usize {
+    (1..=4).take_while(|&k| chi_atoms(res_name, k).is_some()).count()
+}
+
+/// Returns names of the four atoms that define the `k`-th chi angle of a given amino acid type.
+///
+/// Side chain dihedral angles are numbered from 1 to 4. `None` is returned when the requested
+/// angle is not defined for that residue type.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::calc::chi_atoms;
+/// assert_eq!(chi_atoms("SER", 1), Some([" N  ", " CA ", " CB ", " OG "]));
+/// assert_eq!(chi_atoms("SER", 2), None);
+/// ```
+pub fn chi_atoms(res_name: &str, k: usize) -> Option<[&'static str; 4]> {
+    let last = match (k, res_name) {
+        (1, "ARG" | "ASN" | "ASP" | "GLN" | "GLU" | "HIS" | "LEU" | "LYS" | "MET" | "MSE"
+            | "PHE" | "PRO" | "TRP" | "TYR") => " CG ",
+        (1, "CYS") => " SG ",
+        (1, "SER") => " OG ",
+        (1, "THR") => " OG1",
+        (1, "ILE" | "VAL") => " CG1",
+        (2, "ARG" | "GLN" | "GLU" | "LYS" | "PRO") => " CD ",
+        (2, "ASN" | "ASP") => " OD1",
+        (2, "HIS") => " ND1",
+        (2, "ILE" | "LEU" | "PHE" | "TRP" | "TYR") => " CD1",
+        (2, "MET") => " SD ",
+        (2, "MSE") => "SE  ",
+        (3, "ARG") => " NE ",
+        (3, "GLN" | "GLU") => " OE1",
+        (3, "LYS" | "MET" | "MSE") => " CE ",
+        (4, "ARG") => " CZ ",
+        (4, "LYS") => " NZ ",
+        _ => return None,
+    };
+    return match (k, res_name) {
+        (1, _) => Some([" N  ", " CA ", " CB ", last]),
+        (2, "ILE") => Some([" CA ", " CB ", " CG1", last]),
+        (2, _) => Some([" CA ", " CB ", " CG ", last]),
+        (3, "ARG" | "GLN" | "GLU" | "LYS") => Some([" CB ", " CG ", " CD ", last]),
+        (3, "MET") => Some([" CB ", " CG ", " SD ", last]),
+        (3, "MSE") => Some([" CB ", " CG ", "SE  ", last]),
+        (4, "ARG") => Some([" CG ", " CD ", " NE ", last]),
+        (4, _) => Some([" CG ", " CD ", " CE ", last]),
+        _ => None,
+    };
+}
+
+/// Computes the `k`-th side chain dihedral angle for a given amino acid residue.
+///
+/// Returns [`PDBError::NoSuchDihedral`](PDBError::NoSuchDihedral) if the requested angle is not defined
+/// for the type of the residue (e.g. chi1 of glycine) and [`PDBError::NoSuchAtom`](PDBError::NoSuchAtom)
+/// if any of the required atoms is missing.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::chi;
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+/// let strctr = deposit.structure()?;
+/// // --- THR 2 has only one chi angle
+/// let chi1 = chi(&strctr, &ResidueId::new("A", 2, ' '), 1)?;
+/// assert!(chi1.to_degrees() > -180.0 && chi1.to_degrees() <= 180.0);
+/// assert!(chi(&strctr, &ResidueId::new("A", 2, ' '), 2).is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn chi(strctr: &Structure, which_res: &ResidueId, k: usize) -> Result<f64, PDBError> {
+    let res_name = &residue_first_atom(strctr, which_res)?.res_name;
+    match chi_atoms(res_name, k) {
+        Some(names) => torsion(strctr, which_res, &names.map(|n| (0, n))),
+        None => Err(PDBError::NoSuchDihedral { dihedral_name: format!("chi{}", k), res_id: which_res.clone() }),
+    }
+}
+
+/// Computes the alpha backbone dihedral angle of a nucleotide: O3'(i-1) - P - O5' - C5'
+pub fn alpha(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    return torsion(strctr, which_res, &[(-1, " O3'"), (0, " P  "), (0, " O5'"), (0, " C5'")]);
+}
+
+/// Computes the beta backbone dihedral angle of a nucleotide: P - O5' - C5' - C4'
+pub fn beta(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    return torsion(strctr, which_res, &[(0, " P  "), (0, " O5'"), (0, " C5'"), (0, " C4'")]);
+}
+
+/// Computes the gamma backbone dihedral angle of a nucleotide: O5' - C5' - C4' - C3'
+pub fn gamma(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    return torsion(strctr, which_res, &[(0, " O5'"), (0, " C5'"), (0, " C4'"), (0, " C3'")]);
+}
+
+/// Computes the delta backbone dihedral angle of a nucleotide: C5' - C4' - C3' - O3'
+pub fn delta(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    return torsion(strctr, which_res, &[(0, " C5'"), (0, " C4'"), (0, " C3'"), (0, " O3'")]);
+}
+
+/// Computes the epsilon backbone dihedral angle of a nucleotide: C4' - C3' - O3' - P(i+1)
+pub fn epsilon(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    return torsion(strctr, which_res, &[(0, " C4'"), (0, " C3'"), (0, " O3'"), (1, " P  ")]);
+}
+
+/// Computes the zeta backbone dihedral angle of a nucleotide: C3' - O3' - P(i+1) - O5'(i+1)
+pub fn zeta(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    return torsion(strctr, which_res, &[(0, " C3'"), (0, " O3'"), (1, " P  "), (1, " O5'")]);
+}
+
+/// Computes the glycosidic chi dihedral angle of a nucleotide.
+///
+/// The angle is defined as O4' - C1' - N9 - C4 for purines and O4' - C1' - N1 - C2 for pyrimidines.
+/// A residue that has an `N9` atom is considered a purine, therefore modified nucleotides are also handled.
+pub fn chi_nucleic(strctr: &Structure, which_res: &ResidueId) -> Result<f64, PDBError> {
+    if strctr.atom(which_res, " N9 ").is_ok() {
+        return torsion(strctr, which_res, &[(0, " O4'"), (0, " C1'"), (0, " N9 "), (0, " C4 ")]);
+    }
+    return torsion(strctr, which_res, &[(0, " O4'"), (0, " C1'"), (0, " N1 "), (0, " C2 ")]);
+}
+
+/// Computes the pseudorotation phase angle and amplitude of a ribose or deoxyribose ring.
+///
+/// The two values are computed from the five endocyclic torsions ν<sub>0</sub> .. ν<sub>4</sub>
+/// according to [Altona and Sundaralingam (1972)](https://doi.org/10.1021/ja00778a043).
+/// The phase `P` (returned first) is in the range [0, 2π); e.g. C3'-endo pucker, typical for A-form RNA,
+/// corresponds to `P` about 18 degrees while C2'-endo (B-DNA) to about 162 degrees. Both values are in radians.
+pub fn sugar_pucker(strctr: &Structure, which_res: &ResidueId) -> Result<(f64, f64), PDBError> {
+    let nu0 = torsion(strctr, which_res, &[(0, " C4'"), (0, " O4'"), (0, " C1'"), (0, " C2'")])?;
+    let nu1 = torsion(strctr, which_res, &[(0, " O4'"), (0, " C1'"), (0, " C2'"), (0, " C3'")])?;
+    let nu2 = torsion(strctr, which_res, &[(0, " C1'"), (0, " C2'"), (0, " C3'"), (0, " C4'")])?;
+    let nu3 = torsion(strctr, which_res, &[(0, " C2'"), (0, " C3'"), (0, " C4'"), (0, " O4'")])?;
+    let nu4 = torsion(strctr, which_res, &[(0, " C3'"), (0, " C4'"), (0, " O4'"), (0, " C1'")])?;
+
+    let denominator = 2.0 * nu2 * (36.0_f64.to_radians().sin() + 72.0_f64.to_radians().sin());
+    // --- atan2() takes care of the sign of nu2, so the amplitude is always positive
+    let phase = ((nu4 + nu1) - (nu3 + nu0)).atan2(denominator).rem_euclid(2.0 * std::f64::consts::PI);
+    let amplitude = nu2 / phase.cos();
+
+    return Ok((phase, amplitude));
+}
+
+/// Dihedral angles of an amino acid residue.
+///
+/// An angle that can't be computed (e.g. phi of the N-terminal residue or chi1 of glycine) is set to `None`.
+/// All the values are given in radians, but they are printed in degrees by the [`Display`] trait.
+#[derive(Debug, Clone)]
+pub struct ResidueTorsions {
+    /// identifies the residue
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    pub phi: Option<f64>,
+    pub psi: Option<f64>,
+    pub omega: Option<f64>,
+    /// chi1 - chi4 angles, in that order
+    pub chi: [Option<f64>; 4],
+}
+
+impl ResidueTorsions {
+    /// Column names of a table created by printing [`ResidueTorsions`] rows
+    pub const TABLE_HEADER: &'static str = "#res_id   res     phi     psi   omega    chi1    chi2    chi3    chi4";
+}
+
+impl Display for ResidueTorsions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:8} {:>4}", self.res_id, self.res_name)?;
+        for v in [self.phi, self.psi, self.omega].iter().chain(self.chi.iter()) {
+            write!(f, " {}", format_angle(v))?;
+        }
+        Ok(())
+    }
+}
+
+/// Dihedral angles and sugar pucker of a nucleotide.
+///
+/// An angle that can't be computed (e.g. alpha of the 5'-terminal nucleotide) is set to `None`.
+/// All the values are given in radians, but they are printed in degrees by the [`Display`] trait.
+#[derive(Debug, Clone)]
+pub struct NucleotideTorsions {
+    /// identifies the residue
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    pub alpha: Option<f64>,
+    pub beta: Option<f64>,
+    pub gamma: Option<f64>,
+    pub delta: Option<f64>,
+    pub epsilon: Option<f64>,
+    pub zeta: Option<f64>,
+    pub chi: Option<f64>,
+    /// pseudorotation phase angle of the sugar ring
+    pub pucker_phase: Option<f64>,
+    /// pseudorotation amplitude of the sugar ring
+    pub pucker_amplitude: Option<f64>,
+}
+
+impl NucleotideTorsions {
+    /// Column names of a table created by printing [`NucleotideTorsions`] rows
+    pub const TABLE_HEADER: &'static str = "#res_id   res   alpha    beta   gamma   delta epsilon    zeta     chi       P   tau_m";
+}
+
+impl Display for NucleotideTorsions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:8} {:>4}", self.res_id, self.res_name)?;
+        for v in [self.alpha, self.beta, self.gamma, self.delta, self.epsilon, self.zeta, self.chi,
+            self.pucker_phase, self.pucker_amplitude] {
+            write!(f, " {}", format_angle(&v))?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes dihedral angles for every amino acid residue of a given structure.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::calc::{protein_torsions, ResidueTorsions};
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+/// let strctr = deposit.structure()?;
+/// let torsions = protein_torsions(&strctr);
+/// assert_eq!(torsions.len(), 56);
+/// println!("{}", ResidueTorsions::TABLE_HEADER);
+/// for row in &torsions { println!("{}", row); }
+/// # Ok(())
+/// # }
+/// ```
+pub fn protein_torsions(strctr: &Structure) -> Vec<ResidueTorsions> {
+    let mut out: Vec<ResidueTorsions> = vec![];
+    for (i_res, res_id) in strctr.residue_ids.iter().enumerate() {
+        let first = &strctr.atoms[strctr.atoms_for_residue_id[i_res].start];
+        if !KeepProtein.check(first) { continue; }
+        let mut chi_values = [None; 4];
+        for (k, v) in chi_values.iter_mut().enumerate() { *v = chi(strctr, res_id, k + 1).ok(); }
+        out.push(ResidueTorsions {
+            res_id: res_id.clone(),
+            res_name: first.res_name.clone(),
+            phi: phi(strctr, res_id).ok(),
+            psi: psi(strctr, res_id).ok(),
+            omega: omega(strctr, res_id).ok(),
+            chi: chi_values,
+        });
+    }
+
+    return out;
+}
+
+/// Computes dihedral angles and sugar puckers for every nucleotide of a given structure.
+pub fn nucleic_torsions(strctr: &Structure) -> Vec<NucleotideTorsions> {
+    let mut out: Vec<NucleotideTorsions> = vec![];
+    for (i_res, res_id) in strctr.residue_ids.iter().enumerate() {
+        let first = &strctr.atoms[strctr.atoms_for_residue_id[i_res].start];
+        if !KeepNucleicAcid.check(first) { continue; }
+        let pucker = sugar_pucker(strctr, res_id).ok();
+        out.push(NucleotideTorsions {
+            res_id: res_id.clone(),
+            res_name: first.res_name.clone(),
+            alpha: alpha(strctr, res_id).ok(),
+            beta: beta(strctr, res_id).ok(),
+            gamma: gamma(strctr, res_id).ok(),
+            delta: delta(strctr, res_id).ok(),
+            epsilon: epsilon(strctr, res_id).ok(),
+            zeta: zeta(strctr, res_id).ok(),
+            chi: chi_nucleic(strctr, res_id).ok(),
+            pucker_phase: pucker.map(|p| p.0),
+            pucker_amplitude: pucker.map(|p| p.1),
+        });
+    }
+
+    return out;
+}
+
+/// Formats an angle given in radians as degrees; a missing value is printed as `-`
+fn format_angle(v: &Option<f64>) -> String {
+    match v {
+        Some(a) => format!("{:7.1}", a.to_degrees()),
+        None => format!("{:>7}", "-"),
+    }
+}
+
+/// Returns the first atom of a given residue
+fn residue_first_atom<'a>(strctr: &'a Structure, which_res: &ResidueId) -> Result<&'a PdbAtom, PDBError> {
+    let i_residue = strctr.residue_pos(which_res)?;
+    return Ok(&strctr.atoms[strctr.atoms_for_residue_id[i_residue].start]);
+}
+
+/// Computes a dihedral angle defined by four atoms.
+///
+/// Each atom is given by its name and the offset of its residue relative to `which_res`,
+/// e.g. `(-1, " C  ")` denotes the carbonyl carbon of the preceding residue. A residue from a different
+/// chain is never used.
+fn torsion(strctr: &Structure, which_res: &ResidueId, atoms: &[(i32, &str); 4]) -> Result<f64, PDBError> {
+    let i_residue = strctr.residue_pos(which_res)? as i32;
+    let mut positions = vec![];
+    for (offset, name) in atoms {
+        let i_other = i_residue + offset;
+        if i_other < 0 || i_other >= strctr.residue_ids.len() as i32 {
+            return Err(PDBError::ResidueAtTerminus { res_id: which_res.clone() });
+        }
+        let other = &strctr.residue_ids[i_other as usize];
+        if other.chain_id != which_res.chain_id {
+            return Err(PDBError::ResidueAtTerminus { res_id: which_res.clone() });
+        }
+        positions.push(strctr.atom(other, name)?.pos);
+    }
+
+    return Ok(dihedral_angle4(&positions[0], &positions[1], &positions[2], &positions[3]));
+}
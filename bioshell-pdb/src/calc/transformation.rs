@@ -2,6 +2,7 @@ use std::fmt;
 use bioshell_core::{Matrix3x3, Vec3};
 
 /// Rotation-translation operation in 3D
+#[derive(Clone)]
 pub struct Rototranslation {
     _translation: Vec3,
     _rotation_matrix: Matrix3x3,
@@ -0,0 +1,334 @@
+//! Maps the deposited sequence of a chain onto residues observed in a [`Structure`](crate::Structure).
+//!
+//! The sequence of a macromolecule deposited in the PDB (given by `SEQRES` records of a PDB file or by an entity
+//! of an mmCIF file) is often longer than the chain actually observed in the experiment: flexible loops and termini
+//! may be missing from the model. A [`ChainSequenceMap`] links every position of the deposited sequence
+//! with the [`ResidueId`] of the respective residue, if it has been observed. Such a map can report:
+//!
+//!  - segments of residues missing from the structure: unobserved N- and C-termini and internal loops,
+//!    see [`ChainSequenceMap::missing_segments()`],
+//!  - jumps in residue numbering between two residues that are adjacent in the sequence,
+//!    see [`ChainSequenceMap::numbering_jumps()`],
+//!  - chain breaks, i.e. two observed residues adjacent in the sequence that are not connected
+//!    by a peptide bond, see [`ChainSequenceMap::chain_breaks()`]
+//!
+//! The easiest way to obtain a map is to call [`Deposit::chain_sequence_map()`](crate::Deposit::chain_sequence_map()):
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! let deposit = Deposit::from_file("./tests/test_files/4esa.cif")?;
+//! let seq_map = deposit.chain_sequence_map("B")?;
+//! assert_eq!(seq_map.len(), 146);
+//! assert_eq!(seq_map.count_observed(), 144);
+//! for segment in seq_map.missing_segments() {
+//!     println!("{}", segment);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! For mmCIF data the map is taken directly from the `_pdbx_poly_seq_scheme` category. Since PDB files don't
+//! provide such information, `SEQRES` records are aligned to the observed sequence in that case.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use bioshell_cif::{entry_has_value, value_or_default, CifData, CifTable};
+use bioshell_seq::alignment::{AlignmentStep, GlobalAligner};
+use bioshell_seq::chemical::{ResidueTypeManager, ResidueTypeProperties};
+use bioshell_seq::scoring::SimilarityScore;
+use log::warn;
+use crate::{PDBError, ResidueId, Structure};
+use crate::pdb_atom_filters::{KeepProtein, PdbAtomPredicate};
+use crate::residue_filters::{ArePeptideBonded, ResidueFilter2};
+
+/// A single position of a deposited sequence.
+#[derive(Clone, Debug)]
+pub struct SequencePosition {
+    /// index of this residue in the deposited sequence, counted from 1 as in the `_pdbx_poly_seq_scheme.seq_id` column
+    pub seq_id: usize,
+    /// three-letter code of the residue type
+    pub res_name: String,
+    /// ID of the residue in the structure, `None` if it hasn't been observed
+    pub res_id: Option<ResidueId>,
+}
+
+/// Where a segment of missing residues is located in a chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingSegmentKind {
+    /// residues missing at the N-terminus (or the 5' end) of a chain
+    NTerminus,
+    /// residues missing between two observed parts of a chain
+    Loop,
+    /// residues missing at the C-terminus (or the 3' end) of a chain
+    CTerminus,
+}
+
+impl Display for MissingSegmentKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            MissingSegmentKind::NTerminus => "N-term",
+            MissingSegmentKind::Loop => "loop",
+            MissingSegmentKind::CTerminus => "C-term",
+        };
+        return f.pad(s);
+    }
+}
+
+/// A contiguous segment of the deposited sequence that hasn't been observed in a structure.
+#[derive(Clone, Debug)]
+pub struct MissingSegment {
+    /// location of this segment in a chain
+    pub kind: MissingSegmentKind,
+    /// index of the first missing residue in the deposited sequence (counted from 1)
+    pub first_seq_id: usize,
+    /// index of the last missing residue in the deposited sequence (counted from 1), inclusive
+    pub last_seq_id: usize,
+    /// three-letter codes of the missing residues
+    pub res_names: Vec<String>,
+    /// the last observed residue preceding this segment, `None` for the N-terminal segment
+    pub before: Option<ResidueId>,
+    /// the first observed residue following this segment, `None` for the C-terminal segment
+    pub after: Option<ResidueId>,
+}
+
+impl MissingSegment {
+    /// The number of missing residues
+    pub fn len(&self) -> usize { self.last_seq_id - self.first_seq_id + 1 }
+
+    /// A segment always comprises at least one residue
+    pub fn is_empty(&self) -> bool { false }
+}
+
+impl Display for MissingSegment {
+    /// Prints a missing segment in a single line, e.g. `loop     148 -   154    7 H:147    H:150    WTANVGK`
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let before = self.before.as_ref().map_or("-".to_string(), |r| r.to_string());
+        let after = self.after.as_ref().map_or("-".to_string(), |r| r.to_string());
+        let seq: String = self.res_names.iter().map(|n| code1(n)).collect();
+        write!(f, "{:6} {:5} - {:5} {:4} {:8} {:8} {}", self.kind, self.first_seq_id, self.last_seq_id,
+               self.len(), before, after, seq)
+    }
+}
+
+/// Maps positions of a deposited sequence of a chain onto residues observed in a structure.
+///
+/// See the [module documentation](crate::chain_mapping) for details.
+#[derive(Clone, Debug)]
+pub struct ChainSequenceMap {
+    chain_id: String,
+    positions: Vec<SequencePosition>,
+    seq_ids: HashMap<ResidueId, usize>,
+}
+
+impl ChainSequenceMap {
+
+    /// Creates a map for a given chain from the `_pdbx_poly_seq_scheme` category of mmCIF data.
+    ///
+    /// Only these residues that are actually present in the given structure are considered observed.
+    /// When the sequence is heterogeneous at a given position, the observed residue type is taken.
+    pub fn from_cif_data(cif_data: &CifData, strctr: &Structure, chain_id: &str) -> Result<ChainSequenceMap, PDBError> {
+
+        let present: HashSet<&ResidueId> = strctr.residue_ids.iter().collect();
+        let table = CifTable::new(cif_data, "_pdbx_poly_seq_scheme.",
+                ["pdb_strand_id", "seq_id", "mon_id", "pdb_seq_num", "pdb_ins_code", "pdb_mon_id"])?;
+        let mut positions: Vec<SequencePosition> = vec![];
+        for [strand, seq_id, mon_id, pdb_seq_num, ins_code, pdb_mon_id] in table.iter() {
+            if strand != chain_id { continue }
+            let seq_id: usize = value_or_default(seq_id, 0);
+            let mut res_id: Option<ResidueId> = None;
+            if entry_has_value(pdb_mon_id) && entry_has_value(pdb_seq_num) {
+                let id = ResidueId::new(chain_id, value_or_default(pdb_seq_num, 0), value_or_default(ins_code, ' '));
+                if present.contains(&id) { res_id = Some(id); }
+            }
+            let pos = SequencePosition { seq_id, res_name: mon_id.to_string(), res_id };
+            // --- microheterogeneity: the same seq_id listed more than once
+            if let Some(last) = positions.last_mut() {
+                if last.seq_id == seq_id {
+                    if last.res_id.is_none() && pos.res_id.is_some() { *last = pos; }
+                    continue;
+                }
+            }
+            positions.push(pos);
+        }
+        if positions.is_empty() { return Err(PDBError::NoSuchChain { chain_id: chain_id.to_string() }); }
+
+        return Ok(ChainSequenceMap::from_positions(chain_id, positions));
+    }
+
+    /// Creates a map for a given chain by aligning its deposited sequence to the residues observed in a structure.
+    ///
+    /// This method is used for PDB files, where `SEQRES` records provide the deposited sequence.
+    /// Residue names are aligned by the Needleman-Wunsch algorithm; observed residues that can't be matched with
+    /// any position of the deposited sequence are neglected.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PdbAtom, PDBError, Structure};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_pdb::chain_mapping::{ChainSequenceMap, MissingSegmentKind};
+    /// let lines = ["ATOM      2  CA  THR A   2      -9.669  -0.447   4.998  1.00  0.19           C",
+    ///     "ATOM      3  CA  TYR A   3      -7.173  -2.314   2.811  1.00  0.08           C",
+    ///     "ATOM      5  CA  LEU A   6      -0.651  -2.752   2.466  1.00  0.11           C"];
+    /// let strctr = Structure::from_iterator("1xyz", lines.iter().map(|l| PdbAtom::from_atom_line(l)));
+    /// let seqres: Vec<String> = ["MET", "THR", "TYR", "LYS", "GLY", "LEU"].iter().map(|s| s.to_string()).collect();
+    /// let seq_map = ChainSequenceMap::from_sequence(&strctr, "A", &seqres)?;
+    /// let missing = seq_map.missing_segments();
+    /// assert_eq!(missing.len(), 2);
+    /// assert_eq!(missing[0].kind, MissingSegmentKind::NTerminus);
+    /// assert_eq!(missing[1].kind, MissingSegmentKind::Loop);
+    /// assert_eq!(missing[1].len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_sequence(strctr: &Structure, chain_id: &str, res_names: &[String]) -> Result<ChainSequenceMap, PDBError> {
+
+        if !strctr.residue_ids.iter().any(|r| r.chain_id == chain_id) {
+            return Err(PDBError::NoSuchChain { chain_id: chain_id.to_string() });
+        }
+        let observed: Vec<usize> = strctr.residues_in_polymer(chain_id);
+        let observed_names: Vec<&str> = observed.iter()
+            .map(|&i| strctr.atoms[strctr.atoms_for_residue_id[i].start].res_name.as_str()).collect();
+
+        let scoring = ResidueNameScore { query: res_names.iter().map(|s| s.as_str()).collect(), template: observed_names };
+        let mut aligner = GlobalAligner::new(res_names.len().max(observed.len()));
+        aligner.align(&scoring, -10, -1);
+
+        let mut positions: Vec<SequencePosition> = vec![];
+        let mut q_iter = res_names.iter();
+        let mut t_iter = observed.iter();
+        for step in aligner.backtrace().iter() {
+            match step {
+                AlignmentStep::Match => {
+                    let res_id = strctr.residue_ids[*t_iter.next().unwrap()].clone();
+                    positions.push(SequencePosition { seq_id: positions.len() + 1,
+                        res_name: q_iter.next().unwrap().clone(), res_id: Some(res_id) });
+                }
+                AlignmentStep::Vertical => {
+                    positions.push(SequencePosition { seq_id: positions.len() + 1,
+                        res_name: q_iter.next().unwrap().clone(), res_id: None });
+                }
+                AlignmentStep::Horizontal => {
+                    let i_res = *t_iter.next().unwrap();
+                    warn!("residue {} can't be found in the deposited sequence", &strctr.residue_ids[i_res]);
+                }
+            }
+        }
+
+        return Ok(ChainSequenceMap::from_positions(chain_id, positions));
+    }
+
+    /// ID of the chain this map has been created for
+    pub fn chain_id(&self) -> &str { &self.chain_id }
+
+    /// All positions of the deposited sequence, both observed and missing
+    pub fn positions(&self) -> &Vec<SequencePosition> { &self.positions }
+
+    /// Length of the deposited sequence
+    pub fn len(&self) -> usize { self.positions.len() }
+
+    /// Returns `true` if the deposited sequence is empty
+    pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+    /// Number of residues observed in the structure
+    pub fn count_observed(&self) -> usize { self.seq_ids.len() }
+
+    /// Number of residues of the deposited sequence that are missing from the structure
+    pub fn count_missing(&self) -> usize { self.len() - self.count_observed() }
+
+    /// Returns the ID of a residue observed at a given position of the deposited sequence.
+    ///
+    /// Positions are counted from 1; `None` is returned when the residue hasn't been observed
+    /// or when `seq_id` is out of range.
+    pub fn residue_id(&self, seq_id: usize) -> Option<&ResidueId> {
+        if seq_id == 0 { return None; }
+        return self.positions.get(seq_id - 1).and_then(|p| p.res_id.as_ref());
+    }
+
+    /// Returns the position (counted from 1) of a given residue in the deposited sequence
+    pub fn seq_id(&self, res_id: &ResidueId) -> Option<usize> { self.seq_ids.get(res_id).copied() }
+
+    /// Lists contiguous segments of the deposited sequence that are missing from the structure.
+    pub fn missing_segments(&self) -> Vec<MissingSegment> {
+
+        let mut out: Vec<MissingSegment> = vec![];
+        let mut before: Option<ResidueId> = None;
+        let mut i = 0;
+        while i < self.positions.len() {
+            if let Some(res_id) = &self.positions[i].res_id {
+                before = Some(res_id.clone());
+                i += 1;
+                continue;
+            }
+            let first = i;
+            while i < self.positions.len() && self.positions[i].res_id.is_none() { i += 1; }
+            let after = self.positions.get(i).and_then(|p| p.res_id.clone());
+            let kind = if before.is_none() { MissingSegmentKind::NTerminus }
+                else if after.is_none() { MissingSegmentKind::CTerminus } else { MissingSegmentKind::Loop };
+            out.push(MissingSegment {
+                kind, first_seq_id: first + 1, last_seq_id: i,
+                res_names: self.positions[first..i].iter().map(|p| p.res_name.clone()).collect(),
+                before: before.clone(), after,
+            });
+        }
+
+        return out;
+    }
+
+    /// Lists pairs of observed residues adjacent in the deposited sequence whose residue numbers are not consecutive.
+    ///
+    /// Two residues are numbered consecutively when the residue number of the latter is larger by one, or when both
+    /// share the same residue number and differ by the insertion code only.
+    pub fn numbering_jumps(&self) -> Vec<(ResidueId, ResidueId)> {
+        self.observed_neighbors()
+            .filter(|(a, b)| !(b.res_seq == a.res_seq + 1 || (b.res_seq == a.res_seq && b.i_code != a.i_code)))
+            .map(|(a, b)| (a.clone(), b.clone())).collect()
+    }
+
+    /// Lists pairs of observed amino acid residues adjacent in the deposited sequence that are not peptide bonded.
+    ///
+    /// The [`ArePeptideBonded`](ArePeptideBonded) filter is used to check the bond. Such a break means that
+    /// some atoms are missing or the geometry of a chain is broken, even though no residue is missing.
+    pub fn chain_breaks(&self, strctr: &Structure) -> Vec<(ResidueId, ResidueId)> {
+        let is_protein = |res_id: &ResidueId| {
+            strctr.residue_pos(res_id).is_ok_and(|i| KeepProtein.check(&strctr.atoms[strctr.atoms_for_residue_id[i].start]))
+        };
+        self.observed_neighbors()
+            .filter(|(a, b)| is_protein(a) && is_protein(b) && !ArePeptideBonded.check(strctr, a, b))
+            .map(|(a, b)| (a.clone(), b.clone())).collect()
+    }
+
+    /// Iterates over pairs of observed residues that are adjacent in the deposited sequence
+    fn observed_neighbors(&self) -> impl Iterator<Item=(&ResidueId, &ResidueId)> {
+        self.positions.windows(2).filter_map(|w| match (&w[0].res_id, &w[1].res_id) {
+            (Some(a), Some(b)) => Some((a, b)),
+            _ => None,
+        })
+    }
+
+    fn from_positions(chain_id: &str, positions: Vec<SequencePosition>) -> ChainSequenceMap {
+        let seq_ids: HashMap<ResidueId, usize> = positions.iter()
+            .filter_map(|p| p.res_id.as_ref().map(|r| (r.clone(), p.seq_id))).collect();
+        return ChainSequenceMap { chain_id: chain_id.to_string(), positions, seq_ids };
+    }
+}
+
+/// Scores alignment of residue names: identical names are rewarded, any mismatch is penalized
+struct ResidueNameScore<'a> {
+    query: Vec<&'a str>,
+    template: Vec<&'a str>,
+}
+
+impl SimilarityScore for ResidueNameScore<'_> {
+    fn score(&self, i_pos: usize, j_pos: usize) -> i32 { if self.is_identity(i_pos, j_pos) { 5 } else { -5 } }
+
+    fn template_length(&self) -> usize { self.template.len() }
+
+    fn query_length(&self) -> usize { self.query.len() }
+
+    fn is_identity(&self, i: usize, j: usize) -> bool { self.query[i] == self.template[j] }
+}
+
+/// One-letter code of a residue type; `X` when the type is not known
+pub(crate) fn code1(res_name: &str) -> char {
+    return ResidueTypeManager::get().by_code3(res_name).map_or('X', |t| t.parent_type.code1());
+}
@@ -0,0 +1,181 @@
+//! Reduces a full-atom [`Structure`](crate::Structure) to a coarse-grained representation.
+//!
+//! A coarse-grained model replaces groups of atoms by single beads, each placed at the geometric center
+//! of the atoms it represents. Definitions of the beads are not hard-coded but loaded from CIF files.
+//! Each data block defines a single model by a `_cg_bead_atom` loop, that assigns atoms of residues to beads:
+//!
+//! ```text
+//! data_CA_CENTROID
+//! loop_
+//! _cg_bead_atom.res_name
+//! _cg_bead_atom.bead_name
+//! _cg_bead_atom.atom_name
+//! .    CA   CA
+//! ALA  CEN  CB
+//! SER  CEN  CB
+//! SER  CEN  OG
+//! ```
+//! The `.` residue name means the row applies to any amino acid or nucleotide. The models predefined by BioShell
+//! (listed by [`CoarseGrainedModel`]) are stored in the `bioshell-pdb/data/coarse_grained.cif` file:
+//!
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::coarse_grained::{CoarseGrainedMapping, CoarseGrainedModel};
+//! let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+//! let martini = CoarseGrainedMapping::load(CoarseGrainedModel::Martini)?;
+//! let cg = martini.coarse_grain(&strctr);
+//! // --- a backbone bead for every residue
+//! assert_eq!(cg.atoms().iter().filter(|a| a.name == " BB ").count(), 56);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only amino acid and nucleotide residues are coarse-grained; ligands and water molecules are not included
+//! in the resulting structure. Backbone and CB atoms may be restored from a CA trace by the `bioshell-builder` crate.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use bioshell_cif::{read_cif_file, CifData, CifTable};
+use bioshell_core::io::find_bioshell_path;
+use bioshell_core::Vec3;
+use crate::{format_atom_name, is_hydrogen, PDBError, PdbAtom, Structure};
+use crate::pdb_atom_filters::{KeepNucleicAcid, KeepProtein, PdbAtomPredicate};
+
+/// Coarse-grained models predefined in the BioShell data files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoarseGrainedModel {
+    /// a single bead per amino acid: its alpha carbon
+    CA,
+    /// alpha carbon and the center of side chain heavy atoms; glycine has no side chain bead
+    CACentroid,
+    /// MARTINI-like mapping: a backbone bead and up to four side chain beads per amino acid
+    Martini,
+    /// three beads per nucleotide: phosphate, sugar and base
+    Nucleotide3Bead,
+}
+
+impl CoarseGrainedModel {
+    /// Name of the data block that defines this model
+    pub fn block_name(&self) -> &'static str {
+        match self {
+            CoarseGrainedModel::CA => "CA",
+            CoarseGrainedModel::CACentroid => "CA_CENTROID",
+            CoarseGrainedModel::Martini => "MARTINI",
+            CoarseGrainedModel::Nucleotide3Bead => "NUCLEOTIDE_3BEAD",
+        }
+    }
+}
+
+impl Display for CoarseGrainedModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.block_name()) }
+}
+
+/// Defines a single bead of a coarse-grained residue
+#[derive(Clone, Debug)]
+pub struct BeadDefinition {
+    /// name of the bead, e.g. `BB` or `SC1`
+    pub bead_name: String,
+    /// names of atoms represented by this bead
+    pub atom_names: Vec<String>,
+}
+
+/// Defines how residues are converted into beads.
+///
+/// See the [module documentation](crate::coarse_grained) for the data format and an example.
+#[derive(Clone, Debug)]
+pub struct CoarseGrainedMapping {
+    name: String,
+    any_residue: Vec<BeadDefinition>,
+    by_residue: HashMap<String, Vec<BeadDefinition>>,
+}
+
+impl CoarseGrainedMapping {
+
+    /// Loads a mapping predefined by BioShell
+    pub fn load(model: CoarseGrainedModel) -> Result<CoarseGrainedMapping, PDBError> {
+        let fname = "bioshell-pdb/data/coarse_grained.cif";
+        let path = find_bioshell_path().map(|p| p.join(fname))
+            .ok_or(PDBError::MissingBioShellFile { fname: fname.to_string() })?;
+        return CoarseGrainedMapping::from_cif_file(path.to_str().unwrap(), model.block_name());
+    }
+
+    /// Loads a mapping from a given data block of a CIF file
+    pub fn from_cif_file(fname: &str, block_name: &str) -> Result<CoarseGrainedMapping, PDBError> {
+        let blocks = read_cif_file(fname)?;
+        let block = blocks.iter().find(|b| b.name() == block_name)
+            .ok_or(PDBError::UnknownCoarseGrainedModel { model: block_name.to_string() })?;
+        return CoarseGrainedMapping::from_cif_data(block);
+    }
+
+    /// Creates a mapping from a CIF data block
+    pub fn from_cif_data(block: &CifData) -> Result<CoarseGrainedMapping, PDBError> {
+        let mut mapping = CoarseGrainedMapping { name: block.name().to_string(), any_residue: vec![], by_residue: HashMap::new() };
+        let table = CifTable::new(block, "_cg_bead_atom", ["res_name", "bead_name", "atom_name"])?;
+        for [res_name, bead_name, atom_name] in table.iter() {
+            let beads = if res_name == "." { &mut mapping.any_residue }
+                else { mapping.by_residue.entry(res_name.to_string()).or_default() };
+            let atom_name = atom_name.trim_matches(|c| c == '"' || c == '\'').to_string();
+            match beads.iter_mut().find(|b| b.bead_name == bead_name) {
+                Some(bead) => bead.atom_names.push(atom_name),
+                None => beads.push(BeadDefinition { bead_name: bead_name.to_string(), atom_names: vec![atom_name] }),
+            }
+        }
+
+        return Ok(mapping);
+    }
+
+    /// Name of this mapping, i.e. the name of the data block it was loaded from
+    pub fn name(&self) -> &str { &self.name }
+
+    /// Beads defined for a residue type.
+    ///
+    /// Definitions that apply to any residue come first; atoms listed for a specific residue type
+    /// are added to a bead of the same name, if such a bead has already been defined.
+    pub fn beads(&self, res_name: &str) -> Vec<BeadDefinition> {
+        let mut beads = self.any_residue.clone();
+        if let Some(specific) = self.by_residue.get(res_name) {
+            for bead in specific {
+                match beads.iter_mut().find(|b| b.bead_name == bead.bead_name) {
+                    Some(b) => b.atom_names.extend(bead.atom_names.iter().cloned()),
+                    None => beads.push(bead.clone()),
+                }
+            }
+        }
+
+        return beads;
+    }
+
+    /// Creates a coarse-grained copy of a given structure.
+    ///
+    /// Every bead is placed at the geometric center of the heavy atoms it represents; atoms missing in the structure
+    /// are skipped and a bead is not created when none of its atoms is found. The temperature factor of a bead
+    /// is the average over its atoms. A bead made of a single atom keeps its element.
+    pub fn coarse_grain(&self, strctr: &Structure) -> Structure {
+        let (is_aa, is_na) = (KeepProtein, KeepNucleicAcid);
+        let mut beads: Vec<PdbAtom> = vec![];
+        for res_id in strctr.residues() {
+            let atoms: Vec<&PdbAtom> = strctr.atoms_in_residue(res_id).unwrap().filter(|a| !is_hydrogen(a)).collect();
+            if atoms.is_empty() || !(is_aa.check(atoms[0]) || is_na.check(atoms[0])) { continue; }
+            for bead in self.beads(&atoms[0].res_name) {
+                let members: Vec<&&PdbAtom> = atoms.iter()
+                    .filter(|a| bead.atom_names.iter().any(|n| n == a.name.trim())).collect();
+                if members.is_empty() { continue; }
+                let mut center = Vec3::from_float(0.0);
+                for a in &members { center += &a.pos; }
+                center /= members.len() as f64;
+                let mut b = (*members[0]).clone();
+                b.serial = beads.len() as i32 + 1;
+                b.pos = center;
+                b.alt_loc = ' ';
+                b.occupancy = 1.0;
+                b.temp_factor = members.iter().map(|a| a.temp_factor).sum::<f64>() / members.len() as f64;
+                if bead.atom_names.len() > 1 || bead.atom_names[0] != bead.bead_name { b.element = None; }
+                b.name = format_atom_name(&bead.bead_name, b.element.as_deref());
+                beads.push(b);
+            }
+        }
+
+        return Structure::from_iterator(&strctr.id_code, beads);
+    }
+}
@@ -0,0 +1,526 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use log::{debug, warn};
+use bioshell_core::io::open_file;
+use bioshell_core::Vec3;
+use crate::{is_hydrogen, PDBError, Structure};
+use crate::calc::{atom_mass, NeighborGrid};
+use crate::density_maps::SymmetryOperator;
+
+/// Size of the main header of a CCP4/MRC map file (in bytes)
+const HEADER_SIZE: usize = 1024;
+
+/// Length of a single text record of a CCP4 map: a label or a symmetry operator record
+const RECORD_LENGTH: usize = 80;
+
+/// Ratio between the width of Gaussians used to compute a model map and the map resolution.
+///
+/// The value `1 / (PI * sqrt(2))` follows the convention of the `molmap` command of UCSF Chimera
+pub const MODEL_MAP_SIGMA_FACTOR: f64 = 0.225;
+
+/// Basic statistics of density values of a map
+#[derive(Clone, Copy, Debug)]
+pub struct MapStatistics {
+    /// the lowest density value
+    pub min: f64,
+    /// the highest density value
+    pub max: f64,
+    /// average density
+    pub mean: f64,
+    /// root mean square deviation of density values from the mean, i.e. the map sigma
+    pub rms: f64,
+}
+
+/// Volumetric density map, such as an X-ray electron density map or a cryo-EM map.
+///
+/// A map holds density values sampled on a regular grid spanned over a crystallographic unit cell.
+/// [`dims()`](DensityMap::dims) grid points are stored along each of the x, y and z axes,
+/// starting from the [`start()`](DensityMap::start) grid point, while a whole unit cell is divided into
+/// [`sampling()`](DensityMap::sampling) intervals along each axis. Regardless of the axis order used by a
+/// file, the data is stored with x index changing the fastest.
+///
+/// Maps are read from and written to the [CCP4/MRC](https://www.ccpem.ac.uk/mrc_format/mrc2014.php) binary format.
+/// Data modes 0 (8-bit integers), 1 (16-bit integers), 2 (32-bit floats), 6 (16-bit unsigned integers)
+/// and 12 (16-bit floats) can be read, both little- and big-endian; maps are always written in mode 2
+/// and little-endian byte order. Gzip-ed files are also supported by the reader.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::density_maps::DensityMap;
+/// let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+/// // --- a map simulated from the model at 3.0 Angstrom resolution, sampled every 1.0 Angstrom
+/// let map = DensityMap::from_structure(&strctr, 3.0, 1.0);
+/// # let fname = std::env::temp_dir().join("2gb1_doc_density.map");
+/// # let fname = fname.to_str().unwrap();
+/// map.to_file(fname)?;
+/// let map_read = DensityMap::from_file(fname)?;
+/// assert_eq!(map_read.dims(), map.dims());
+/// // --- density interpolated at the position of an atom
+/// let ca = strctr.atoms().iter().find(|a| a.name == " CA ").unwrap();
+/// assert!(map_read.interpolate(&ca.pos) > map_read.statistics().mean);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DensityMap {
+    dims: [usize; 3],
+    start: [i32; 3],
+    sampling: [usize; 3],
+    cell: [f64; 6],
+    origin: Vec3,
+    space_group: u32,
+    symmetry: Vec<SymmetryOperator>,
+    labels: Vec<String>,
+    data: Vec<f32>,
+    to_orth: [[f64; 3]; 3],
+    to_frac: [[f64; 3]; 3],
+}
+
+impl DensityMap {
+
+    /// Creates a new map filled with zeros.
+    ///
+    /// # Arguments
+    /// * `dims` - the number of grid points along x, y and z axes
+    /// * `start` - index of the first grid point along each axis
+    /// * `sampling` - the number of grid intervals per unit cell along each axis
+    /// * `cell` - unit cell dimensions `[a, b, c, alpha, beta, gamma]`, angles in degrees
+    pub fn new(dims: [usize; 3], start: [i32; 3], sampling: [usize; 3], cell: [f64; 6]) -> DensityMap {
+        let (to_orth, to_frac) = orthogonalization_matrices(&cell);
+        return DensityMap { dims, start, sampling, cell, origin: Vec3::from_float(0.0), space_group: 1,
+            symmetry: vec![], labels: vec![], data: vec![0.0; dims[0] * dims[1] * dims[2]], to_orth, to_frac };
+    }
+
+    /// Computes a map from atomic coordinates of a structure.
+    ///
+    /// Each atom contributes a Gaussian, weighted by the atom mass, whose width is
+    /// [`MODEL_MAP_SIGMA_FACTOR`] times the requested `resolution`. The map is computed on an orthogonal
+    /// grid of a given `spacing` (in Angstroms) that encloses all the atoms. Hydrogen atoms are neglected.
+    pub fn from_structure(strctr: &Structure, resolution: f64, spacing: f64) -> DensityMap {
+        let atoms: Vec<_> = strctr.atoms().iter().filter(|a| !is_hydrogen(a)).collect();
+        let sigma = MODEL_MAP_SIGMA_FACTOR * resolution;
+        let margin = 3.0 * sigma + spacing;
+        let mut min = [f64::MAX; 3];
+        let mut max = [f64::MIN; 3];
+        for a in &atoms {
+            for k in 0..3 {
+                min[k] = min[k].min(a.pos[k]);
+                max[k] = max[k].max(a.pos[k]);
+            }
+        }
+        if atoms.is_empty() { min = [0.0; 3]; max = [0.0; 3]; }
+        let start: [i32; 3] = std::array::from_fn(|k| ((min[k] - margin) / spacing).floor() as i32);
+        let dims: [usize; 3] = std::array::from_fn(|k| (((max[k] + margin) / spacing).ceil() as i32 - start[k]) as usize + 1);
+        // --- the map box defines the unit cell, as it's done for cryo-EM maps
+        let cell = [dims[0] as f64 * spacing, dims[1] as f64 * spacing, dims[2] as f64 * spacing, 90.0, 90.0, 90.0];
+        let mut map = DensityMap::new(dims, start, dims, cell);
+        map.labels.push(format!("model map of {} at {:.2} A resolution", strctr.id_code, resolution));
+
+        let positions: Vec<&Vec3> = atoms.iter().map(|a| &a.pos).collect();
+        let masses: Vec<f64> = atoms.iter().map(|a| atom_mass(a)).collect();
+        let grid = NeighborGrid::new(&positions, 3.0 * sigma);
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                for i in 0..dims[0] {
+                    let p = map.grid_position(i, j, k);
+                    let v = model_density(&p, &grid, &masses, &positions, sigma);
+                    map.set_value(i, j, k, v);
+                }
+            }
+        }
+
+        return map;
+    }
+
+    /// Reads a map from a CCP4/MRC file; `.gz` files are decompressed on the fly
+    pub fn from_file(file_name: &str) -> Result<DensityMap, PDBError> {
+        let mut reader = open_file(file_name)?;
+        return DensityMap::from_reader(&mut reader);
+    }
+
+    /// Reads a map in the CCP4/MRC format from a given binary stream
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<DensityMap, PDBError> {
+        let mut bytes: Vec<u8> = vec![];
+        reader.read_to_end(&mut bytes)?;
+        let error = |details: &str| PDBError::DensityMapFormatError { details: details.to_string() };
+        if bytes.len() < HEADER_SIZE { return Err(error("file is shorter than the header")); }
+
+        // --- byte order is given by the machine stamp; old files may lack it, then the mode value decides
+        let little_endian = match bytes[212] {
+            0x44 => true,
+            0x11 => false,
+            _ => (0..=16).contains(&i32::from_le_bytes(bytes[12..16].try_into().unwrap())),
+        };
+        let word = |i: usize| -> i32 {
+            let b: [u8; 4] = bytes[4 * i..4 * i + 4].try_into().unwrap();
+            if little_endian { i32::from_le_bytes(b) } else { i32::from_be_bytes(b) }
+        };
+        let real = |i: usize| -> f64 {
+            let b: [u8; 4] = bytes[4 * i..4 * i + 4].try_into().unwrap();
+            (if little_endian { f32::from_le_bytes(b) } else { f32::from_be_bytes(b) }) as f64
+        };
+
+        let file_dims = [word(0), word(1), word(2)];
+        let mode = word(3);
+        let file_start = [word(4), word(5), word(6)];
+        let sampling = [word(7), word(8), word(9)];
+        let cell = [real(10), real(11), real(12), real(13), real(14), real(15)];
+        let axes = [word(16), word(17), word(18)];
+        let space_group = word(22);
+        let n_symbt = word(23);
+        if file_dims.iter().any(|&d| d <= 0) { return Err(error("number of grid points must be positive")); }
+        if n_symbt < 0 { return Err(error("negative size of the extended header")); }
+        let mut sorted_axes = axes;
+        sorted_axes.sort();
+        if sorted_axes != [1, 2, 3] { return Err(error(&format!("incorrect axis order: {:?}", axes))); }
+        debug!("map header: dims {:?}, mode {}, start {:?}, axes {:?}", file_dims, mode, file_start, axes);
+
+        // --- columns, rows and sections are assigned to x, y and z axes
+        let mut dims = [0usize; 3];
+        let mut start = [0i32; 3];
+        for f in 0..3 {
+            dims[axes[f] as usize - 1] = file_dims[f] as usize;
+            start[axes[f] as usize - 1] = file_start[f];
+        }
+        // --- some EM maps don't define the sampling; then a map box covers a whole cell
+        let sampling: [usize; 3] = std::array::from_fn(|k| if sampling[k] > 0 { sampling[k] as usize } else { dims[k] });
+        let cell = if cell[0] <= 0.0 || cell[1] <= 0.0 || cell[2] <= 0.0 {
+            warn!("map cell dimensions are not defined, assuming 1 Angstrom grid spacing");
+            [sampling[0] as f64, sampling[1] as f64, sampling[2] as f64, 90.0, 90.0, 90.0]
+        } else { cell };
+        let mut map = DensityMap::new(dims, start, sampling, cell);
+        map.space_group = space_group.max(0) as u32;
+        map.origin = Vec3::new(real(49), real(50), real(51));
+
+        // --- text labels
+        let n_labels = (word(55).clamp(0, 10)) as usize;
+        for i in 0..n_labels {
+            let label = String::from_utf8_lossy(&bytes[224 + i * RECORD_LENGTH..224 + (i + 1) * RECORD_LENGTH]);
+            map.labels.push(label.trim_end_matches(['\0', ' ']).to_string());
+        }
+
+        // --- symmetry operators, stored as text in the extended header of CCP4 maps
+        let n_symbt = n_symbt as usize;
+        if bytes.len() < HEADER_SIZE + n_symbt { return Err(error("file is shorter than the extended header")); }
+        let ext_type = String::from_utf8_lossy(&bytes[104..108]).to_string();
+        let extended = &bytes[HEADER_SIZE..HEADER_SIZE + n_symbt];
+        if (ext_type == "CCP4" || !ext_type.chars().all(|c| c.is_ascii_alphanumeric())) && extended.is_ascii() {
+            for record in extended.chunks(RECORD_LENGTH) {
+                for op_text in String::from_utf8_lossy(record).split('*') {
+                    let op_text = op_text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+                    if !op_text.is_empty() { map.symmetry.push(SymmetryOperator::from_text(op_text)?); }
+                }
+            }
+        }
+
+        // --- density values
+        let n = dims[0] * dims[1] * dims[2];
+        let value_size = match mode {
+            0 => 1,
+            1 | 6 | 12 => 2,
+            2 => 4,
+            _ => return Err(error(&format!("unsupported data mode: {}", mode))),
+        };
+        let data = &bytes[HEADER_SIZE + n_symbt..];
+        if data.len() < n * value_size {
+            return Err(error(&format!("expected {} density values, found only {} bytes", n, data.len())));
+        }
+        let value = |i: usize| -> f32 {
+            let b = &data[i * value_size..(i + 1) * value_size];
+            let b2 = || -> [u8; 2] { if little_endian { [b[0], b[1]] } else { [b[1], b[0]] } };
+            match mode {
+                0 => b[0] as i8 as f32,
+                1 => i16::from_le_bytes(b2()) as f32,
+                6 => u16::from_le_bytes(b2()) as f32,
+                12 => half_to_f32(u16::from_le_bytes(b2())),
+                _ => {
+                    let b4: [u8; 4] = b.try_into().unwrap();
+                    if little_endian { f32::from_le_bytes(b4) } else { f32::from_be_bytes(b4) }
+                }
+            }
+        };
+        let mut idx = 0;
+        let mut xyz = [0usize; 3];
+        for s in 0..file_dims[2] as usize {
+            xyz[axes[2] as usize - 1] = s;
+            for r in 0..file_dims[1] as usize {
+                xyz[axes[1] as usize - 1] = r;
+                for c in 0..file_dims[0] as usize {
+                    xyz[axes[0] as usize - 1] = c;
+                    let at = map.index(xyz[0], xyz[1], xyz[2]);
+                    map.data[at] = value(idx);
+                    idx += 1;
+                }
+            }
+        }
+
+        return Ok(map);
+    }
+
+    /// Writes this map into a file in the CCP4/MRC format
+    pub fn to_file(&self, file_name: &str) -> Result<(), PDBError> {
+        let mut out = BufWriter::new(File::create(file_name)?);
+        self.write(&mut out)?;
+        out.flush()?;
+
+        return Ok(());
+    }
+
+    /// Writes this map in the CCP4/MRC format, as 32-bit floats, into a given stream.
+    ///
+    /// Symmetry operators are stored in the extended header, one operator per 80-character record.
+    pub fn write<W: Write>(&self, out: &mut W) -> Result<(), PDBError> {
+        let mut header = [0u8; HEADER_SIZE];
+        let put_int = |i: usize, v: i32, header: &mut [u8; HEADER_SIZE]| header[4 * i..4 * i + 4].copy_from_slice(&v.to_le_bytes());
+        let put_real = |i: usize, v: f64, header: &mut [u8; HEADER_SIZE]| header[4 * i..4 * i + 4].copy_from_slice(&(v as f32).to_le_bytes());
+        let stats = self.statistics();
+        for k in 0..3 {
+            put_int(k, self.dims[k] as i32, &mut header);
+            put_int(4 + k, self.start[k], &mut header);
+            put_int(7 + k, self.sampling[k] as i32, &mut header);
+            put_int(16 + k, k as i32 + 1, &mut header);
+        }
+        put_int(3, 2, &mut header);
+        for k in 0..6 { put_real(10 + k, self.cell[k], &mut header); }
+        put_real(19, stats.min, &mut header);
+        put_real(20, stats.max, &mut header);
+        put_real(21, stats.mean, &mut header);
+        put_int(22, self.space_group as i32, &mut header);
+        put_int(23, (self.symmetry.len() * RECORD_LENGTH) as i32, &mut header);
+        if !self.symmetry.is_empty() { header[104..108].copy_from_slice(b"CCP4"); }
+        put_int(27, 20140, &mut header);
+        put_real(49, self.origin.x, &mut header);
+        put_real(50, self.origin.y, &mut header);
+        put_real(51, self.origin.z, &mut header);
+        header[208..212].copy_from_slice(b"MAP ");
+        header[212..216].copy_from_slice(&[0x44, 0x44, 0x00, 0x00]);
+        put_real(54, stats.rms, &mut header);
+        let n_labels = self.labels.len().min(10);
+        put_int(55, n_labels as i32, &mut header);
+        for (i, label) in self.labels.iter().take(n_labels).enumerate() {
+            let text = label.as_bytes();
+            let n = text.len().min(RECORD_LENGTH);
+            header[224 + i * RECORD_LENGTH..224 + i * RECORD_LENGTH + n].copy_from_slice(&text[..n]);
+        }
+        out.write_all(&header)?;
+
+        for op in &self.symmetry {
+            out.write_all(format!("{:<80}", op.to_string()).as_bytes())?;
+        }
+        for v in &self.data { out.write_all(&v.to_le_bytes())?; }
+
+        return Ok(());
+    }
+
+    /// The number of grid points along x, y and z axes
+    pub fn dims(&self) -> [usize; 3] { self.dims }
+
+    /// Index of the first grid point along x, y and z axes
+    pub fn start(&self) -> [i32; 3] { self.start }
+
+    /// The number of grid intervals a unit cell is divided into along x, y and z axes
+    pub fn sampling(&self) -> [usize; 3] { self.sampling }
+
+    /// Unit cell of this map: `[a, b, c, alpha, beta, gamma]`, angles in degrees
+    pub fn cell(&self) -> [f64; 6] { self.cell }
+
+    /// Origin of this map, as given by the `ORIGIN` field of the MRC2014 format
+    pub fn origin(&self) -> &Vec3 { &self.origin }
+
+    /// Space group number of this map; 0 or 1 for cryo-EM maps
+    pub fn space_group(&self) -> u32 { self.space_group }
+
+    /// Sets the space group number of this map
+    pub fn set_space_group(&mut self, space_group: u32) { self.space_group = space_group; }
+
+    /// Symmetry operators stored in this map; may be empty
+    pub fn symmetry_operators(&self) -> &[SymmetryOperator] { &self.symmetry }
+
+    /// Sets symmetry operators of this map
+    pub fn set_symmetry_operators(&mut self, operators: Vec<SymmetryOperator>) { self.symmetry = operators; }
+
+    /// Text labels of this map
+    pub fn labels(&self) -> &[String] { &self.labels }
+
+    /// Adds a text label; only the first 10 labels, up to 80 characters each, are written to a file
+    pub fn add_label(&mut self, label: &str) { self.labels.push(label.to_string()); }
+
+    /// Distance between grid points along x, y and z axes, in Angstroms
+    pub fn voxel_size(&self) -> [f64; 3] { std::array::from_fn(|k| self.cell[k] / self.sampling[k] as f64) }
+
+    /// Density value at a given grid point
+    pub fn value(&self, i: usize, j: usize, k: usize) -> f64 { self.data[self.index(i, j, k)] as f64 }
+
+    /// Sets the density value at a given grid point
+    pub fn set_value(&mut self, i: usize, j: usize, k: usize, v: f64) {
+        let at = self.index(i, j, k);
+        self.data[at] = v as f32;
+    }
+
+    /// Computes statistics of the density values of this map
+    pub fn statistics(&self) -> MapStatistics {
+        let n = self.data.len().max(1) as f64;
+        let mut stats = MapStatistics { min: f64::MAX, max: f64::MIN, mean: 0.0, rms: 0.0 };
+        for &v in &self.data {
+            stats.min = stats.min.min(v as f64);
+            stats.max = stats.max.max(v as f64);
+            stats.mean += v as f64;
+        }
+        stats.mean /= n;
+        stats.rms = (self.data.iter().map(|&v| (v as f64 - stats.mean).powi(2)).sum::<f64>() / n).sqrt();
+        if self.data.is_empty() { stats.min = 0.0; stats.max = 0.0; }
+
+        return stats;
+    }
+
+    /// Cartesian coordinates of a given grid point.
+    ///
+    /// When the map defines a non-zero [`origin()`](DensityMap::origin), the first grid point is located
+    /// at the origin and the [`start()`](DensityMap::start) indexes are not used; otherwise the position
+    /// of a grid point follows from its indexes in the unit cell.
+    pub fn grid_position(&self, i: usize, j: usize, k: usize) -> Vec3 {
+        let offset = self.grid_offset();
+        let frac = [(i as f64 + offset[0]) / self.sampling[0] as f64, (j as f64 + offset[1]) / self.sampling[1] as f64,
+            (k as f64 + offset[2]) / self.sampling[2] as f64];
+        let mut p = mat_vec(&self.to_orth, &frac);
+        p += &self.origin;
+
+        return p;
+    }
+
+    /// Converts Cartesian coordinates into fractional coordinates of the unit cell of this map
+    pub fn fractional(&self, pos: &Vec3) -> [f64; 3] {
+        let mut p = *pos;
+        p -= &self.origin;
+        let f = mat_vec(&self.to_frac, &[p.x, p.y, p.z]);
+
+        return [f.x, f.y, f.z];
+    }
+
+    /// Density at an arbitrary position computed by trilinear interpolation.
+    ///
+    /// A crystallographic map, i.e. a map that defines symmetry operators, is treated as periodic when it
+    /// covers the whole unit cell (see [`expand_to_unit_cell()`](DensityMap::expand_to_unit_cell)).
+    /// Otherwise, 0.0 is returned for positions outside the map box.
+    pub fn interpolate(&self, pos: &Vec3) -> f64 {
+        let frac = self.fractional(pos);
+        let offset = self.grid_offset();
+        let periodic = self.dims == self.sampling && !self.symmetry.is_empty();
+        let mut base = [0i64; 3];
+        let mut w = [0.0; 3];
+        for k in 0..3 {
+            let u = frac[k] * self.sampling[k] as f64 - offset[k];
+            let u0 = u.floor();
+            base[k] = u0 as i64;
+            w[k] = u - u0;
+            if !periodic && (u < 0.0 || u > (self.dims[k] - 1) as f64) { return 0.0; }
+        }
+        let index = |k: usize, di: i64| -> usize {
+            let i = base[k] + di;
+            if periodic { i.rem_euclid(self.dims[k] as i64) as usize } else { (i as usize).min(self.dims[k] - 1) }
+        };
+        let mut out = 0.0;
+        for dk in 0..2 {
+            for dj in 0..2 {
+                for di in 0..2 {
+                    let weight = (if di == 1 { w[0] } else { 1.0 - w[0] }) * (if dj == 1 { w[1] } else { 1.0 - w[1] })
+                        * (if dk == 1 { w[2] } else { 1.0 - w[2] });
+                    if weight == 0.0 { continue; }
+                    out += weight * self.value(index(0, di), index(1, dj), index(2, dk));
+                }
+            }
+        }
+
+        return out;
+    }
+
+    /// Creates a map that covers the whole unit cell by applying symmetry operators of this map.
+    ///
+    /// X-ray maps are often deposited for an asymmetric unit only. Every grid point of this map is copied
+    /// to all its symmetry-related positions in the unit cell; the resulting map is periodic,
+    /// so it can be interpolated at any position. The identity operator is used when the map lists no
+    /// symmetry operators. Grid points that can't be reached by any operator are set to 0.0.
+    /// The resulting map is in the P1 space group.
+    pub fn expand_to_unit_cell(&self) -> DensityMap {
+        let mut out = DensityMap::new(self.sampling, [0; 3], self.sampling, self.cell);
+        out.origin = self.origin;
+        out.labels = self.labels.clone();
+        out.symmetry = vec![SymmetryOperator::identity()];
+        let operators = if self.symmetry.is_empty() { vec![SymmetryOperator::identity()] } else { self.symmetry.clone() };
+        let mut filled = vec![false; out.data.len()];
+        for k in 0..self.dims[2] {
+            for j in 0..self.dims[1] {
+                for i in 0..self.dims[0] {
+                    let v = self.data[self.index(i, j, k)];
+                    let g = [i as i32 + self.start[0], j as i32 + self.start[1], k as i32 + self.start[2]];
+                    let frac: [f64; 3] = std::array::from_fn(|a| g[a] as f64 / self.sampling[a] as f64);
+                    for op in &operators {
+                        let f = op.apply(&frac);
+                        let g2: [usize; 3] = std::array::from_fn(|a|
+                            ((f[a] * self.sampling[a] as f64).round() as i64).rem_euclid(self.sampling[a] as i64) as usize);
+                        let at = out.index(g2[0], g2[1], g2[2]);
+                        out.data[at] = v;
+                        filled[at] = true;
+                    }
+                }
+            }
+        }
+        let n_empty = filled.iter().filter(|&&f| !f).count();
+        if n_empty > 0 { warn!("{} grid points of the unit cell not covered by the symmetry-expanded map", n_empty); }
+
+        return out;
+    }
+
+    fn index(&self, i: usize, j: usize, k: usize) -> usize { (k * self.dims[1] + j) * self.dims[0] + i }
+
+    /// Grid offset of the first map point; zero when the map origin is given explicitly
+    fn grid_offset(&self) -> [f64; 3] {
+        if self.origin.x != 0.0 || self.origin.y != 0.0 || self.origin.z != 0.0 { return [0.0; 3]; }
+        return [self.start[0] as f64, self.start[1] as f64, self.start[2] as f64];
+    }
+}
+
+/// Density of a model at a given point, computed as a sum of Gaussians centered at atoms
+pub(crate) fn model_density(p: &Vec3, grid: &NeighborGrid, weights: &[f64], positions: &[&Vec3], sigma: f64) -> f64 {
+    let two_sigma2 = 2.0 * sigma * sigma;
+    return grid.neighbors(p, 3.0 * sigma).iter()
+        .map(|&i| weights[i] * (-positions[i].distance_square_to(p) / two_sigma2).exp()).sum();
+}
+
+/// Matrices converting fractional coordinates into Cartesian coordinates and back, following the PDB convention
+fn orthogonalization_matrices(cell: &[f64; 6]) -> ([[f64; 3]; 3], [[f64; 3]; 3]) {
+    let (a, b, c) = (cell[0], cell[1], cell[2]);
+    let (ca, cb, cg) = (cell[3].to_radians().cos(), cell[4].to_radians().cos(), cell[5].to_radians().cos());
+    let sg = cell[5].to_radians().sin();
+    let v = (1.0 - ca * ca - cb * cb - cg * cg + 2.0 * ca * cb * cg).sqrt();
+    let m = [[a, b * cg, c * cb], [0.0, b * sg, c * (ca - cb * cg) / sg], [0.0, 0.0, c * v / sg]];
+    // --- inverse of the upper triangular matrix
+    let mut inv = [[0.0; 3]; 3];
+    inv[0][0] = 1.0 / m[0][0];
+    inv[1][1] = 1.0 / m[1][1];
+    inv[2][2] = 1.0 / m[2][2];
+    inv[0][1] = -m[0][1] / (m[0][0] * m[1][1]);
+    inv[1][2] = -m[1][2] / (m[1][1] * m[2][2]);
+    inv[0][2] = (m[0][1] * m[1][2] - m[0][2] * m[1][1]) / (m[0][0] * m[1][1] * m[2][2]);
+
+    return (m, inv);
+}
+
+fn mat_vec(m: &[[f64; 3]; 3], v: &[f64; 3]) -> Vec3 {
+    return Vec3::new(m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+                     m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+                     m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2]);
+}
+
+/// Converts an IEEE 754 half-precision number into `f32`
+fn half_to_f32(h: u16) -> f32 {
+    let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = ((h >> 10) & 0x1f) as i32;
+    let frac = (h & 0x3ff) as f32;
+    return sign * match exp {
+        0 => frac * 2f32.powi(-24),
+        31 => if frac == 0.0 { f32::INFINITY } else { f32::NAN },
+        _ => (1.0 + frac / 1024.0) * 2f32.powi(exp - 15),
+    };
+}
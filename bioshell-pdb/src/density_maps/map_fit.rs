@@ -0,0 +1,225 @@
+use std::fmt::{Display, Formatter};
+use bioshell_core::Vec3;
+use crate::{is_hydrogen, PdbAtom, ResidueId, Structure};
+use crate::calc::{atom_mass, golden_spiral_points, NeighborGrid};
+use crate::pdb_atom_filters::PdbAtomPredicate;
+use crate::density_maps::ccp4::{model_density, MODEL_MAP_SIGMA_FACTOR};
+use crate::density_maps::DensityMap;
+
+/// The number of points sampled on each sphere around an atom while its Q-score is computed
+const Q_SCORE_POINTS: usize = 8;
+
+/// The number of candidate points generated on each sphere; only those closer to the atom than to any other atom are used
+const Q_SCORE_CANDIDATES: usize = 64;
+
+/// Model-to-map fit of a single residue, computed by [`MapFitEvaluator`]
+#[derive(Clone, Debug)]
+pub struct ResidueMapFit {
+    /// residue identifier
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    /// the number of heavy atoms of the residue
+    pub n_atoms: usize,
+    /// Pearson correlation between the map and the model density, computed around the residue
+    pub correlation: f64,
+    /// the average Q-score of the residue atoms
+    pub q_score: f64,
+    /// fraction of the residue atoms located in a density above the contour level
+    pub atom_inclusion: f64,
+    /// the average map density at the residue atoms
+    pub mean_density: f64,
+}
+
+impl Display for ResidueMapFit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {:3} {:6.3} {:6.3} {:5.3} {:8.3}", self.res_id, self.res_name, self.n_atoms,
+               self.correlation, self.q_score, self.atom_inclusion, self.mean_density)
+    }
+}
+
+/// Computes per-residue metrics measuring how well a [`Structure`] fits a [`DensityMap`].
+///
+/// Three metrics are evaluated for each residue:
+///
+///  - **density correlation**: Pearson correlation between the map and a model map, computed at points
+///    located within [`mask_radius`](MapFitEvaluator::mask_radius) from any atom of the residue.
+///    The model density is a sum of Gaussians, as in [`DensityMap::from_structure()`], evaluated for all
+///    the atoms of the structure, so neighbouring residues contribute as well.
+///  - **Q-score**: resolvability of atoms, following [Pintilie *et al.*](https://doi.org/10.1038/s41592-020-0731-1).
+///    Map values are sampled on spheres of radius growing from 0.0 up to [`q_radius`](MapFitEvaluator::q_radius)
+///    around an atom, at points closer to that atom than to any other one. These values are correlated
+///    with a reference Gaussian of width [`q_sigma`](MapFitEvaluator::q_sigma), whose height and offset
+///    are derived from the map average and standard deviation. The value reported for a residue is the average
+///    over its atoms; well resolved atoms score close to 1.0.
+///  - **atom inclusion**: the fraction of the residue atoms found in a density above the contour level.
+///
+/// Hydrogen atoms are neglected. Low values of these metrics point to poorly fitted regions of a model.
+///
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::density_maps::{DensityMap, MapFitEvaluator};
+/// let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+/// let map = DensityMap::from_structure(&strctr, 3.0, 1.0);
+/// let fits = MapFitEvaluator::default().evaluate(&strctr, &map);
+/// assert_eq!(fits.len(), strctr.count_residues());
+/// // --- a model fits its own map perfectly
+/// assert!(fits.iter().all(|f| f.correlation > 0.95 && f.atom_inclusion == 1.0));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MapFitEvaluator {
+    /// resolution of the map, used to compute the model density (in Angstroms)
+    pub resolution: f64,
+    /// radius around atoms of a residue where density correlation is computed (in Angstroms)
+    pub mask_radius: f64,
+    /// width of the reference Gaussian used by the Q-score (in Angstroms)
+    pub q_sigma: f64,
+    /// the largest radius of spheres sampled by the Q-score (in Angstroms)
+    pub q_radius: f64,
+    /// density threshold used to compute atom inclusion; when not given, the map mean plus one map sigma is used
+    pub contour_level: Option<f64>,
+}
+
+impl Default for MapFitEvaluator {
+    fn default() -> Self {
+        MapFitEvaluator { resolution: 3.0, mask_radius: 2.0, q_sigma: 0.6, q_radius: 2.0, contour_level: None }
+    }
+}
+
+impl MapFitEvaluator {
+
+    /// Computes the fit of every residue of a structure to a given map.
+    ///
+    /// The returned vector is ordered as [`Structure::residues()`](Structure::residues()).
+    pub fn evaluate(&self, strctr: &Structure, map: &DensityMap) -> Vec<ResidueMapFit> {
+        let heavy: Vec<&PdbAtom> = strctr.atoms().iter().filter(|a| !is_hydrogen(a)).collect();
+        let positions: Vec<&Vec3> = heavy.iter().map(|a| &a.pos).collect();
+        let masses: Vec<f64> = heavy.iter().map(|a| atom_mass(a)).collect();
+        let sigma = MODEL_MAP_SIGMA_FACTOR * self.resolution;
+        let model_grid = NeighborGrid::new(&positions, 3.0 * sigma);
+        let atom_grid = NeighborGrid::new(&positions, self.q_radius);
+
+        let stats = map.statistics();
+        let contour = self.contour_level.unwrap_or(stats.mean + stats.rms);
+        let step = map.voxel_size().iter().cloned().fold(f64::MAX, f64::min);
+        let sphere = golden_spiral_points(Q_SCORE_CANDIDATES);
+
+        let mut out: Vec<ResidueMapFit> = vec![];
+        let mut first = 0;
+        for res_id in strctr.residues() {
+            // --- heavy atoms are ordered as in the structure, so each residue takes a contiguous range
+            let mut last = first;
+            while last < heavy.len() && res_id.check(heavy[last]) { last += 1; }
+            let res_atoms: Vec<usize> = (first..last).collect();
+            first = last;
+            let res_name = strctr.residue_name(res_id).unwrap_or("UNK").to_string();
+            if res_atoms.is_empty() {
+                out.push(ResidueMapFit { res_id: res_id.clone(), res_name, n_atoms: 0, correlation: 0.0,
+                    q_score: 0.0, atom_inclusion: 0.0, mean_density: 0.0 });
+                continue;
+            }
+
+            // --- density correlation on a Cartesian lattice around the residue
+            let (obs, calc) = self.masked_values(&res_atoms, &positions, step, |p| {
+                (map.interpolate(p), model_density(p, &model_grid, &masses, &positions, sigma))
+            });
+            let correlation = pearson(&obs, &calc);
+
+            let q_score = res_atoms.iter()
+                .map(|&i| self.q_score(i, &positions, &atom_grid, &sphere, map, stats.mean, stats.rms))
+                .sum::<f64>() / res_atoms.len() as f64;
+
+            let densities: Vec<f64> = res_atoms.iter().map(|&i| map.interpolate(positions[i])).collect();
+            let atom_inclusion = densities.iter().filter(|&&d| d >= contour).count() as f64 / res_atoms.len() as f64;
+            let mean_density = densities.iter().sum::<f64>() / res_atoms.len() as f64;
+
+            out.push(ResidueMapFit { res_id: res_id.clone(), res_name, n_atoms: res_atoms.len(), correlation,
+                q_score, atom_inclusion, mean_density });
+        }
+
+        return out;
+    }
+
+    /// Evaluates a function at lattice points located within the mask radius from any of the given atoms
+    fn masked_values<F>(&self, atoms: &[usize], positions: &[&Vec3], step: f64, f: F) -> (Vec<f64>, Vec<f64>)
+        where F: Fn(&Vec3) -> (f64, f64) {
+        let mut min = [f64::MAX; 3];
+        let mut max = [f64::MIN; 3];
+        for &i in atoms {
+            for k in 0..3 {
+                min[k] = min[k].min(positions[i][k] - self.mask_radius);
+                max[k] = max[k].max(positions[i][k] + self.mask_radius);
+            }
+        }
+        let n: [usize; 3] = std::array::from_fn(|k| ((max[k] - min[k]) / step).ceil() as usize + 1);
+        let r2 = self.mask_radius * self.mask_radius;
+        let (mut a, mut b) = (vec![], vec![]);
+        for iz in 0..n[2] {
+            for iy in 0..n[1] {
+                for ix in 0..n[0] {
+                    let p = Vec3::new(min[0] + ix as f64 * step, min[1] + iy as f64 * step, min[2] + iz as f64 * step);
+                    if !atoms.iter().any(|&i| positions[i].distance_square_to(&p) <= r2) { continue; }
+                    let (va, vb) = f(&p);
+                    a.push(va);
+                    b.push(vb);
+                }
+            }
+        }
+
+        return (a, b);
+    }
+
+    /// Computes the Q-score of the `i`-th atom
+    #[allow(clippy::too_many_arguments)]
+    fn q_score(&self, i: usize, positions: &[&Vec3], atom_grid: &NeighborGrid, sphere: &[Vec3],
+               map: &DensityMap, map_mean: f64, map_sigma: f64) -> f64 {
+        let height = map_mean + 10.0 * map_sigma;
+        let offset = map_mean - map_sigma;
+        let center = positions[i];
+        let (mut u, mut v) = (vec![], vec![]);
+        let n_shells = (self.q_radius / 0.1).round() as usize;
+        for shell in 0..=n_shells {
+            let r = shell as f64 * self.q_radius / n_shells as f64;
+            let reference = height * (-r * r / (2.0 * self.q_sigma * self.q_sigma)).exp() + offset;
+            let mut n_points = 0;
+            for dir in sphere {
+                if n_points == Q_SCORE_POINTS { break; }
+                let mut p = *dir;
+                p *= r;
+                p += center;
+                // --- a point must be closer to this atom than to any other atom
+                if r > 0.0 && atom_grid.neighbors(&p, r).iter()
+                    .any(|&j| j != i && positions[j].distance_square_to(&p) < r * r) { continue; }
+                u.push(map.interpolate(&p));
+                v.push(reference);
+                n_points += 1;
+            }
+        }
+
+        return pearson(&u, &v);
+    }
+}
+
+/// Pearson correlation coefficient; 0.0 is returned when any of the two series is constant
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() < 2 { return 0.0; }
+    let n = a.len() as f64;
+    let (ma, mb) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let a0: Vec<f64> = a.iter().map(|x| x - ma).collect();
+    let b0: Vec<f64> = b.iter().map(|x| x - mb).collect();
+
+    return normalized_cross_correlation(&a0, &b0);
+}
+
+/// Cross-correlation of two series normalized by their lengths
+fn normalized_cross_correlation(a: &[f64], b: &[f64]) -> f64 {
+    let ab: f64 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let aa: f64 = a.iter().map(|x| x * x).sum();
+    let bb: f64 = b.iter().map(|x| x * x).sum();
+    if aa == 0.0 || bb == 0.0 { return 0.0; }
+
+    return ab / (aa * bb).sqrt();
+}
@@ -0,0 +1,33 @@
+//! Reads and writes volumetric density maps and measures how well a model fits a map.
+//!
+//! [`DensityMap`] holds X-ray or cryo-EM density sampled on a regular grid; it's read from and written to
+//! the CCP4/MRC binary format. Symmetry operators found in the extended header of a CCP4 map
+//! are parsed into [`SymmetryOperator`] objects, which allows to expand an asymmetric unit map
+//! to the whole unit cell. Density at any position is computed by trilinear interpolation.
+//!
+//! [`MapFitEvaluator`] computes per-residue model-to-map metrics: density correlation, Q-score and atom inclusion.
+//! Residues of low scores are likely poorly fitted into the map:
+//!
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::density_maps::{DensityMap, MapFitEvaluator};
+//! let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+//! # let fname = std::env::temp_dir().join("2gb1_doc_density_maps.map");
+//! # let fname = fname.to_str().unwrap();
+//! # DensityMap::from_structure(&strctr, 3.0, 1.0).to_file(fname)?;
+//! let map = DensityMap::from_file(fname)?;
+//! let evaluator = MapFitEvaluator { resolution: 3.0, ..Default::default() };
+//! for fit in evaluator.evaluate(&strctr, &map).iter().filter(|f| f.q_score < 0.5) {
+//!     println!("poorly fitted: {}", fit);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+mod ccp4;
+mod symmetry;
+mod map_fit;
+
+pub use ccp4::{DensityMap, MapStatistics, MODEL_MAP_SIGMA_FACTOR};
+pub use symmetry::SymmetryOperator;
+pub use map_fit::{MapFitEvaluator, ResidueMapFit};
@@ -0,0 +1,122 @@
+use std::fmt::{Display, Formatter};
+use crate::PDBError;
+
+/// A crystallographic symmetry operator acting on fractional coordinates.
+///
+/// An operator is given by a rotation matrix and a translation vector, such as `X,Y+1/2,-Z`.
+/// CCP4 maps store symmetry operators in their extended header, as text in that format.
+///
+/// ```
+/// use bioshell_pdb::density_maps::SymmetryOperator;
+/// let op = SymmetryOperator::from_text("-X,Y+1/2,-Z").unwrap();
+/// let f = op.apply(&[0.1, 0.2, 0.3]);
+/// assert!((f[0] + 0.1).abs() < 1e-6 && (f[1] - 0.7).abs() < 1e-6 && (f[2] + 0.3).abs() < 1e-6);
+/// assert_eq!(op.to_string(), "-X,Y+1/2,-Z");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymmetryOperator {
+    /// rotation part of this operator
+    pub rotation: [[f64; 3]; 3],
+    /// translation part of this operator, in fractional units
+    pub translation: [f64; 3],
+}
+
+impl SymmetryOperator {
+
+    /// The identity operator: `X,Y,Z`
+    pub fn identity() -> SymmetryOperator {
+        return SymmetryOperator { rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], translation: [0.0; 3] };
+    }
+
+    /// Parses a symmetry operator given as text, e.g. `"-X,Y+1/2,-Z"` or `"1/2+x, 1/2-y, -z"`
+    pub fn from_text(text: &str) -> Result<SymmetryOperator, PDBError> {
+        let error = || PDBError::DensityMapFormatError { details: format!("can't parse symmetry operator: {}", text) };
+        let rows: Vec<&str> = text.split(',').collect();
+        if rows.len() != 3 { return Err(error()); }
+
+        let mut op = SymmetryOperator { rotation: [[0.0; 3]; 3], translation: [0.0; 3] };
+        for (i, row) in rows.iter().enumerate() {
+            let row: String = row.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+            if row.is_empty() { return Err(error()); }
+            // --- split a row into signed terms, e.g. "-X+1/2" -> ["-X", "+1/2"]
+            let mut terms: Vec<String> = vec![];
+            for c in row.chars() {
+                if (c == '+' || c == '-') || terms.is_empty() { terms.push(String::new()); }
+                terms.last_mut().unwrap().push(c);
+            }
+            for term in terms {
+                let (sign, body) = match term.strip_prefix('-') {
+                    Some(body) => (-1.0, body),
+                    None => (1.0, term.trim_start_matches('+')),
+                };
+                if let Some(axis) = body.chars().last().and_then(|c| "XYZ".find(c)) {
+                    let factor = &body[..body.len() - 1];
+                    let factor = factor.trim_end_matches('*');
+                    let factor = if factor.is_empty() { 1.0 } else { parse_fraction(factor).ok_or_else(error)? };
+                    op.rotation[i][axis] += sign * factor;
+                } else {
+                    op.translation[i] += sign * parse_fraction(body).ok_or_else(error)?;
+                }
+            }
+        }
+
+        return Ok(op);
+    }
+
+    /// Applies this operator to fractional coordinates
+    pub fn apply(&self, frac: &[f64; 3]) -> [f64; 3] {
+        let mut out = self.translation;
+        for (i, row) in self.rotation.iter().enumerate() {
+            for (k, r) in row.iter().enumerate() { out[i] += r * frac[k]; }
+        }
+
+        return out;
+    }
+
+    /// Returns `true` if this is the identity operator
+    pub fn is_identity(&self) -> bool { *self == SymmetryOperator::identity() }
+}
+
+impl Display for SymmetryOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rows: Vec<String> = vec![];
+        for i in 0..3 {
+            let mut row = String::new();
+            for (axis, name) in ["X", "Y", "Z"].iter().enumerate() {
+                let r = self.rotation[i][axis];
+                if r == 0.0 { continue; }
+                let sign = if r < 0.0 { "-" } else if row.is_empty() { "" } else { "+" };
+                if r.abs() == 1.0 { row.push_str(&format!("{}{}", sign, name)); }
+                else { row.push_str(&format!("{}{}*{}", sign, format_fraction(r.abs()), name)); }
+            }
+            let t = self.translation[i];
+            if t != 0.0 {
+                let sign = if t < 0.0 { "-" } else if row.is_empty() { "" } else { "+" };
+                row.push_str(&format!("{}{}", sign, format_fraction(t.abs())));
+            }
+            if row.is_empty() { row.push('0'); }
+            rows.push(row);
+        }
+        write!(f, "{}", rows.join(","))
+    }
+}
+
+/// Parses a number that may be given as a fraction, e.g. `"1/2"` or `"0.5"`
+fn parse_fraction(text: &str) -> Option<f64> {
+    return match text.split_once('/') {
+        Some((n, d)) => Some(n.parse::<f64>().ok()? / d.parse::<f64>().ok()?),
+        None => text.parse::<f64>().ok(),
+    };
+}
+
+/// Formats a positive number as a fraction when its denominator is 2, 3, 4 or 6
+fn format_fraction(v: f64) -> String {
+    for d in [1, 2, 3, 4, 6] {
+        let n = v * d as f64;
+        if (n - n.round()).abs() < 1e-6 {
+            return if d == 1 { format!("{}", n.round() as i64) } else { format!("{}/{}", n.round() as i64, d) };
+        }
+    }
+
+    return format!("{}", v);
+}
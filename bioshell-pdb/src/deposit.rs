@@ -1,7 +1,9 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use bioshell_cif::CifData;
-use crate::{Entity, ExperimentalMethod, is_cif_file, is_pdb_file, PDBError, Structure, UnitCell};
+use crate::{Entity, ExperimentalMethod, is_cif_file, is_pdb_file, ModifiedResidue, PDBError, Structure, UnitCell};
+use crate::chain_mapping::ChainSequenceMap;
+use crate::sifts::UniProtMapping;
 
 /// Holds all the data describing a macromolecular deposit, parsed from either an mmCIF or PDB file.
 pub struct Deposit {
@@ -36,7 +38,10 @@ pub struct Deposit {
     pub n_models: usize,
     pub(crate) entities: HashMap<String, Entity>,
     pub(crate) structure: Option<Structure>,
-    pub(crate) cif_buffer: Option<CifData>
+    pub(crate) cif_buffer: Option<CifData>,
+    /// residue names listed by SEQRES records of a PDB file, for every chain
+    pub(crate) seqres: HashMap<String, Vec<String>>,
+    pub(crate) modified_residues: Vec<ModifiedResidue>,
 }
 
 impl Deposit {
@@ -58,6 +63,8 @@ impl Deposit {
             entities: Default::default(),
             structure: None,
             cif_buffer: None,
+            seqres: Default::default(),
+            modified_residues: vec![],
         }
     }
 
@@ -165,6 +172,82 @@ impl Deposit {
     /// ```
     pub fn entity(&self, entity_id: &str) -> Option<&Entity>  { self.entities.get(entity_id) }
 
+    /// Maps the deposited sequence of a given chain onto residues observed in the structure.
+    ///
+    /// The deposited sequence is taken from the `_pdbx_poly_seq_scheme` category of an mmCIF file
+    /// or from `SEQRES` records of a PDB file. See [`ChainSequenceMap`] for details.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_pdb::chain_mapping::MissingSegmentKind;
+    /// let deposit = Deposit::from_file("./tests/test_files/1c5n.cif")?;
+    /// let seq_map = deposit.chain_sequence_map("H")?;
+    /// let missing = seq_map.missing_segments();
+    /// assert_eq!(missing.len(), 1);
+    /// assert_eq!(missing[0].kind, MissingSegmentKind::Loop);
+    /// assert_eq!(missing[0].len(), 7);
+    /// assert_eq!(missing[0].before, Some(ResidueId::new("H", 147, ' ')));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chain_sequence_map(&self, chain_id: &str) -> Result<ChainSequenceMap, PDBError> {
+        let strctr = self.structure()?;
+        if let Some(cif_data) = &self.cif_buffer {
+            return ChainSequenceMap::from_cif_data(cif_data, &strctr, chain_id);
+        }
+        return match self.seqres.get(chain_id) {
+            Some(res_names) => ChainSequenceMap::from_sequence(&strctr, chain_id, res_names),
+            None => Err(PDBError::NoSequenceData { chain_id: chain_id.to_string() }),
+        };
+    }
+
+    /// Maps residues of this deposit onto UniProt sequences.
+    ///
+    /// The mapping is created from the `_struct_ref_seq` and `_struct_ref_seq_dif` categories, therefore
+    /// it's available only for deposits loaded from mmCIF data. Use a SIFTS file otherwise,
+    /// see [`UniProtMapping`] for details.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_pdb::sifts::SequenceDifference;
+    /// let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    /// let mapping = deposit.uniprot_mapping()?;
+    /// let tags: Vec<_> = mapping.differences()
+    ///     .filter(|r| r.difference == Some(SequenceDifference::CloningArtifact)).collect();
+    /// assert_eq!(tags.len(), 4);  // GLY-HIS at the N-terminus of both chains
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn uniprot_mapping(&self) -> Result<UniProtMapping, PDBError> {
+        return match &self.cif_buffer {
+            Some(cif_data) => UniProtMapping::from_cif_data(cif_data),
+            None => Err(PDBError::MmCifDataRequired { feature: "UniProt mapping".to_string() }),
+        };
+    }
+
+    /// Modified residues of this deposit.
+    ///
+    /// The list is loaded from `MODRES` records of a PDB file or from the `_pdbx_struct_mod_residue`
+    /// category of an mmCIF file. Parent types of these residues are also registered in the
+    /// [`ResidueTypeManager`](bioshell_seq::chemical::ResidueTypeManager) when a deposit is loaded.
+    ///
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError};
+    /// # fn main() -> Result<(), PDBError> {
+    /// let deposit = Deposit::from_file("./tests/test_files/1c5n.cif")?;
+    /// let modified = deposit.modified_residues();
+    /// assert_eq!(modified.len(), 1);
+    /// assert_eq!(modified[0].res_name, "TYS");
+    /// assert_eq!(modified[0].parent_name, "TYR");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn modified_residues(&self) -> &[ModifiedResidue] { &self.modified_residues }
+
     /// Returns a [`Structure`] object.
     ///
     /// A structure is lazily parsed from a PDB or mmCIF file, i.e. it is not parsed until this method is called.
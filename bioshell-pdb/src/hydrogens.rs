@@ -0,0 +1,377 @@
+//! Adds hydrogen atoms to protein structures.
+//!
+//! Crystal structures usually lack hydrogen atoms, which are however necessary e.g. to detect hydrogen bonds
+//! or to evaluate an energy function. [`HydrogenPlacement`] adds them to every amino acid residue,
+//! using the ideal geometry of the respective monomer, provided by the [`MonomerManager`].
+//! For every hydrogen, the ideal coordinates of its parent heavy atom and of the heavy atoms bonded to it
+//! are superimposed on the actual residue; the resulting transformation is then applied to the ideal position
+//! of the hydrogen. The following exceptions apply:
+//!
+//!  - the amide hydrogen `H` is placed in the peptide plane, along the bisector of the `C-N-CA` angle;
+//!    the N-terminal amine group is charged, its hydrogens are named `H1`, `H2` and `H3`,
+//!  - carboxyl groups of ASP, GLU and the C-terminus are deprotonated, as at neutral pH,
+//!  - no `HG` hydrogen is added to a cysteine that forms a disulfide bond,
+//!  - a histidine is protonated on `ND1`, `NE2` or on both nitrogens, according to [`his_tautomers()`],
+//!  - rotatable polar hydrogens of SER, THR, TYR, CYS and LYS are turned around their bond
+//!    to form the best hydrogen bonds with acceptors of the other residues, while avoiding clashes.
+//!
+//! Hydrogens present in the input structure are removed; ligands and water molecules are copied without changes.
+//!
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::hydrogens::HydrogenPlacement;
+//! let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+//! let with_h = HydrogenPlacement::default().add_hydrogens(&strctr)?;
+//! let ala_h: Vec<String> = with_h.atoms_in_residue(&ResidueId::new("A", 20, ' '))?
+//!         .filter(|a| a.element.as_deref() == Some("H")).map(|a| a.name.clone()).collect();
+//! assert_eq!(ala_h, vec![" H  ", " HA ", " HB1", " HB2", " HB3"]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::{Display, Formatter};
+use bioshell_core::{planar_angle3, Vec3};
+use crate::calc::{superimpose, NeighborGrid, Rototranslation};
+use crate::monomers::{Monomer, MonomerManager};
+use crate::{atom_element, format_atom_name, is_hydrogen, is_metal, PdbAtom, PDBError, ResidueId, Structure};
+use crate::PDBError::UnknownResidueType;
+
+/// Length of the N-H bond used for the amide and the N-terminal hydrogens
+const N_H_BOND: f64 = 1.01;
+/// Largest distance between a hydrogen and an acceptor that still contributes to a hydrogen bond
+const MAX_H_ACCEPTOR_DISTANCE: f64 = 2.6;
+/// Smallest D-H..A angle of a hydrogen bond, in degrees
+const MIN_DHA_ANGLE: f64 = 120.0;
+/// Hydrogens closer than that to a heavy atom (other than an acceptor) clash with it
+const H_CLASH_DISTANCE: f64 = 2.2;
+/// Rotatable polar groups: residue name, hydrogens, the atom they are attached to and the axis atom
+const ROTATABLE_GROUPS: [(&str, &[&str], &str, &str); 5] = [
+    ("SER", &["HG"], "OG", "CB"),
+    ("THR", &["HG1"], "OG1", "CB"),
+    ("TYR", &["HH"], "OH", "CZ"),
+    ("CYS", &["HG"], "SG", "CB"),
+    ("LYS", &["HZ1", "HZ2", "HZ3"], "NZ", "CE"),
+];
+/// Side chain atoms that may donate a hydrogen bond
+const SIDE_CHAIN_DONORS: [(&str, &str); 10] = [("SER", "OG"), ("THR", "OG1"), ("TYR", "OH"), ("LYS", "NZ"),
+    ("ARG", "NE"), ("ARG", "NH1"), ("ARG", "NH2"), ("ASN", "ND2"), ("GLN", "NE2"), ("TRP", "NE1")];
+/// Carboxylate oxygens, that are charged acceptors
+const CARBOXYLATE_OXYGENS: [(&str, &str); 4] = [("ASP", "OD1"), ("ASP", "OD2"), ("GLU", "OE1"), ("GLU", "OE2")];
+
+/// Protonation state of a histidine residue
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HisTautomer {
+    /// neutral histidine protonated on `ND1`
+    HID,
+    /// neutral histidine protonated on `NE2`
+    HIE,
+    /// positively charged histidine, protonated on both nitrogens
+    HIP,
+}
+
+impl HisTautomer {
+    /// Names of the hydrogens attached to the imidazole nitrogens of this tautomer
+    pub fn ring_hydrogens(&self) -> &'static [&'static str] {
+        match self {
+            HisTautomer::HID => &["HD1"],
+            HisTautomer::HIE => &["HE2"],
+            HisTautomer::HIP => &["HD1", "HE2"],
+        }
+    }
+}
+
+impl Display for HisTautomer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{:?}", self) }
+}
+
+/// Adds hydrogen atoms to amino acid residues of a [`Structure`].
+///
+/// See the [module documentation](crate::hydrogens) for the details of the method and an example.
+#[derive(Clone, Debug)]
+pub struct HydrogenPlacement {
+    /// rotate polar hydrogens to optimise hydrogen bonds; when `false`, the ideal monomer geometry is used
+    pub optimize_polar: bool,
+    /// protonation state used for every histidine; when `None`, it's assigned by [`his_tautomers()`]
+    pub his_tautomer: Option<HisTautomer>,
+}
+
+impl Default for HydrogenPlacement {
+    fn default() -> Self { HydrogenPlacement { optimize_polar: true, his_tautomer: None } }
+}
+
+impl HydrogenPlacement {
+
+    /// Creates a copy of a given structure with hydrogen atoms added to amino acid residues.
+    ///
+    /// Hydrogens are listed right after heavy atoms of their residue; all atoms are renumbered.
+    /// Results in a [`PDBError`] when the histidine monomer needed to protonate histidines is not available.
+    pub fn add_hydrogens(&self, strctr: &Structure) -> Result<Structure, PDBError> {
+        let environment = Environment::new(strctr);
+        let tautomers = match self.his_tautomer {
+            Some(t) => strctr.residues().iter().filter(|r| residue_name(strctr, r) == "HIS")
+                .map(|r| (r.clone(), t)).collect(),
+            None => his_tautomers(strctr)?,
+        };
+
+        let mgr = MonomerManager::get();
+        let mut atoms: Vec<PdbAtom> = vec![];
+        let mut prev_c: Option<Vec3> = None;
+        let mut prev_chain = String::new();
+        for res_id in strctr.residues() {
+            let heavy: Vec<&PdbAtom> = strctr.atoms_in_residue(res_id)?.filter(|a| !is_hydrogen(a)).collect();
+            atoms.extend(heavy.iter().map(|&a| a.clone()));
+            if heavy.is_empty() { continue; }
+            if res_id.chain_id != prev_chain { prev_c = None; }
+            prev_chain = res_id.chain_id.clone();
+            let monomer = match mgr.by_code3(&heavy[0].res_name) {
+                Some(m) if m.residue_type().chem_compound_type.is_peptide_linking() => m,
+                _ => { prev_c = None; continue; }
+            };
+            let tautomer = tautomers.iter().find(|(r, _)| r == res_id).map(|(_, t)| *t);
+            let mut hydrogens = residue_hydrogens(monomer, &heavy, prev_c.as_ref(), tautomer, &environment);
+            if self.optimize_polar { optimize_polar_hydrogens(&heavy, &mut hydrogens, &environment); }
+            for (name, pos) in hydrogens {
+                let mut h = heavy[0].clone();
+                h.name = format_atom_name(&name, Some("H"));
+                h.element = Some("H".to_string());
+                h.pos = pos;
+                h.alt_loc = ' ';
+                h.occupancy = 1.0;
+                atoms.push(h);
+            }
+            prev_c = residue_atom(&heavy, "C").map(|a| a.pos);
+        }
+        for (i, a) in atoms.iter_mut().enumerate() { a.serial = i as i32 + 1; }
+
+        return Ok(Structure::from_iterator(&strctr.id_code, atoms));
+    }
+}
+
+/// Adds hydrogen atoms to a structure with the default settings of [`HydrogenPlacement`]
+pub fn add_hydrogens(strctr: &Structure) -> Result<Structure, PDBError> { HydrogenPlacement::default().add_hydrogens(strctr) }
+
+/// Assigns a protonation state to every histidine residue of a given structure.
+///
+/// The assignment is based on the environment of the two imidazole nitrogens:
+///
+///  - a nitrogen that coordinates a metal ion is not protonated,
+///  - a histidine is charged ([`HisTautomer::HIP`]) when both its nitrogens may donate a hydrogen bond
+///    to a carboxylate oxygen of ASP or GLU,
+///  - otherwise the nitrogen that makes the better hydrogen bond as a donor, and is less likely to be
+///    an acceptor of a hydrogen bond from a known donor, is protonated; [`HisTautomer::HIE`] is assigned
+///    when neither of the nitrogens is preferred.
+///
+/// Results in a [`PDBError`] when the histidine monomer is not available from the [`MonomerManager`].
+pub fn his_tautomers(strctr: &Structure) -> Result<Vec<(ResidueId, HisTautomer)>, PDBError> {
+    let environment = Environment::new(strctr);
+    let mgr = MonomerManager::get();
+    let his = mgr.by_code3("HIS").ok_or(UnknownResidueType { res_type: "HIS".to_string() })?;
+    let mut out: Vec<(ResidueId, HisTautomer)> = vec![];
+    for res_id in strctr.residues() {
+        if residue_name(strctr, res_id) != "HIS" { continue; }
+        let heavy: Vec<&PdbAtom> = strctr.atoms_in_residue(res_id)?.filter(|a| !is_hydrogen(a)).collect();
+        let (Some(nd1), Some(ne2)) = (residue_atom(&heavy, "ND1"), residue_atom(&heavy, "NE2")) else {
+            out.push((res_id.clone(), HisTautomer::HIE));
+            continue;
+        };
+        let hd1 = template_hydrogen(his, &heavy, "HD1");
+        let he2 = template_hydrogen(his, &heavy, "HE2");
+        let tautomer = match (environment.near_metal(&nd1.pos), environment.near_metal(&ne2.pos)) {
+            (true, false) => HisTautomer::HIE,
+            (false, true) => HisTautomer::HID,
+            (true, true) => HisTautomer::HIE,
+            (false, false) => {
+                let hbond_d1 = hd1.map_or(0.0, |h| environment.hbond_score(&h, &nd1.pos, res_id, false));
+                let hbond_e2 = he2.map_or(0.0, |h| environment.hbond_score(&h, &ne2.pos, res_id, false));
+                let salt_d1 = hd1.map_or(0.0, |h| environment.hbond_score(&h, &nd1.pos, res_id, true));
+                let salt_e2 = he2.map_or(0.0, |h| environment.hbond_score(&h, &ne2.pos, res_id, true));
+                let net_d1 = hbond_d1 - environment.count_donors(&nd1.pos, res_id) as f64;
+                let net_e2 = hbond_e2 - environment.count_donors(&ne2.pos, res_id) as f64;
+                if salt_d1 > 0.0 && salt_e2 > 0.0 { HisTautomer::HIP }
+                else if net_d1 > net_e2 { HisTautomer::HID }
+                else { HisTautomer::HIE }
+            }
+        };
+        out.push((res_id.clone(), tautomer));
+    }
+
+    return Ok(out);
+}
+
+/// Heavy atoms of a structure, used to evaluate the placement of polar hydrogens
+struct Environment<'a> {
+    atoms: Vec<&'a PdbAtom>,
+    res_ids: Vec<ResidueId>,
+    grid: NeighborGrid,
+}
+
+impl<'a> Environment<'a> {
+    fn new(strctr: &'a Structure) -> Environment<'a> {
+        let atoms: Vec<&PdbAtom> = strctr.atoms().iter().filter(|a| !is_hydrogen(a)).collect();
+        let res_ids: Vec<ResidueId> = atoms.iter().map(|a| ResidueId::from(*a)).collect();
+        let positions: Vec<Vec3> = atoms.iter().map(|a| a.pos).collect();
+        let grid = NeighborGrid::new(&positions, 4.0);
+        return Environment { atoms, res_ids, grid };
+    }
+
+    /// Hydrogen bonds a hydrogen `h` bonded to `donor` makes with acceptors of other residues, minus clashes
+    fn hbond_score(&self, h: &Vec3, donor: &Vec3, res_id: &ResidueId, carboxylates_only: bool) -> f64 {
+        let mut score = 0.0;
+        for i in self.grid.neighbors(h, MAX_H_ACCEPTOR_DISTANCE.max(H_CLASH_DISTANCE)) {
+            if &self.res_ids[i] == res_id { continue; }
+            let a = self.atoms[i];
+            let d = h.distance_to(&a.pos);
+            let is_acceptor = if carboxylates_only { is_carboxylate_oxygen(a) } else { atom_element(a) == "O" };
+            if is_acceptor {
+                if planar_angle3(donor, h, &a.pos).to_degrees() >= MIN_DHA_ANGLE && d <= MAX_H_ACCEPTOR_DISTANCE {
+                    score += ((MAX_H_ACCEPTOR_DISTANCE - d) / 0.6).min(1.0);
+                }
+            } else if !carboxylates_only && d < H_CLASH_DISTANCE {
+                score -= 2.0 * (H_CLASH_DISTANCE - d);
+            }
+        }
+
+        return score;
+    }
+
+    /// Counts hydrogen bond donors of other residues found within 3.3 Å from a given position
+    fn count_donors(&self, pos: &Vec3, res_id: &ResidueId) -> usize {
+        return self.grid.neighbors(pos, 3.3).iter()
+            .filter(|&&i| &self.res_ids[i] != res_id && is_donor(self.atoms[i])).count();
+    }
+
+    /// True when a metal ion, i.e. an atom of any of [`METAL_ELEMENTS`](crate::METAL_ELEMENTS), is found within 2.6 Å from a given position
+    fn near_metal(&self, pos: &Vec3) -> bool {
+        return self.grid.neighbors(pos, 2.6).iter().any(|&i| is_metal(self.atoms[i]));
+    }
+}
+
+/// Places hydrogens of a single amino acid residue
+fn residue_hydrogens(monomer: &Monomer, heavy: &[&PdbAtom], prev_c: Option<&Vec3>, tautomer: Option<HisTautomer>,
+                     environment: &Environment) -> Vec<(String, Vec3)> {
+    let res_name = heavy[0].res_name.as_str();
+    let mut out: Vec<(String, Vec3)> = vec![];
+
+    // --- backbone amide hydrogen(s)
+    if let (Some(n), Some(ca)) = (residue_atom(heavy, "N"), residue_atom(heavy, "CA")) {
+        match prev_c.filter(|c| c.distance_to(&n.pos) < 2.0) {
+            Some(c) => if res_name != "PRO" {
+                let mut h = Vec3::sub_s(&n.pos, c).normalized();
+                h += &Vec3::sub_s(&n.pos, &ca.pos).normalized();
+                h.normalize();
+                h *= N_H_BOND;
+                h += &n.pos;
+                out.push(("H".to_string(), h));
+            },
+            None => if let Some(h1) = template_hydrogen(monomer, heavy, "H") {
+                // --- charged N-terminus: three hydrogens staggered around the CA-N bond
+                for (i, name) in ["H1", "H2", "H3"].iter().enumerate() {
+                    let rot = Rototranslation::around_axis(&ca.pos, &n.pos, i as f64 * 120.0_f64.to_radians());
+                    let mut h = Vec3::sub_s(&rot.apply(&h1), &n.pos).normalized();
+                    h *= N_H_BOND;
+                    h += &n.pos;
+                    out.push((name.to_string(), h));
+                }
+            }
+        }
+    }
+
+    let disulfide = res_name == "CYS" && residue_atom(heavy, "SG").is_some_and(|sg|
+        environment.grid.neighbors(&sg.pos, 2.5).iter()
+            .any(|&i| environment.atoms[i].name == " SG " && environment.atoms[i].pos.distance_to(&sg.pos) > 0.1));
+    for atom in monomer.atoms().iter().filter(|a| a.atom_type == "H" && !a.is_leaving) {
+        let name = atom.atom_name.as_str();
+        let skip = match (res_name, name) {
+            (_, "H") => true,
+            ("ASP", "HD2") | ("GLU", "HE2") => true,
+            ("CYS", "HG") => disulfide,
+            ("HIS", "HD1") | ("HIS", "HE2") => !tautomer.unwrap_or(HisTautomer::HIE).ring_hydrogens().contains(&name),
+            _ => false,
+        };
+        if skip { continue; }
+        if let Some(pos) = template_hydrogen(monomer, heavy, name) { out.push((name.to_string(), pos)); }
+    }
+
+    return out;
+}
+
+/// Places a hydrogen by superimposing its parent atom and the surrounding heavy atoms of the ideal monomer structure
+fn template_hydrogen(monomer: &Monomer, heavy: &[&PdbAtom], h_name: &str) -> Option<Vec3> {
+    if !monomer.has_ideal_positions() { return None; }
+    let atoms = monomer.atoms();
+    let h_idx = monomer.atom_index(h_name)?;
+    let neighbors = |i: usize| -> Vec<usize> {
+        monomer.bonds().iter().filter_map(|b| if b.first == i { Some(b.second) } else if b.second == i { Some(b.first) } else { None })
+            .filter(|&j| atoms[j].atom_type != "H" && !atoms[j].is_leaving).collect()
+    };
+    let parent = *neighbors(h_idx).first()?;
+    let mut reference = vec![parent];
+    reference.extend(neighbors(parent));
+    let first_shell = reference.clone();
+    for &i in &first_shell[1..] {
+        if reference.len() >= 3 { break; }
+        for j in neighbors(i) {
+            if !reference.contains(&j) { reference.push(j); }
+        }
+    }
+    if reference.len() < 3 { return None; }
+
+    let mut ideal: Vec<Vec3> = vec![];
+    let mut actual: Vec<Vec3> = vec![];
+    for i in reference {
+        let a = residue_atom(heavy, &atoms[i].atom_name)?;
+        ideal.push(atoms[i].ideal_pos);
+        actual.push(a.pos);
+    }
+    let rot = superimpose(&ideal, &actual);
+    // --- the ideal bond length is restored, as the superposition may not be exact at the parent atom
+    let bond = atoms[h_idx].ideal_pos.distance_to(&ideal[0]);
+    let mut h = Vec3::sub_s(&rot.apply(&atoms[h_idx].ideal_pos), &actual[0]).normalized();
+    h *= bond;
+    h += &actual[0];
+
+    return Some(h);
+}
+
+/// Rotates polar hydrogens around their bond to maximise hydrogen bonding with the environment
+fn optimize_polar_hydrogens(heavy: &[&PdbAtom], hydrogens: &mut [(String, Vec3)], environment: &Environment) {
+    let res_name = heavy[0].res_name.as_str();
+    let res_id = ResidueId::from(heavy[0]);
+    for (_, h_names, parent, axis) in ROTATABLE_GROUPS.iter().filter(|g| g.0 == res_name) {
+        let (Some(parent), Some(axis)) = (residue_atom(heavy, parent), residue_atom(heavy, axis)) else { continue };
+        let group: Vec<usize> = (0..hydrogens.len()).filter(|&i| h_names.contains(&hydrogens[i].0.as_str())).collect();
+        if group.is_empty() { continue; }
+        let score = |rot: &Rototranslation| -> f64 {
+            group.iter().map(|&i| environment.hbond_score(&rot.apply(&hydrogens[i].1), &parent.pos, &res_id, false)).sum()
+        };
+        let mut best = Rototranslation::around_axis(&axis.pos, &parent.pos, 0.0);
+        let mut best_score = score(&best);
+        for step in 1..36 {
+            let rot = Rototranslation::around_axis(&axis.pos, &parent.pos, (step as f64 * 10.0).to_radians());
+            let s = score(&rot);
+            if s > best_score + 1e-6 {
+                best_score = s;
+                best = rot;
+            }
+        }
+        for &i in &group { hydrogens[i].1 = best.apply(&hydrogens[i].1); }
+    }
+}
+
+fn residue_atom<'a>(heavy: &[&'a PdbAtom], name: &str) -> Option<&'a PdbAtom> {
+    return heavy.iter().find(|a| a.name.trim() == name).copied();
+}
+
+fn residue_name(strctr: &Structure, res_id: &ResidueId) -> String {
+    return strctr.atoms_in_residue(res_id).unwrap().next().map(|a| a.res_name.clone()).unwrap_or_default();
+}
+
+fn is_carboxylate_oxygen(a: &PdbAtom) -> bool {
+    return a.name == " OXT" || CARBOXYLATE_OXYGENS.iter().any(|(r, n)| a.res_name == *r && a.name.trim() == *n);
+}
+
+fn is_donor(a: &PdbAtom) -> bool {
+    if a.name == " N  " { return a.res_name != "PRO"; }
+    return SIDE_CHAIN_DONORS.iter().any(|(r, n)| a.res_name == *r && a.name.trim() == *n);
+}
@@ -0,0 +1,190 @@
+//! Detects and describes interfaces between chains of a [`Structure`](crate::Structure).
+//!
+//! A [`ChainInterface`] struct holds residues of two chains that are in contact, the list of
+//! interacting atom pairs, hydrogen bonds and salt bridges formed across the interface as well as
+//! the solvent accessible surface area buried upon complex formation.
+//!
+//! Interfaces between all pairs of chains of a given structure can be found with a single call:
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::interfaces::{find_interfaces, INTERFACE_CONTACT_CUTOFF};
+//! let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+//! let strctr = deposit.structure()?;
+//! let interfaces = find_interfaces(&strctr, INTERFACE_CONTACT_CUTOFF);
+//! assert_eq!(interfaces.len(), 1);
+//! for iface in &interfaces {
+//!     println!("{} {} buried area: {:.1}", iface.first_chain, iface.second_chain, iface.buried_area);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only heavy atoms of polymer residues (amino acids and nucleotides) are taken into account;
+//! water molecules and ligands are neglected.
+
+use std::fmt::{Display, Formatter};
+use crate::{is_hydrogen, PdbAtom, ResidueId, Structure};
+use crate::calc::{NeighborGrid, solvent_accessible_area, SASA_SPHERE_POINTS};
+use crate::pdb_atom_filters::{IsAnionic, IsCationic, IsHBondAcceptor, IsHBondDonor, KeepNucleicAcid, KeepProtein, PdbAtomPredicate};
+
+/// Default distance between two heavy atoms to consider them in contact (in Angstroms)
+pub const INTERFACE_CONTACT_CUTOFF: f64 = 4.5;
+
+/// Maximum distance between a donor and an acceptor atom of a hydrogen bond (in Angstroms)
+pub const INTERFACE_HBOND_CUTOFF: f64 = 3.5;
+
+/// Maximum distance between two oppositely charged atoms of a salt bridge (in Angstroms)
+pub const INTERFACE_SALT_BRIDGE_CUTOFF: f64 = 4.0;
+
+/// A pair of atoms from two different chains of a [`Structure`](crate::Structure).
+///
+/// Atoms are given as indexes to the vector of atoms returned by [`Structure::atoms()`](crate::Structure::atoms());
+/// the `first_atom` belongs to the [`ChainInterface::first_chain`], the `second_atom` to the [`ChainInterface::second_chain`].
+#[derive(Clone, Debug)]
+pub struct AtomContact {
+    /// index of an atom from the first chain
+    pub first_atom: usize,
+    /// index of an atom from the second chain
+    pub second_atom: usize,
+    /// distance between the two atoms
+    pub distance: f64,
+}
+
+/// Describes an interface between two chains of a [`Structure`](crate::Structure).
+#[derive(Clone, Debug)]
+pub struct ChainInterface {
+    /// ID of the first chain
+    pub first_chain: String,
+    /// ID of the second chain
+    pub second_chain: String,
+    /// residues of the first chain that are in contact with the second chain
+    pub first_residues: Vec<ResidueId>,
+    /// residues of the second chain that are in contact with the first chain
+    pub second_residues: Vec<ResidueId>,
+    /// all heavy atom pairs in contact
+    pub contacts: Vec<AtomContact>,
+    /// hydrogen bonds formed across the interface
+    pub hydrogen_bonds: Vec<AtomContact>,
+    /// salt bridges formed across the interface
+    pub salt_bridges: Vec<AtomContact>,
+    /// solvent accessible surface area buried upon complex formation (in square Angstroms)
+    ///
+    /// This value is the sum of areas buried on both sides of the interface
+    pub buried_area: f64,
+}
+
+impl ChainInterface {
+    /// Total number of interface residues, counted on both sides of the interface
+    pub fn count_residues(&self) -> usize { self.first_residues.len() + self.second_residues.len() }
+}
+
+impl Display for ChainInterface {
+    /// Prints a single-line summary of an interface
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>4} {:>4} {:5} {:5} {:6} {:5} {:5} {:9.1}", self.first_chain, self.second_chain,
+               self.first_residues.len(), self.second_residues.len(), self.contacts.len(),
+               self.hydrogen_bonds.len(), self.salt_bridges.len(), self.buried_area)
+    }
+}
+
+/// Finds an interface between two chains of a given structure.
+///
+/// Returns `None` when the two chains have no heavy atoms closer than `cutoff`.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::interfaces::{chain_interface, INTERFACE_CONTACT_CUTOFF};
+/// let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+/// let strctr = deposit.structure()?;
+/// let iface = chain_interface(&strctr, "A", "B", INTERFACE_CONTACT_CUTOFF).unwrap();
+/// assert!(iface.hydrogen_bonds.len() > 0);
+/// assert!(iface.buried_area > 500.0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn chain_interface(strctr: &Structure, first_chain: &str, second_chain: &str, cutoff: f64) -> Option<ChainInterface> {
+
+    let first_idx = polymer_heavy_atoms(strctr, first_chain);
+    let second_idx = polymer_heavy_atoms(strctr, second_chain);
+    if first_idx.is_empty() || second_idx.is_empty() { return None; }
+
+    let atoms = strctr.atoms();
+    let second_pos: Vec<&PdbAtom> = second_idx.iter().map(|&i| &atoms[i]).collect();
+    let grid = NeighborGrid::new(&second_pos.iter().map(|a| a.pos).collect::<Vec<_>>(), cutoff);
+
+    let mut contacts: Vec<AtomContact> = vec![];
+    let mut hydrogen_bonds: Vec<AtomContact> = vec![];
+    let mut salt_bridges: Vec<AtomContact> = vec![];
+    for &i in &first_idx {
+        let ai = &atoms[i];
+        let mut neighbors = grid.neighbors(&ai.pos, cutoff);
+        neighbors.sort();
+        for k in neighbors {
+            let j = second_idx[k];
+            let aj = &atoms[j];
+            let contact = AtomContact { first_atom: i, second_atom: j, distance: ai.pos.distance_to(&aj.pos) };
+            if contact.distance <= INTERFACE_HBOND_CUTOFF &&
+                ((IsHBondDonor.check(ai) && IsHBondAcceptor.check(aj)) || (IsHBondAcceptor.check(ai) && IsHBondDonor.check(aj))) {
+                hydrogen_bonds.push(contact.clone());
+            }
+            if contact.distance <= INTERFACE_SALT_BRIDGE_CUTOFF &&
+                ((IsCationic.check(ai) && IsAnionic.check(aj)) || (IsAnionic.check(ai) && IsCationic.check(aj))) {
+                salt_bridges.push(contact.clone());
+            }
+            contacts.push(contact);
+        }
+    }
+    if contacts.is_empty() { return None; }
+
+    let first_residues = Structure::residue_ids_from_atoms(unique_atoms(atoms, contacts.iter().map(|c| c.first_atom)).into_iter());
+    let second_residues = Structure::residue_ids_from_atoms(unique_atoms(atoms, contacts.iter().map(|c| c.second_atom)).into_iter());
+
+    // ---------- buried area: SASA(first) + SASA(second) - SASA(complex)
+    let first_atoms: Vec<&PdbAtom> = first_idx.iter().map(|&i| &atoms[i]).collect();
+    let complex: Vec<&PdbAtom> = first_atoms.iter().chain(second_pos.iter()).cloned().collect();
+    let area_first: f64 = solvent_accessible_area(&first_atoms, SASA_SPHERE_POINTS).iter().sum();
+    let area_second: f64 = solvent_accessible_area(&second_pos, SASA_SPHERE_POINTS).iter().sum();
+    let area_complex: f64 = solvent_accessible_area(&complex, SASA_SPHERE_POINTS).iter().sum();
+
+    return Some(ChainInterface {
+        first_chain: first_chain.to_string(),
+        second_chain: second_chain.to_string(),
+        first_residues, second_residues, contacts, hydrogen_bonds, salt_bridges,
+        buried_area: area_first + area_second - area_complex,
+    });
+}
+
+/// Finds interfaces between every pair of chains of a given structure.
+///
+/// Only these pairs of chains that are in contact are reported. Chains are processed in
+/// the order given by [`Structure::chain_ids()`](crate::Structure::chain_ids()).
+pub fn find_interfaces(strctr: &Structure, cutoff: f64) -> Vec<ChainInterface> {
+    let chain_ids = strctr.chain_ids();
+    let mut out: Vec<ChainInterface> = vec![];
+    for (i, ci) in chain_ids.iter().enumerate() {
+        for cj in chain_ids.iter().skip(i + 1) {
+            if let Some(iface) = chain_interface(strctr, ci, cj, cutoff) { out.push(iface); }
+        }
+    }
+
+    return out;
+}
+
+/// Indexes of heavy atoms of polymer residues from a given chain
+fn polymer_heavy_atoms(strctr: &Structure, chain_id: &str) -> Vec<usize> {
+    strctr.atoms().iter().enumerate()
+        .filter(|(_, a)| a.chain_id == chain_id && !is_hydrogen(a))
+        .filter(|(_, a)| KeepProtein.check(a) || KeepNucleicAcid.check(a))
+        .map(|(i, _)| i).collect()
+}
+
+/// Returns atoms for the given indexes, sorted in the order of a structure and without repetitions
+fn unique_atoms(atoms: &[PdbAtom], indexes: impl Iterator<Item=usize>) -> Vec<&PdbAtom> {
+    let mut idx: Vec<usize> = indexes.collect();
+    idx.sort();
+    idx.dedup();
+    idx.iter().map(|&i| &atoms[i]).collect()
+}
@@ -98,12 +98,26 @@ mod pdb_title;
 mod pdb_parsing_error;
 mod pdb_helix;
 mod pdb_sheet;
+pub use pdb_sheet::PdbSheet;
 mod pdb_atom;
 
 pub mod monomers;
 pub mod pdb_atom_filters;
 pub mod residue_filters;
 pub mod calc;
+pub mod interfaces;
+pub mod validation;
+pub mod chain_mapping;
+pub mod sifts;
+pub mod binding_sites;
+pub mod pockets;
+pub mod structural_alignment;
+pub mod coarse_grained;
+pub mod hydrogens;
+pub mod pdb_mirror;
+pub mod small_molecules;
+pub mod density_maps;
+pub mod nucleic_acids;
 
 mod residue_id;
 mod load_pdb;
@@ -120,13 +134,13 @@ pub mod documentation;
 
 
 use std::path::Path;
-pub use structure::{Structure, write_pdb};
+pub use structure::{Structure, write_pdb, write_secondary_cif};
 pub use deposit::*;
 pub use ligands::*;
 pub use secondary_structure::{SecondaryRange, SecondaryStructure, SecondaryStructureTypes};
 pub use load_pdb::{find_pdb_file_name, is_pdb_file};
 pub use pdb_parsing_error::PDBError;
-pub use pdb_atom::{format_atom_name, is_hydrogen, PdbAtom, same_residue_atoms};
+pub use pdb_atom::{atom_element, format_atom_name, is_hydrogen, is_metal, PdbAtom, same_residue_atoms, METAL_ELEMENTS, RESIDUE_RINGS};
 pub use residue_id::ResidueId;
 pub use exp_data::ExperimentalMethod;
 pub use unit_cell::UnitCell;
@@ -135,6 +149,8 @@ pub use entity::{Entity, EntitySource, EntityType, PolymerEntityType};
 
 mod secondary_view;
 pub use secondary_view::*;
+mod modified_residues;
+pub use modified_residues::{ModifiedResidue, ModifiedResidueCode};
 mod cif_to_pdb;
 pub use cif_to_pdb::*;
 
@@ -5,7 +5,8 @@ use std::time::Instant;
 use log::{debug, info};
 use reqwest::blocking::get;
 use bioshell_cif::{read_cif_buffer, CifData, CifTable};
-use crate::{Deposit, Entity, ExperimentalMethod, PdbAtom, PDBError, SecondaryStructureTypes, Structure, UnitCell};
+use crate::{Deposit, Entity, ExperimentalMethod, ModifiedResidue, PdbAtom, PDBError, SecondaryStructureTypes, Structure, UnitCell};
+use crate::modified_residues::register_parent_types;
 use bioshell_cif::CifError::{ExtraDataBlock};
 use bioshell_core::io::open_file;
 use crate::pdb_sheet::PdbSheet;
@@ -44,6 +45,8 @@ impl Deposit {
 
         // ---------- load the residue types into the ResidueTypeManager before atoms and entities
         load_residue_types(cif_data_block)?;
+        deposit.modified_residues = ModifiedResidue::from_cif_data(cif_data_block)?;
+        register_parent_types(&deposit.modified_residues);
 
         // --- header data
         deposit.classification = cif_data_block.get_item("_struct_keywords.pdbx_keywords");
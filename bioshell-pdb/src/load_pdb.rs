@@ -3,7 +3,7 @@ use std::io;
 use std::io::{BufRead};
 use std::path::Path;
 use std::time::Instant;
-use log::{debug, info};
+use log::{debug, info, warn};
 use bioshell_core::io::open_file;
 use bioshell_seq::chemical::{ResidueTypeManager, ResidueTypeProperties};
 use bioshell_seq::sequence::Sequence;
@@ -11,7 +11,8 @@ use bioshell_core::Vec3;
 use crate::pdb_title::PdbTitle;
 use crate::pdb_header::PdbHeader;
 use crate::remarks::PDBRemarks;
-use crate::{Deposit, ExperimentalMethod, PdbAtom, SecondaryStructureTypes, Structure, UnitCell};
+use crate::{Deposit, ExperimentalMethod, ModifiedResidue, PdbAtom, SecondaryStructureTypes, Structure, UnitCell};
+use crate::modified_residues::register_parent_types;
 use crate::pdb_sheet::PdbSheet;
 use crate::pdb_helix::PdbHelix;
 use crate::crate_utils::find_deposit_file_name;
@@ -119,6 +120,13 @@ impl Deposit {
                 "SEQRES" => {
                     seqres.push(line);
                 }
+                "MODRES" => {
+                    // --- a broken annotation must not make the coordinates unreadable
+                    match ModifiedResidue::from_modres_line(&line) {
+                        Ok(modres) => deposit.modified_residues.push(modres),
+                        Err(e) => warn!("Skipping a MODRES record: {}", e),
+                    }
+                }
                 "CRYST1" => {
                     deposit.unit_cell = Some(UnitCell::from_cryst1_line(&line));
                 }
@@ -150,6 +158,9 @@ impl Deposit {
             }
         }
 
+        deposit.seqres = parse_seqres_monomers(&seqres);
+        register_parent_types(&deposit.modified_residues);
+
         // ---------- Extract values stored in remarks
         deposit.resolution = remarks.resolution();
 
@@ -182,6 +193,18 @@ impl Deposit {
 }
 
 
+/// Collects residue names listed by SEQRES records, for every chain
+fn parse_seqres_monomers(seqres_records: &[String]) -> HashMap<String, Vec<String>> {
+    let mut monomers: HashMap<String, Vec<String>> = HashMap::new();
+    for record in seqres_records {
+        let parts: Vec<&str> = record.split_whitespace().collect();
+        if parts.len() < 5 { continue; }
+        monomers.entry(parts[2].to_string()).or_default().extend(parts[4..].iter().map(|s| s.to_string()));
+    }
+
+    return monomers;
+}
+
 fn parse_seqres_records(seqres_records: Vec<String>) -> HashMap<String, Sequence> {
     let mut sequences: HashMap<String, Vec<u8>> = HashMap::new();
 
@@ -220,12 +243,12 @@ fn parse_seqres_records(seqres_records: Vec<String>) -> HashMap<String, Sequence
 /// Returns true if a given file is in PDB format.
 ///
 /// This function simply tests whether the first data line of a given file starts with ``HEADER``,
-/// ``REMARK``, ``ATOM`` or ``HETATM``.
+/// ``REMARK``, ``ATOM``, ``HETATM``, ``MODEL``, ``HELIX`` or ``SHEET``.
 /// Otherwise, it returns ``false``. When the file can't be open returns I/O error..
 pub fn is_pdb_file<P: AsRef<Path>>(file_path: P) -> io::Result<bool> {
     let reader = open_file(file_path)?;
 
-    let pdb_starts_with = ["HEADER", "ATOM", "HETATM", "REMARK", "MODEL"];
+    let pdb_starts_with = ["HEADER", "ATOM", "HETATM", "REMARK", "MODEL", "HELIX", "SHEET"];
     for line in reader.lines() {
         let line = line?;
         if !line.is_empty() {
@@ -0,0 +1,115 @@
+use bioshell_cif::{CifData, CifTable};
+use bioshell_seq::chemical::{ResidueType, ResidueTypeManager, ResidueTypeProperties, StandardResidueType};
+use crate::{PDBError, ResidueId};
+
+/// Says how a modified residue, such as `MSE` or `SEP`, is represented in a one-letter sequence.
+///
+/// See [`Structure::sequence_with_modified()`](crate::Structure::sequence_with_modified) for an example.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModifiedResidueCode {
+    /// every modified residue is represented by `'X'`
+    Unknown,
+    /// a modified residue is represented by the one-letter code of its parent, e.g. `'M'` for `MSE`
+    Parent,
+    /// a modified residue is represented by the lowercase one-letter code of its parent, e.g. `'m'` for `MSE`
+    Lowercase,
+}
+
+impl ModifiedResidueCode {
+    /// One-letter code of a modified residue derived from a given parent type.
+    ///
+    /// `'X'` is returned when the parent type is not known, i.e. it's [`UNK`](StandardResidueType::UNK).
+    ///
+    /// ```
+    /// use bioshell_pdb::ModifiedResidueCode;
+    /// use bioshell_seq::chemical::StandardResidueType;
+    /// assert_eq!(ModifiedResidueCode::Parent.code1(StandardResidueType::SER), 'S');
+    /// assert_eq!(ModifiedResidueCode::Lowercase.code1(StandardResidueType::SER), 's');
+    /// assert_eq!(ModifiedResidueCode::Unknown.code1(StandardResidueType::SER), 'X');
+    /// ```
+    pub fn code1(&self, parent: StandardResidueType) -> char {
+        if parent == StandardResidueType::UNK { return 'X'; }
+        return match self {
+            ModifiedResidueCode::Unknown => 'X',
+            ModifiedResidueCode::Parent => parent.code1(),
+            ModifiedResidueCode::Lowercase => parent.code1().to_ascii_lowercase(),
+        };
+    }
+}
+
+/// A modified residue of a deposit, as declared by a `MODRES` record of a PDB file
+/// or by the `_pdbx_struct_mod_residue` category of an mmCIF file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModifiedResidue {
+    /// identifies the modified residue in a structure
+    pub res_id: ResidueId,
+    /// three-letter code of the modified residue, e.g. `"MSE"`
+    pub res_name: String,
+    /// three-letter code of the standard residue it has been derived from, e.g. `"MET"`
+    pub parent_name: String,
+    /// description of the modification, if provided
+    pub details: Option<String>,
+}
+
+impl ModifiedResidue {
+
+    /// Creates a [`ModifiedResidue`] from a `MODRES` line of a PDB file
+    ///
+    /// ```
+    /// use bioshell_pdb::ModifiedResidue;
+    /// let modres = ModifiedResidue::from_modres_line("MODRES 1C5N TYS I   63  TYR  O-SULFO-L-TYROSINE").unwrap();
+    /// assert_eq!(modres.res_name, "TYS");
+    /// assert_eq!(modres.parent_name, "TYR");
+    /// assert_eq!(modres.res_id.res_seq, 63);
+    /// ```
+    pub fn from_modres_line(line: &str) -> Result<ModifiedResidue, PDBError> {
+        let field = |from: usize, to: usize| line.get(from..to.min(line.len())).unwrap_or("").trim();
+        let res_seq = field(18, 22).parse::<i32>()
+            .map_err(|_| PDBError::InvalidPdbLineFormat { broken_pdb_line: line.to_string() })?;
+        let details = field(29, 70);
+
+        return Ok(ModifiedResidue {
+            res_id: ResidueId::new(field(16, 17), res_seq, line.chars().nth(22).unwrap_or(' ')),
+            res_name: field(12, 15).to_string(),
+            parent_name: field(24, 27).to_string(),
+            details: if details.is_empty() { None } else { Some(details.to_string()) },
+        });
+    }
+
+    /// Loads all modified residues listed in the `_pdbx_struct_mod_residue` category of an mmCIF data block
+    pub fn from_cif_data(cif_data: &CifData) -> Result<Vec<ModifiedResidue>, PDBError> {
+        let mut out: Vec<ModifiedResidue> = vec![];
+        let Ok(table) = CifTable::new(cif_data, "_pdbx_struct_mod_residue",
+            ["auth_asym_id", "auth_comp_id", "auth_seq_id", "PDB_ins_code", "parent_comp_id", "details"]) else { return Ok(out); };
+        for [chain_id, res_name, res_seq, i_code, parent, details] in table.iter() {
+            let res_seq = res_seq.parse::<i32>().map_err(|_| PDBError::ResidueIdParsingError { residue_id: res_seq.to_string() })?;
+            let i_code = if i_code == "?" || i_code == "." { ' ' } else { i_code.chars().next().unwrap_or(' ') };
+            let details = details.trim_matches(|c| c == '"' || c == '\'');
+            out.push(ModifiedResidue {
+                res_id: ResidueId::new(chain_id, res_seq, i_code),
+                res_name: res_name.to_string(),
+                parent_name: parent.to_string(),
+                details: if details == "?" || details == "." { None } else { Some(details.to_string()) },
+            });
+        }
+
+        return Ok(out);
+    }
+}
+
+/// Registers parent types of modified residues in the [`ResidueTypeManager`].
+///
+/// A residue type is updated only when its parent is not known yet; standard residue types are never modified.
+pub(crate) fn register_parent_types(modified: &[ModifiedResidue]) {
+    let mut rtm = ResidueTypeManager::get();
+    for m in modified {
+        if StandardResidueType::TYPES.iter().any(|srt| srt.code3() == m.res_name) { continue; }
+        let Some(&parent) = StandardResidueType::TYPES.iter().find(|srt| srt.code3() == m.parent_name) else { continue };
+        let updated = match rtm.by_code3(&m.res_name) {
+            Some(rt) if rt.parent_type != StandardResidueType::UNK => continue,
+            Some(rt) => ResidueType::from_attrs(&m.res_name, parent, rt.chem_compound_type.clone()),
+            None => ResidueType::from_attrs(&m.res_name, parent, parent.chem_compound_type()),
+        };
+        rtm.update_residue_type(updated);
+    }
+}
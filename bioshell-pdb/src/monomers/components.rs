@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{copy, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use once_cell::unsync::OnceCell;
+use log::{info, warn};
+use bioshell_cif::read_cif_buffer;
+use bioshell_core::io::open_file;
+use bioshell_seq::chemical::{MonomerType, ResidueType, ResidueTypeProperties, StandardResidueType};
+use crate::monomers::Monomer;
+use crate::PDBError;
+
+/// Basic information about a single component of the [`ComponentDictionary`].
+///
+/// The data is taken from the `_chem_comp` category of the respective data block
+/// and stored in the index file of a dictionary.
+#[derive(Clone, Debug)]
+pub struct ComponentEntry {
+    /// three-letter code of the component, e.g. `"MSE"`
+    pub code: String,
+    /// name of the component, e.g. `"SELENOMETHIONINE"`
+    pub name: String,
+    /// chemical type of the component as given in the dictionary, e.g. `"L-PEPTIDE LINKING"`
+    pub monomer_type: String,
+    /// code of the standard residue this component is a variant of, e.g. `"MET"` for `"MSE"`
+    pub parent: Option<String>,
+    /// one-letter code of the component, if defined
+    pub one_letter_code: Option<char>,
+    offset: u64,
+    length: u64,
+}
+
+impl ComponentEntry {
+
+    /// Standard residue type this component is a variant of.
+    ///
+    /// The parent type is given by the `_chem_comp.mon_nstd_parent_comp_id` field of the dictionary;
+    /// [`UNK`](StandardResidueType::UNK) is returned for components that have no standard parent, e.g. ligands.
+    /// When more than one parent is listed, the first one is used.
+    pub fn parent_type(&self) -> StandardResidueType {
+        let parent = self.parent.as_deref().and_then(|p| p.split(',').next()).unwrap_or(&self.code).trim();
+        return StandardResidueType::TYPES.iter().find(|srt| srt.code3() == parent)
+            .copied().unwrap_or(StandardResidueType::UNK);
+    }
+
+    /// Creates a [`ResidueType`] object for this component
+    pub fn residue_type(&self) -> ResidueType {
+        let chem_type = MonomerType::try_from(self.monomer_type.as_str()).unwrap_or(MonomerType::Other);
+        return ResidueType::from_attrs(&self.code, self.parent_type(), chem_type);
+    }
+
+    /// Stores the value of a `_chem_comp` item this index keeps
+    fn set_item(&mut self, key: &str, value: &str) {
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        let value = if value == "?" || value == "." { "" } else { value };
+        match key {
+            "name" => self.name = value.to_string(),
+            "type" => self.monomer_type = value.to_string(),
+            "mon_nstd_parent_comp_id" if !value.is_empty() => self.parent = Some(value.to_string()),
+            "one_letter_code" => self.one_letter_code = value.chars().next(),
+            _ => {}
+        }
+    }
+}
+
+/// Provides lazy access to the wwPDB Chemical Component Dictionary.
+///
+/// The [dictionary](https://files.wwpdb.org/pub/pdb/data/monomers/components.cif.gz) defines every monomer
+/// found in the PDB, which makes it a large file of roughly 40 thousand data blocks. When a dictionary
+/// is opened for the first time, the whole file is scanned and an index is created: it stores the position
+/// of each data block in the file, along with the [`ComponentEntry`] data. The index is saved next to the
+/// dictionary file, with the `.idx` extension appended to its name, and is used as long as it's not older than
+/// the dictionary itself. A [`Monomer`] is parsed only when it's requested for the first time.
+///
+/// A gzip-ed dictionary doesn't allow random access, so it's decompressed once, when opened: the plain copy is
+/// written next to it, with the `.gz` extension removed, and is used instead of the compressed file
+/// as long as it's not older than the latter.
+///
+/// ```
+/// # use bioshell_pdb::PDBError;
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::monomers::ComponentDictionary;
+/// use bioshell_seq::chemical::StandardResidueType;
+/// # let fname = std::env::temp_dir().join("components_doc_dictionary.cif");
+/// # std::fs::copy("./tests/test_files/components_subset.cif", &fname)?;
+/// # let fname = fname.to_str().unwrap();
+/// let dictionary = ComponentDictionary::open(fname)?;
+/// assert_eq!(dictionary.len(), 4);
+/// assert_eq!(dictionary.entry("PTR").unwrap().parent_type(), StandardResidueType::TYR);
+/// let mse = dictionary.monomer("MSE").unwrap();
+/// assert!(mse.atom_index("SE").is_some());
+/// # Ok(())
+/// # }
+/// ```
+pub struct ComponentDictionary {
+    file_name: String,
+    entries: Vec<ComponentEntry>,
+    by_code: HashMap<String, usize>,
+    monomers: Vec<OnceCell<Option<Monomer>>>,
+}
+
+impl ComponentDictionary {
+
+    /// Opens a chemical component dictionary, creating its index if necessary.
+    ///
+    /// Results in a [`PDBError`] when a gzip-ed dictionary can't be decompressed into the folder it's located in.
+    pub fn open(file_name: &str) -> Result<ComponentDictionary, PDBError> {
+        let file_name = match file_name.strip_suffix(".gz") {
+            Some(plain_name) => {
+                decompress(file_name, plain_name)?;
+                plain_name
+            }
+            None => file_name,
+        };
+        let index_name = format!("{}.idx", file_name);
+        let entries = if is_up_to_date(&index_name, file_name) {
+            load_index(&index_name)?
+        } else {
+            info!("indexing chemical component dictionary {}", file_name);
+            let entries = ComponentDictionary::index_entries(file_name)?;
+            if let Err(e) = save_index(&entries, &index_name) {
+                warn!("Can't save the index of chemical components in {}: {}", index_name, e);
+            }
+            entries
+        };
+        let by_code: HashMap<String, usize> = entries.iter().enumerate().map(|(i, e)| (e.code.clone(), i)).collect();
+        let monomers = (0..entries.len()).map(|_| OnceCell::new()).collect();
+
+        return Ok(ComponentDictionary { file_name: file_name.to_string(), entries, by_code, monomers });
+    }
+
+    /// Scans a dictionary file and creates an entry for each of its data blocks.
+    ///
+    /// A value of a `_chem_comp` item may be given in the line that follows its name, also as a multi-line
+    /// string delimited by semicolons; lines of such a string are joined with spaces.
+    pub fn index_entries(file_name: &str) -> Result<Vec<ComponentEntry>, PDBError> {
+        let mut reader = open_file(file_name)?;
+        let mut entries: Vec<ComponentEntry> = vec![];
+        let mut buffer: Vec<u8> = vec![];
+        let mut offset: u64 = 0;
+        // --- an item whose value has not been read yet and the lines of its multi-line value, if any
+        let mut open_item: Option<String> = None;
+        let mut text: Option<Vec<String>> = None;
+        loop {
+            buffer.clear();
+            let n = reader.read_until(b'\n', &mut buffer)? as u64;
+            if n == 0 { break; }
+            let start = offset;
+            offset += n;
+            let line = String::from_utf8_lossy(&buffer);
+            let line = line.trim_end();
+            if let Some(lines) = text.as_mut() {
+                if !line.starts_with(';') {
+                    lines.push(line.trim().to_string());
+                    continue;
+                }
+                // --- the closing semicolon
+                let value = lines.iter().filter(|l| !l.is_empty()).cloned().collect::<Vec<_>>().join(" ");
+                if let (Some(key), Some(entry)) = (open_item.take(), entries.last_mut()) { entry.set_item(&key, &value); }
+                text = None;
+                continue;
+            }
+            if let Some(key) = open_item.take() {
+                if let Some(first) = line.strip_prefix(';') {
+                    text = Some(vec![first.trim().to_string()]);
+                    open_item = Some(key);
+                } else if let Some(entry) = entries.last_mut() {
+                    entry.set_item(&key, line.trim());
+                }
+                continue;
+            }
+            if let Some(code) = line.strip_prefix("data_") {
+                if let Some(last) = entries.last_mut() { last.length = start - last.offset; }
+                entries.push(ComponentEntry { code: code.trim().to_string(), name: String::new(), monomer_type: String::new(),
+                    parent: None, one_letter_code: None, offset: start, length: 0 });
+            } else if let (Some(item), Some(entry)) = (line.strip_prefix("_chem_comp."), entries.last_mut()) {
+                match item.split_once(char::is_whitespace) {
+                    Some((key, value)) if !value.trim().is_empty() => entry.set_item(key, value.trim()),
+                    _ => open_item = Some(item.trim().to_string()),
+                }
+            }
+        }
+        if let Some(last) = entries.last_mut() { last.length = offset - last.offset; }
+
+        return Ok(entries);
+    }
+
+    /// Name of the dictionary file; for a gzip-ed dictionary this is its decompressed copy
+    pub fn file_name(&self) -> &str { &self.file_name }
+
+    /// The number of components listed in this dictionary
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    /// Returns `true` if this dictionary has no components
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// All the components listed in this dictionary
+    pub fn entries(&self) -> &[ComponentEntry] { &self.entries }
+
+    /// Index data of a component given by its three-letter code
+    pub fn entry(&self, code: &str) -> Option<&ComponentEntry> { self.by_code.get(code).map(|&i| &self.entries[i]) }
+
+    /// Provides a monomer for a given three-letter code; the respective data block is parsed at the first call
+    pub fn monomer(&self, code: &str) -> Option<&Monomer> {
+        let idx = *self.by_code.get(code)?;
+        return self.monomers[idx].get_or_init(|| match self.parse_monomer(idx) {
+            Ok(monomer) => Some(monomer),
+            Err(e) => {
+                warn!("Can't parse the {} chemical component: {}", code, e);
+                None
+            }
+        }).as_ref();
+    }
+
+    fn parse_monomer(&self, idx: usize) -> Result<Monomer, PDBError> {
+        let entry = &self.entries[idx];
+        let mut text: Vec<u8> = Vec::with_capacity(entry.length as usize);
+        let mut file = File::open(&self.file_name)?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        file.take(entry.length).read_to_end(&mut text)?;
+        let block = read_cif_buffer(BufReader::new(text.as_slice()))?.into_iter().next()
+            .ok_or(PDBError::IncorrectComponentIndex { line: entry.code.clone() })?;
+
+        return Ok(Monomer::from_cif_data(&block, entry.residue_type())?);
+    }
+}
+
+/// Decompresses a gzip-ed dictionary, unless an up-to-date copy already exists
+fn decompress(gz_name: &str, plain_name: &str) -> Result<(), PDBError> {
+    if is_up_to_date(plain_name, gz_name) { return Ok(()); }
+    info!("decompressing chemical component dictionary {} into {}", gz_name, plain_name);
+    // --- a partially written copy must not be taken for a complete one
+    let part_name = format!("{}.part", plain_name);
+    let mut out = BufWriter::new(File::create(&part_name)?);
+    copy(&mut open_file(gz_name)?, &mut out)?;
+    out.flush()?;
+    std::fs::rename(&part_name, plain_name)?;
+
+    return Ok(());
+}
+
+/// Returns `true` if the index file exists and is not older than the dictionary
+fn is_up_to_date(index_name: &str, file_name: &str) -> bool {
+    let modified = |f: &str| Path::new(f).metadata().and_then(|m| m.modified()).ok();
+    return match (modified(index_name), modified(file_name)) {
+        (Some(index_time), Some(file_time)) => index_time >= file_time,
+        _ => false,
+    };
+}
+
+/// Saves index entries as tab-separated lines: code, offset, length, type, parent, one-letter code and name
+fn save_index(entries: &[ComponentEntry], index_name: &str) -> Result<(), PDBError> {
+    let mut out = BufWriter::new(File::create(index_name)?);
+    for e in entries {
+        writeln!(out, "{}\t{}\t{}\t{}\t{}\t{}\t{}", e.code, e.offset, e.length, e.monomer_type,
+                 e.parent.as_deref().unwrap_or("?"), e.one_letter_code.unwrap_or('?'), e.name.replace('\t', " "))?;
+    }
+
+    return Ok(());
+}
+
+fn load_index(index_name: &str) -> Result<Vec<ComponentEntry>, PDBError> {
+    let mut entries: Vec<ComponentEntry> = vec![];
+    for line in open_file(index_name)?.lines() {
+        let line = line?;
+        let tokens: Vec<&str> = line.split('\t').collect();
+        if tokens.len() != 7 { return Err(PDBError::IncorrectComponentIndex { line }); }
+        let parse = |s: &str| s.parse::<u64>().map_err(|_| PDBError::IncorrectComponentIndex { line: line.clone() });
+        entries.push(ComponentEntry {
+            code: tokens[0].to_string(),
+            name: tokens[6].to_string(),
+            monomer_type: tokens[3].to_string(),
+            parent: if tokens[4] == "?" { None } else { Some(tokens[4].to_string()) },
+            one_letter_code: if tokens[5] == "?" { None } else { tokens[5].chars().next() },
+            offset: parse(tokens[1])?,
+            length: parse(tokens[2])?,
+        });
+    }
+
+    return Ok(entries);
+}
@@ -1,6 +1,10 @@
 //! Defines structures of monomers that constitute to biopolymers: amino acids and nucleotides
 //!
+//! Definitions of the standard monomers are distributed with BioShell; any other component
+//! can be loaded from the wwPDB Chemical Component Dictionary with [`ComponentDictionary`].
 //!
 mod monomers;
+mod components;
 
-pub use monomers::{MonomerAtom, Monomer, MonomerManager};
\ No newline at end of file
+pub use monomers::{MonomerAtom, MonomerBond, Monomer, MonomerManager};
+pub use components::{ComponentDictionary, ComponentEntry};
@@ -3,10 +3,13 @@ use std::num::ParseIntError;
 use std::sync::{Mutex, MutexGuard};
 use once_cell::sync::Lazy;
 use log::{debug, info};
-use bioshell_cif::{CifError, CifTable, parse_bool, read_cif_file};
+use bioshell_cif::{CifData, CifError, CifTable, parse_bool, read_cif_file};
 use bioshell_core::io::find_bioshell_path;
+use bioshell_core::Vec3;
 use bioshell_seq::chemical::{ResidueType, ResidueTypeManager, ResidueTypeProperties, StandardResidueType};
-use bioshell_seq::chemical::StandardResidueType::{UNK, UNL, GAP, GPE};
+use bioshell_seq::chemical::StandardResidueType::{UNK, UNL, GAP, GPE, STOP};
+use crate::monomers::ComponentDictionary;
+use crate::PDBError;
 
 /// Defines an atom of a monomer structure
 pub struct MonomerAtom {
@@ -18,12 +21,33 @@ pub struct MonomerAtom {
     pub is_leaving: bool,
     /// Chemical element of the atom
     pub atom_type: String,
+    /// Stereo configuration of the atom: `'R'`, `'S'` or `'N'` when the atom is not a stereo center
+    /// or when the configuration is not given
+    pub stereo_config: char,
+    /// Position of this atom in the ideal geometry of the monomer
+    ///
+    /// When the ideal coordinates are not given for an atom, its model coordinates are used instead.
+    /// When a monomer provides no coordinates at all, every atom is placed at the origin,
+    /// see [`Monomer::has_ideal_positions()`]
+    pub ideal_pos: Vec3,
+}
+
+/// Defines a covalent bond between two atoms of a monomer structure
+pub struct MonomerBond {
+    /// index of the first atom, as stored in the vector returned by [`Monomer::atoms()`]
+    pub first: usize,
+    /// index of the second atom, as stored in the vector returned by [`Monomer::atoms()`]
+    pub second: usize,
+    /// bond order, as given in the CIF file, e.g. `"SING"` or `"DOUB"`
+    pub order: String,
 }
 
 /// Defines a monomer chemical structure
 pub struct Monomer {
     residue_type: ResidueType,
     atoms: Vec<MonomerAtom>,
+    bonds: Vec<MonomerBond>,
+    has_ideal_positions: bool,
 }
 
 impl Monomer {
@@ -33,6 +57,31 @@ impl Monomer {
     /// Provides the reference to the list of atoms in this residue
     pub fn atoms(&self) -> &Vec<MonomerAtom> { &self.atoms }
 
+    /// Provides the reference to the list of covalent bonds in this residue
+    pub fn bonds(&self) -> &Vec<MonomerBond> { &self.bonds }
+
+    /// Returns `false` when the CIF definition of this monomer provides no atomic coordinates
+    ///
+    /// Ideal geometry of such a monomer is unknown, therefore it can't be used to validate bonds and angles
+    /// or to place hydrogen atoms.
+    pub fn has_ideal_positions(&self) -> bool { self.has_ideal_positions }
+
+    /// Finds the index of an atom of a given name.
+    ///
+    /// Leading and trailing spaces are neglected, so the name may be given in the PDB-style formatting
+    ///
+    /// ```
+    /// use bioshell_pdb::monomers::MonomerManager;
+    /// let manager = MonomerManager::get();
+    /// let ala = manager.by_code3("ALA").unwrap();
+    /// assert_eq!(ala.atom_index(" CA "), Some(1));
+    /// assert_eq!(ala.atom_index("SG"), None);
+    /// ```
+    pub fn atom_index(&self, atom_name: &str) -> Option<usize> {
+        let name = atom_name.trim();
+        self.atoms.iter().position(|a| a.atom_name == name)
+    }
+
     /// Total number of atoms in this monomer, including leaving atoms
     ///
     /// A leaving atom is an atom that is present in the structure of the monomer, but is removed
@@ -69,6 +118,67 @@ impl Monomer {
         self.atoms.iter().filter(|a| ! (a.is_leaving ||  &a.atom_type == "H")).count()
     }
 
+    /// Creates a monomer from a CIF data block that defines a chemical component
+    ///
+    /// Only the `pdbx_ordinal`, `atom_id`, `pdbx_leaving_atom_flag` and `type_symbol` items of the `_chem_comp_atom`
+    /// category are required. A missing stereo configuration defaults to `'N'`; when neither the ideal
+    /// nor the model coordinates are provided, atoms are placed at the origin. Results in an error when
+    /// coordinates are provided, but neither the ideal nor the model ones are given for a particular atom.
+    pub(crate) fn from_cif_data(data_block: &CifData, residue_type: ResidueType) -> Result<Monomer, CifError> {
+        let mut atoms: Vec<MonomerAtom> = vec![];
+        let atom_table = CifTable::new(data_block, "_chem_comp_atom",
+    ["pdbx_ordinal", "atom_id", "pdbx_leaving_atom_flag", "type_symbol"])?;
+        let mut stereo_configs: Vec<char> = vec![];
+        if let Ok(stereo_table) = CifTable::new(data_block, "_chem_comp_atom", ["pdbx_stereo_config"]) {
+            for [stereo] in stereo_table.iter() { stereo_configs.push(stereo.chars().next().unwrap_or('N')); }
+        }
+        let mut ideal_positions: Vec<Option<Vec3>> = vec![];
+        if let Ok(ideal_table) = CifTable::new(data_block, "_chem_comp_atom",
+                ["pdbx_model_Cartn_x_ideal", "pdbx_model_Cartn_y_ideal", "pdbx_model_Cartn_z_ideal"]) {
+            for [x, y, z] in ideal_table.iter() { ideal_positions.push(parse_position(x, y, z)?); }
+        }
+        // --- model coordinates replace the ideal ones that are missing
+        let mut model_positions: Vec<Option<Vec3>> = vec![];
+        if let Ok(model_table) = CifTable::new(data_block, "_chem_comp_atom", [".model_Cartn_x", ".model_Cartn_y", ".model_Cartn_z"]) {
+            for [x, y, z] in model_table.iter() { model_positions.push(parse_position(x, y, z)?); }
+        }
+        let has_ideal_positions = !ideal_positions.is_empty() || !model_positions.is_empty();
+        for (k, [idx, atom_name, is_leaving, element]) in atom_table.iter().enumerate() {
+            let leaving_bool = parse_bool(is_leaving)?;
+            let ideal_pos = match ideal_positions.get(k).copied().flatten().or(model_positions.get(k).copied().flatten()) {
+                Some(pos) => pos,
+                None if !has_ideal_positions => Vec3::from_float(0.0),
+                None => return Err(CifError::ItemParsingError { item: "pdbx_model_Cartn_x_ideal".to_string(),
+                    type_name: "f64".to_string(), details: format!("no coordinates given for the {} atom", atom_name) }),
+            };
+            atoms.push(MonomerAtom {
+                atom_number: idx.parse().map_err(|e: ParseIntError| CifError::ItemParsingError {
+                    item: "pdbx_ordinal".to_string(),
+                    type_name: "i32".to_string(),
+                    details: e.to_string(),
+                })?,
+                atom_name: atom_name.trim_matches('"').to_string(),
+                is_leaving: leaving_bool,
+                atom_type: element.to_string(),
+                stereo_config: stereo_configs.get(k).copied().unwrap_or('N'),
+                ideal_pos,
+            });
+        }
+        let mut bonds: Vec<MonomerBond> = vec![];
+        // --- a single-atom component, e.g. a metal ion, has no bonds
+        if let Ok(bond_table) = CifTable::new(data_block, "_chem_comp_bond", ["atom_id_1", "atom_id_2", "value_order"]) {
+            for [name_1, name_2, order] in bond_table.iter() {
+                let first = atoms.iter().position(|a| a.atom_name == name_1.trim_matches('"'));
+                let second = atoms.iter().position(|a| a.atom_name == name_2.trim_matches('"'));
+                if let (Some(first), Some(second)) = (first, second) {
+                    bonds.push(MonomerBond { first, second, order: order.to_string() });
+                }
+            }
+        }
+
+        Ok(Monomer { residue_type, atoms, bonds, has_ideal_positions })
+    }
+
 }
 
 /// Provides a definition of a monomer type.
@@ -79,19 +189,20 @@ impl Monomer {
 ///
 /// Standard monomers: 20 amino acids and 9 nucleotide variants are provided by default.
 pub struct MonomerManager {
-    by_code_3: HashMap<String, Monomer>
+    by_code_3: HashMap<String, Monomer>,
+    components: Option<ComponentDictionary>,
 }
 
 impl MonomerManager {
 
     pub(crate) fn new() -> MonomerManager {
-        let mut mgr = MonomerManager { by_code_3: HashMap::new() };
+        let mut mgr = MonomerManager { by_code_3: HashMap::new(), components: None };
 
         if let Some(path) = find_bioshell_path() {
             let path = path.join("bioshell-pdb").join("data").join("monomers");
             for rt in &StandardResidueType::TYPES {
                 match rt {
-                    UNK | UNL | GAP | GPE=> {}
+                    UNK | UNL | GAP | GPE | STOP => {}
                     _ => {
                         let fname = format!("{}.cif", rt.code3());
                         let out = mgr.load_cif_file(path.join(fname).to_str().unwrap());
@@ -124,41 +235,94 @@ impl MonomerManager {
     }
 
     /// Provides the residue structure for a given three-letter code.
-    /// Returns an option that contains the monomer or None if it hasn’t been registered
-    pub fn by_code3(&self, code_3: &str) -> Option<&Monomer> { self.by_code_3.get(code_3) }
+    ///
+    /// Returns an option that contains the monomer or None if it hasn’t been registered. Monomers that
+    /// haven't been loaded explicitly are looked up in the chemical component dictionary, if one has been loaded.
+    pub fn by_code3(&self, code_3: &str) -> Option<&Monomer> {
+        if let Some(monomer) = self.by_code_3.get(code_3) { return Some(monomer); }
+        return self.components.as_ref().and_then(|d| d.monomer(code_3));
+    }
 
-    /// Counts the residue structures registered in this manager
+    /// Counts the residue structures registered in this manager.
+    ///
+    /// Components of a chemical component dictionary are not included in this count.
     pub fn count(&self) -> usize { self.by_code_3.len() }
 
+    /// Loads monomer definitions from a CIF file, e.g. downloaded from the PDB Ligand Repository.
+    ///
+    /// Only monomers whose residue types are known to the [`ResidueTypeManager`] are registered.
     pub fn load_cif_file(&mut self, file_name: &str) -> Result<(), CifError> {
         let cif_data = read_cif_file(file_name)?;
         for data_block in &cif_data {
             let res_name = data_block.name();
-            let mut atoms: Vec<MonomerAtom> = vec![];
-            let atom_table = CifTable::new(data_block, "_chem_comp_atom",
-        ["pdbx_ordinal", "atom_id", "pdbx_leaving_atom_flag", "type_symbol"])?;
-            for [idx, atom_name, is_leaving, element] in atom_table.iter() {
-                let leaving_bool = parse_bool(is_leaving)?;
-                atoms.push(MonomerAtom {
-                    atom_number: idx.parse().map_err(|e: ParseIntError| CifError::ItemParsingError {
-                        item: "pdbx_ordinal".to_string(),
-                        type_name: "i32".to_string(),
-                        details: e.to_string(),
-                    })?,
-                    atom_name: atom_name.to_string(),
-                    is_leaving: leaving_bool,
-                    atom_type: element.to_string(),
-                });
-            }
-            if let Some(residue_type) = ResidueTypeManager::get().by_code3(res_name) {
-                self.by_code_3.insert(res_name.to_string(), Monomer { residue_type: residue_type.clone(), atoms, });
+            let residue_type = ResidueTypeManager::get().by_code3(res_name).cloned();
+            if let Some(residue_type) = residue_type {
+                self.by_code_3.insert(res_name.to_string(), Monomer::from_cif_data(data_block, residue_type)?);
             };
         }
 
         Ok(())
     }
+
+    /// Loads the wwPDB Chemical Component Dictionary, i.e. the `components.cif` file, possibly gzip-ed.
+    /// A gzip-ed dictionary is decompressed once, as described in the [`ComponentDictionary`] documentation.
+    ///
+    /// The dictionary is indexed as described in the [`ComponentDictionary`] documentation; a monomer
+    /// is parsed only when it's requested for the first time by [`by_code3()`](MonomerManager::by_code3).
+    /// Every non-standard component is also registered in the [`ResidueTypeManager`] with its parent type,
+    /// replacing the definition of that residue type registered previously, e.g. from a deposit file.
+    /// Returns the number of components listed in the dictionary.
+    ///
+    /// The dictionary is never loaded implicitly: until this method is called, only the standard monomers
+    /// and those loaded by [`load_cif_file()`](MonomerManager::load_cif_file) are available.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::PDBError;
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_pdb::monomers::MonomerManager;
+    /// use bioshell_seq::chemical::{ResidueTypeManager, StandardResidueType};
+    /// # let fname = std::env::temp_dir().join("components_doc_manager.cif");
+    /// # std::fs::copy("./tests/test_files/components_subset.cif", &fname)?;
+    /// let mut mgr = MonomerManager::get();
+    /// mgr.load_components(fname.to_str().unwrap())?;
+    /// assert_eq!(mgr.by_code3("SEP").unwrap().count_residue_heavy(), 10);
+    /// assert_eq!(ResidueTypeManager::get().by_code3("MSE").unwrap().parent_type, StandardResidueType::MET);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_components(&mut self, file_name: &str) -> Result<usize, PDBError> {
+        let dictionary = ComponentDictionary::open(file_name)?;
+        let mut rts = ResidueTypeManager::get();
+        for entry in dictionary.entries() {
+            // --- standard residue types are never replaced
+            if StandardResidueType::TYPES.iter().any(|srt| srt.code3() == entry.code) { continue; }
+            rts.update_residue_type(entry.residue_type());
+        }
+        info!("{} chemical components listed in {}", dictionary.len(), file_name);
+        let n = dictionary.len();
+        self.components = Some(dictionary);
+
+        return Ok(n);
+    }
+
+    /// Provides the chemical component dictionary loaded by this manager, if any
+    pub fn components(&self) -> Option<&ComponentDictionary> { self.components.as_ref() }
 }
 
+/// Parses a coordinate of an atom position; `None` is returned for a missing value
+fn parse_coordinate(token: &str) -> Result<Option<f64>, CifError> {
+    if token == "?" || token == "." { return Ok(None); }
+    token.parse::<f64>().map(Some).map_err(|_| CifError::CantParseFloatValue { value: token.to_string() })
+}
+
+/// Parses an atom position, which is `None` when any of its coordinates is missing
+fn parse_position(x: &str, y: &str, z: &str) -> Result<Option<Vec3>, CifError> {
+    return match (parse_coordinate(x)?, parse_coordinate(y)?, parse_coordinate(z)?) {
+        (Some(x), Some(y), Some(z)) => Ok(Some(Vec3::new(x, y, z))),
+        _ => Ok(None),
+    };
+}
 
 pub static KNOWN_MONOMERS: Lazy<Mutex<MonomerManager>>
                 = Lazy::new(|| Mutex::new(MonomerManager::new()));
@@ -0,0 +1,460 @@
+//! Annotates base pairs and base stacking in nucleic acid structures.
+//!
+//! Bases are recognised for every nucleotide of a [`Structure`](crate::Structure), including modified ones,
+//! as long as their parent residue type is known (see [`ResidueTypeManager`](bioshell_seq::chemical::ResidueTypeManager)).
+//! Two bases form a pair when they are roughly coplanar and connected by at least
+//! [`MIN_BASE_PAIR_HBONDS`] hydrogen bonds; each pair is classified according to the
+//! [Leontis–Westhof](https://doi.org/10.1017/S1355838201002515) nomenclature by the interacting edges
+//! of the two bases (Watson–Crick, Hoogsteen or sugar edge) and by the relative orientation of their
+//! glycosidic bonds (*cis* or *trans*). Canonical pairs can be converted into the dot-bracket notation:
+//!
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::nucleic_acids::{dot_bracket, find_base_pairs, find_base_stackings, BasePairType};
+//! let strctr = Deposit::from_file("./tests/test_files/5edw.cif")?.structure()?;
+//! let pairs = find_base_pairs(&strctr);
+//! for p in &pairs { println!("{}", p); }
+//! let n_wc = pairs.iter().filter(|p| p.pair_type == BasePairType::WatsonCrick).count();
+//! # assert!(n_wc >= 10);
+//! // --- the template strand T and the primer strand P form a duplex
+//! let ss = dot_bracket(&strctr, &["T", "P"], &pairs);
+//! println!("{}", ss);
+//! # assert_eq!(ss.chars().filter(|&c| c == '(').count(), ss.chars().filter(|&c| c == ')').count());
+//! let stacks = find_base_stackings(&strctr);
+//! # assert!(!stacks.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+//! The annotation is derived from the atoms that form hydrogen bonds, which is a simplification
+//! of the geometric criteria used by dedicated tools such as FR3D, MC-Annotate or DSSR.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use bioshell_core::Vec3;
+use bioshell_seq::chemical::{ResidueType, ResidueTypeManager, ResidueTypeProperties};
+use crate::{ResidueId, Structure};
+use crate::calc::NeighborGrid;
+
+/// Maximum distance between a donor and an acceptor of a hydrogen bond between two bases (in Angstroms)
+pub const BASE_PAIR_HBOND_CUTOFF: f64 = 3.5;
+
+/// Minimum number of hydrogen bonds between two bases to consider them paired
+pub const MIN_BASE_PAIR_HBONDS: usize = 2;
+
+/// Maximum angle between normal vectors of two paired bases (in degrees)
+pub const BASE_PAIR_MAX_ANGLE: f64 = 40.0;
+
+/// Maximum distance between the center of a base and the plane of its pairing partner (in Angstroms)
+pub const BASE_PAIR_MAX_VERTICAL: f64 = 2.5;
+
+/// Maximum distance between centers of two stacked bases (in Angstroms)
+pub const STACKING_MAX_DISTANCE: f64 = 5.0;
+
+/// Maximum angle between normal vectors of two stacked bases (in degrees)
+pub const STACKING_MAX_ANGLE: f64 = 30.0;
+
+/// Maximum offset between centers of two stacked bases, measured in the plane of either base (in Angstroms)
+pub const STACKING_MAX_OFFSET: f64 = 3.0;
+
+/// Candidate base pairs are searched within that distance between base centers (in Angstroms)
+const BASE_PAIR_SEARCH_DISTANCE: f64 = 12.0;
+
+/// Atoms of a base that may form hydrogen bonds: atom name, is donor, is acceptor, and the edges it belongs to
+type HBondAtom = (&'static str, bool, bool, &'static [BaseEdge]);
+
+use BaseEdge::{Hoogsteen as H, Sugar as S, WatsonCrick as W};
+
+const ADENINE_HBOND_ATOMS: [HBondAtom; 5] = [("N6", true, false, &[W, H]), ("N1", false, true, &[W]),
+    ("N7", false, true, &[H]), ("N3", false, true, &[S]), ("O2'", true, true, &[S])];
+const GUANINE_HBOND_ATOMS: [HBondAtom; 6] = [("O6", false, true, &[W, H]), ("N1", true, false, &[W]),
+    ("N2", true, false, &[W, S]), ("N7", false, true, &[H]), ("N3", false, true, &[S]), ("O2'", true, true, &[S])];
+const CYTOSINE_HBOND_ATOMS: [HBondAtom; 4] = [("N4", true, false, &[W, H]), ("N3", false, true, &[W]),
+    ("O2", false, true, &[W, S]), ("O2'", true, true, &[S])];
+const URACIL_HBOND_ATOMS: [HBondAtom; 4] = [("O4", false, true, &[W, H]), ("N3", true, false, &[W]),
+    ("O2", false, true, &[W, S]), ("O2'", true, true, &[S])];
+
+const PURINE_RING: [&str; 9] = ["N1", "C2", "N3", "C4", "C5", "C6", "N7", "C8", "N9"];
+const PYRIMIDINE_RING: [&str; 6] = ["N1", "C2", "N3", "C4", "C5", "C6"];
+
+/// Edge of a base that interacts with another base, according to the Leontis–Westhof classification
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BaseEdge {
+    /// the Watson–Crick edge
+    WatsonCrick,
+    /// the Hoogsteen edge; called the C-H edge for pyrimidines
+    Hoogsteen,
+    /// the sugar edge, which includes the 2'-hydroxyl group of ribose
+    Sugar,
+}
+
+impl Display for BaseEdge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaseEdge::WatsonCrick => write!(f, "W"),
+            BaseEdge::Hoogsteen => write!(f, "H"),
+            BaseEdge::Sugar => write!(f, "S"),
+        }
+    }
+}
+
+/// Relative orientation of glycosidic bonds of two paired bases
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GlycosidicOrientation {
+    /// both glycosidic bonds are on the same side of the axis connecting the two bases
+    Cis,
+    /// glycosidic bonds are on the opposite sides of the axis connecting the two bases
+    Trans,
+}
+
+impl Display for GlycosidicOrientation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlycosidicOrientation::Cis => write!(f, "c"),
+            GlycosidicOrientation::Trans => write!(f, "t"),
+        }
+    }
+}
+
+/// Type of a base pair
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BasePairType {
+    /// a canonical pair: A-U, A-T or G-C in the cis Watson–Crick / Watson–Crick geometry
+    WatsonCrick,
+    /// the G-U (or G-T) pair in the cis Watson–Crick / Watson–Crick geometry
+    Wobble,
+    /// any other pair
+    NonCanonical,
+}
+
+impl Display for BasePairType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BasePairType::WatsonCrick => write!(f, "WC"),
+            BasePairType::Wobble => write!(f, "wobble"),
+            BasePairType::NonCanonical => write!(f, "non-canonical"),
+        }
+    }
+}
+
+/// A hydrogen bond between two paired bases
+#[derive(Clone, Debug)]
+pub struct BaseHBond {
+    /// name of the atom of the first base, e.g. `"N1"`
+    pub first_atom: String,
+    /// name of the atom of the second base
+    pub second_atom: String,
+    /// distance between the donor and the acceptor
+    pub distance: f64,
+}
+
+/// A pair of hydrogen-bonded bases
+#[derive(Clone, Debug)]
+pub struct BasePair {
+    /// the first residue of the pair, which comes first in a structure
+    pub first: ResidueId,
+    /// the second residue of the pair
+    pub second: ResidueId,
+    /// one-letter code of the first base: `'A'`, `'C'`, `'G'`, `'T'` or `'U'`
+    pub first_base: char,
+    /// one-letter code of the second base
+    pub second_base: char,
+    /// type of this pair
+    pub pair_type: BasePairType,
+    /// edge of the first base involved in the pair
+    pub first_edge: BaseEdge,
+    /// edge of the second base involved in the pair
+    pub second_edge: BaseEdge,
+    /// relative orientation of glycosidic bonds
+    pub orientation: GlycosidicOrientation,
+    /// hydrogen bonds between the two bases
+    pub hbonds: Vec<BaseHBond>,
+}
+
+impl BasePair {
+    /// Leontis–Westhof class of this pair, e.g. `"cWW"` for a canonical pair or `"tHS"` for the sheared G-A pair
+    pub fn lw_class(&self) -> String { format!("{}{}{}", self.orientation, self.first_edge, self.second_edge) }
+
+    /// Returns `true` for Watson–Crick and wobble pairs, i.e. pairs that define the secondary structure
+    pub fn is_canonical(&self) -> bool { self.pair_type != BasePairType::NonCanonical }
+}
+
+impl Display for BasePair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} - {} {} {} {} {}", self.first, self.first_base, self.second, self.second_base,
+               self.lw_class(), self.pair_type, self.hbonds.len())
+    }
+}
+
+/// Two stacked bases
+#[derive(Clone, Debug)]
+pub struct BaseStacking {
+    /// the first residue, which comes first in a structure
+    pub first: ResidueId,
+    /// the second residue
+    pub second: ResidueId,
+    /// distance between the centers of the two bases
+    pub distance: f64,
+    /// angle between normal vectors of the two bases (in degrees)
+    pub angle: f64,
+    /// offset between centers of the two bases, measured in the plane of the first base
+    pub offset: f64,
+}
+
+impl Display for BaseStacking {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {} {:5.2} {:5.1} {:5.2}", self.first, self.second, self.distance, self.angle, self.offset)
+    }
+}
+
+/// Geometry and hydrogen-bonding atoms of a single base
+struct Base {
+    res_id: ResidueId,
+    letter: char,
+    center: Vec3,
+    normal: Vec3,
+    glycosidic: Vec3,
+    hbond_atoms: Vec<(HBondAtom, Vec3)>,
+}
+
+/// Finds all base pairs in a given structure.
+///
+/// A base pair is formed by two bases whose normal vectors make an angle not larger than [`BASE_PAIR_MAX_ANGLE`],
+/// whose centers lie no further than [`BASE_PAIR_MAX_VERTICAL`] from the plane of the other base and which are connected
+/// by at least [`MIN_BASE_PAIR_HBONDS`] hydrogen bonds; at least one of them must not involve the 2'-hydroxyl group.
+/// A hydrogen bond is a donor–acceptor pair of base atoms within [`BASE_PAIR_HBOND_CUTOFF`]; the closest pairs are
+/// assigned first and each atom, except the 2'-hydroxyl oxygen, may form only one hydrogen bond with the partner base.
+///
+/// The edge of each base is the one that contains most of the atoms forming the hydrogen bonds; ties are resolved
+/// in the Watson–Crick, Hoogsteen, sugar edge order. A base may be involved in more than one pair, e.g. in base triples.
+/// Pairs are returned in the order of residues of a structure.
+pub fn find_base_pairs(strctr: &Structure) -> Vec<BasePair> {
+    let bases = structure_bases(strctr);
+    let centers: Vec<Vec3> = bases.iter().map(|b| b.center).collect();
+    let grid = NeighborGrid::new(&centers, BASE_PAIR_SEARCH_DISTANCE);
+    let max_cos = BASE_PAIR_MAX_ANGLE.to_radians().cos();
+
+    let mut out: Vec<BasePair> = vec![];
+    for (i, bi) in bases.iter().enumerate() {
+        let mut neighbors = grid.neighbors(&bi.center, BASE_PAIR_SEARCH_DISTANCE);
+        neighbors.sort();
+        for j in neighbors.into_iter().filter(|&j| j > i) {
+            let bj = &bases[j];
+            if Vec3::dot(&bi.normal, &bj.normal).abs() < max_cos { continue; }
+            let mut d = bj.center;
+            d -= &bi.center;
+            if Vec3::dot(&d, &bi.normal).abs() > BASE_PAIR_MAX_VERTICAL
+                || Vec3::dot(&d, &bj.normal).abs() > BASE_PAIR_MAX_VERTICAL { continue; }
+
+            // --- hydrogen bonds between the two bases; each atom, except O2', takes part in at most one of them
+            let mut candidates: Vec<(f64, usize, usize)> = vec![];
+            for (ki, (ai, pi)) in bi.hbond_atoms.iter().enumerate() {
+                for (kj, (aj, pj)) in bj.hbond_atoms.iter().enumerate() {
+                    if !((ai.1 && aj.2) || (ai.2 && aj.1)) { continue; }
+                    let distance = pi.distance_to(pj);
+                    if distance <= BASE_PAIR_HBOND_CUTOFF { candidates.push((distance, ki, kj)); }
+                }
+            }
+            candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let (mut used_i, mut used_j) = (vec![false; bi.hbond_atoms.len()], vec![false; bj.hbond_atoms.len()]);
+            let mut hbonds: Vec<BaseHBond> = vec![];
+            let mut edge_atoms: (Vec<&HBondAtom>, Vec<&HBondAtom>) = (vec![], vec![]);
+            let mut n_base_base = 0;
+            for (distance, ki, kj) in candidates {
+                let (ai, aj) = (&bi.hbond_atoms[ki].0, &bj.hbond_atoms[kj].0);
+                if (used_i[ki] && ai.0 != "O2'") || (used_j[kj] && aj.0 != "O2'") { continue; }
+                used_i[ki] = true;
+                used_j[kj] = true;
+                if ai.0 != "O2'" && aj.0 != "O2'" { n_base_base += 1; }
+                hbonds.push(BaseHBond { first_atom: ai.0.to_string(), second_atom: aj.0.to_string(), distance });
+                edge_atoms.0.push(ai);
+                edge_atoms.1.push(aj);
+            }
+            if hbonds.len() < MIN_BASE_PAIR_HBONDS || n_base_base == 0 { continue; }
+
+            let first_edge = dominant_edge(&edge_atoms.0);
+            let second_edge = dominant_edge(&edge_atoms.1);
+            // --- cis when glycosidic bonds, projected on a plane perpendicular to the pair axis, point the same way
+            d.normalize();
+            let project = |g: &Vec3| {
+                let mut p = d;
+                p *= -Vec3::dot(g, &d);
+                p += g;
+                p
+            };
+            let orientation = if Vec3::dot(&project(&bi.glycosidic), &project(&bj.glycosidic)) > 0.0 {
+                GlycosidicOrientation::Cis
+            } else { GlycosidicOrientation::Trans };
+
+            let is_cww = orientation == GlycosidicOrientation::Cis && first_edge == BaseEdge::WatsonCrick
+                && second_edge == BaseEdge::WatsonCrick;
+            let mut letters = [bi.letter, bj.letter];
+            letters.sort();
+            let pair_type = match (is_cww, letters) {
+                (true, ['A', 'T']) | (true, ['A', 'U']) | (true, ['C', 'G']) => BasePairType::WatsonCrick,
+                (true, ['G', 'T']) | (true, ['G', 'U']) => BasePairType::Wobble,
+                _ => BasePairType::NonCanonical,
+            };
+            out.push(BasePair { first: bi.res_id.clone(), second: bj.res_id.clone(), first_base: bi.letter,
+                second_base: bj.letter, pair_type, first_edge, second_edge, orientation, hbonds });
+        }
+    }
+
+    return out;
+}
+
+/// Finds all pairs of stacked bases in a given structure.
+///
+/// Two bases are stacked when their centers are closer than [`STACKING_MAX_DISTANCE`], their planes make an angle
+/// not larger than [`STACKING_MAX_ANGLE`] and the offset between their centers, measured in the plane of either base,
+/// doesn't exceed [`STACKING_MAX_OFFSET`]. Both sequential and non-sequential (e.g. cross-strand) stacking is reported.
+pub fn find_base_stackings(strctr: &Structure) -> Vec<BaseStacking> {
+    let bases = structure_bases(strctr);
+    let centers: Vec<Vec3> = bases.iter().map(|b| b.center).collect();
+    let grid = NeighborGrid::new(&centers, STACKING_MAX_DISTANCE);
+    let max_cos = STACKING_MAX_ANGLE.to_radians().cos();
+
+    let mut out: Vec<BaseStacking> = vec![];
+    for (i, bi) in bases.iter().enumerate() {
+        let mut neighbors = grid.neighbors(&bi.center, STACKING_MAX_DISTANCE);
+        neighbors.sort();
+        for j in neighbors.into_iter().filter(|&j| j > i) {
+            let bj = &bases[j];
+            let cos = Vec3::dot(&bi.normal, &bj.normal).abs();
+            if cos < max_cos { continue; }
+            let mut d = bj.center;
+            d -= &bi.center;
+            let distance = d.length();
+            let offset = |n: &Vec3| (distance * distance - Vec3::dot(&d, n).powi(2)).max(0.0).sqrt();
+            let (offset_i, offset_j) = (offset(&bi.normal), offset(&bj.normal));
+            if offset_i > STACKING_MAX_OFFSET && offset_j > STACKING_MAX_OFFSET { continue; }
+            out.push(BaseStacking { first: bi.res_id.clone(), second: bj.res_id.clone(), distance,
+                angle: cos.min(1.0).acos().to_degrees(), offset: offset_i });
+        }
+    }
+
+    return out;
+}
+
+/// Secondary structure of nucleic acid chains in the dot-bracket notation.
+///
+/// Only canonical pairs (see [`BasePair::is_canonical()`]) formed within the given chains are used.
+/// When more than one chain is given, the respective strings are joined with the `'&'` character.
+/// Pseudoknots are marked with square, curly and angle brackets, in that order; when a residue forms more than
+/// one canonical pair, the pair with more hydrogen bonds is used.
+///
+/// ```
+/// use bioshell_pdb::{PdbAtom, Structure};
+/// use bioshell_pdb::nucleic_acids::dot_bracket;
+/// let mut strctr = Structure::new("1xyz");
+/// for i in 1..=6 {
+///     let mut a = PdbAtom::new();
+///     (a.name, a.res_name, a.chain_id, a.res_seq) = (" P  ".to_string(), "G".to_string(), "A".to_string(), i);
+///     strctr.push_atom(a);
+/// }
+/// // --- no pairs given, all residues are unpaired
+/// assert_eq!(dot_bracket(&strctr, &["A"], &[]), "......");
+/// ```
+pub fn dot_bracket(strctr: &Structure, chain_ids: &[&str], pairs: &[BasePair]) -> String {
+    // --- position of each residue in the output string
+    let mut positions: HashMap<&ResidueId, usize> = HashMap::new();
+    let mut symbols: Vec<char> = vec![];
+    for (k, chain_id) in chain_ids.iter().enumerate() {
+        if k > 0 { symbols.push('&'); }
+        for res_id in strctr.residues().iter().filter(|r| &r.chain_id == chain_id) {
+            let first_atom = strctr.atoms_in_residue(res_id).ok().and_then(|mut it| it.next());
+            if first_atom.and_then(|a| nucleotide_type(strctr, res_id, &a.res_name)).is_none() { continue; }
+            positions.insert(res_id, symbols.len());
+            symbols.push('.');
+        }
+    }
+
+    // --- canonical pairs of the selected chains; pairs with more H-bonds take precedence
+    let mut selected: Vec<(usize, usize, usize)> = pairs.iter().filter(|p| p.is_canonical())
+        .filter_map(|p| Some((*positions.get(&p.first)?, *positions.get(&p.second)?, p.hbonds.len())))
+        .map(|(i, j, n)| (i.min(j), i.max(j), n)).collect();
+    selected.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+    let mut used = vec![false; symbols.len()];
+    let mut accepted: Vec<(usize, usize)> = vec![];
+    for (i, j, _) in selected {
+        if used[i] || used[j] { continue; }
+        used[i] = true;
+        used[j] = true;
+        accepted.push((i, j));
+    }
+    accepted.sort();
+
+    // --- each pair goes to the first bracket level where it doesn't cross any other pair
+    const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    let mut levels: Vec<Vec<(usize, usize)>> = vec![vec![]; BRACKETS.len()];
+    for (i, j) in accepted {
+        let crosses = |other: &(usize, usize)| (other.0 < i && i < other.1 && other.1 < j) || (i < other.0 && other.0 < j && j < other.1);
+        if let Some(level) = levels.iter().position(|l| !l.iter().any(crosses)) {
+            levels[level].push((i, j));
+            symbols[i] = BRACKETS[level].0;
+            symbols[j] = BRACKETS[level].1;
+        }
+    }
+
+    return symbols.into_iter().collect();
+}
+
+/// Creates a [`Base`] for every nucleotide of a structure whose base atoms are complete
+fn structure_bases(strctr: &Structure) -> Vec<Base> {
+    let mut out: Vec<Base> = vec![];
+    for res_id in strctr.residues() {
+        let Ok(atoms) = strctr.atoms_in_residue(res_id) else { continue };
+        let atoms: Vec<_> = atoms.collect();
+        let Some(res_type) = atoms.first().and_then(|a| nucleotide_type(strctr, res_id, &a.res_name)) else { continue };
+        let letter = res_type.parent_type.code1().to_ascii_uppercase();
+        let (ring, hbond_atoms): (&[&str], &[HBondAtom]) = match letter {
+            'A' => (&PURINE_RING, &ADENINE_HBOND_ATOMS),
+            'G' => (&PURINE_RING, &GUANINE_HBOND_ATOMS),
+            'C' => (&PYRIMIDINE_RING, &CYTOSINE_HBOND_ATOMS),
+            'U' | 'T' => (&PYRIMIDINE_RING, &URACIL_HBOND_ATOMS),
+            _ => continue,
+        };
+        // --- the first location of every atom is used
+        let mut by_name: HashMap<&str, Vec3> = HashMap::new();
+        for a in &atoms { by_name.entry(a.name.trim()).or_insert(a.pos); }
+        let Some(ring_pos) = ring.iter().map(|n| by_name.get(n).copied()).collect::<Option<Vec<Vec3>>>() else { continue };
+        let Some(c1) = by_name.get("C1'") else { continue };
+
+        let mut center = Vec3::from_float(0.0);
+        for p in &ring_pos { center += p; }
+        center /= ring_pos.len() as f64;
+        // --- N1, N3 and C5 span a triangle in the plane of the six-membered ring
+        let (mut a, mut b) = (ring_pos[2], ring_pos[4]);
+        a -= &ring_pos[0];
+        b -= &ring_pos[0];
+        let normal = Vec3::cross(&a, &b).normalized();
+        let mut glycosidic = *c1;
+        glycosidic -= &ring_pos[if ring.len() == 9 { 8 } else { 0 }];
+        glycosidic.normalize();
+        let hbond_atoms = hbond_atoms.iter().filter_map(|ha| Some((*ha, *by_name.get(ha.0)?))).collect();
+
+        out.push(Base { res_id: res_id.clone(), letter, center, normal, glycosidic, hbond_atoms });
+    }
+
+    return out;
+}
+
+/// Residue type of a nucleotide, or `None` for any other residue.
+///
+/// Residue names read from PDB files are right-aligned, e.g. `"  U"`, so they are trimmed before the lookup.
+fn nucleotide_type(strctr: &Structure, res_id: &ResidueId, res_name: &str) -> Option<ResidueType> {
+    let res_type = strctr.residue_type(res_id).ok()
+        .or_else(|| ResidueTypeManager::get().by_code3(res_name.trim()).cloned())?;
+    return if res_type.chem_compound_type.is_nucleic_linking() { Some(res_type) } else { None };
+}
+
+/// The edge that contains most of the given atoms; ties are resolved in the W, H, S order
+fn dominant_edge(atoms: &[&HBondAtom]) -> BaseEdge {
+    let mut best = (BaseEdge::WatsonCrick, 0);
+    for edge in [BaseEdge::WatsonCrick, BaseEdge::Hoogsteen, BaseEdge::Sugar] {
+        let n = atoms.iter().filter(|a| a.3.contains(&edge)).count();
+        if n > best.1 { best = (edge, n); }
+    }
+
+    return best.0;
+}
@@ -176,7 +176,8 @@ impl PdbAtom {
 
         let serial = parse_item_or_error!(tokens[0], i32);
         let element = Some(tokens[12].to_string());
-        let name = format_atom_name(tokens[1], element.as_deref());
+        // --- nucleotide atom names such as "O5'" are quoted in mmCIF files
+        let name = format_atom_name(tokens[1].trim_matches('"'), element.as_deref());
         let alt_loc = value_or_default(tokens[2], ' ');
         let res_name = tokens[3].to_string();
         let chain_id = tokens[4].to_string();
@@ -362,4 +363,68 @@ pub fn is_hydrogen(atom: &PdbAtom) -> bool {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Elements of metal ions, upper case, recognised e.g. in ligands, binding sites and coordination sites
+pub const METAL_ELEMENTS: [&str; 16] = ["LI", "NA", "K", "MG", "CA", "MN", "FE", "CO", "NI", "CU", "ZN", "CD", "HG", "SR", "BA", "PT"];
+
+/// Aromatic rings of standard amino acids and nucleotides.
+///
+/// Each entry gives a residue name and the names of its ring atoms, listed in the order along the ring.
+/// Tryptophan, adenine and guanine have two rings each, listed as separate entries.
+pub const RESIDUE_RINGS: [(&str, &[&str]); 17] = [
+    ("PHE", &["CG", "CD1", "CE1", "CZ", "CE2", "CD2"]),
+    ("TYR", &["CG", "CD1", "CE1", "CZ", "CE2", "CD2"]),
+    ("TRP", &["CG", "CD1", "NE1", "CE2", "CD2"]),
+    ("TRP", &["CD2", "CE2", "CZ2", "CH2", "CZ3", "CE3"]),
+    ("HIS", &["CG", "ND1", "CE1", "NE2", "CD2"]),
+    ("A", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+    ("A", &["C4", "C5", "N7", "C8", "N9"]),
+    ("G", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+    ("G", &["C4", "C5", "N7", "C8", "N9"]),
+    ("DA", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+    ("DA", &["C4", "C5", "N7", "C8", "N9"]),
+    ("DG", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+    ("DG", &["C4", "C5", "N7", "C8", "N9"]),
+    ("C", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+    ("U", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+    ("DC", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+    ("DT", &["N1", "C2", "N3", "C4", "C5", "C6"]),
+];
+
+/// Returns the chemical element of an atom, upper case.
+///
+/// When the element is not given explicitly, it is deduced from the first letter of the atom name, skipping
+/// leading digits and spaces; an empty string is returned when the name contains no letter at all.
+///
+/// # Examples
+/// ```
+/// use bioshell_pdb::{atom_element, PdbAtom};
+/// let mut atom = PdbAtom::from_atom_line("ATOM    320  CA  PHE A  43      16.101   9.057  19.587  1.00 18.18           C  ");
+/// assert_eq!(atom_element(&atom), "C");
+/// atom.element = Some("Fe".to_string());
+/// assert_eq!(atom_element(&atom), "FE");
+/// atom.element = None;
+/// atom.name = "1HA".to_string();
+/// assert_eq!(atom_element(&atom), "H");
+/// atom.name = "1".to_string();
+/// assert_eq!(atom_element(&atom), "");
+/// ```
+pub fn atom_element(atom: &PdbAtom) -> String {
+    return match &atom.element {
+        Some(e) if !e.trim().is_empty() => e.trim().to_uppercase(),
+        _ => atom.name.chars().find(|c| c.is_alphabetic()).map_or(String::new(), |c| c.to_uppercase().to_string()),
+    };
+}
+
+/// Returns `true` if the element of an atom is listed in [`METAL_ELEMENTS`]
+///
+/// # Examples
+/// ```
+/// use bioshell_pdb::{is_metal, PdbAtom};
+/// let zn = PdbAtom::from_atom_line("HETATM 2001 ZN    ZN A 201      10.000  10.000  10.000  1.00 20.00           ZN ");
+/// assert!(is_metal(&zn));
+/// let ca = PdbAtom::from_atom_line("ATOM    320  CA  PHE A  43      16.101   9.057  19.587  1.00 18.18           C  ");
+/// assert!(!is_metal(&ca));
+/// ```
+pub fn is_metal(atom: &PdbAtom) -> bool { METAL_ELEMENTS.contains(&atom_element(atom).as_str()) }
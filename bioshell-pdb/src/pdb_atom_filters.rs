@@ -505,6 +505,90 @@ impl PdbAtomPredicate for IsNotWater {
     fn check(&self, a: &PdbAtom) -> bool { a.res_name != "HOH" }
 }
 
+/// Returns `true` if an atom of a standard residue can donate a hydrogen to a hydrogen bond.
+///
+/// The predicate recognises backbone amide nitrogen (except proline), polar side chain atoms
+/// of standard amino acids and water oxygen.
+///
+/// # Examples
+/// ```
+/// use bioshell_pdb::PdbAtom;
+/// use bioshell_pdb::pdb_atom_filters::{IsHBondDonor, PdbAtomPredicate};
+/// let n = PdbAtom::from_atom_line("ATOM    514  N   ALA A  69      26.532  28.200  28.365  1.00 17.85           N");
+/// let o = PdbAtom::from_atom_line("ATOM    517  O   ALA A  69      26.657  29.867  31.341  1.00 20.90           O");
+/// assert!(IsHBondDonor.check(&n));
+/// assert!(!IsHBondDonor.check(&o));
+/// ```
+pub struct IsHBondDonor;
+
+impl PdbAtomPredicate for IsHBondDonor {
+    fn check(&self, a: &PdbAtom) -> bool {
+        let name: &str = format_name!(&a.name);
+        match (a.res_name.as_str(), name) {
+            ("PRO", " N  ") => false,
+            (_, " N  ") => KeepProtein.check(a),
+            ("HOH", " O  ") => true,
+            ("ARG", " NE " | " NH1" | " NH2") => true,
+            ("ASN", " ND2") | ("GLN", " NE2") | ("LYS", " NZ ") | ("TRP", " NE1") => true,
+            ("HIS", " ND1" | " NE2") => true,
+            ("SER", " OG ") | ("THR", " OG1") | ("TYR", " OH ") | ("CYS", " SG ") => true,
+            _ => false,
+        }
+    }
+}
+
+/// Returns `true` if an atom of a standard residue can accept a hydrogen bond.
+///
+/// The predicate recognises backbone carbonyl oxygen, polar side chain atoms of standard
+/// amino acids and water oxygen.
+///
+/// # Examples
+/// ```
+/// use bioshell_pdb::PdbAtom;
+/// use bioshell_pdb::pdb_atom_filters::{IsHBondAcceptor, PdbAtomPredicate};
+/// let n = PdbAtom::from_atom_line("ATOM    514  N   ALA A  69      26.532  28.200  28.365  1.00 17.85           N");
+/// let o = PdbAtom::from_atom_line("ATOM    517  O   ALA A  69      26.657  29.867  31.341  1.00 20.90           O");
+/// assert!(!IsHBondAcceptor.check(&n));
+/// assert!(IsHBondAcceptor.check(&o));
+/// ```
+pub struct IsHBondAcceptor;
+
+impl PdbAtomPredicate for IsHBondAcceptor {
+    fn check(&self, a: &PdbAtom) -> bool {
+        let name: &str = format_name!(&a.name);
+        matches!((a.res_name.as_str(), name),
+            (_, " O  " | " OXT")
+            | ("ASP", " OD1" | " OD2") | ("GLU", " OE1" | " OE2")
+            | ("ASN", " OD1") | ("GLN", " OE1")
+            | ("HIS", " ND1" | " NE2")
+            | ("SER", " OG ") | ("THR", " OG1") | ("TYR", " OH ") | ("MET", " SD "))
+    }
+}
+
+/// Returns `true` for a positively charged atom of a standard amino acid residue.
+///
+/// The predicate recognises side chain nitrogen atoms of `LYS` and `ARG` as well as `HIS` ring nitrogens.
+pub struct IsCationic;
+
+impl PdbAtomPredicate for IsCationic {
+    fn check(&self, a: &PdbAtom) -> bool {
+        let name: &str = format_name!(&a.name);
+        matches!((a.res_name.as_str(), name), ("LYS", " NZ ") | ("ARG", " NE " | " NH1" | " NH2") | ("HIS", " ND1" | " NE2"))
+    }
+}
+
+/// Returns `true` for a negatively charged atom of a standard amino acid residue.
+///
+/// The predicate recognises side chain oxygen atoms of `ASP` and `GLU` and the terminal `OXT` oxygen.
+pub struct IsAnionic;
+
+impl PdbAtomPredicate for IsAnionic {
+    fn check(&self, a: &PdbAtom) -> bool {
+        let name: &str = format_name!(&a.name);
+        matches!((a.res_name.as_str(), name), ("ASP", " OD1" | " OD2") | ("GLU", " OE1" | " OE2") | (_, " OXT"))
+    }
+}
+
 /// A filter defined for a pair of atoms.
 ///
 /// Structs implementing [`PdbAtomPredicate`](PdbAtomPredicate) trait can be used as predicates
@@ -1,5 +1,6 @@
+use std::fmt::{Display, Formatter};
 use bioshell_cif::{CifData, parse_item_or_error, CifError, entry_has_value, CifTable};
-use crate::{PDBError, ResidueId};
+use crate::{PDBError, ResidueId, SecondaryView, Structure};
 use bioshell_cif::CifError::{ItemParsingError};
 
 /// Corresponds to a HELIX record.
@@ -55,7 +56,7 @@ impl PdbHelix {
         let init_res_name = line[15..18].trim().to_string();
         let init_chain_id = line[19..20].to_string();
         let init_seq_num = line[21..25].trim().parse().unwrap();
-        let init_i_code = line.chars().nth(26).unwrap();
+        let init_i_code = line.chars().nth(25).unwrap();
         let end_res_name = line[27..30].trim().to_string();
         let end_chain_id = line[31..32].to_string();
         let end_seq_num = line[33..37].trim().parse().unwrap();
@@ -107,6 +108,53 @@ impl PdbHelix {
 
         return Ok(helices);
     }
+
+    /// Creates a helix record for every helical segment of a given structure.
+    ///
+    /// Segments are taken from the secondary structure annotation of atoms, as provided by [`SecondaryView`].
+    /// Helices are numbered from 1 and their class is derived from the respective
+    /// [`SecondaryStructureTypes`](crate::SecondaryStructureTypes) variant.
+    pub fn from_structure(strctr: &Structure) -> Vec<PdbHelix> {
+        let mut helices: Vec<PdbHelix> = Vec::new();
+        for chain_id in strctr.chain_ids() {
+            let view = SecondaryView::new(strctr, &chain_id);
+            for segment in view.helices() {
+                let (first, last) = (segment.residues()[0], segment.residues()[segment.residues().len() - 1]);
+                let ser_num = (helices.len() + 1).to_string();
+                helices.push(PdbHelix {
+                    helix_id: ser_num.clone(), ser_num,
+                    init_res_name: strctr.residue_name(first).unwrap_or("UNK").to_string(),
+                    init_chain_id: first.chain_id.clone(), init_seq_num: first.res_seq, init_i_code: first.i_code,
+                    end_res_name: strctr.residue_name(last).unwrap_or("UNK").to_string(),
+                    end_chain_id: last.chain_id.clone(), end_seq_num: last.res_seq, end_i_code: last.i_code,
+                    helix_class: segment.kind.pdb_class() as u8,
+                    comment: String::new(),
+                    length: segment.residues().len() as i32,
+                });
+            }
+        }
+
+        return helices;
+    }
+
+    /// Values of this helix, given in the order of the `_struct_conf` items written by
+    /// [`write_secondary_cif()`](crate::write_secondary_cif)
+    pub fn cif_row(&self) -> Vec<String> {
+        let i_code = |c: char| if c == ' ' { "?".to_string() } else { c.to_string() };
+        return vec!["HELX_P".to_string(), format!("HELX_P{}", self.ser_num), self.helix_id.clone(),
+            self.init_res_name.clone(), self.init_chain_id.clone(), self.init_seq_num.to_string(), i_code(self.init_i_code),
+            self.end_res_name.clone(), self.end_chain_id.clone(), self.end_seq_num.to_string(), i_code(self.end_i_code),
+            self.helix_class.to_string(), self.length.to_string()];
+    }
+}
+
+impl Display for PdbHelix {
+    /// Writes this helix as a `HELIX` record of a PDB file
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HELIX  {:>3} {:>3} {:>3} {} {:>4}{} {:>3} {} {:>4}{}{:>2}{:30} {:>5}", self.ser_num, self.helix_id,
+               self.init_res_name, self.init_chain_id, self.init_seq_num, self.init_i_code, self.end_res_name,
+               self.end_chain_id, self.end_seq_num, self.end_i_code, self.helix_class, self.comment, self.length)
+    }
 }
 
 #[cfg(test)]
@@ -114,7 +162,8 @@ mod tests {
     use std::io::BufReader;
     use bioshell_cif::read_cif_buffer;
     use crate::pdb_helix::PdbHelix;
-    use crate::{PDBError, ResidueId};
+    use crate::pdb_sheet::PdbSheet;
+    use crate::{write_secondary_cif, Deposit, PDBError, ResidueId};
 
     #[test]
     fn test_helix_from_line() {
@@ -122,6 +171,17 @@ mod tests {
         let pdb_helix = PdbHelix::from_helix_line(helix_line);
         assert_eq!(pdb_helix.init_res_id(), ResidueId::new("A", 5, ' '));
         assert_eq!(pdb_helix.end_res_id(), ResidueId::new("A", 11, ' '));
+        assert_eq!(pdb_helix.to_string(), helix_line);
+    }
+
+    #[test]
+    fn helix_insertion_codes() {
+        // --- insertion codes are given in columns 26 and 38
+        let helix_line = "HELIX    1   A SER A    5A GLN A   11B 1                                   7";
+        let pdb_helix = PdbHelix::from_helix_line(helix_line);
+        assert_eq!(pdb_helix.init_res_id(), ResidueId::new("A", 5, 'A'));
+        assert_eq!(pdb_helix.end_res_id(), ResidueId::new("A", 11, 'B'));
+        assert_eq!(pdb_helix.to_string(), helix_line);
     }
 
 
@@ -164,4 +224,27 @@ mod tests {
         let helices = PdbHelix::from_cif_data(&cif_data[0]).unwrap();
         assert_eq!(helices.len(), 8);
     }
+
+    #[test]
+    fn helices_to_cif_and_back() -> Result<(), PDBError> {
+        let reader = BufReader::new(include_str!("../tests/test_files/2fdo.cif").as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let mut out: Vec<u8> = vec![];
+        write_secondary_cif(&mut out, &strctr)?;
+        let text = format!("data_2fdo\n{}", String::from_utf8(out).unwrap());
+        let cif_data = read_cif_buffer(BufReader::new(text.as_bytes())).unwrap();
+
+        let expected = PdbHelix::from_structure(&strctr);
+        let helices = PdbHelix::from_cif_data(&cif_data[0])?;
+        assert_eq!(helices.len(), expected.len());
+        for (h, e) in helices.iter().zip(expected.iter()) {
+            assert_eq!(h.init_res_id(), e.init_res_id());
+            assert_eq!(h.end_res_id(), e.end_res_id());
+            assert_eq!(h.length, e.length);
+        }
+        let strands = PdbSheet::from_cif_data(&cif_data[0])?;
+        assert_eq!(strands.len(), PdbSheet::from_structure(&strctr).len());
+
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,418 @@
+//! Provides access to a local copy of the Protein Data Bank.
+//!
+//! A local mirror is typically a *divided* directory tree, where a deposit is stored in a subdirectory named
+//! by the two middle characters of its ID, e.g. `mmCIF/gb/2gb1.cif.gz`. [`PdbMirror`] scans such a tree
+//! (the exact layout doesn't really matter, as all subdirectories are visited), reads the header of every deposit
+//! found there, skipping its coordinates, and records its location and header metadata in an index: experimental methods, resolution,
+//! deposition date and entities. The index can be saved in a tab-separated text file and loaded later on,
+//! so the mirror doesn't have to be parsed again. Deposits may be then selected by a [`MirrorQuery`]
+//! and loaded in parallel:
+//!
+//! ```no_run
+//! # use bioshell_pdb::PDBError;
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::ExperimentalMethod;
+//! use bioshell_pdb::pdb_mirror::{MirrorQuery, PdbMirror};
+//! // --- the index is loaded from a file if it exists; otherwise the mirror is scanned and the index is saved
+//! let mirror = PdbMirror::open("/data/pdb/mmCIF", "/data/pdb/mmCIF.idx")?;
+//! let query = MirrorQuery { method: Some(ExperimentalMethod::XRay), max_resolution: Some(1.5), ..Default::default() };
+//! let ids: Vec<String> = mirror.query(&query).iter().map(|e| e.id_code.clone()).collect();
+//! for (id, deposit) in ids.iter().zip(mirror.load_parallel(&ids)) {
+//!     println!("{} {}", id, deposit?.title.unwrap_or_default());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use log::{info, warn};
+use rayon::prelude::*;
+use bioshell_core::io::open_file;
+use crate::{is_cif_file, Deposit, EntityType, ExperimentalMethod, PDBError, PolymerEntityType};
+
+/// Name extensions of files recognised as PDB deposits
+const DEPOSIT_EXTENSIONS: [&str; 8] = [".cif.gz", ".cif", ".ent.gz", ".ent", ".pdb.gz", ".pdb", ".CIF", ".CIF.gz"];
+
+/// Summary of an entity of a deposit, stored in a [`PdbMirror`] index
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntitySummary {
+    /// entity ID, e.g. `"1"`
+    pub id: String,
+    /// type of this entity
+    pub entity_type: EntityType,
+    /// chains of this entity
+    pub chain_ids: Vec<String>,
+    /// the number of monomers of a polymer entity, 0 otherwise
+    pub length: usize,
+}
+
+/// Location and metadata of a single deposit of a [`PdbMirror`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MirrorEntry {
+    /// upper-case PDB ID of a deposit, e.g. `2GB1`
+    pub id_code: String,
+    /// path to the deposit file, relative to the root of the mirror
+    pub path: String,
+    /// experimental methods used to determine the structure
+    pub methods: Vec<ExperimentalMethod>,
+    /// resolution, if known
+    pub resolution: Option<f64>,
+    /// date of the initial deposition, in the `YYYY-MM-DD` format
+    pub dep_date: Option<String>,
+    /// entities of the deposit
+    pub entities: Vec<EntitySummary>,
+}
+
+impl MirrorEntry {
+
+    /// Creates an index entry from a deposit loaded from a given path
+    pub fn from_deposit(deposit: &Deposit, path: &str) -> MirrorEntry {
+        let mut entities: Vec<EntitySummary> = deposit.entities().map(|(id, e)| EntitySummary {
+            id: id.clone(),
+            entity_type: e.entity_type(),
+            chain_ids: e.chain_ids().clone(),
+            length: e.entity_monomers().len(),
+        }).collect();
+        entities.sort_by(|a, b| a.id.cmp(&b.id));
+
+        return MirrorEntry {
+            id_code: deposit.id_code.to_uppercase(),
+            path: path.to_string(),
+            methods: deposit.methods.clone(),
+            resolution: deposit.resolution,
+            dep_date: deposit.dep_date.as_deref().map(iso_date),
+            entities,
+        };
+    }
+
+    /// Counts polymer chains of this deposit
+    pub fn count_polymer_chains(&self) -> usize {
+        return self.entities.iter().filter(|e| matches!(e.entity_type, EntityType::Polymer(_)))
+            .map(|e| e.chain_ids.len()).sum();
+    }
+
+    /// Parses a line of an index file, as created by the [`Display`] trait
+    fn from_index_line(line: &str) -> Result<MirrorEntry, PDBError> {
+        let error = || PDBError::IncorrectMirrorIndex { line: line.to_string() };
+        let tokens: Vec<&str> = line.split('\t').collect();
+        if tokens.len() != 6 { return Err(error()); }
+        let methods = tokens[2].split(';').filter(|m| !m.is_empty())
+            .flat_map(ExperimentalMethod::from_expdata_line).collect();
+        let resolution = if tokens[3] == "." { None } else { Some(tokens[3].parse::<f64>().map_err(|_| error())?) };
+        let dep_date = if tokens[4] == "." { None } else { Some(tokens[4].to_string()) };
+        let mut entities: Vec<EntitySummary> = vec![];
+        for e in tokens[5].split(';').filter(|e| !e.is_empty()) {
+            let fields: Vec<&str> = e.split('|').collect();
+            if fields.len() != 4 { return Err(error()); }
+            entities.push(EntitySummary {
+                id: fields[0].to_string(),
+                entity_type: parse_entity_type(fields[1]).map_err(|_| error())?,
+                chain_ids: fields[2].split(',').filter(|c| !c.is_empty()).map(|c| c.to_string()).collect(),
+                length: fields[3].parse().map_err(|_| error())?,
+            });
+        }
+
+        return Ok(MirrorEntry { id_code: tokens[0].to_string(), path: tokens[1].to_string(), methods, resolution, dep_date, entities });
+    }
+}
+
+/// Writes an entry as a single tab-separated line of an index file
+impl Display for MirrorEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let methods: Vec<&str> = self.methods.iter().map(|m| m.to_string()).collect();
+        let entities: Vec<String> = self.entities.iter()
+            .map(|e| format!("{}|{}|{}|{}", e.id, entity_type_code(&e.entity_type), e.chain_ids.join(","), e.length))
+            .collect();
+        write!(f, "{}\t{}\t{}\t{}\t{}\t{}", self.id_code, self.path, methods.join(";"),
+               self.resolution.map_or(".".to_string(), |r| r.to_string()),
+               self.dep_date.as_deref().unwrap_or("."), entities.join(";"))
+    }
+}
+
+/// Selects deposits of a [`PdbMirror`] based on their metadata.
+///
+/// Every field that is set must be satisfied by a [`MirrorEntry`] to be selected; the default query
+/// selects all deposits.
+#[derive(Clone, Debug, Default)]
+pub struct MirrorQuery {
+    /// deposit must have been solved with this experimental method
+    pub method: Option<ExperimentalMethod>,
+    /// resolution must be known and not larger than this value
+    pub max_resolution: Option<f64>,
+    /// deposited on that day (`YYYY-MM-DD`) or later
+    pub deposited_after: Option<String>,
+    /// deposited on that day (`YYYY-MM-DD`) or earlier
+    pub deposited_before: Option<String>,
+    /// deposit must contain a polymer entity of this type
+    pub polymer_type: Option<PolymerEntityType>,
+    /// smallest number of polymer chains
+    pub min_polymer_chains: Option<usize>,
+    /// largest number of polymer chains
+    pub max_polymer_chains: Option<usize>,
+}
+
+impl MirrorQuery {
+    /// Returns `true` if a given entry satisfies this query
+    pub fn matches(&self, entry: &MirrorEntry) -> bool {
+        if let Some(m) = &self.method {
+            if !entry.methods.contains(m) { return false; }
+        }
+        if let Some(r) = self.max_resolution {
+            if entry.resolution.is_none_or(|res| res > r) { return false; }
+        }
+        if let Some(after) = &self.deposited_after {
+            if entry.dep_date.as_ref().is_none_or(|d| d < after) { return false; }
+        }
+        if let Some(before) = &self.deposited_before {
+            if entry.dep_date.as_ref().is_none_or(|d| d > before) { return false; }
+        }
+        if let Some(t) = self.polymer_type {
+            if !entry.entities.iter().any(|e| e.entity_type == EntityType::Polymer(t)) { return false; }
+        }
+        let n_chains = entry.count_polymer_chains();
+        if self.min_polymer_chains.is_some_and(|n| n_chains < n) { return false; }
+        if self.max_polymer_chains.is_some_and(|n| n_chains > n) { return false; }
+
+        return true;
+    }
+}
+
+/// Index of a local copy of the Protein Data Bank.
+///
+/// See the [module documentation](crate::pdb_mirror) for an example.
+pub struct PdbMirror {
+    root: PathBuf,
+    entries: Vec<MirrorEntry>,
+    by_id: HashMap<String, usize>,
+}
+
+impl PdbMirror {
+
+    /// Opens a mirror, using its index file.
+    ///
+    /// When the index file doesn't exist, the mirror is scanned with [`PdbMirror::build_index()`]
+    /// and the resulting index is written to that file.
+    pub fn open(root: &str, index_file: &str) -> Result<PdbMirror, PDBError> {
+        if Path::new(index_file).is_file() { return PdbMirror::load_index(root, index_file); }
+        let mirror = PdbMirror::build_index(root)?;
+        mirror.save_index(index_file)?;
+
+        return Ok(mirror);
+    }
+
+    /// Scans all files found in the `root` directory and its subdirectories and indexes their content.
+    ///
+    /// Headers of deposits are parsed in parallel; files that can't be parsed are skipped with a warning.
+    pub fn build_index(root: &str) -> Result<PdbMirror, PDBError> {
+        let mut mirror = PdbMirror { root: PathBuf::from(root), entries: vec![], by_id: HashMap::new() };
+        mirror.update()?;
+
+        return Ok(mirror);
+    }
+
+    /// Loads an index of a mirror from a file
+    pub fn load_index(root: &str, index_file: &str) -> Result<PdbMirror, PDBError> {
+        let mut mirror = PdbMirror { root: PathBuf::from(root), entries: vec![], by_id: HashMap::new() };
+        let reader = BufReader::new(File::open(index_file)?);
+        for line in reader.lines() {
+            let line = line?;
+            if line.is_empty() || line.starts_with('#') { continue; }
+            mirror.insert(MirrorEntry::from_index_line(&line)?);
+        }
+        info!("{} deposits loaded from the PDB mirror index {}", mirror.len(), index_file);
+
+        return Ok(mirror);
+    }
+
+    /// Writes the index of this mirror to a file
+    pub fn save_index(&self, index_file: &str) -> Result<(), PDBError> {
+        let mut writer = BufWriter::new(File::create(index_file)?);
+        writeln!(writer, "#id_code\tpath\tmethods\tresolution\tdep_date\tentities")?;
+        for e in &self.entries { writeln!(writer, "{}", e)?; }
+
+        return Ok(());
+    }
+
+    /// Indexes deposit files of the mirror that are not listed in the index yet.
+    ///
+    /// Entries of deposit files that no longer exist in the mirror are removed from the index.
+    /// Returns the number of deposits added to the index.
+    pub fn update(&mut self) -> Result<usize, PDBError> {
+        let mut files: Vec<PathBuf> = vec![];
+        collect_deposit_files(&self.root, &mut files)?;
+        let files: Vec<String> = files.iter()
+            .filter_map(|f| f.strip_prefix(&self.root).ok().map(|p| p.to_string_lossy().into_owned())).collect();
+
+        // --- remove entries of deleted files
+        let present: HashSet<&str> = files.iter().map(|p| p.as_str()).collect();
+        let n_before = self.entries.len();
+        self.entries.retain(|e| present.contains(e.path.as_str()));
+        if self.entries.len() < n_before {
+            info!("{} deposits removed from the index of the PDB mirror {}", n_before - self.entries.len(), self.root.display());
+            self.by_id = self.entries.iter().enumerate().map(|(i, e)| (e.id_code.clone(), i)).collect();
+        }
+
+        let indexed: HashSet<&str> = self.entries.iter().map(|e| e.path.as_str()).collect();
+        let new_files: Vec<&String> = files.iter().filter(|p| !indexed.contains(p.as_str())).collect();
+        let new_entries: Vec<MirrorEntry> = new_files.par_iter().filter_map(|&path| {
+            match load_header(&self.root.join(path)) {
+                Ok(deposit) => Some(MirrorEntry::from_deposit(&deposit, path)),
+                Err(e) => { warn!("Can't index a deposit file {}: {}", path, e); None }
+            }
+        }).collect();
+        let n = new_entries.len();
+        for e in new_entries { self.insert(e); }
+        info!("{} deposits added to the index of the PDB mirror {}", n, self.root.display());
+
+        return Ok(n);
+    }
+
+    /// The number of deposits in this mirror
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    /// Returns `true` if no deposits were indexed
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// Iterates over all entries of this mirror
+    pub fn entries(&self) -> impl Iterator<Item = &MirrorEntry> { self.entries.iter() }
+
+    /// Index entry of a deposit; the PDB ID is case-insensitive
+    pub fn entry(&self, pdb_id: &str) -> Option<&MirrorEntry> {
+        return self.by_id.get(&pdb_id.to_uppercase()).map(|&i| &self.entries[i]);
+    }
+
+    /// Full path to a deposit file
+    pub fn path(&self, pdb_id: &str) -> Option<PathBuf> { self.entry(pdb_id).map(|e| self.root.join(&e.path)) }
+
+    /// Entries that satisfy a given query
+    pub fn query(&self, query: &MirrorQuery) -> Vec<&MirrorEntry> {
+        return self.entries.iter().filter(|e| query.matches(e)).collect();
+    }
+
+    /// Loads a single deposit from the mirror
+    pub fn load(&self, pdb_id: &str) -> Result<Deposit, PDBError> {
+        let path = self.path(pdb_id).ok_or(PDBError::DepositNotInMirror { pdb_id: pdb_id.to_string() })?;
+        return Deposit::from_file(path);
+    }
+
+    /// Loads deposits in parallel; results are returned in the order of the given IDs
+    pub fn load_parallel<S: AsRef<str> + Sync>(&self, pdb_ids: &[S]) -> Vec<Result<Deposit, PDBError>> {
+        return pdb_ids.par_iter().map(|id| self.load(id.as_ref())).collect();
+    }
+
+    fn insert(&mut self, entry: MirrorEntry) {
+        match self.by_id.get(&entry.id_code) {
+            Some(&i) => self.entries[i] = entry,
+            None => {
+                self.by_id.insert(entry.id_code.clone(), self.entries.len());
+                self.entries.push(entry);
+            }
+        }
+    }
+}
+
+/// Loads a deposit without its coordinates, which are not needed to index it.
+///
+/// Reading of a PDB file stops at its first coordinate record; the `_atom_site` loops of an mmCIF file are skipped.
+fn load_header(path: &Path) -> Result<Deposit, PDBError> {
+    let reader = open_file(path)?;
+    let mut text = String::new();
+    if is_cif_file(path)? {
+        let is_coordinate_item = |l: &str| l.starts_with("_atom_site.") || l.starts_with("_atom_site_anisotrop.");
+        let mut loop_open = false;
+        let mut skip = false;
+        for line in reader.lines() {
+            let line = line?;
+            if skip {
+                // --- a coordinate loop ends where a new category or a new data block begins
+                if !(line.starts_with('#') || line.starts_with("loop_") || line.starts_with("data_")
+                        || (line.starts_with('_') && !is_coordinate_item(&line))) { continue; }
+                skip = false;
+            }
+            if loop_open {
+                loop_open = false;
+                if is_coordinate_item(&line) {
+                    skip = true;
+                    continue;
+                }
+                text.push_str("loop_\n");
+            }
+            if line.starts_with("loop_") {
+                loop_open = true;
+                continue;
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+        return Deposit::from_cif_reader(BufReader::new(text.as_bytes()));
+    }
+    for line in reader.lines() {
+        let line = line?;
+        if line.starts_with("ATOM") || line.starts_with("HETATM") || line.starts_with("MODEL") { break; }
+        text.push_str(&line);
+        text.push('\n');
+    }
+
+    return Deposit::from_pdb_reader(BufReader::new(text.as_bytes()));
+}
+
+fn collect_deposit_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), PDBError> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() { collect_deposit_files(&path, files)?; }
+        else if path.file_name().and_then(|n| n.to_str())
+                .is_some_and(|n| DEPOSIT_EXTENSIONS.iter().any(|ext| n.ends_with(ext))) {
+            files.push(path);
+        }
+    }
+
+    return Ok(());
+}
+
+/// Converts a `DD-MMM-YY` date of a PDB header into the `YYYY-MM-DD` format; other strings are returned unchanged
+fn iso_date(date: &str) -> String {
+    const MONTHS: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+    let tokens: Vec<&str> = date.split('-').collect();
+    if tokens.len() == 3 && tokens[2].len() == 2 {
+        if let (Some(m), Ok(d), Ok(y)) = (MONTHS.iter().position(|m| *m == tokens[1]), tokens[0].parse::<u32>(),
+                                          tokens[2].parse::<u32>()) {
+            let year = if y < 70 { 2000 + y } else { 1900 + y };
+            return format!("{}-{:02}-{:02}", year, m + 1, d);
+        }
+    }
+
+    return date.to_string();
+}
+
+/// mmCIF name of an entity type, as used in the index file
+fn entity_type_code(entity_type: &EntityType) -> &'static str {
+    match entity_type {
+        EntityType::NonPolymer => "non-polymer",
+        EntityType::Water => "water",
+        EntityType::Branched => "branched",
+        EntityType::Polymer(p) => match p {
+            PolymerEntityType::PolypeptideL => "polypeptide(L)",
+            PolymerEntityType::PolypeptideD => "polypeptide(D)",
+            PolymerEntityType::DNA => "polydeoxyribonucleotide",
+            PolymerEntityType::RNA => "polyribonucleotide",
+            PolymerEntityType::PolysaccharideL => "polysaccharide(L)",
+            PolymerEntityType::PolysaccharideD => "polysaccharide(D)",
+            PolymerEntityType::PeptideNucleicAcid => "peptide nucleic acid",
+            PolymerEntityType::Other => "other",
+        },
+    }
+}
+
+fn parse_entity_type(code: &str) -> Result<EntityType, PDBError> {
+    return match code {
+        "non-polymer" | "water" | "branched" => EntityType::from_str(code),
+        _ => Ok(EntityType::Polymer(PolymerEntityType::from_str(code)?)),
+    };
+}
@@ -25,6 +25,14 @@ pub enum PDBError {
     /// Chain corresponding to a given `chain_id` could not be located
     NoSuchChain {chain_id: String},
 
+    #[error("Chain already exists: {chain_id}")]
+    /// A chain can't be created or renamed, because its `chain_id` is already used in a structure
+    ChainAlreadyExists {chain_id: String},
+
+    #[error("Residue already exists: {res_id}")]
+    /// A residue can't be added to a structure, because its `res_id` is already used
+    ResidueAlreadyExists {res_id: ResidueId},
+
     #[error("Entity not found: {entity_id}")]
     /// Can't find entity for the given `entity_id` string
     NoSuchEntity {entity_id: String},
@@ -37,6 +45,14 @@ pub enum PDBError {
     /// Atom named `atom_name` could not be located in a residue `res_id`
     NoSuchAtom {atom_name: String, res_id: ResidueId},
 
+    #[error("Atom {atom_name} already exists in the residue {res_id}")]
+    /// Atom named `atom_name` can't be added to a residue `res_id`, because it's already there
+    AtomAlreadyExists {atom_name: String, res_id: ResidueId},
+
+    #[error("Dihedral angle {dihedral_name} is not defined for the residue {res_id}")]
+    /// Dihedral angle `dihedral_name` is not defined for the type of a residue `res_id`, e.g. chi1 of glycine
+    NoSuchDihedral {dihedral_name: String, res_id: ResidueId},
+
     #[error("Residue type not registered: {res_type}")]
     /// Unknown 3-letter residue code: `res_type`
     UnknownResidueType {res_type: String},
@@ -85,6 +101,18 @@ pub enum PDBError {
     /// Error while parsing a residue id string
     ResidueIdParsingError {residue_id: String},
 
+    #[error("Deposited sequence of the chain {chain_id} is not available")]
+    /// Neither `SEQRES` records nor entity sequence is available for a given chain
+    NoSequenceData {chain_id: String},
+
+    #[error("{feature} is available only for deposits loaded from mmCIF data")]
+    /// A requested feature needs data that is not provided by the PDB file format
+    MmCifDataRequired {feature: String},
+
+    #[error("Coarse-grained model {model} is not defined")]
+    /// A data block defining a requested coarse-grained model can't be found
+    UnknownCoarseGrainedModel {model: String},
+
     #[error("No PDB / CIF data loaded")]
     /// No PDB / CIF data loaded
     NoStructureDataLoaded,
@@ -93,6 +121,30 @@ pub enum PDBError {
     /// Can't download mmCIF file from RCSB website a deposit
     CantDownladFromRCSB{pdb_id: String, reason: String},
 
+    #[error("Deposit {pdb_id} can't be found in the local PDB mirror")]
+    /// A requested deposit is not listed in the index of a local PDB mirror
+    DepositNotInMirror{pdb_id: String},
+
+    #[error("Incorrect line in a PDB mirror index: {line}")]
+    /// A line of a PDB mirror index file can't be parsed
+    IncorrectMirrorIndex{line: String},
+
+    #[error("Incorrect {format} data: {details}")]
+    /// Small molecule data (SDF, MOL2 or PDBQT) can't be parsed or written
+    SmallMoleculeFormatError{format: String, details: String},
+
+    #[error("Incorrect line in a chemical component dictionary index: {line}")]
+    /// A line of an index file of the chemical component dictionary can't be parsed
+    IncorrectComponentIndex{line: String},
+
+    #[error("Incorrect CCP4/MRC map data: {details}")]
+    /// A volumetric map can't be parsed, e.g. its header is inconsistent or its data mode is not supported
+    DensityMapFormatError{details: String},
+
+    #[error("At least {required} atoms are required, {found} given")]
+    /// A calculation has been requested for a set of atoms that is too small
+    TooFewAtoms{required: usize, found: usize},
+
     #[error("structure cannot be converted to PDB-compatible form: {reason}")]
     PdbConversionNotPossible {
         reason: PdbConversionImpossibleReason,
@@ -1,6 +1,7 @@
+use std::fmt::{Display, Formatter};
 use bioshell_cif::{CifData, CifError, CifTable, entry_has_value, parse_item_or_error};
 use bioshell_cif::CifError::{ItemParsingError};
-use crate::{PDBError, ResidueId};
+use crate::{PDBError, ResidueId, SecondaryView, Structure};
 
 /// Corresponds to a SHEET record.
 ///
@@ -9,7 +10,8 @@ use crate::{PDBError, ResidueId};
 ///
 /// Refer to the [official documentation of the `SHEET` entry](https://www.wwpdb.org/documentation/file-format-content/format33/sect5.html#SHEET)
 #[allow(dead_code)]
-pub(crate) struct PdbSheet {
+#[derive(Clone, Debug)]
+pub struct PdbSheet {
     /// strand  number
     ///
     /// starts at 1 for each strand within a sheet
@@ -18,6 +20,10 @@ pub(crate) struct PdbSheet {
     pub sheet_id: String,
     /// number  of strands in sheet
     pub num_strands: i32,
+    /// sense of this strand with respect to the previous one in its sheet
+    ///
+    /// 0 for the first strand of a sheet, 1 for a parallel and -1 for an anti-parallel strand
+    pub sense: i32,
     /// name of the initial residue for this strand
     pub init_res_name: String,
     /// chain identifier of the initial residue for this strand
@@ -53,8 +59,9 @@ impl PdbSheet {
         let end_chain_id = line[32..33].to_string();
         let end_seq_num = line[33..37].trim().parse().map_err(|_| PDBError::InvalidPdbLineFormat { broken_pdb_line: line.to_string() })?;
         let end_i_code = line.chars().nth(37).ok_or(PDBError::InvalidPdbLineFormat { broken_pdb_line: line.to_string() })?;
+        let sense = line.get(38..40).and_then(|s| s.trim().parse().ok()).unwrap_or(0);
 
-        Ok(PdbSheet { strand, sheet_id, num_strands, init_res_name, init_chain_id, init_seq_num, init_i_code,
+        Ok(PdbSheet { strand, sheet_id, num_strands, sense, init_res_name, init_chain_id, init_seq_num, init_i_code,
             end_res_name, end_chain_id, end_seq_num, end_i_code })
     }
 
@@ -71,6 +78,7 @@ impl PdbSheet {
                 strand: parse_item_or_error!(tokens[1], i32),
                 sheet_id: tokens[0].to_string(),
                 num_strands: 0,
+                sense: 0,
                 init_res_name: tokens[2].to_string(),
                 init_chain_id: tokens[3].to_string(),
                 init_seq_num: parse_item_or_error!(tokens[4], i32),
@@ -95,6 +103,55 @@ impl PdbSheet {
 
         return Ok(strands);
     }
+
+    /// Creates a strand record for every beta strand of a given structure.
+    ///
+    /// Strands are taken from the secondary structure annotation of atoms, as provided by [`SecondaryView`].
+    /// Since that annotation does not tell which strands are paired, all the strands of a chain
+    /// are listed as a single sheet and their sense is 0; sheets are numbered from 1.
+    /// Strand records that describe the actual sheets may be stored with [`Structure::set_sheets()`].
+    pub fn from_structure(strctr: &Structure) -> Vec<PdbSheet> {
+        let mut strands: Vec<PdbSheet> = Vec::new();
+        let mut sheet_index = 0;
+        for chain_id in strctr.chain_ids() {
+            let view = SecondaryView::new(strctr, &chain_id);
+            let segments: Vec<_> = view.strands().collect();
+            if segments.is_empty() { continue }
+            sheet_index += 1;
+            for (i, segment) in segments.iter().enumerate() {
+                let (first, last) = (segment.residues()[0], segment.residues()[segment.residues().len() - 1]);
+                strands.push(PdbSheet {
+                    strand: i as i32 + 1, sheet_id: sheet_index.to_string(), num_strands: segments.len() as i32, sense: 0,
+                    init_res_name: strctr.residue_name(first).unwrap_or("UNK").to_string(),
+                    init_chain_id: first.chain_id.clone(), init_seq_num: first.res_seq, init_i_code: first.i_code,
+                    end_res_name: strctr.residue_name(last).unwrap_or("UNK").to_string(),
+                    end_chain_id: last.chain_id.clone(), end_seq_num: last.res_seq, end_i_code: last.i_code,
+                });
+            }
+        }
+
+        return strands;
+    }
+
+    /// Values of this strand, given in the order of the `_struct_sheet_range` items written by
+    /// [`write_secondary_cif()`](crate::write_secondary_cif)
+    pub fn cif_row(&self) -> Vec<String> {
+        let i_code = |c: char| if c == ' ' { "?".to_string() } else { c.to_string() };
+        return vec![self.sheet_id.clone(), self.strand.to_string(),
+            self.init_res_name.clone(), self.init_chain_id.clone(), self.init_seq_num.to_string(), i_code(self.init_i_code),
+            self.end_res_name.clone(), self.end_chain_id.clone(), self.end_seq_num.to_string(), i_code(self.end_i_code)];
+    }
+}
+
+impl Display for PdbSheet {
+    /// Writes this strand as a `SHEET` record of a PDB file.
+    ///
+    /// Registration of a strand with respect to the previous one is not written.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SHEET  {:>3} {:>3}{:>2} {:>3} {}{:>4}{} {:>3} {}{:>4}{}{:>2}", self.strand, self.sheet_id,
+               self.num_strands, self.init_res_name, self.init_chain_id, self.init_seq_num, self.init_i_code,
+               self.end_res_name, self.end_chain_id, self.end_seq_num, self.end_i_code, self.sense)
+    }
 }
 
 #[cfg(test)]
@@ -121,6 +178,11 @@ mod tests {
         let pdb_strand = PdbSheet::from_sheet_line(sheet_line)?;
         assert_eq!(pdb_strand.init_res_id(), ResidueId::new("A", 107, ' '));
         assert_eq!(pdb_strand.end_res_id(), ResidueId::new("A", 110, ' '));
+        assert_eq!(pdb_strand.to_string(), sheet_line);
+        let sheet_line = "SHEET    2   A 5 VAL A  40  ASP A  46 -1";
+        let pdb_strand = PdbSheet::from_sheet_line(sheet_line)?;
+        assert_eq!(pdb_strand.sense, -1);
+        assert_eq!(pdb_strand.to_string(), sheet_line);
         Ok(())
     }
 
@@ -0,0 +1,270 @@
+//! Finds pockets and cavities on the surface of a [`Structure`](crate::Structure).
+//!
+//! The method follows the [LIGSITE](https://doi.org/10.1016/S1093-3263(98)00002-3) algorithm. The structure
+//! is placed in a regular grid; grid points that lie within the van der Waals radius of any atom, enlarged
+//! by [`PocketFinder::probe_radius`], are marked as occupied. Empty points are thus possible positions
+//! of a probe center. Every empty point is then scanned along seven directions: the three axes and the four
+//! cube diagonals. When occupied points are found on both sides of an empty point along a given direction,
+//! that direction is counted as *enclosed*. The number of enclosed directions (0 to 7) measures buriedness of a point.
+//! Points buried at least as much as [`PocketFinder::min_buriedness`] are clustered into pockets.
+//! Pockets are ranked by their [`score`](Pocket::score), which grows with both the volume of a pocket
+//! and the average buriedness of its points.
+//!
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError, write_pdb};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::pockets::{pockets_as_structure, PocketFinder};
+//! let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+//! let strctr = deposit.structure()?;
+//! let pockets = PocketFinder::default().find_pockets(&strctr);
+//! for (i, p) in pockets.iter().enumerate() {
+//!     println!("{:3} {}", i + 1, p);
+//! }
+//! // --- pseudo-atoms of all pockets, ready to be written in the PDB format
+//! let pseudo_atoms = pockets_as_structure(&pockets);
+//! # assert_eq!(pseudo_atoms.count_atoms(), pockets.iter().map(|p| p.points.len()).sum::<usize>());
+//! # Ok(())
+//! # }
+//! ```
+//! Hydrogen atoms and water molecules are neglected. Remove ligands from a structure
+//! to find pockets they occupy.
+
+use std::fmt::{Display, Formatter};
+use bioshell_core::Vec3;
+use crate::{is_hydrogen, PdbAtom, ResidueId, Structure};
+use crate::calc::{atom_radius, NeighborGrid};
+use crate::pdb_atom_filters::{IsNotWater, PdbAtomPredicate};
+
+/// Grid points closer than that to an atom of a structure define residues lining a pocket (in Angstroms)
+pub const POCKET_LINING_DISTANCE: f64 = 3.5;
+
+/// The seven scan directions: three axes and four cube diagonals
+const SCAN_DIRECTIONS: [(i32, i32, i32); 7] = [(1, 0, 0), (0, 1, 0), (0, 0, 1), (1, 1, 1), (1, 1, -1), (1, -1, 1), (-1, 1, 1)];
+
+/// A pocket found on the surface of a [`Structure`](crate::Structure).
+#[derive(Clone, Debug)]
+pub struct Pocket {
+    /// grid points that form this pocket
+    pub points: Vec<Vec3>,
+    /// buriedness of every grid point, i.e. the number of scan directions enclosed by the structure (0 to 7)
+    pub point_buriedness: Vec<u8>,
+    /// volume of this pocket (in cubic Angstroms), i.e. the volume accessible for a probe center
+    pub volume: f64,
+    /// average buriedness of the pocket points
+    pub buriedness: f64,
+    /// residues lining this pocket, given in the order of a structure
+    pub residues: Vec<ResidueId>,
+    /// the score used to rank pockets: volume times the average buriedness divided by 7
+    pub score: f64,
+}
+
+impl Pocket {
+    /// Geometric center of this pocket
+    pub fn center(&self) -> Vec3 {
+        let mut c = Vec3::from_float(0.0);
+        for p in &self.points { c += p; }
+        c /= self.points.len() as f64;
+
+        return c;
+    }
+
+    /// Creates a pseudo-atom for every point of this pocket.
+    ///
+    /// Pseudo-atoms are `HETATM` records of a `STP` residue numbered by `pocket_id` in chain `P`;
+    /// the temperature factor column holds buriedness of each point.
+    pub fn pseudo_atoms(&self, pocket_id: i32) -> Vec<PdbAtom> {
+        let mut out: Vec<PdbAtom> = vec![];
+        for (i, (p, b)) in self.points.iter().zip(self.point_buriedness.iter()).enumerate() {
+            let mut a = PdbAtom::new();
+            a.serial = i as i32 + 1;
+            a.name = " PC ".to_string();
+            a.res_name = "STP".to_string();
+            a.chain_id = "P".to_string();
+            a.res_seq = pocket_id;
+            a.pos = *p;
+            a.occupancy = 1.0;
+            a.temp_factor = *b as f64;
+            a.element = Some("C".to_string());
+            a.is_hetero_atom = true;
+            out.push(a);
+        }
+
+        return out;
+    }
+}
+
+impl Display for Pocket {
+    /// Prints a single-line summary of a pocket
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let c = self.center();
+        write!(f, "{:8.1} {:5.2} {:8.2} {:4} {:8.3} {:8.3} {:8.3}", self.volume, self.buriedness, self.score,
+               self.residues.len(), c.x, c.y, c.z)
+    }
+}
+
+/// Combines pseudo-atoms of the given pockets into a single [`Structure`](Structure).
+///
+/// The i-th pocket on the list becomes the residue `i+1` of the chain `P`, see [`Pocket::pseudo_atoms()`].
+/// Such a structure can be written in the PDB format with [`write_pdb()`](crate::write_pdb()).
+pub fn pockets_as_structure(pockets: &[Pocket]) -> Structure {
+    let mut atoms: Vec<PdbAtom> = vec![];
+    for (i, p) in pockets.iter().enumerate() {
+        atoms.extend(p.pseudo_atoms(i as i32 + 1));
+    }
+    for (i, a) in atoms.iter_mut().enumerate() { a.serial = i as i32 + 1; }
+
+    return Structure::from_atoms("PCKT", atoms);
+}
+
+/// Parameters of the grid-based pocket detection.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::pockets::PocketFinder;
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+/// let strctr = deposit.structure()?;
+/// // --- more buried pockets are found on a finer grid
+/// let finder = PocketFinder { grid_spacing: 0.8, min_buriedness: 6, ..Default::default() };
+/// for p in finder.find_pockets(&strctr) {
+///     assert!(p.buriedness >= 6.0);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PocketFinder {
+    /// distance between grid points (in Angstroms)
+    pub grid_spacing: f64,
+    /// minimum number of enclosed directions (out of 7) for a grid point to be a part of a pocket
+    pub min_buriedness: u8,
+    /// maximum distance (in Angstroms) scanned along each direction while looking for enclosing atoms
+    pub scan_range: f64,
+    /// pockets smaller than that (in cubic Angstroms) are discarded
+    pub min_volume: f64,
+    /// radius added to van der Waals radius of every atom while marking occupied grid points (in Angstroms)
+    pub probe_radius: f64,
+}
+
+impl Default for PocketFinder {
+    fn default() -> Self {
+        PocketFinder { grid_spacing: 1.0, min_buriedness: 5, scan_range: 10.0, min_volume: 30.0, probe_radius: 1.0 }
+    }
+}
+
+impl PocketFinder {
+
+    /// Finds pockets of a given structure.
+    ///
+    /// Pockets are sorted by their [`score`](Pocket::score), the best first.
+    pub fn find_pockets(&self, strctr: &Structure) -> Vec<Pocket> {
+
+        let atoms: Vec<&PdbAtom> = strctr.atoms().iter().filter(|a| !is_hydrogen(a) && IsNotWater.check(a)).collect();
+        if atoms.is_empty() { return vec![]; }
+
+        // ---------- grid box enclosing all the atoms
+        let margin = 2.0 + self.probe_radius;
+        let mut min = atoms[0].pos;
+        let mut max = atoms[0].pos;
+        for a in &atoms {
+            min.x = min.x.min(a.pos.x); min.y = min.y.min(a.pos.y); min.z = min.z.min(a.pos.z);
+            max.x = max.x.max(a.pos.x); max.y = max.y.max(a.pos.y); max.z = max.z.max(a.pos.z);
+        }
+        min -= &Vec3::from_float(margin);
+        let h = self.grid_spacing;
+        let n = [((max.x - min.x + margin) / h) as i32 + 2, ((max.y - min.y + margin) / h) as i32 + 2,
+            ((max.z - min.z + margin) / h) as i32 + 2];
+        let index = |i: i32, j: i32, k: i32| ((i * n[1] + j) * n[2] + k) as usize;
+        let point = |i: i32, j: i32, k: i32| Vec3::new(min.x + i as f64 * h, min.y + j as f64 * h, min.z + k as f64 * h);
+
+        // ---------- mark occupied points
+        let mut occupied = vec![false; (n[0] * n[1] * n[2]) as usize];
+        for a in &atoms {
+            let r = atom_radius(a) + self.probe_radius;
+            let lo = |x: f64, m: f64| (((x - r - m) / h).floor() as i32).max(0);
+            let hi = |x: f64, m: f64, nn: i32| (((x + r - m) / h).ceil() as i32).min(nn - 1);
+            for i in lo(a.pos.x, min.x)..=hi(a.pos.x, min.x, n[0]) {
+                for j in lo(a.pos.y, min.y)..=hi(a.pos.y, min.y, n[1]) {
+                    for k in lo(a.pos.z, min.z)..=hi(a.pos.z, min.z, n[2]) {
+                        if point(i, j, k).distance_square_to(&a.pos) <= r * r { occupied[index(i, j, k)] = true; }
+                    }
+                }
+            }
+        }
+
+        // ---------- buriedness of every empty point
+        let inside = |i: i32, j: i32, k: i32| i >= 0 && j >= 0 && k >= 0 && i < n[0] && j < n[1] && k < n[2];
+        let mut buriedness = vec![0u8; occupied.len()];
+        for i in 0..n[0] {
+            for j in 0..n[1] {
+                for k in 0..n[2] {
+                    if occupied[index(i, j, k)] { continue; }
+                    let mut enclosed = 0;
+                    for (di, dj, dk) in SCAN_DIRECTIONS {
+                        let step = h * ((di * di + dj * dj + dk * dk) as f64).sqrt();
+                        let max_steps = (self.scan_range / step) as i32;
+                        let hits = |sign: i32| (1..=max_steps).map(|s| (i + sign * s * di, j + sign * s * dj, k + sign * s * dk))
+                            .take_while(|&(x, y, z)| inside(x, y, z))
+                            .any(|(x, y, z)| occupied[index(x, y, z)]);
+                        if hits(1) && hits(-1) { enclosed += 1; }
+                    }
+                    buriedness[index(i, j, k)] = enclosed;
+                }
+            }
+        }
+
+        // ---------- cluster buried points into pockets
+        let mut visited = vec![false; occupied.len()];
+        let mut clusters: Vec<Vec<(i32, i32, i32)>> = vec![];
+        for i in 0..n[0] {
+            for j in 0..n[1] {
+                for k in 0..n[2] {
+                    let idx = index(i, j, k);
+                    if visited[idx] || occupied[idx] || buriedness[idx] < self.min_buriedness { continue; }
+                    visited[idx] = true;
+                    let mut cluster = vec![(i, j, k)];
+                    let mut next = 0;
+                    while next < cluster.len() {
+                        let (ci, cj, ck) = cluster[next];
+                        next += 1;
+                        for di in -1..=1 {
+                            for dj in -1..=1 {
+                                for dk in -1..=1 {
+                                    let (x, y, z) = (ci + di, cj + dj, ck + dk);
+                                    if !inside(x, y, z) { continue; }
+                                    let nidx = index(x, y, z);
+                                    if visited[nidx] || occupied[nidx] || buriedness[nidx] < self.min_buriedness { continue; }
+                                    visited[nidx] = true;
+                                    cluster.push((x, y, z));
+                                }
+                            }
+                        }
+                    }
+                    clusters.push(cluster);
+                }
+            }
+        }
+
+        // ---------- describe pockets
+        let atoms_grid = NeighborGrid::new(&atoms.iter().map(|a| a.pos).collect::<Vec<_>>(), POCKET_LINING_DISTANCE);
+        let voxel = h * h * h;
+        let mut pockets: Vec<Pocket> = vec![];
+        for cluster in clusters {
+            let volume = cluster.len() as f64 * voxel;
+            if volume < self.min_volume { continue; }
+            let points: Vec<Vec3> = cluster.iter().map(|&(i, j, k)| point(i, j, k)).collect();
+            let point_buriedness: Vec<u8> = cluster.iter().map(|&(i, j, k)| buriedness[index(i, j, k)]).collect();
+            let mean = point_buriedness.iter().map(|&b| b as f64).sum::<f64>() / cluster.len() as f64;
+            let mut lining: Vec<usize> = vec![];
+            for p in &points { lining.extend(atoms_grid.neighbors(p, POCKET_LINING_DISTANCE)); }
+            lining.sort();
+            lining.dedup();
+            let residues = Structure::residue_ids_from_atoms(lining.iter().map(|&i| atoms[i]));
+            pockets.push(Pocket { points, point_buriedness, volume, buriedness: mean, residues, score: volume * mean / 7.0 });
+        }
+        pockets.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        return pockets;
+    }
+}
@@ -72,6 +72,35 @@ impl SecondaryStructureTypes {
             _ => Coil
         }
     }
+
+    /// Returns the PDB class of this secondary structure element type.
+    ///
+    /// This is the inverse of [`from_pdb_class()`](SecondaryStructureTypes::from_pdb_class): helices are given their
+    /// `HELIX` class (from 1 to 10), a strand is 11 and a coil is 0, which is not a valid PDB class.
+    ///
+    /// # Example
+    /// ```
+    /// use bioshell_pdb::SecondaryStructureTypes;
+    /// let helix = SecondaryStructureTypes::from_pdb_class(5, 0);
+    /// assert_eq!(helix, SecondaryStructureTypes::Right3_10Helix(0));
+    /// assert_eq!(helix.pdb_class(), 5);
+    /// ```
+    pub fn pdb_class(&self) -> usize {
+        match *self {
+            RightAlphaHelix(_) => 1,
+            RightOmegaHelix(_) => 2,
+            RightPiHelix(_) => 3,
+            RightGammaHelix(_) => 4,
+            Right3_10Helix(_) => 5,
+            LeftAlphaHelix(_) => 6,
+            LeftOmegaHelix(_) => 7,
+            LeftGammaHelix(_) => 8,
+            RibbonHelix(_) => 9,
+            Polyproline(_) => 10,
+            Strand(_) => 11,
+            Coil => 0,
+        }
+    }
 }
 
 use std::fmt;
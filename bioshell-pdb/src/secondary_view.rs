@@ -29,7 +29,8 @@ pub struct SecondaryView<'a> {
 
 impl<'a> SecondaryView<'a> {
     pub fn new(structure: &'a Structure, chain_id : &str) -> Self {
-        let residues: Vec<&'a ResidueId> = structure.residues().into_iter().collect();
+        let residues: Vec<&'a ResidueId> = structure.residues_in_polymer(chain_id).iter()
+            .map(|&i| &structure.residues()[i]).collect();
         let secondary = structure.secondary(chain_id);
         Self { structure, annotation: secondary, residues }
     }
@@ -0,0 +1,446 @@
+//! Residue-level mapping between PDB and UniProt numbering.
+//!
+//! While [`Entity::db_ref()`](crate::Entity::db_ref()) tells which UniProt entry a polymer entity comes from,
+//! a [`UniProtMapping`] links every residue of a deposit with the respective position of a UniProt sequence.
+//! It also provides a list of residues that differ from the reference sequence: engineered mutations,
+//! expression tags, insertions etc., as defined by the [`SequenceDifference`] enum. The mapping may be created from:
+//!
+//!  - `_struct_ref_seq` and `_struct_ref_seq_dif` categories of an mmCIF file,
+//!    see [`Deposit::uniprot_mapping()`](crate::Deposit::uniprot_mapping()),
+//!  - a residue-level SIFTS XML file, see [`UniProtMapping::from_sifts_xml()`],
+//!  - a segment-level SIFTS TSV file such as `pdb_chain_uniprot.tsv`, see [`UniProtMapping::from_sifts_tsv()`]
+//!
+//! The following example places a UniProt variant on a structure:
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::sifts::SequenceDifference;
+//! let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+//! let mapping = deposit.uniprot_mapping()?;
+//! // --- Met42 of O27953 has been observed in both chains of 2fdo as selenomethionine
+//! let residues = mapping.residues_at("O27953", 42);
+//! assert_eq!(residues.len(), 2);
+//! assert_eq!(residues[0].res_name, "MSE");
+//! assert_eq!(residues[0].difference, Some(SequenceDifference::ModifiedResidue));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+use std::str::FromStr;
+use bioshell_cif::{entry_has_value, value_or_default, CifData, CifTable};
+use log::warn;
+use crate::{Deposit, PDBError, ResidueId};
+use crate::chain_mapping::code1;
+
+/// Reason why a residue of a deposited sequence differs from its reference (UniProt) sequence.
+///
+/// Variants correspond to the values of the `_struct_ref_seq_dif.details` mmCIF item and to
+/// `Annotation` residue details of SIFTS files.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SequenceDifference {
+    /// residue mutated on purpose
+    EngineeredMutation,
+    /// residue of an expression tag, such as a His-tag
+    ExpressionTag,
+    /// residue left after cloning, e.g. after a tag cleavage
+    CloningArtifact,
+    /// residue inserted into the reference sequence
+    Insertion,
+    /// residue deleted from the reference sequence
+    Deletion,
+    /// residue that differs from the reference sequence for unknown reason
+    Conflict,
+    /// chemically modified residue, e.g. selenomethionine in place of methionine
+    ModifiedResidue,
+    /// initiating methionine
+    InitiatingMethionine,
+    /// residue of a linker connecting two fused proteins or domains
+    Linker,
+    /// natural variant of the reference sequence
+    Variant,
+    /// any other reason, given as a free text
+    Other(String),
+}
+
+impl FromStr for SequenceDifference {
+    type Err = PDBError;
+
+    /// Recognizes a difference from its description, case-insensitive.
+    ///
+    /// Underscores are treated as spaces, so both `"engineered mutation"` and `"Engineered_Mutation"`
+    /// result in [`EngineeredMutation`](SequenceDifference::EngineeredMutation). Unknown descriptions
+    /// result in [`Other`](SequenceDifference::Other).
+    ///
+    /// # Example
+    /// ```
+    /// use std::str::FromStr;
+    /// use bioshell_pdb::sifts::SequenceDifference;
+    /// assert_eq!(SequenceDifference::from_str("'expression tag'").unwrap(), SequenceDifference::ExpressionTag);
+    /// assert_eq!(SequenceDifference::from_str("Engineered_Mutation").unwrap(), SequenceDifference::EngineeredMutation);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let details = s.trim().trim_matches(|c| c == '\'' || c == '"').replace('_', " ").to_lowercase();
+        return Ok(match details.as_str() {
+            "engineered mutation" => SequenceDifference::EngineeredMutation,
+            "expression tag" => SequenceDifference::ExpressionTag,
+            "cloning artifact" => SequenceDifference::CloningArtifact,
+            "insertion" => SequenceDifference::Insertion,
+            "deletion" => SequenceDifference::Deletion,
+            "conflict" => SequenceDifference::Conflict,
+            "modified residue" => SequenceDifference::ModifiedResidue,
+            "initiating methionine" => SequenceDifference::InitiatingMethionine,
+            "linker" => SequenceDifference::Linker,
+            "variant" | "microheterogeneity" => SequenceDifference::Variant,
+            _ => SequenceDifference::Other(details),
+        });
+    }
+}
+
+impl Display for SequenceDifference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SequenceDifference::EngineeredMutation => "engineered mutation",
+            SequenceDifference::ExpressionTag => "expression tag",
+            SequenceDifference::CloningArtifact => "cloning artifact",
+            SequenceDifference::Insertion => "insertion",
+            SequenceDifference::Deletion => "deletion",
+            SequenceDifference::Conflict => "conflict",
+            SequenceDifference::ModifiedResidue => "modified residue",
+            SequenceDifference::InitiatingMethionine => "initiating methionine",
+            SequenceDifference::Linker => "linker",
+            SequenceDifference::Variant => "variant",
+            SequenceDifference::Other(details) => details,
+        };
+        return f.pad(s);
+    }
+}
+
+/// A residue of a deposit mapped on a UniProt sequence.
+#[derive(Clone, Debug)]
+pub struct UniProtResidue {
+    /// ID of the residue in the deposit
+    pub res_id: ResidueId,
+    /// three-letter code of the residue in the deposit
+    pub res_name: String,
+    /// UniProt accession of the reference sequence; `None` if the residue is not covered by any reference
+    pub accession: Option<String>,
+    /// position in the UniProt sequence (counted from 1); `None` for residues that are not present in the reference,
+    /// such as expression tags or insertions
+    pub unp_pos: Option<usize>,
+    /// one-letter code of the respective UniProt residue, if known
+    pub unp_res: Option<char>,
+    /// why this residue differs from the reference sequence, if it does
+    pub difference: Option<SequenceDifference>,
+}
+
+impl Display for UniProtResidue {
+    /// Prints a residue mapping in a single line, e.g. `A:1     MSE O27953          1 M modified residue`
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let pos = self.unp_pos.map_or("-".to_string(), |p| p.to_string());
+        let diff = self.difference.as_ref().map_or("".to_string(), |d| d.to_string());
+        write!(f, "{:7} {:3} {:10} {:>6} {} {}", self.res_id, self.res_name, self.accession.as_deref().unwrap_or("-"),
+               pos, self.unp_res.unwrap_or('-'), diff)
+    }
+}
+
+/// Maps residues of a deposit onto UniProt sequences.
+///
+/// See the [module documentation](crate::sifts) for details.
+#[derive(Clone, Debug, Default)]
+pub struct UniProtMapping {
+    residues: Vec<UniProtResidue>,
+    by_res_id: HashMap<ResidueId, usize>,
+}
+
+impl UniProtMapping {
+
+    /// Creates the mapping from the `_struct_ref_seq` and `_struct_ref_seq_dif` categories of mmCIF data.
+    ///
+    /// Every residue listed in the `_pdbx_poly_seq_scheme` category is included, whether it has been observed or not;
+    /// only references to the UniProt database (`UNP`) are taken into account. An empty mapping is returned
+    /// when the data provides no `_struct_ref` category, e.g. for a model that has not been deposited.
+    pub fn from_cif_data(cif_data: &CifData) -> Result<UniProtMapping, PDBError> {
+
+        // ---------- IDs of references to UniProt
+        let Ok(ref_table) = CifTable::new(cif_data, "_struct_ref.", ["_struct_ref.id", "_struct_ref.db_name"]) else {
+            return Ok(UniProtMapping::default());
+        };
+        let unp_refs: Vec<String> = ref_table.iter().filter(|[_, db]| *db == "UNP").map(|[id, _]| id.to_string()).collect();
+
+        // ---------- residues of every chain, indexed by seq_id
+        let mut residues: Vec<UniProtResidue> = vec![];
+        let mut index: HashMap<(String, usize), usize> = HashMap::new();
+        let seq_table = CifTable::new(cif_data, "_pdbx_poly_seq_scheme",
+                [".pdb_strand_id", ".seq_id", ".mon_id", ".pdb_seq_num", ".pdb_ins_code"])?;
+        for [chain, seq_id, mon_id, pdb_seq_num, ins_code] in seq_table.iter() {
+            let key = (chain.to_string(), value_or_default(seq_id, 0));
+            if index.contains_key(&key) { continue }       // --- microheterogeneity: keep the first residue type
+            index.insert(key, residues.len());
+            residues.push(UniProtResidue {
+                res_id: ResidueId::new(chain, value_or_default(pdb_seq_num, 0), value_or_default(ins_code, ' ')),
+                res_name: mon_id.to_string(), accession: None, unp_pos: None, unp_res: None, difference: None,
+            });
+        }
+
+        // ---------- aligned segments
+        if let Ok(align_table) = CifTable::new(cif_data, "_struct_ref_seq.",
+                ["_struct_ref_seq.ref_id", "_struct_ref_seq.pdbx_strand_id", "_struct_ref_seq.seq_align_beg",
+                    "_struct_ref_seq.seq_align_end", "_struct_ref_seq.pdbx_db_accession", "_struct_ref_seq.db_align_beg"]) {
+            for [ref_id, chain, seq_beg, seq_end, accession, db_beg] in align_table.iter() {
+                if !unp_refs.iter().any(|r| r == ref_id) { continue }
+                let seq_beg: usize = value_or_default(seq_beg, 0);
+                let seq_end: usize = value_or_default(seq_end, 0);
+                let db_beg: usize = value_or_default(db_beg, 0);
+                for seq_id in seq_beg..=seq_end {
+                    if let Some(&i) = index.get(&(chain.to_string(), seq_id)) {
+                        let r = &mut residues[i];
+                        r.accession = Some(accession.to_string());
+                        r.unp_pos = Some(db_beg + seq_id - seq_beg);
+                        r.unp_res = Some(code1(&r.res_name));
+                    }
+                }
+            }
+        }
+
+        // ---------- differences between the deposited and the reference sequence
+        if let Ok(dif_table) = CifTable::new(cif_data, "_struct_ref_seq_dif.",
+                ["_struct_ref_seq_dif.pdbx_pdb_strand_id", "_struct_ref_seq_dif.seq_num", "_struct_ref_seq_dif.pdbx_seq_db_name",
+                    "_struct_ref_seq_dif.pdbx_seq_db_accession_code", "_struct_ref_seq_dif.db_mon_id",
+                    "_struct_ref_seq_dif.pdbx_seq_db_seq_num", "_struct_ref_seq_dif.details"]) {
+            for [chain, seq_num, db_name, accession, db_mon_id, db_seq_num, details] in dif_table.iter() {
+                if db_name != "UNP" || !entry_has_value(seq_num) { continue }
+                let Some(&i) = index.get(&(chain.to_string(), value_or_default(seq_num, 0))) else { continue };
+                let r = &mut residues[i];
+                r.accession = Some(accession.to_string());
+                r.unp_pos = if entry_has_value(db_seq_num) { Some(value_or_default(db_seq_num, 0)) } else { None };
+                r.unp_res = if entry_has_value(db_mon_id) { Some(code1(db_mon_id)) } else { None };
+                r.difference = Some(SequenceDifference::from_str(details)?);
+            }
+        }
+
+        return Ok(UniProtMapping::from_residues(residues));
+    }
+
+    /// Creates the mapping from a residue-level SIFTS XML file.
+    ///
+    /// Only residues observed in a structure are included, i.e. these that have a PDB residue number assigned.
+    /// Differences are taken from `Annotation` residue details. Predefined XML entities, such as `&amp;`,
+    /// and numeric character references are decoded in attribute values and annotations.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PDBError, ResidueId};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_core::io::open_file;
+    /// use bioshell_pdb::sifts::{SequenceDifference, UniProtMapping};
+    /// let reader = open_file("./tests/test_files/2fdo_sifts.xml")?;
+    /// let mapping = UniProtMapping::from_sifts_xml(reader)?;
+    /// let res = mapping.residue(&ResidueId::new("A", 1, ' ')).unwrap();
+    /// assert_eq!(res.unp_pos, Some(1));
+    /// assert_eq!(res.difference, Some(SequenceDifference::ModifiedResidue));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_sifts_xml<R: BufRead>(mut reader: R) -> Result<UniProtMapping, PDBError> {
+
+        let mut xml = String::new();
+        reader.read_to_string(&mut xml)?;
+
+        let mut residues: Vec<UniProtResidue> = vec![];
+        let mut current: Option<UniProtResidue> = None;
+        let mut in_annotation = false;
+        let mut rest = xml.as_str();
+        while let Some(start) = rest.find('<') {
+            let text = &rest[..start];
+            if in_annotation {
+                if let Some(r) = current.as_mut() {
+                    let d = SequenceDifference::from_str(&decode_xml_entities(text))?;
+                    // --- "Not_Observed" and similar annotations are not sequence differences
+                    if !matches!(d, SequenceDifference::Other(_)) { r.difference = Some(d); }
+                }
+            }
+            let Some(end) = rest[start..].find('>') else { break };
+            let tag = &rest[start + 1..start + end];
+            rest = &rest[start + end + 1..];
+
+            let name = tag.split_whitespace().next().unwrap_or("");
+            in_annotation = false;
+            match name {
+                "residue" => {
+                    current = Some(UniProtResidue {
+                        res_id: ResidueId::new("", 0, ' '), res_name: xml_attribute(tag, "dbResName").unwrap_or_default(),
+                        accession: None, unp_pos: None, unp_res: None, difference: None,
+                    });
+                }
+                "crossRefDb" => {
+                    let Some(r) = current.as_mut() else { continue };
+                    match xml_attribute(tag, "dbSource").as_deref() {
+                        Some("PDB") => {
+                            let res_num = xml_attribute(tag, "dbResNum").unwrap_or("null".to_string());
+                            let chain = xml_attribute(tag, "dbChainId").unwrap_or_default();
+                            if let Ok(res_id) = ResidueId::try_from(format!("{}:{}", chain, res_num).as_str()) {
+                                r.res_id = res_id;
+                                r.res_name = xml_attribute(tag, "dbResName").unwrap_or_default();
+                            }
+                        }
+                        Some("UniProt") => {
+                            r.accession = xml_attribute(tag, "dbAccessionId");
+                            r.unp_pos = xml_attribute(tag, "dbResNum").and_then(|s| s.parse().ok());
+                            r.unp_res = xml_attribute(tag, "dbResName").and_then(|s| s.chars().next());
+                        }
+                        _ => {}
+                    }
+                }
+                "residueDetail" => { in_annotation = xml_attribute(tag, "property").as_deref() == Some("Annotation"); }
+                "/residue" => {
+                    if let Some(r) = current.take() {
+                        if !r.res_id.chain_id.is_empty() { residues.push(r); }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        return Ok(UniProtMapping::from_residues(residues));
+    }
+
+    /// Creates the mapping from a segment-level SIFTS TSV file, such as `pdb_chain_uniprot.tsv`.
+    ///
+    /// Every data row of such a file provides: `PDB`, `CHAIN`, `SP_PRIMARY`, `RES_BEG`, `RES_END`, `PDB_BEG`,
+    /// `PDB_END`, `SP_BEG` and `SP_END` columns. Only rows of the given deposit are used; positions of the deposited
+    /// sequence (`RES_BEG`, `RES_END`) are mapped to residues with
+    /// [`Deposit::chain_sequence_map()`](Deposit::chain_sequence_map()), therefore only observed residues are included.
+    /// Such a file provides no information about sequence differences.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_core::io::open_file;
+    /// use bioshell_pdb::sifts::UniProtMapping;
+    /// let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    /// let reader = open_file("./tests/test_files/pdb_chain_uniprot.tsv")?;
+    /// let mapping = UniProtMapping::from_sifts_tsv(reader, &deposit)?;
+    /// assert_eq!(mapping.unp_position(&ResidueId::new("A", 2, ' ')), Some(("P06654", 228)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_sifts_tsv<R: BufRead>(reader: R, deposit: &Deposit) -> Result<UniProtMapping, PDBError> {
+
+        let mut residues: Vec<UniProtResidue> = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            let tokens: Vec<&str> = line.split('\t').map(|s| s.trim()).collect();
+            if line.starts_with('#') || tokens.len() < 9 || tokens[0] == "PDB" { continue }
+            if !tokens[0].eq_ignore_ascii_case(&deposit.id_code) { continue }
+            let (Ok(res_beg), Ok(res_end), Ok(sp_beg)) = (tokens[3].parse::<usize>(), tokens[4].parse::<usize>(),
+                                                         tokens[7].parse::<usize>()) else {
+                warn!("Can't parse SIFTS line: {}", &line);
+                continue
+            };
+            let seq_map = match deposit.chain_sequence_map(tokens[1]) {
+                Ok(seq_map) => seq_map,
+                Err(e) => {
+                    warn!("Can't map SIFTS line: {}: {}", &line, e);
+                    continue
+                }
+            };
+            for seq_id in res_beg..=res_end {
+                let Some(res_id) = seq_map.residue_id(seq_id) else { continue };
+                let res_name = seq_map.positions()[seq_id - 1].res_name.clone();
+                residues.push(UniProtResidue {
+                    res_id: res_id.clone(), accession: Some(tokens[2].to_string()),
+                    unp_pos: Some(sp_beg + seq_id - res_beg), unp_res: Some(code1(&res_name)), res_name, difference: None,
+                });
+            }
+        }
+
+        return Ok(UniProtMapping::from_residues(residues));
+    }
+
+    /// All mapped residues, in the order they have been loaded
+    pub fn residues(&self) -> &Vec<UniProtResidue> { &self.residues }
+
+    /// Number of residues in this mapping
+    pub fn len(&self) -> usize { self.residues.len() }
+
+    /// Returns `true` if this mapping contains no residues
+    pub fn is_empty(&self) -> bool { self.residues.is_empty() }
+
+    /// Provides mapping of a given residue
+    pub fn residue(&self, res_id: &ResidueId) -> Option<&UniProtResidue> {
+        self.by_res_id.get(res_id).map(|&i| &self.residues[i])
+    }
+
+    /// Provides UniProt accession and position of a given residue, if it's known
+    pub fn unp_position(&self, res_id: &ResidueId) -> Option<(&str, usize)> {
+        let r = self.residue(res_id)?;
+        return Some((r.accession.as_deref()?, r.unp_pos?));
+    }
+
+    /// Lists residues mapped to a given position of a given UniProt sequence.
+    ///
+    /// More than one residue is returned when the same protein has been deposited in several chains.
+    pub fn residues_at(&self, accession: &str, unp_pos: usize) -> Vec<&UniProtResidue> {
+        self.residues.iter().filter(|r| r.unp_pos == Some(unp_pos) && r.accession.as_deref() == Some(accession)).collect()
+    }
+
+    /// Iterates over residues that differ from their reference sequence
+    pub fn differences(&self) -> impl Iterator<Item=&UniProtResidue> {
+        self.residues.iter().filter(|r| r.difference.is_some())
+    }
+
+    fn from_residues(residues: Vec<UniProtResidue>) -> UniProtMapping {
+        let by_res_id = residues.iter().enumerate().map(|(i, r)| (r.res_id.clone(), i)).collect();
+        return UniProtMapping { residues, by_res_id };
+    }
+}
+
+/// Value of an attribute of an XML tag, e.g. `dbResNum` from `<crossRefDb dbResNum="12"/>`
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let key = format!(" {}=\"", name);
+    let start = tag.find(&key)? + key.len();
+    let end = tag[start..].find('"')?;
+    return Some(decode_xml_entities(&tag[start..start + end]));
+}
+
+/// Replaces predefined XML entities and numeric character references, e.g. `&amp;` or `&#38;`, with characters.
+///
+/// A reference that can't be decoded is left as it is.
+fn decode_xml_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                code => code.strip_prefix("#x").map(|hex| u32::from_str_radix(hex, 16))
+                    .unwrap_or_else(|| code.strip_prefix('#').unwrap_or("").parse::<u32>())
+                    .ok().and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    return out;
+}
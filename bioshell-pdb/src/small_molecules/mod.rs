@@ -0,0 +1,32 @@
+//! Small molecules and the file formats used to store them: SDF/MOL, Tripos MOL2 and AutoDock PDBQT.
+//!
+//! Unlike a [`Structure`](crate::Structure), a [`SmallMolecule`] keeps covalent bonds along with their orders
+//! and formal as well as partial charges of its atoms. A ligand may be extracted from a structure
+//! with [`SmallMolecule::from_residue()`] and converted back with [`SmallMolecule::to_structure()`]:
+//!
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::small_molecules::{read_sdf, write_sdf, MolfileVersion, SmallMolecule};
+//! let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+//! let trp = SmallMolecule::from_residue(&strctr, &ResidueId::new("A", 43, ' '))?;
+//! // --- write the residue in the SDF format and read it back
+//! let mut buffer: Vec<u8> = vec![];
+//! write_sdf(&mut buffer, &[trp.clone()], MolfileVersion::V2000)?;
+//! let molecules = read_sdf(buffer.as_slice())?;
+//! assert_eq!(molecules[0].count_atoms(), trp.count_atoms());
+//! assert_eq!(molecules[0].count_bonds(), trp.count_bonds());
+//! let trp_strctr = molecules[0].to_structure("TRP");
+//! assert_eq!(trp_strctr.count_atoms(), trp.count_atoms());
+//! # Ok(())
+//! # }
+//! ```
+mod molecule;
+mod sdf;
+mod mol2;
+mod pdbqt;
+
+pub use molecule::{covalent_radius, BondOrder, MoleculeAtom, MoleculeBond, SmallMolecule};
+pub use sdf::{read_sdf, read_sdf_file, write_sdf, MolfileVersion};
+pub use mol2::{read_mol2, read_mol2_file, write_mol2};
+pub use pdbqt::{read_pdbqt, read_pdbqt_file, write_pdbqt};
@@ -0,0 +1,189 @@
+use std::io::{BufRead, Write};
+use bioshell_core::io::open_file;
+use bioshell_core::Vec3;
+use crate::PDBError;
+use crate::small_molecules::{BondOrder, MoleculeAtom, SmallMolecule};
+
+/// Reads all molecules from a Tripos MOL2 file
+pub fn read_mol2_file(fname: &str) -> Result<Vec<SmallMolecule>, PDBError> {
+    let reader = open_file(fname)?;
+    return read_mol2(reader);
+}
+
+/// Reads molecules stored in the Tripos MOL2 format.
+///
+/// Only the `MOLECULE`, `ATOM` and `BOND` sections are used; the element of an atom is deduced from its SYBYL type.
+/// Partial charges are stored in [`MoleculeAtom::partial_charge`](crate::small_molecules::MoleculeAtom).
+///
+/// ```
+/// # use bioshell_pdb::PDBError;
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::small_molecules::{read_mol2, BondOrder};
+/// let mol2 = "@<TRIPOS>MOLECULE
+/// methanol
+///  2 1 1 0 0
+/// SMALL
+/// USER_CHARGES
+///
+/// @<TRIPOS>ATOM
+///       1 C1          0.0000    0.0000    0.0000 C.3       1 MOH1       0.1160
+///       2 O1          1.4200    0.0000    0.0000 O.3       1 MOH1      -0.5980
+/// @<TRIPOS>BOND
+///      1     1     2    1
+/// ";
+/// let molecules = read_mol2(mol2.as_bytes())?;
+/// assert_eq!(molecules[0].name, "methanol");
+/// assert_eq!(molecules[0].atoms[1].element, "O");
+/// assert_eq!(molecules[0].atoms[1].partial_charge, Some(-0.598));
+/// assert_eq!(molecules[0].bonds[0].order, BondOrder::Single);
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_mol2<R: BufRead>(reader: R) -> Result<Vec<SmallMolecule>, PDBError> {
+    let mut molecules: Vec<SmallMolecule> = vec![];
+    let mut section = String::new();
+    let mut line_in_section = 0;
+    for line in reader.lines() {
+        let line = line?;
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix("@<TRIPOS>") {
+            section = name.to_string();
+            line_in_section = 0;
+            if section == "MOLECULE" { molecules.push(SmallMolecule::default()); }
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') { continue; }
+        let Some(mol) = molecules.last_mut() else { continue };
+        line_in_section += 1;
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+        match section.as_str() {
+            "MOLECULE" if line_in_section == 1 => mol.name = trimmed.to_string(),
+            "ATOM" => {
+                if tokens.len() < 6 { return Err(mol2_error(&format!("incorrect ATOM line: {}", line))); }
+                let parse = |s: &str| s.parse::<f64>().map_err(|_| mol2_error(&format!("can't parse a real value: {}", s)));
+                let element = tokens[5].split('.').next().unwrap_or("");
+                let mut atom = MoleculeAtom::new(tokens[1], element, Vec3::new(parse(tokens[2])?, parse(tokens[3])?, parse(tokens[4])?));
+                if tokens.len() > 8 { atom.partial_charge = Some(parse(tokens[8])?); }
+                mol.atoms.push(atom);
+            }
+            "BOND" => {
+                if tokens.len() < 4 { return Err(mol2_error(&format!("incorrect BOND line: {}", line))); }
+                let parse = |s: &str| s.parse::<usize>().map_err(|_| mol2_error(&format!("incorrect atom index in a bond line: {}", line)));
+                let (first, second) = (parse(tokens[1])?, parse(tokens[2])?);
+                if first < 1 || second < 1 || first > mol.atoms.len() || second > mol.atoms.len() {
+                    return Err(mol2_error(&format!("incorrect atom index in a bond line: {}", line)));
+                }
+                mol.add_bond(first - 1, second - 1, bond_order(tokens[3]));
+            }
+            _ => {}
+        }
+    }
+
+    return Ok(molecules);
+}
+
+/// Writes molecules in the Tripos MOL2 format.
+///
+/// SYBYL atom types are assigned based on elements and bonds of each atom. Partial charges are written
+/// when at least one atom of a molecule has them defined.
+pub fn write_mol2<W: Write>(out: &mut W, molecules: &[SmallMolecule]) -> Result<(), PDBError> {
+    for mol in molecules {
+        let has_charges = mol.atoms.iter().any(|a| a.partial_charge.is_some());
+        writeln!(out, "@<TRIPOS>MOLECULE\n{}", mol.name)?;
+        writeln!(out, "{:>5} {:>5} {:>5} {:>5} {:>5}", mol.count_atoms(), mol.count_bonds(), 1, 0, 0)?;
+        writeln!(out, "SMALL\n{}\n", if has_charges { "USER_CHARGES" } else { "NO_CHARGES" })?;
+        writeln!(out, "@<TRIPOS>ATOM")?;
+        let subst_name = if mol.name.is_empty() { "UNL".to_string() } else { mol.name.chars().take(3).collect() };
+        for (i, a) in mol.atoms.iter().enumerate() {
+            writeln!(out, "{:>7} {:<8} {:>10.4}{:>10.4}{:>10.4} {:<8} {:>3} {:<8} {:>9.4}", i + 1, a.name,
+                     a.pos.x, a.pos.y, a.pos.z, sybyl_type(mol, i), 1, subst_name, a.partial_charge.unwrap_or(0.0))?;
+        }
+        writeln!(out, "@<TRIPOS>BOND")?;
+        for (i, b) in mol.bonds.iter().enumerate() {
+            let code = match b.order {
+                BondOrder::Single => "1",
+                BondOrder::Double => "2",
+                BondOrder::Triple => "3",
+                BondOrder::Aromatic => "ar",
+                BondOrder::Unknown => "un",
+            };
+            writeln!(out, "{:>6}{:>6}{:>6} {}", i + 1, b.first + 1, b.second + 1, code)?;
+        }
+    }
+
+    return Ok(());
+}
+
+/// SYBYL type of the i-th atom of a molecule, deduced from its element and bonds
+fn sybyl_type(mol: &SmallMolecule, i: usize) -> String {
+    let atom = &mol.atoms[i];
+    let bonds: Vec<BondOrder> = mol.bonds.iter()
+        .filter(|b| b.first == i || b.second == i).map(|b| b.order).collect();
+    let count = |o: BondOrder| bonds.iter().filter(|&&b| b == o).count();
+    let neighbor_elements: Vec<&str> = mol.neighbors(i).iter().map(|&j| mol.atoms[j].element.as_str()).collect();
+    let n_oxygens = neighbor_elements.iter().filter(|&&e| e == "O").count();
+
+    let t = match atom.element.as_str() {
+        "C" => {
+            if count(BondOrder::Aromatic) > 0 { "C.ar" }
+            else if count(BondOrder::Triple) > 0 || count(BondOrder::Double) > 1 { "C.1" }
+            else if count(BondOrder::Double) > 0 { "C.2" }
+            else { "C.3" }
+        }
+        "N" => {
+            if count(BondOrder::Aromatic) > 0 { "N.ar" }
+            else if count(BondOrder::Triple) > 0 { "N.1" }
+            else if atom.formal_charge > 0 && bonds.len() == 4 { "N.4" }
+            else if count(BondOrder::Double) > 0 { "N.2" }
+            else if is_amide_nitrogen(mol, i) { "N.am" }
+            else if is_conjugated(mol, i) { "N.pl3" }
+            else { "N.3" }
+        }
+        "O" => {
+            let carboxylate = mol.neighbors(i).iter().any(|&j| mol.atoms[j].element == "C"
+                && mol.neighbors(j).iter().filter(|&&k| mol.atoms[k].element == "O" && mol.neighbors(k).len() == 1).count() == 2);
+            if bonds.len() == 1 && carboxylate { "O.co2" }
+            else if count(BondOrder::Double) > 0 { "O.2" }
+            else { "O.3" }
+        }
+        "S" => {
+            if count(BondOrder::Double) > 0 && n_oxygens == 0 { "S.2" }
+            else if n_oxygens == 1 && bonds.len() == 3 { "S.O" }
+            else if n_oxygens >= 2 && bonds.len() == 4 { "S.O2" }
+            else { "S.3" }
+        }
+        "P" => "P.3",
+        "H" => "H",
+        _ => return atom.element.clone(),
+    };
+
+    return t.to_string();
+}
+
+/// `true` if the i-th atom is a nitrogen bonded to a carbonyl carbon
+fn is_amide_nitrogen(mol: &SmallMolecule, i: usize) -> bool {
+    mol.neighbors(i).iter().any(|&j| mol.atoms[j].element == "C" && mol.bonds.iter().any(|b| {
+        b.order == BondOrder::Double && (b.first == j || b.second == j)
+            && mol.atoms[if b.first == j { b.second } else { b.first }].element == "O"
+    }))
+}
+
+/// `true` if the i-th atom is bonded to an atom involved in a double or an aromatic bond
+fn is_conjugated(mol: &SmallMolecule, i: usize) -> bool {
+    mol.neighbors(i).iter().any(|&j| mol.bonds.iter().any(|b| (b.first == j || b.second == j)
+        && (b.order == BondOrder::Double || b.order == BondOrder::Aromatic)))
+}
+
+fn bond_order(code: &str) -> BondOrder {
+    match code {
+        "1" | "am" => BondOrder::Single,
+        "2" => BondOrder::Double,
+        "3" => BondOrder::Triple,
+        "ar" => BondOrder::Aromatic,
+        _ => BondOrder::Unknown,
+    }
+}
+
+fn mol2_error(details: &str) -> PDBError {
+    PDBError::SmallMoleculeFormatError { format: "MOL2".to_string(), details: details.to_string() }
+}
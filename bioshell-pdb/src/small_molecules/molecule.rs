@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+use bioshell_core::Vec3;
+use crate::calc::NeighborGrid;
+use crate::monomers::MonomerManager;
+use crate::{atom_element, format_atom_name, PDBError, PdbAtom, ResidueId, Structure};
+
+/// Order of a covalent bond
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BondOrder {
+    /// single bond
+    Single,
+    /// double bond
+    Double,
+    /// triple bond
+    Triple,
+    /// aromatic bond
+    Aromatic,
+    /// order of the bond is not known, e.g. when the bond has been deduced from interatomic distance
+    Unknown,
+}
+
+impl BondOrder {
+    /// Converts a `_chem_comp_bond.value_order` code of an mmCIF file, e.g. `"DOUB"`, into a [`BondOrder`]
+    ///
+    /// ```
+    /// use bioshell_pdb::small_molecules::BondOrder;
+    /// assert_eq!(BondOrder::from_cif_code("SING"), BondOrder::Single);
+    /// assert_eq!(BondOrder::from_cif_code("AROM"), BondOrder::Aromatic);
+    /// ```
+    pub fn from_cif_code(code: &str) -> BondOrder {
+        match code.to_uppercase().as_str() {
+            "SING" => BondOrder::Single,
+            "DOUB" => BondOrder::Double,
+            "TRIP" => BondOrder::Triple,
+            "AROM" => BondOrder::Aromatic,
+            _ => BondOrder::Unknown,
+        }
+    }
+}
+
+/// An atom of a [`SmallMolecule`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoleculeAtom {
+    /// name of the atom, e.g. `"C1"`; atoms loaded from an SDF file are named by their element and index
+    pub name: String,
+    /// chemical element, e.g. `"C"` or `"Cl"`
+    pub element: String,
+    /// position of the atom
+    pub pos: Vec3,
+    /// formal charge
+    pub formal_charge: i32,
+    /// partial charge, if known
+    pub partial_charge: Option<f64>,
+}
+
+impl MoleculeAtom {
+    /// Creates a new neutral atom
+    pub fn new(name: &str, element: &str, pos: Vec3) -> MoleculeAtom {
+        MoleculeAtom { name: name.to_string(), element: normalize_element(element), pos, formal_charge: 0, partial_charge: None }
+    }
+
+    /// Returns `true` if this is a hydrogen atom
+    pub fn is_hydrogen(&self) -> bool { self.element == "H" || self.element == "D" }
+}
+
+/// A covalent bond of a [`SmallMolecule`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoleculeBond {
+    /// index of the first atom
+    pub first: usize,
+    /// index of the second atom
+    pub second: usize,
+    /// bond order
+    pub order: BondOrder,
+}
+
+/// A small molecule: atoms with their charges and covalent bonds with their orders.
+///
+/// See the [module documentation](crate::small_molecules) for an example.
+#[derive(Clone, Debug, Default)]
+pub struct SmallMolecule {
+    /// name of the molecule
+    pub name: String,
+    /// atoms of the molecule
+    pub atoms: Vec<MoleculeAtom>,
+    /// covalent bonds
+    pub bonds: Vec<MoleculeBond>,
+    /// properties of the molecule as key-value pairs, such as SDF data items
+    pub properties: Vec<(String, String)>,
+}
+
+impl SmallMolecule {
+
+    /// Creates an empty molecule
+    pub fn new(name: &str) -> SmallMolecule { SmallMolecule { name: name.to_string(), ..Default::default() } }
+
+    /// The number of atoms of this molecule
+    pub fn count_atoms(&self) -> usize { self.atoms.len() }
+
+    /// The number of bonds of this molecule
+    pub fn count_bonds(&self) -> usize { self.bonds.len() }
+
+    /// Sum of formal charges of all atoms
+    pub fn total_charge(&self) -> i32 { self.atoms.iter().map(|a| a.formal_charge).sum() }
+
+    /// Value of a property, if defined
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Indexes of atoms bonded to a given atom
+    pub fn neighbors(&self, atom_index: usize) -> Vec<usize> {
+        return self.bonds.iter().filter_map(|b| {
+            if b.first == atom_index { Some(b.second) } else if b.second == atom_index { Some(b.first) } else { None }
+        }).collect();
+    }
+
+    /// Adds a bond
+    pub fn add_bond(&mut self, first: usize, second: usize, order: BondOrder) {
+        self.bonds.push(MoleculeBond { first, second, order });
+    }
+
+    /// Returns `true` if the i-th bond belongs to a ring
+    pub fn is_ring_bond(&self, bond_index: usize) -> bool {
+        let bond = &self.bonds[bond_index];
+        // --- a bond is a ring bond if its atoms remain connected when the bond is removed
+        let mut visited = vec![false; self.atoms.len()];
+        let mut queue = VecDeque::from([bond.first]);
+        visited[bond.first] = true;
+        while let Some(i) = queue.pop_front() {
+            for (k, b) in self.bonds.iter().enumerate() {
+                if k == bond_index { continue; }
+                let j = if b.first == i { b.second } else if b.second == i { b.first } else { continue };
+                if j == bond.second { return true; }
+                if !visited[j] {
+                    visited[j] = true;
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        return false;
+    }
+
+    /// Creates bonds between atoms based on their distances and covalent radii.
+    ///
+    /// All bonds defined previously are removed; orders of the new bonds are [`BondOrder::Unknown`].
+    pub fn infer_bonds(&mut self) {
+        self.bonds.clear();
+        let positions: Vec<Vec3> = self.atoms.iter().map(|a| a.pos).collect();
+        let grid = NeighborGrid::new(&positions, 3.0);
+        for (i, ai) in self.atoms.iter().enumerate() {
+            let ri = covalent_radius(&ai.element);
+            let mut neighbors = grid.neighbors(&ai.pos, 2.8);
+            neighbors.sort();
+            for j in neighbors.into_iter().filter(|&j| j > i) {
+                let aj = &self.atoms[j];
+                if ai.is_hydrogen() && aj.is_hydrogen() { continue; }
+                let d = ai.pos.distance_to(&aj.pos);
+                if d > 0.4 && d < ri + covalent_radius(&aj.element) + 0.45 {
+                    self.bonds.push(MoleculeBond { first: i, second: j, order: BondOrder::Unknown });
+                }
+            }
+        }
+    }
+
+    /// Creates a molecule from atoms of a single residue of a structure.
+    ///
+    /// Bonds and their orders are taken from the respective monomer definition provided by the [`MonomerManager`];
+    /// when the residue type is not known, bonds are deduced from interatomic distances.
+    ///
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError, ResidueId};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_pdb::small_molecules::{BondOrder, SmallMolecule};
+    /// let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    /// let phe = SmallMolecule::from_residue(&strctr, &ResidueId::new("A", 30, ' '))?;
+    /// assert_eq!(phe.name, "PHE");
+    /// assert_eq!(phe.bonds.iter().filter(|b| b.order == BondOrder::Double).count(), 4);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_residue(strctr: &Structure, res_id: &ResidueId) -> Result<SmallMolecule, PDBError> {
+        let atoms: Vec<PdbAtom> = strctr.atoms_in_residue(res_id)?.cloned().collect();
+        return Ok(SmallMolecule::from_atoms(&atoms));
+    }
+
+    /// Creates a molecule from a given set of atoms, named after the residue of the first atom
+    ///
+    /// See [`SmallMolecule::from_residue()`] for details.
+    pub fn from_atoms(atoms: &[PdbAtom]) -> SmallMolecule {
+        let mut mol = SmallMolecule::new(atoms.first().map_or("", |a| a.res_name.as_str()));
+        for a in atoms {
+            let name = a.name.trim();
+            // --- only the first alternate location of an atom is used
+            if mol.atoms.iter().any(|m| m.name == name) { continue; }
+            let mut atom = MoleculeAtom::new(name, &atom_element(a), a.pos);
+            atom.formal_charge = a.charge.as_deref().map_or(0, parse_pdb_charge);
+            mol.atoms.push(atom);
+        }
+
+        let mgr = MonomerManager::get();
+        match mgr.by_code3(&mol.name) {
+            Some(monomer) => {
+                let template = monomer.atoms();
+                for b in monomer.bonds() {
+                    let first = mol.atoms.iter().position(|a| a.name == template[b.first].atom_name);
+                    let second = mol.atoms.iter().position(|a| a.name == template[b.second].atom_name);
+                    if let (Some(first), Some(second)) = (first, second) {
+                        mol.add_bond(first, second, BondOrder::from_cif_code(&b.order));
+                    }
+                }
+            }
+            None => mol.infer_bonds(),
+        }
+
+        return mol;
+    }
+
+    /// Converts this molecule into `HETATM` atoms of a single residue
+    pub fn to_atoms(&self, res_name: &str, chain_id: &str, res_seq: i32) -> Vec<PdbAtom> {
+        let mut out: Vec<PdbAtom> = vec![];
+        for (i, a) in self.atoms.iter().enumerate() {
+            let mut atom = PdbAtom::new();
+            let element = a.element.to_uppercase();
+            atom.serial = i as i32 + 1;
+            atom.name = format_atom_name(&a.name, Some(&element));
+            atom.res_name = res_name.to_string();
+            atom.chain_id = chain_id.to_string();
+            atom.entity_id = chain_id.to_string();
+            atom.res_seq = res_seq;
+            atom.pos = a.pos;
+            atom.temp_factor = 0.0;
+            atom.charge = if a.formal_charge == 0 { None }
+                else { Some(format!("{}{}", a.formal_charge.abs(), if a.formal_charge > 0 { '+' } else { '-' })) };
+            atom.element = Some(element);
+            atom.is_hetero_atom = true;
+            out.push(atom);
+        }
+
+        return out;
+    }
+
+    /// Creates a [`Structure`] holding this molecule as a single residue of chain `A`
+    pub fn to_structure(&self, res_name: &str) -> Structure {
+        return Structure::from_iterator(&self.name, self.to_atoms(res_name, "A", 1));
+    }
+}
+
+/// Covalent radius of an element, in Angstroms.
+///
+/// ```
+/// use bioshell_pdb::small_molecules::covalent_radius;
+/// assert_eq!(covalent_radius("C"), 0.76);
+/// assert_eq!(covalent_radius("Cl"), 1.02);
+/// ```
+pub fn covalent_radius(element: &str) -> f64 {
+    match element.trim().to_ascii_uppercase().as_str() {
+        "H" | "D" => 0.31,
+        "B" => 0.84,
+        "C" => 0.76,
+        "N" => 0.71,
+        "O" => 0.66,
+        "F" => 0.57,
+        "SI" => 1.11,
+        "P" => 1.07,
+        "S" => 1.05,
+        "CL" => 1.02,
+        "SE" => 1.20,
+        "BR" => 1.20,
+        "I" => 1.39,
+        _ => 1.4,
+    }
+}
+
+/// Converts an element symbol into the standard form, e.g. `"CL"` into `"Cl"`
+pub(crate) fn normalize_element(element: &str) -> String {
+    let e = element.trim();
+    let mut chars = e.chars();
+    return match chars.next() {
+        Some(c) => c.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+        None => String::new(),
+    };
+}
+
+/// Parses a charge given in the PDB format, e.g. `"1+"` or `"2-"`
+fn parse_pdb_charge(charge: &str) -> i32 {
+    let c = charge.trim();
+    let value: i32 = c.trim_matches(|ch| ch == '+' || ch == '-').parse().unwrap_or(if c.is_empty() { 0 } else { 1 });
+    return if c.contains('-') { -value } else { value };
+}
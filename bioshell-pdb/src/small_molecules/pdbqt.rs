@@ -0,0 +1,271 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+use bioshell_core::io::open_file;
+use bioshell_core::Vec3;
+use crate::PDBError;
+use crate::small_molecules::{BondOrder, MoleculeAtom, SmallMolecule};
+
+/// Reads all molecules from an AutoDock PDBQT file
+pub fn read_pdbqt_file(fname: &str) -> Result<Vec<SmallMolecule>, PDBError> {
+    let reader = open_file(fname)?;
+    return read_pdbqt(reader);
+}
+
+/// Reads molecules stored in the AutoDock PDBQT format.
+///
+/// Every `MODEL` of the input, e.g. a docking pose produced by AutoDock Vina, becomes a separate molecule;
+/// the `REMARK VINA RESULT` line of a pose is stored as its `"vina_result"` property. Elements are deduced
+/// from AutoDock atom types and partial charges are stored in [`MoleculeAtom::partial_charge`](crate::small_molecules::MoleculeAtom).
+/// Since PDBQT doesn't store bonds, they are deduced from interatomic distances; bonds between two aromatic
+/// carbons (AutoDock type `A`) are marked as aromatic, all the other bonds are of unknown order.
+///
+/// ```
+/// # use bioshell_pdb::PDBError;
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::small_molecules::read_pdbqt;
+/// let pdbqt = "REMARK  Name = methanol
+/// ROOT
+/// ATOM      1  C1  UNL     1       0.000   0.000   0.000  0.00  0.00     0.116 C
+/// ATOM      2  O1  UNL     1       1.420   0.000   0.000  0.00  0.00    -0.398 OA
+/// ENDROOT
+/// TORSDOF 0
+/// ";
+/// let molecules = read_pdbqt(pdbqt.as_bytes())?;
+/// assert_eq!(molecules[0].name, "methanol");
+/// assert_eq!(molecules[0].atoms[1].element, "O");
+/// assert_eq!(molecules[0].count_bonds(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_pdbqt<R: BufRead>(reader: R) -> Result<Vec<SmallMolecule>, PDBError> {
+    let mut molecules: Vec<SmallMolecule> = vec![];
+    let mut mol = SmallMolecule::default();
+    let mut ad_types: Vec<String> = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.starts_with("ATOM") || line.starts_with("HETATM") {
+            if line.len() < 78 { return Err(pdbqt_error(&format!("line is too short: {}", line))); }
+            let parse = |from: usize, to: usize| line[from..to.min(line.len())].trim().parse::<f64>()
+                .map_err(|_| pdbqt_error(&format!("can't parse columns {}-{} of the line: {}", from + 1, to, line)));
+            let ad_type = line[77..line.len().min(79)].trim().to_string();
+            let mut atom = MoleculeAtom::new(line[12..16].trim(), element_for_type(&ad_type),
+                                             Vec3::new(parse(30, 38)?, parse(38, 46)?, parse(46, 54)?));
+            atom.partial_charge = Some(parse(70, 76)?);
+            if mol.name.is_empty() { mol.name = line[17..20].trim().to_string(); }
+            mol.atoms.push(atom);
+            ad_types.push(ad_type);
+        } else if let Some(name) = line.strip_prefix("REMARK  Name =") {
+            mol.name = name.trim().to_string();
+        } else if let Some(result) = line.strip_prefix("REMARK VINA RESULT:") {
+            mol.properties.push(("vina_result".to_string(), result.trim().to_string()));
+        } else if line.starts_with("ENDMDL") {
+            molecules.push(finish_molecule(std::mem::take(&mut mol), &ad_types));
+            ad_types.clear();
+        }
+    }
+    if !mol.atoms.is_empty() { molecules.push(finish_molecule(mol, &ad_types)); }
+
+    return Ok(molecules);
+}
+
+/// Writes molecules in the AutoDock PDBQT format.
+///
+/// Non-polar hydrogens are merged with the carbon atoms they are bonded to, which inherit their partial charges.
+/// A bond is considered rotatable when it's a single (or unknown order), non-ring, non-amide bond between two atoms
+/// that have at least one other neighbor each. The largest rigid fragment becomes the `ROOT` of the torsion tree.
+/// When more than one molecule is given, each of them is written as a separate `MODEL`.
+pub fn write_pdbqt<W: Write>(out: &mut W, molecules: &[SmallMolecule]) -> Result<(), PDBError> {
+    for (m, mol) in molecules.iter().enumerate() {
+        if molecules.len() > 1 { writeln!(out, "MODEL {:>8}", m + 1)?; }
+        TorsionTreeWriter::new(mol).write(out)?;
+        if molecules.len() > 1 { writeln!(out, "ENDMDL")?; }
+    }
+
+    return Ok(());
+}
+
+/// Writes a single molecule as a torsion tree
+struct TorsionTreeWriter<'a> {
+    mol: &'a SmallMolecule,
+    /// atoms that are written, i.e. all but non-polar hydrogens
+    kept: Vec<bool>,
+    /// partial charges, including charges of the merged hydrogens
+    charges: Vec<f64>,
+    /// index of the rigid fragment each atom belongs to
+    fragment: Vec<usize>,
+    rotatable: Vec<bool>,
+    /// serial number assigned to an atom when it's written
+    serials: Vec<usize>,
+    n_written: usize,
+}
+
+impl<'a> TorsionTreeWriter<'a> {
+
+    fn new(mol: &'a SmallMolecule) -> TorsionTreeWriter<'a> {
+        let n = mol.count_atoms();
+        let mut kept = vec![true; n];
+        let mut charges: Vec<f64> = mol.atoms.iter().map(|a| a.partial_charge.unwrap_or(0.0)).collect();
+        for i in 0..n {
+            if !mol.atoms[i].is_hydrogen() { continue; }
+            if let Some(&c) = mol.neighbors(i).iter().find(|&&j| mol.atoms[j].element == "C") {
+                kept[i] = false;
+                charges[c] += charges[i];
+            }
+        }
+        let heavy_degree = |i: usize| mol.neighbors(i).iter().filter(|&&j| kept[j]).count();
+        let rotatable: Vec<bool> = (0..mol.count_bonds()).map(|k| {
+            let b = &mol.bonds[k];
+            kept[b.first] && kept[b.second]
+                && (b.order == BondOrder::Single || b.order == BondOrder::Unknown)
+                && heavy_degree(b.first) > 1 && heavy_degree(b.second) > 1
+                && !is_amide_bond(mol, b.first, b.second)
+                && !mol.is_ring_bond(k)
+        }).collect();
+
+        // --- rigid fragments: atoms connected by non-rotatable bonds
+        let mut fragment = vec![usize::MAX; n];
+        let mut n_fragments = 0;
+        for start in (0..n).filter(|&i| kept[i]) {
+            if fragment[start] != usize::MAX { continue; }
+            let mut queue = VecDeque::from([start]);
+            fragment[start] = n_fragments;
+            while let Some(i) = queue.pop_front() {
+                for (k, b) in mol.bonds.iter().enumerate() {
+                    if rotatable[k] { continue; }
+                    let j = if b.first == i { b.second } else if b.second == i { b.first } else { continue };
+                    if kept[j] && fragment[j] == usize::MAX {
+                        fragment[j] = n_fragments;
+                        queue.push_back(j);
+                    }
+                }
+            }
+            n_fragments += 1;
+        }
+
+        return TorsionTreeWriter { mol, kept, charges, fragment, rotatable, serials: vec![0; n], n_written: 0 };
+    }
+
+    fn write<W: Write>(&mut self, out: &mut W) -> Result<(), PDBError> {
+        let n_torsions = self.rotatable.iter().filter(|&&r| r).count();
+        writeln!(out, "REMARK  Name = {}", self.mol.name)?;
+        writeln!(out, "REMARK  {} active torsions", n_torsions)?;
+        let root = self.largest_fragment();
+        writeln!(out, "ROOT")?;
+        let mut visited = vec![false; self.mol.count_atoms()];
+        self.write_fragment(out, root, None, &mut visited)?;
+        writeln!(out, "ENDROOT")?;
+        self.write_branches(out, root, &mut visited)?;
+        writeln!(out, "TORSDOF {}", n_torsions)?;
+
+        return Ok(());
+    }
+
+    /// Writes atoms of a fragment; the atom `first`, if given, is written before all the others
+    fn write_fragment<W: Write>(&mut self, out: &mut W, fragment: usize, first: Option<usize>, visited: &mut [bool]) -> Result<(), PDBError> {
+        let mut order: Vec<usize> = first.into_iter().collect();
+        order.extend((0..self.mol.count_atoms()).filter(|&i| self.kept[i] && self.fragment[i] == fragment && Some(i) != first));
+        for i in order {
+            visited[i] = true;
+            self.n_written += 1;
+            self.serials[i] = self.n_written;
+            self.write_atom(out, i)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Recursively writes all the fragments attached to a given one by rotatable bonds
+    fn write_branches<W: Write>(&mut self, out: &mut W, fragment: usize, visited: &mut [bool]) -> Result<(), PDBError> {
+        for k in 0..self.mol.count_bonds() {
+            if !self.rotatable[k] { continue; }
+            let b = &self.mol.bonds[k];
+            let (parent, child) = if self.fragment[b.first] == fragment && !visited[b.second] { (b.first, b.second) }
+                else if self.fragment[b.second] == fragment && !visited[b.first] { (b.second, b.first) }
+                else { continue };
+            let child_fragment = self.fragment[child];
+            writeln!(out, "BRANCH {:>3} {:>3}", self.serials[parent], self.n_written + 1)?;
+            self.write_fragment(out, child_fragment, Some(child), visited)?;
+            self.write_branches(out, child_fragment, visited)?;
+            writeln!(out, "ENDBRANCH {:>3} {:>3}", self.serials[parent], self.serials[child])?;
+        }
+
+        return Ok(());
+    }
+
+    fn write_atom<W: Write>(&self, out: &mut W, i: usize) -> Result<(), PDBError> {
+        let a = &self.mol.atoms[i];
+        let name = if a.name.len() < 4 { format!(" {}", a.name) } else { a.name.clone() };
+        let res_name: String = if self.mol.name.is_empty() { "UNL".to_string() } else { self.mol.name.chars().take(3).collect() };
+        writeln!(out, "ATOM  {:>5} {:<4} {:>3}     1    {:>8.3}{:>8.3}{:>8.3}  0.00  0.00    {:>6.3} {:<2}",
+                 self.serials[i], name, res_name, a.pos.x, a.pos.y, a.pos.z, self.charges[i], self.autodock_type(i))?;
+
+        return Ok(());
+    }
+
+    /// AutoDock type of the i-th atom
+    fn autodock_type(&self, i: usize) -> String {
+        let mol = self.mol;
+        let neighbors = mol.neighbors(i);
+        let t = match mol.atoms[i].element.as_str() {
+            "C" => if mol.bonds.iter().any(|b| (b.first == i || b.second == i) && b.order == BondOrder::Aromatic) { "A" } else { "C" },
+            "N" => {
+                // --- a nitrogen with a lone pair available is an acceptor
+                let has_h = neighbors.iter().any(|&j| mol.atoms[j].is_hydrogen());
+                if !has_h && neighbors.len() < 3 && mol.atoms[i].formal_charge <= 0 && !is_amide_nitrogen(mol, i) { "NA" } else { "N" }
+            }
+            "O" => "OA",
+            "S" => "SA",
+            "H" | "D" => "HD",
+            _ => return mol.atoms[i].element.clone(),
+        };
+
+        return t.to_string();
+    }
+
+    fn largest_fragment(&self) -> usize {
+        let n_fragments = self.fragment.iter().filter(|&&f| f != usize::MAX).max().map_or(0, |&f| f + 1);
+        let mut sizes = vec![0; n_fragments];
+        for &f in self.fragment.iter().filter(|&&f| f != usize::MAX) { sizes[f] += 1; }
+        return (0..n_fragments).max_by_key(|&f| (sizes[f], usize::MAX - f)).unwrap_or(0);
+    }
+}
+
+/// Deduces bonds of a molecule read from a PDBQT file
+fn finish_molecule(mut mol: SmallMolecule, ad_types: &[String]) -> SmallMolecule {
+    mol.infer_bonds();
+    for b in mol.bonds.iter_mut() {
+        if ad_types[b.first] == "A" && ad_types[b.second] == "A" { b.order = BondOrder::Aromatic; }
+    }
+
+    return mol;
+}
+
+/// `true` if the bond between atoms `i` and `j` is a C-N bond of an amide group
+fn is_amide_bond(mol: &SmallMolecule, i: usize, j: usize) -> bool {
+    let (ei, ej) = (mol.atoms[i].element.as_str(), mol.atoms[j].element.as_str());
+    return (ei == "N" && ej == "C" && is_carbonyl_carbon(mol, j)) || (ei == "C" && ej == "N" && is_carbonyl_carbon(mol, i));
+}
+
+fn is_amide_nitrogen(mol: &SmallMolecule, i: usize) -> bool {
+    mol.neighbors(i).iter().any(|&j| mol.atoms[j].element == "C" && is_carbonyl_carbon(mol, j))
+}
+
+fn is_carbonyl_carbon(mol: &SmallMolecule, i: usize) -> bool {
+    mol.bonds.iter().any(|b| b.order == BondOrder::Double && (b.first == i || b.second == i)
+        && mol.atoms[if b.first == i { b.second } else { b.first }].element == "O")
+}
+
+fn element_for_type(ad_type: &str) -> &str {
+    match ad_type {
+        "A" => "C",
+        "HD" | "HS" => "H",
+        "NA" | "NS" => "N",
+        "OA" | "OS" => "O",
+        "SA" => "S",
+        t => t,
+    }
+}
+
+fn pdbqt_error(details: &str) -> PDBError {
+    PDBError::SmallMoleculeFormatError { format: "PDBQT".to_string(), details: details.to_string() }
+}
@@ -0,0 +1,284 @@
+use std::io::{BufRead, Write};
+use bioshell_core::io::open_file;
+use bioshell_core::Vec3;
+use crate::PDBError;
+use crate::small_molecules::{BondOrder, MoleculeAtom, SmallMolecule};
+
+/// Version of the MDL molfile connection table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MolfileVersion {
+    /// the classic fixed-column format, limited to 999 atoms and bonds
+    V2000,
+    /// the extended, free-format version
+    V3000,
+}
+
+/// Reads all molecules from an SDF file; a single MOL file is also accepted
+pub fn read_sdf_file(fname: &str) -> Result<Vec<SmallMolecule>, PDBError> {
+    let reader = open_file(fname)?;
+    return read_sdf(reader);
+}
+
+/// Reads molecules stored in the SDF format.
+///
+/// Both V2000 and V3000 connection tables are supported, as well as the data items that follow them.
+/// Since SDF atoms are not named, every atom is named by its element followed by its index, e.g. `C1`.
+///
+/// ```
+/// # use bioshell_pdb::PDBError;
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::small_molecules::{read_sdf, BondOrder};
+/// let sdf = "acetate
+///   bioshell
+///
+///   4  3  0  0  0  0  0  0  0  0999 V2000
+///     0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+///     1.5000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+///     2.1200    1.0700    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+///     2.1200   -1.0700    0.0000 O   0  5  0  0  0  0  0  0  0  0  0  0
+///   1  2  1  0
+///   2  3  2  0
+///   2  4  1  0
+/// M  CHG  1   4  -1
+/// M  END
+/// > <source>
+/// example
+///
+/// $$$$
+/// ";
+/// let molecules = read_sdf(sdf.as_bytes())?;
+/// assert_eq!(molecules[0].atoms[3].name, "O4");
+/// assert_eq!(molecules[0].total_charge(), -1);
+/// assert_eq!(molecules[0].bonds[1].order, BondOrder::Double);
+/// assert_eq!(molecules[0].property("source"), Some("example"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_sdf<R: BufRead>(reader: R) -> Result<Vec<SmallMolecule>, PDBError> {
+    let mut molecules: Vec<SmallMolecule> = vec![];
+    let mut record: Vec<String> = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.starts_with("$$$$") {
+            molecules.push(parse_record(&record)?);
+            record.clear();
+        } else { record.push(line.trim_end().to_string()); }
+    }
+    if record.iter().any(|l| !l.is_empty()) { molecules.push(parse_record(&record)?); }
+
+    return Ok(molecules);
+}
+
+/// Writes molecules in the SDF format.
+///
+/// The V2000 format can't store more than 999 atoms or bonds; [`MolfileVersion::V3000`] must be used for larger molecules.
+/// Properties of a molecule are written as SDF data items.
+pub fn write_sdf<W: Write>(out: &mut W, molecules: &[SmallMolecule], version: MolfileVersion) -> Result<(), PDBError> {
+    for mol in molecules {
+        writeln!(out, "{}\n  bioshell      3D\n", mol.name)?;
+        match version {
+            MolfileVersion::V2000 => write_v2000(out, mol)?,
+            MolfileVersion::V3000 => write_v3000(out, mol)?,
+        }
+        writeln!(out, "M  END")?;
+        for (key, value) in &mol.properties { writeln!(out, "> <{}>\n{}\n", key, value)?; }
+        writeln!(out, "$$$$")?;
+    }
+
+    return Ok(());
+}
+
+fn write_v2000<W: Write>(out: &mut W, mol: &SmallMolecule) -> Result<(), PDBError> {
+    if mol.count_atoms() > 999 || mol.count_bonds() > 999 {
+        return Err(sdf_error(&format!("molecule {} is too large for the V2000 format", mol.name)));
+    }
+    writeln!(out, "{:>3}{:>3}  0  0  0  0  0  0  0  0999 V2000", mol.count_atoms(), mol.count_bonds())?;
+    for a in &mol.atoms {
+        let charge_code = match a.formal_charge { -3..=-1 => 4 - a.formal_charge, 1..=3 => 4 - a.formal_charge, _ => 0 };
+        writeln!(out, "{:>10.4}{:>10.4}{:>10.4} {:<3} 0{:>3}  0  0  0  0  0  0  0  0  0  0",
+                 a.pos.x, a.pos.y, a.pos.z, a.element, charge_code)?;
+    }
+    for b in &mol.bonds { writeln!(out, "{:>3}{:>3}{:>3}  0", b.first + 1, b.second + 1, bond_code(b.order))?; }
+    let charged: Vec<(usize, i32)> = mol.atoms.iter().enumerate()
+        .filter(|(_, a)| a.formal_charge != 0).map(|(i, a)| (i + 1, a.formal_charge)).collect();
+    for chunk in charged.chunks(8) {
+        write!(out, "M  CHG{:>3}", chunk.len())?;
+        for (i, c) in chunk { write!(out, " {:>3} {:>3}", i, c)?; }
+        writeln!(out)?;
+    }
+
+    return Ok(());
+}
+
+fn write_v3000<W: Write>(out: &mut W, mol: &SmallMolecule) -> Result<(), PDBError> {
+    writeln!(out, "  0  0  0     0  0            999 V3000")?;
+    writeln!(out, "M  V30 BEGIN CTAB")?;
+    writeln!(out, "M  V30 COUNTS {} {} 0 0 0", mol.count_atoms(), mol.count_bonds())?;
+    writeln!(out, "M  V30 BEGIN ATOM")?;
+    for (i, a) in mol.atoms.iter().enumerate() {
+        write!(out, "M  V30 {} {} {:.4} {:.4} {:.4} 0", i + 1, a.element, a.pos.x, a.pos.y, a.pos.z)?;
+        if a.formal_charge != 0 { write!(out, " CHG={}", a.formal_charge)?; }
+        writeln!(out)?;
+    }
+    writeln!(out, "M  V30 END ATOM")?;
+    writeln!(out, "M  V30 BEGIN BOND")?;
+    for (i, b) in mol.bonds.iter().enumerate() {
+        writeln!(out, "M  V30 {} {} {} {}", i + 1, bond_code(b.order), b.first + 1, b.second + 1)?;
+    }
+    writeln!(out, "M  V30 END BOND")?;
+    writeln!(out, "M  V30 END CTAB")?;
+
+    return Ok(());
+}
+
+/// Parses a single record of an SDF file: a molfile followed by data items
+fn parse_record(lines: &[String]) -> Result<SmallMolecule, PDBError> {
+    if lines.len() < 4 { return Err(sdf_error("a molfile must start with a three-line header and a counts line")); }
+    let mut mol = SmallMolecule::new(lines[0].trim());
+    let end = if lines[3].contains("V3000") { parse_v3000(&lines[4..], &mut mol)? }
+        else { parse_v2000(&lines[3..], &mut mol)? } + 4;
+
+    // --- data items: "> <key>" followed by a value that ends with an empty line
+    let mut i = end;
+    while i < lines.len() {
+        if lines[i].starts_with('>') {
+            let key = lines[i].split('<').nth(1).and_then(|s| s.split('>').next()).unwrap_or("").to_string();
+            let mut value: Vec<&str> = vec![];
+            i += 1;
+            while i < lines.len() && !lines[i].is_empty() {
+                value.push(&lines[i]);
+                i += 1;
+            }
+            mol.properties.push((key, value.join("\n")));
+        }
+        i += 1;
+    }
+
+    return Ok(mol);
+}
+
+/// Parses a V2000 connection table, starting from the counts line; returns the number of lines consumed
+fn parse_v2000(lines: &[String], mol: &mut SmallMolecule) -> Result<usize, PDBError> {
+    let n_atoms = parse_field::<usize>(&lines[0], 0, 3)?;
+    let n_bonds = parse_field::<usize>(&lines[0], 3, 6)?;
+    if lines.len() < 1 + n_atoms + n_bonds { return Err(sdf_error("connection table is truncated")); }
+    for line in &lines[1..=n_atoms] {
+        let element = field(line, 31, 34);
+        let pos = Vec3::new(parse_field(line, 0, 10)?, parse_field(line, 10, 20)?, parse_field(line, 20, 30)?);
+        let mut atom = MoleculeAtom::new(&format!("{}{}", element, mol.atoms.len() + 1), element, pos);
+        atom.formal_charge = match field(line, 36, 39).parse::<i32>().unwrap_or(0) {
+            c @ 1..=3 => 4 - c,
+            c @ 5..=7 => 4 - c,
+            _ => 0,
+        };
+        mol.atoms.push(atom);
+    }
+    for line in &lines[1 + n_atoms..1 + n_atoms + n_bonds] {
+        let first = parse_field::<usize>(line, 0, 3)?;
+        let second = parse_field::<usize>(line, 3, 6)?;
+        if first < 1 || second < 1 || first > n_atoms || second > n_atoms {
+            return Err(sdf_error(&format!("incorrect atom index in a bond line: {}", line)));
+        }
+        mol.add_bond(first - 1, second - 1, bond_order(parse_field(line, 6, 9)?));
+    }
+    let mut i = 1 + n_atoms + n_bonds;
+    let mut charges_reset = false;
+    while i < lines.len() && !lines[i].starts_with("M  END") {
+        if lines[i].starts_with("M  CHG") {
+            // --- charges given by "M  CHG" lines supersede those of the atom block
+            if !charges_reset {
+                for a in mol.atoms.iter_mut() { a.formal_charge = 0; }
+                charges_reset = true;
+            }
+            let tokens: Vec<&str> = lines[i].split_whitespace().skip(3).collect();
+            for pair in tokens.chunks(2) {
+                if let [idx, chg] = pair {
+                    let idx = idx.parse::<usize>().map_err(|_| sdf_error(&lines[i]))?;
+                    let atom = mol.atoms.get_mut(idx.wrapping_sub(1)).ok_or(sdf_error(&lines[i]))?;
+                    atom.formal_charge = chg.parse().map_err(|_| sdf_error(&lines[i]))?;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    return Ok(i);
+}
+
+/// Parses a V3000 connection table, starting from the line that follows the counts line;
+/// returns the number of lines consumed
+fn parse_v3000(lines: &[String], mol: &mut SmallMolecule) -> Result<usize, PDBError> {
+    let mut block = "";
+    let mut i = 0;
+    let mut pending = String::new();
+    while i < lines.len() && !lines[i].starts_with("M  END") {
+        let line = &lines[i];
+        i += 1;
+        let Some(content) = line.strip_prefix("M  V30 ") else { continue };
+        // --- a line that ends with a dash is continued in the next one
+        if let Some(head) = content.strip_suffix('-') {
+            pending.push_str(head);
+            continue;
+        }
+        pending.push_str(content);
+        let tokens: Vec<&str> = pending.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["BEGIN", name, ..] => block = if *name == "ATOM" { "ATOM" } else if *name == "BOND" { "BOND" } else { "" },
+            ["END", ..] => block = "",
+            [idx, element, x, y, z, _, options @ ..] if block == "ATOM" => {
+                let parse = |s: &str| s.parse::<f64>().map_err(|_| sdf_error(&pending));
+                let mut atom = MoleculeAtom::new(&format!("{}{}", element, idx), element, Vec3::new(parse(x)?, parse(y)?, parse(z)?));
+                for opt in options {
+                    if let Some(chg) = opt.strip_prefix("CHG=") { atom.formal_charge = chg.parse().map_err(|_| sdf_error(&pending))?; }
+                }
+                mol.atoms.push(atom);
+            }
+            [_, order, first, second, ..] if block == "BOND" => {
+                let parse = |s: &str| s.parse::<usize>().map_err(|_| sdf_error(&pending));
+                let (first, second) = (parse(first)?, parse(second)?);
+                if first < 1 || second < 1 || first > mol.atoms.len() || second > mol.atoms.len() {
+                    return Err(sdf_error(&format!("incorrect atom index in a bond line: {}", pending)));
+                }
+                mol.add_bond(first - 1, second - 1, bond_order(order.parse().map_err(|_| sdf_error(&pending))?));
+            }
+            _ => {}
+        }
+        pending.clear();
+    }
+
+    return Ok(i);
+}
+
+fn bond_order(code: u32) -> BondOrder {
+    match code {
+        1 => BondOrder::Single,
+        2 => BondOrder::Double,
+        3 => BondOrder::Triple,
+        4 => BondOrder::Aromatic,
+        _ => BondOrder::Unknown,
+    }
+}
+
+fn bond_code(order: BondOrder) -> u32 {
+    match order {
+        BondOrder::Single => 1,
+        BondOrder::Double => 2,
+        BondOrder::Triple => 3,
+        BondOrder::Aromatic => 4,
+        BondOrder::Unknown => 8,
+    }
+}
+
+/// Trimmed substring of a fixed-column line; empty if the line is too short
+fn field(line: &str, from: usize, to: usize) -> &str {
+    if line.len() <= from { return ""; }
+    return line[from..to.min(line.len())].trim();
+}
+
+fn parse_field<T: std::str::FromStr>(line: &str, from: usize, to: usize) -> Result<T, PDBError> {
+    return field(line, from, to).parse::<T>().map_err(|_| sdf_error(&format!("can't parse columns {}-{} of the line: {}", from + 1, to, line)));
+}
+
+fn sdf_error(details: &str) -> PDBError {
+    PDBError::SmallMoleculeFormatError { format: "SDF".to_string(), details: details.to_string() }
+}
@@ -0,0 +1,287 @@
+//! Sequence-independent structural alignment of two protein chains.
+//!
+//! The [`StructuralAligner`] follows the [TM-align](https://doi.org/10.1093/nar/gki524) approach: residues are
+//! represented by their alpha carbons, the query is superimposed on the template and then both chains are aligned
+//! by dynamic programming, where the score of aligning two residues depends on the distance between their
+//! superimposed alpha carbons:
+//!
+//! ```math
+//! s(i,j) = \frac{1}{1 + (d_{ij}/d_0)^2}
+//! ```
+//!
+//! Such an alignment defines new pairs of residues, that are used to find a better superposition.
+//! The superposition and alignment steps are repeated until the alignment doesn't change.
+//! The procedure starts from a few initial alignments: the best gapless threading of the query along the template
+//! and an alignment of the secondary structure of both chains, assigned from their CA traces.
+//! Dynamic programming is done by the [`GlobalAligner`] from the `bioshell-seq` crate, which is provided
+//! with a structural [`SimilarityScore`].
+//!
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::structural_alignment::StructuralAligner;
+//! let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+//! let aligner = StructuralAligner::default();
+//! let alignment = aligner.align_structures(&strctr, &strctr);
+//! assert!((alignment.tm_score - 1.0).abs() < 1e-6);
+//! assert_eq!(alignment.path.to_string(), "*".repeat(56));
+//! # Ok(())
+//! # }
+//! ```
+
+use bioshell_core::Vec3;
+use bioshell_seq::alignment::{AlignmentPath, AlignmentStep, GlobalAligner};
+use bioshell_seq::scoring::SimilarityScore;
+use crate::{ResidueId, Structure};
+use crate::calc::{superposition_search, superimpose, tm_d0, Rototranslation, TMScore};
+
+/// Scores of [`StructuralSimilarityScore`] are multiplied by this factor and rounded to integers
+pub const STRUCTURAL_SCORE_SCALE: f64 = 100.0;
+
+/// Scores alignment of two CA traces that have been already superimposed.
+///
+/// The score of aligning the i-th query residue with the j-th template residue is
+/// `1 / (1 + (d_ij/d0)^2)`, multiplied by [`STRUCTURAL_SCORE_SCALE`].
+pub struct StructuralSimilarityScore {
+    query: Vec<Vec3>,
+    template: Vec<Vec3>,
+    d0: f64,
+}
+
+impl StructuralSimilarityScore {
+    /// Creates a new scoring object; the `query` coordinates should be already superimposed onto the `template`
+    pub fn new(query: Vec<Vec3>, template: Vec<Vec3>, d0: f64) -> StructuralSimilarityScore {
+        StructuralSimilarityScore { query, template, d0 }
+    }
+}
+
+impl SimilarityScore for StructuralSimilarityScore {
+    fn score(&self, i_pos: usize, j_pos: usize) -> i32 {
+        let d2 = self.query[i_pos].distance_square_to(&self.template[j_pos]) / (self.d0 * self.d0);
+        return (STRUCTURAL_SCORE_SCALE / (1.0 + d2)).round() as i32;
+    }
+
+    fn template_length(&self) -> usize { self.template.len() }
+
+    fn query_length(&self) -> usize { self.query.len() }
+
+    /// Two positions are considered identical when their alpha carbons are closer than `d0`
+    fn is_identity(&self, i: usize, j: usize) -> bool {
+        self.query[i].distance_square_to(&self.template[j]) < self.d0 * self.d0
+    }
+}
+
+/// Scores alignment of secondary structure strings; used to find one of the initial alignments
+struct SecondaryScore {
+    query: Vec<u8>,
+    template: Vec<u8>,
+}
+
+impl SimilarityScore for SecondaryScore {
+    fn score(&self, i_pos: usize, j_pos: usize) -> i32 { if self.is_identity(i_pos, j_pos) { 1 } else { 0 } }
+
+    fn template_length(&self) -> usize { self.template.len() }
+
+    fn query_length(&self) -> usize { self.query.len() }
+
+    fn is_identity(&self, i: usize, j: usize) -> bool { self.query[i] == self.template[j] }
+}
+
+/// Result of a structural alignment.
+pub struct StructuralAlignment {
+    /// alignment of the query (the first) and the template (the second) chain
+    pub path: AlignmentPath,
+    /// transformation that superimposes the query onto the template
+    pub transformation: Rototranslation,
+    /// TM-score of the alignment, normalised by the length of the template
+    pub tm_score: f64,
+    /// TM-score of the alignment, normalised by the length of the query
+    pub tm_score_query: f64,
+    /// number of aligned residue pairs closer than 5.0 Angstroms after the superposition
+    pub n_aligned: usize,
+    /// crmsd on the `n_aligned` residue pairs
+    pub rmsd: f64,
+}
+
+impl StructuralAlignment {
+    /// Indexes of query and template residues aligned by this alignment
+    pub fn aligned_pairs(&self) -> Vec<(usize, usize)> { aligned_pairs(&self.path) }
+}
+
+/// Aligns two protein structures regardless of their sequences.
+///
+/// See the [module documentation](crate::structural_alignment) for the description of the algorithm.
+pub struct StructuralAligner {
+    /// penalty for opening a gap in the alignment; gaps are not penalised for their length
+    pub gap_open: f64,
+    /// maximum number of superposition - alignment cycles made for each initial alignment
+    pub max_iterations: usize,
+}
+
+impl Default for StructuralAligner {
+    fn default() -> Self { StructuralAligner { gap_open: -0.6, max_iterations: 30 } }
+}
+
+impl StructuralAligner {
+
+    /// Aligns two structures using alpha carbons of their residues.
+    ///
+    /// Positions of the returned alignment refer to residues listed by [`ca_trace()`]
+    pub fn align_structures(&self, query: &Structure, template: &Structure) -> StructuralAlignment {
+        let (_, query_ca) = ca_trace(query);
+        let (_, template_ca) = ca_trace(template);
+        return self.align(&query_ca, &template_ca);
+    }
+
+    /// Aligns two CA traces
+    pub fn align(&self, query: &[Vec3], template: &[Vec3]) -> StructuralAlignment {
+
+        let l_min = query.len().min(template.len());
+        let d0 = tm_d0(l_min);
+        let mut aligner = GlobalAligner::new(query.len().max(template.len()));
+        let gap_open = (self.gap_open * STRUCTURAL_SCORE_SCALE).round() as i32;
+
+        let mut best: Option<(f64, AlignmentPath)> = None;
+        for seed in [self.gapless_threading(query, template, d0), secondary_alignment(query, template)] {
+            let mut path = seed;
+            let mut pairs = aligned_pairs(&path);
+            for _iter in 0..self.max_iterations {
+                let (tm, rot) = search_superposition(query, template, &pairs, l_min, d0);
+                if best.as_ref().is_none_or(|(best_tm, _)| tm > *best_tm) { best = Some((tm, path)); }
+                let moved: Vec<Vec3> = query.iter().map(|v| rot.apply(v)).collect();
+                let scoring = StructuralSimilarityScore::new(moved, template.to_vec(), d0);
+                aligner.align(&scoring, gap_open, 0);
+                path = aligner.backtrace();
+                let new_pairs = aligned_pairs(&path);
+                if new_pairs == pairs { break; }
+                pairs = new_pairs;
+            }
+        }
+        let path = best.map_or_else(|| AlignmentPath::from_attrs(vec![]), |(_, path)| path);
+
+        // --- final scores of the best alignment
+        let pairs = aligned_pairs(&path);
+        let q: Vec<Vec3> = pairs.iter().map(|&(i, _)| query[i]).collect();
+        let t: Vec<Vec3> = pairs.iter().map(|&(_, j)| template[j]).collect();
+        let tm = TMScore::from_coordinates(&q, &t, template.len());
+        let tm_query = TMScore::from_coordinates(&q, &t, query.len());
+
+        return StructuralAlignment { path, transformation: tm.transformation, tm_score: tm.tm_score,
+            tm_score_query: tm_query.tm_score, n_aligned: tm.n_aligned, rmsd: tm.rmsd };
+    }
+
+    /// Finds the best alignment without gaps, scored by a quick TM-score estimate
+    fn gapless_threading(&self, query: &[Vec3], template: &[Vec3], d0: f64) -> AlignmentPath {
+        let (nq, nt) = (query.len() as i64, template.len() as i64);
+        let min_overlap = (nq.min(nt) / 2).max(3);
+        let mut best_tm = -1.0;
+        let mut best_shift = 0;
+        // --- the i-th query residue is aligned with the (i + shift)-th template residue
+        for shift in -(nq - min_overlap)..=(nt - min_overlap) {
+            let pairs: Vec<(usize, usize)> = (0..nq).filter(|i| i + shift >= 0 && i + shift < nt)
+                .map(|i| (i as usize, (i + shift) as usize)).collect();
+            if (pairs.len() as i64) < min_overlap { continue; }
+            let tm = quick_tm(query, template, &pairs, nq.min(nt) as usize, d0);
+            if tm > best_tm { best_tm = tm; best_shift = shift; }
+        }
+        let mut steps: Vec<AlignmentStep> = vec![];
+        for _ in 0..best_shift.max(0) { steps.push(AlignmentStep::Horizontal); }
+        for _ in 0..(-best_shift).max(0) { steps.push(AlignmentStep::Vertical); }
+        let n_match = (nq - (-best_shift).max(0)).min(nt - best_shift.max(0)).max(0);
+        for _ in 0..n_match { steps.push(AlignmentStep::Match); }
+        let n_q_left = nq - (-best_shift).max(0) - n_match;
+        let n_t_left = nt - best_shift.max(0) - n_match;
+        for _ in 0..n_q_left { steps.push(AlignmentStep::Vertical); }
+        for _ in 0..n_t_left { steps.push(AlignmentStep::Horizontal); }
+
+        return AlignmentPath::from_attrs(steps);
+    }
+}
+
+/// Lists residues of a structure that have an alpha carbon, together with positions of these atoms
+pub fn ca_trace(strctr: &Structure) -> (Vec<ResidueId>, Vec<Vec3>) {
+    let mut residues: Vec<ResidueId> = vec![];
+    let mut positions: Vec<Vec3> = vec![];
+    for res_id in strctr.residues() {
+        if let Ok(a) = strctr.atom(res_id, " CA ") {
+            residues.push(res_id.clone());
+            positions.push(a.pos);
+        }
+    }
+
+    return (residues, positions);
+}
+
+fn aligned_pairs(path: &AlignmentPath) -> Vec<(usize, usize)> {
+    let (mut i, mut j) = (0, 0);
+    let mut pairs: Vec<(usize, usize)> = vec![];
+    for step in path.iter() {
+        match step {
+            AlignmentStep::Match => { pairs.push((i, j)); i += 1; j += 1; }
+            AlignmentStep::Vertical => { i += 1; }
+            AlignmentStep::Horizontal => { j += 1; }
+        }
+    }
+
+    return pairs;
+}
+
+/// TM-score of an alignment and the superposition found by the full search
+fn search_superposition(query: &[Vec3], template: &[Vec3], pairs: &[(usize, usize)], l_norm: usize, d0: f64) -> (f64, Rototranslation) {
+    let q: Vec<Vec3> = pairs.iter().map(|&(i, _)| query[i]).collect();
+    let t: Vec<Vec3> = pairs.iter().map(|&(_, j)| template[j]).collect();
+    let d0_search = d0.clamp(4.5, 8.0);
+    return superposition_search(&q, &t, |d: &[f64]| {
+        let score = d.iter().map(|x| 1.0 / (1.0 + (x / d0) * (x / d0))).sum::<f64>() / l_norm as f64;
+        (score, (0..d.len()).filter(|&i| d[i] < d0_search).collect())
+    });
+}
+
+/// TM-score estimate: superposition on all the pairs refined a few times on the closest ones
+fn quick_tm(query: &[Vec3], template: &[Vec3], pairs: &[(usize, usize)], l_norm: usize, d0: f64) -> f64 {
+    let d0_search = d0.clamp(4.5, 8.0);
+    let mut selected: Vec<(usize, usize)> = pairs.to_vec();
+    let mut best = 0.0;
+    for _ in 0..3 {
+        let q: Vec<Vec3> = selected.iter().map(|&(i, _)| query[i]).collect();
+        let t: Vec<Vec3> = selected.iter().map(|&(_, j)| template[j]).collect();
+        let rot = superimpose(&q, &t);
+        let d: Vec<f64> = pairs.iter().map(|&(i, j)| rot.apply(&query[i]).distance_to(&template[j])).collect();
+        let tm = d.iter().map(|x| 1.0 / (1.0 + (x / d0) * (x / d0))).sum::<f64>() / l_norm as f64;
+        if tm > best { best = tm; }
+        let next: Vec<(usize, usize)> = pairs.iter().zip(d.iter()).filter(|(_, &x)| x < d0_search).map(|(p, _)| *p).collect();
+        if next.len() < 3 || next == selected { break; }
+        selected = next;
+    }
+
+    return best;
+}
+
+/// Initial alignment of secondary structure assigned from CA traces
+fn secondary_alignment(query: &[Vec3], template: &[Vec3]) -> AlignmentPath {
+    let mut aligner = GlobalAligner::new(query.len().max(template.len()));
+    let scoring = SecondaryScore { query: ca_secondary(query), template: ca_secondary(template) };
+    aligner.align(&scoring, -1, 0);
+    return aligner.backtrace();
+}
+
+/// Assigns secondary structure (`H`, `E` or `C`) from distances between nearby alpha carbons, as TM-align does
+fn ca_secondary(ca: &[Vec3]) -> Vec<u8> {
+    let mut sec = vec![b'C'; ca.len()];
+    let close = |d: f64, expected: f64| (d - expected).abs() < 2.1;
+    let n = ca.len();
+    for (i, s) in sec.iter_mut().enumerate().take(n.saturating_sub(2)).skip(2) {
+        let d = |a: usize, b: usize| ca[a].distance_to(&ca[b]);
+        let (d13, d14, d15) = (d(i - 2, i), d(i - 2, i + 1), d(i - 2, i + 2));
+        let (d24, d25, d35) = (d(i - 1, i + 1), d(i - 1, i + 2), d(i, i + 2));
+        if close(d15, 6.37) && close(d14, 5.18) && close(d25, 5.18)
+            && close(d13, 5.45) && close(d24, 5.45) && close(d35, 5.45) {
+            *s = b'H';
+        } else if close(d15, 13.0) && close(d14, 10.4) && close(d25, 10.4)
+            && close(d13, 6.1) && close(d24, 6.1) && close(d35, 6.1) {
+            *s = b'E';
+        }
+    }
+
+    return sec;
+}
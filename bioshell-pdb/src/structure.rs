@@ -4,17 +4,21 @@ use std::io::Write;
 use std::ops::Range;
 
 use itertools::{Itertools};
-use bioshell_seq::chemical::{ResidueType, ResidueTypeManager, ResidueTypeProperties, KNOWN_RESIDUE_TYPES};
+use bioshell_seq::chemical::{ResidueType, ResidueTypeManager, ResidueTypeProperties, StandardResidueType, KNOWN_RESIDUE_TYPES};
 use bioshell_seq::sequence::Sequence;
 use bioshell_core::Vec3;
 
-use crate::pdb_atom::{PdbAtom, same_residue_atoms};
+use crate::pdb_atom::PdbAtom;
 use crate::pdb_atom_filters::{SameResidue, PdbAtomPredicate, PdbAtomPredicate2, ByResidueRange};
 use crate::pdb_parsing_error::PDBError;
-use crate::pdb_parsing_error::PDBError::{NoSuchAtom, NoSuchResidue};
-use crate::{ResidueId, SecondaryStructureTypes};
+use crate::pdb_parsing_error::PDBError::{AtomAlreadyExists, ChainAlreadyExists, NoSuchAtom, NoSuchChain, NoSuchResidue, ResidueAlreadyExists, UnknownResidueType};
+use crate::monomers::{Monomer, MonomerManager};
+use crate::{ModifiedResidueCode, ResidueId, SecondaryStructureTypes};
 use crate::PDBError::WrongAtomsNumberInModel;
 use crate::secondary_structure::SecondaryStructure;
+use crate::pdb_helix::PdbHelix;
+use crate::pdb_sheet::PdbSheet;
+use bioshell_cif::CifLoop;
 
 
 /// A biomacromolecular structure composed of [`PdbAtom`](PdbAtom) objects.
@@ -92,6 +96,11 @@ use crate::secondary_structure::SecondaryStructure;
 /// let new_strctr = Structure::from_iterator("1xyz", strctr.atoms().iter().filter(|a| !is_h.check(&a)).cloned());
 /// # assert_eq!(new_strctr.count_atoms(), 1);
 /// ```
+/// Single residues or chains may be also removed in place with [`remove_residue()`](Structure::remove_residue())
+/// and [`remove_chain()`](Structure::remove_chain()), respectively. Other editing methods include
+/// [`rename_chain()`](Structure::rename_chain()), [`merge_chains()`](Structure::merge_chains()),
+/// [`mutate_residue()`](Structure::mutate_residue()) and [`insert_atoms()`](Structure::insert_atoms()).
+/// All of them keep the residue index of a [`Structure`](Structure) up to date.
 ///
 #[derive(Clone)]
 pub struct Structure {
@@ -103,6 +112,8 @@ pub struct Structure {
     pub(crate) residue_ids: Vec<ResidueId>,
     /// range of atoms that belong to i-th residue; order is the same as in `residue_ids`
     pub(crate) atoms_for_residue_id: Vec<Range<usize>>,
+    /// strand records set by [`Structure::set_sheets()`], which describe how strands are paired into sheets
+    pub(crate) sheets: Vec<PdbSheet>,
 }
 
 impl Structure {
@@ -114,6 +125,7 @@ impl Structure {
             model_coordinates: vec![],
             residue_ids: vec![],
             atoms_for_residue_id: vec![],
+            sheets: vec![],
         }
     }
 
@@ -482,7 +494,7 @@ impl Structure {
     /// assumed that is the polymer chain. In CIF files water molecules and ligands
     /// are placed into separate entities. In the PDB format they are separated from the polymer entity
     /// by a `TER` record and are marked appropriately by bioshell while loading.
-    fn residues_in_polymer(&self, chain_id: &str) -> Vec<usize> {
+    pub(crate) fn residues_in_polymer(&self, chain_id: &str) -> Vec<usize> {
 
         let mut counts: HashMap<String, Vec<usize>> = HashMap::new();
         for (i_res, res_id) in self.residue_ids.iter().enumerate() {
@@ -512,6 +524,8 @@ impl Structure {
     /// Returns the chemical type of residue as a [`ResidueType`] object.
     ///
     /// Results in an [`PDBError`] if the type of the residue hasn't been registered in the [`ResidueTypeManager`].
+    /// Types of non-standard residues, such as `MSE` or `SEP`, are also looked up in the chemical component dictionary
+    /// loaded by the [`MonomerManager`], which provides their parent types.
     /// If that residue type hasn't been registered, it's still possible to get the name of `res_id`
     /// by calling [`residue_name`].
     ///
@@ -530,11 +544,15 @@ impl Structure {
 
         // --- check if such a residue has at least one atom in this struct
         if let Some(atom) = self.atoms().iter().find(|&a| res_id.check(a)) {
-            if let Some(res_type) = KNOWN_RESIDUE_TYPES.lock().unwrap().by_code3(&atom.res_name) {
-                return Ok(res_type.clone());    // --- atom exists and its residue type is known
-            } else {                            // --- atom exists but its residue type is NOT known
-                return Err(PDBError::UnknownResidueType { res_type: atom.res_name.clone()});
+            let known = KNOWN_RESIDUE_TYPES.lock().unwrap().by_code3(&atom.res_name).cloned();
+            // --- a residue type that is not known or has no parent may be defined by the chemical component dictionary
+            if known.as_ref().is_none_or(|rt| rt.parent_type == StandardResidueType::UNK && rt.code3 != "UNK") {
+                let from_dictionary = MonomerManager::get().components()
+                    .and_then(|d| d.entry(&atom.res_name)).map(|e| e.residue_type());
+                if let Some(res_type) = from_dictionary { return Ok(res_type); }
             }
+            // --- atom exists but its residue type is NOT known
+            return known.ok_or(PDBError::UnknownResidueType { res_type: atom.res_name.clone()});
         } else {                        // --- atom doesn't exist
             return Err(NoSuchResidue { res_id: res_id.clone() });
         }
@@ -562,7 +580,7 @@ impl Structure {
     pub fn residue_name(&self, res_id: &ResidueId) -> Result<&str, PDBError> {
 
         if let Some(pos) = self.residue_ids.iter().position(|x| x == res_id) {
-            return Ok(&self.atoms[pos].res_name);
+            return Ok(&self.atoms[self.atoms_for_residue_id[pos].start].res_name);
         } else {
             return  Err(NoSuchResidue{res_id: res_id.clone()})
         }
@@ -621,6 +639,69 @@ impl Structure {
             return Err(NoSuchResidue { res_id: res_id.clone() });
         }
     }
+
+    /// Sets the secondary structure of a given residue.
+    ///
+    /// The new [`SecondaryStructureTypes`] value is assigned to every atom of the residue, replacing the annotation
+    /// loaded from `HELIX` / `SHEET` records or the `_struct_conf` / `_struct_sheet_range` categories. This allows
+    /// one to annotate structures computationally, e.g. predicted models deposited without any secondary structure.
+    /// The new annotation is then used by [`Structure::secondary()`], [`SecondaryView`](crate::SecondaryView)
+    /// as well as by the [`write_pdb()`] and [`write_secondary_cif()`] functions.
+    ///
+    /// Results in a [`PDBError`] if the residue cannot be located.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError, ResidueId, SecondaryStructureTypes};
+    /// # fn main() -> Result<(), PDBError> {
+    /// # let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+    /// let mut strctr = deposit.structure().unwrap();
+    /// let res_id = ResidueId::new("A", 27, ' ');
+    /// strctr.set_residue_secondary(&res_id, SecondaryStructureTypes::Right3_10Helix(0))?;
+    /// assert_eq!(strctr.residue_secondary(&res_id)?, SecondaryStructureTypes::Right3_10Helix(0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_residue_secondary(&mut self, res_id: &ResidueId, sse: SecondaryStructureTypes) -> Result<(), PDBError> {
+        let i_residue = self.residue_pos(res_id)?;
+        let range = self.atoms_for_residue_id[i_residue].clone();
+        self.atoms[range].iter_mut().for_each(|a| a.secondary_struct_type = sse);
+        // --- strand records set previously may not match the new annotation
+        self.sheets.clear();
+
+        return Ok(());
+    }
+
+    /// Lists beta strands of this structure, grouped into sheets.
+    ///
+    /// Returns the records given by [`Structure::set_sheets()`]; when none were given, the records are created from
+    /// the secondary structure annotation of atoms by [`PdbSheet::from_structure()`], which lists all the strands
+    /// of a chain as a single sheet. These records are written by [`write_pdb()`] and [`write_secondary_cif()`].
+    pub fn sheets(&self) -> Vec<PdbSheet> {
+        if self.sheets.is_empty() { return PdbSheet::from_structure(self); }
+        return self.sheets.clone();
+    }
+
+    /// Stores strand records that describe how strands of this structure are paired into sheets.
+    ///
+    /// Every strand should be annotated as a [`Strand`](SecondaryStructureTypes::Strand) beforehand, e.g. by
+    /// [`Structure::set_residue_secondary()`]; the records are dropped by any further call to that method.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError, PdbSheet};
+    /// # fn main() -> Result<(), PDBError> {
+    /// let mut strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    /// let mut sheets = strctr.sheets();
+    /// assert_eq!(sheets.len(), 4);
+    /// sheets[1].sense = -1;
+    /// strctr.set_sheets(sheets);
+    /// assert_eq!(strctr.sheets()[1].sense, -1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_sheets(&mut self, sheets: Vec<PdbSheet>) { self.sheets = sheets; }
+
     /// Provides a sequence of a given chain.
     ///
     /// The sequence contains only in the residues found in atoms of this structure; some of its residues
@@ -651,6 +732,30 @@ impl Structure {
     /// # }
     /// ```
     pub fn sequence(&self, chain_id: &str) -> Sequence {
+        return self.sequence_with_modified(chain_id, ModifiedResidueCode::Parent);
+    }
+
+    /// Creates a [`Sequence`] object for a given chain, representing modified residues as requested.
+    ///
+    /// Works as [`sequence()`](Structure::sequence), but allows to choose how modified residues are represented:
+    /// by `'X'`, by the one-letter code of their parent residue type or by the lowercase version of that code.
+    /// Parent types are taken from the [`ResidueTypeManager`], where they are registered from the `MODRES` records
+    /// or the `_pdbx_struct_mod_residue` category of a deposit, as well as from the chemical component dictionary
+    /// (see [`MonomerManager::load_components()`]). Residues of unknown parent type are always represented by `'X'`.
+    ///
+    /// ```
+    /// # use bioshell_pdb::{Deposit, ModifiedResidueCode, PDBError};
+    /// # fn main() -> Result<(), PDBError> {
+    /// let strctr = Deposit::from_file("./tests/test_files/1c5n.cif")?.structure()?;
+    /// // --- sulfotyrosine 63 is followed by leucine 64 at the C-terminus of the hirudin fragment
+    /// let seq = strctr.sequence_with_modified("I", ModifiedResidueCode::Lowercase);
+    /// assert!(seq.to_string(100).ends_with("EEyL"));
+    /// let seq = strctr.sequence_with_modified("I", ModifiedResidueCode::Unknown);
+    /// assert!(seq.to_string(100).ends_with("EEXL"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sequence_with_modified(&self, chain_id: &str, code: ModifiedResidueCode) -> Sequence {
 
         let res_ids = self.residues_in_polymer(chain_id);
         let rtm = ResidueTypeManager::get();
@@ -658,10 +763,11 @@ impl Structure {
         for i_res in res_ids {
             let first_atom: &PdbAtom = &self.atoms[self.atoms_for_residue_id[i_res].start];
             // --- if the monomer type of this residue has been already registered,
-            // --- use its code1, otherwise use 'X'.
+            // --- use its code1 or the code1 of its parent, otherwise use 'X'.
             let mut code_1 = b'X';
             if let Some(res_type) = rtm.by_code3(&first_atom.res_name) {
-                code_1 = res_type.parent_type.code1() as u8;
+                let is_standard = StandardResidueType::TYPES.iter().any(|srt| srt.code3() == res_type.code3);
+                code_1 = if is_standard { res_type.parent_type.code1() as u8 } else { code.code1(res_type.parent_type) as u8 };
             }
             residue_sequence.push(code_1);
         }
@@ -711,8 +817,8 @@ impl Structure {
     pub fn remove_ligands(&mut self) {
         let mut first_id_map: HashMap<String, String> = HashMap::new();
 
-        self.atoms
-            .retain(|atom| {
+        let keep: Vec<bool> = self.atoms.iter()
+            .map(|atom| {
                 // Check if we've seen this entity before
                 if let Some(first_id) = first_id_map.get(&atom.chain_id) {
                     // If we have, only keep it if the id matches the first one seen
@@ -722,7 +828,319 @@ impl Structure {
                     first_id_map.insert(atom.chain_id.clone(), atom.entity_id.clone());
                     true
                 }
-            });
+            }).collect();
+        self.retain_atoms(&keep);
+    }
+
+    /// Removes a residue from this [`Structure`](Structure).
+    ///
+    /// Atoms of the residue are removed from every model of this structure.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PdbAtom, ResidueId, Structure};
+    /// # let pdb_lines = vec!["ATOM    514  N   ALA A  68      26.532  28.200  28.365  1.00 17.85           N",
+    /// #                     "ATOM    515  CA  ALA A  68      25.790  28.757  29.513  1.00 16.12           C",
+    /// #                     "ATOM    514  N   ALA A  69      26.532  28.200  28.365  1.00 17.85           N",
+    /// #                     "ATOM    515  CA  ALA A  69      25.790  28.757  29.513  1.00 16.12           C"];
+    /// # let atoms: Vec<PdbAtom> = pdb_lines.iter().map(|l| PdbAtom::from_atom_line(l)).collect();
+    /// let mut strctr = Structure::from_atoms("1xyz", atoms);
+    /// strctr.remove_residue(&ResidueId::new("A", 68, ' ')).unwrap();
+    /// assert_eq!(strctr.count_atoms(), 2);
+    /// assert_eq!(strctr.residues().len(), 1);
+    /// assert!(strctr.remove_residue(&ResidueId::new("A", 68, ' ')).is_err());
+    /// ```
+    pub fn remove_residue(&mut self, res_id: &ResidueId) -> Result<(), PDBError> {
+        self.take_residue(res_id)?;
+        return Ok(());
+    }
+
+    /// Removes a residue from this [`Structure`](Structure) and returns its atoms.
+    ///
+    /// The returned atoms may be inserted into another structure with [`insert_atoms()`](Structure::insert_atoms()).
+    pub fn take_residue(&mut self, res_id: &ResidueId) -> Result<Vec<PdbAtom>, PDBError> {
+        let i_residue = self.residue_pos(res_id)?;
+        let range = self.atoms_for_residue_id[i_residue].clone();
+
+        return Ok(self.drain_atoms(range));
+    }
+
+    /// Removes all atoms of a given chain from this [`Structure`](Structure).
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PdbAtom, Structure};
+    /// let mut strctr = Structure::new("1xyz");
+    /// strctr.push_atom(PdbAtom::from_atom_line("ATOM    515  CA  ALA A  68      25.790  28.757  29.513  1.00 16.12           C"));
+    /// strctr.push_atom(PdbAtom::from_atom_line("ATOM    516  CA  ALA B  68      25.790  28.757  29.513  1.00 16.12           C"));
+    /// strctr.remove_chain("A").unwrap();
+    /// assert_eq!(strctr.chain_ids(), vec!["B"]);
+    /// ```
+    pub fn remove_chain(&mut self, chain_id: &str) -> Result<(), PDBError> {
+        self.take_chain(chain_id)?;
+        return Ok(());
+    }
+
+    /// Removes a chain from this [`Structure`](Structure) and returns its atoms.
+    pub fn take_chain(&mut self, chain_id: &str) -> Result<Vec<PdbAtom>, PDBError> {
+        let keep: Vec<bool> = self.atoms.iter().map(|a| a.chain_id != chain_id).collect();
+        if keep.iter().all(|&k| k) { return Err(NoSuchChain { chain_id: chain_id.to_string() }); }
+
+        let taken: Vec<PdbAtom> = self.atoms.iter().zip(keep.iter())
+            .filter(|(_, &k)| !k).map(|(a, _)| a.clone()).collect();
+        self.retain_atoms(&keep);
+
+        return Ok(taken);
+    }
+
+    /// Changes the ID of a chain.
+    ///
+    /// Fails when there is no chain `old_id` or the chain `new_id` already exists in this [`Structure`](Structure);
+    /// use [`merge_chains()`](Structure::merge_chains()) to join two chains.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PdbAtom, ResidueId, Structure};
+    /// let mut strctr = Structure::new("1xyz");
+    /// strctr.push_atom(PdbAtom::from_atom_line("ATOM    515  CA  ALA A  68      25.790  28.757  29.513  1.00 16.12           C"));
+    /// strctr.push_atom(PdbAtom::from_atom_line("ATOM    516  CA  ALA B  68      25.790  28.757  29.513  1.00 16.12           C"));
+    /// strctr.rename_chain("A", "H").unwrap();
+    /// assert_eq!(strctr.chain_ids(), vec!["B", "H"]);
+    /// assert_eq!(strctr.residues()[0], ResidueId::new("H", 68, ' '));
+    /// assert!(strctr.rename_chain("B", "H").is_err());
+    /// ```
+    pub fn rename_chain(&mut self, old_id: &str, new_id: &str) -> Result<(), PDBError> {
+        if !self.atoms.iter().any(|a| a.chain_id == old_id) {
+            return Err(NoSuchChain { chain_id: old_id.to_string() });
+        }
+        if old_id == new_id { return Ok(()); }
+        if self.atoms.iter().any(|a| a.chain_id == new_id) {
+            return Err(ChainAlreadyExists { chain_id: new_id.to_string() });
+        }
+        for a in self.atoms.iter_mut().filter(|a| a.chain_id == old_id) {
+            a.chain_id = new_id.to_string();
+        }
+        self.update();
+
+        return Ok(());
+    }
+
+    /// Moves all residues of the chain `source` into the chain `target`.
+    ///
+    /// Residues of the source chain are placed after the atoms of the target chain and keep their numbers.
+    /// The call fails, leaving this [`Structure`](Structure) intact, when any of the moved residues has the same
+    /// number as a residue already present in the target chain; [`renumbered_structure()`](Structure::renumbered_structure())
+    /// may be used to resolve such conflicts.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PdbAtom, ResidueId, Structure};
+    /// let mut strctr = Structure::new("1xyz");
+    /// strctr.push_atom(PdbAtom::from_atom_line("ATOM    515  CA  ALA A  68      25.790  28.757  29.513  1.00 16.12           C"));
+    /// strctr.push_atom(PdbAtom::from_atom_line("ATOM    516  CA  ALA B  69      25.790  28.757  29.513  1.00 16.12           C"));
+    /// strctr.push_atom(PdbAtom::from_atom_line("ATOM    517  CA  ALA C  70      25.790  28.757  29.513  1.00 16.12           C"));
+    /// strctr.merge_chains("A", "C").unwrap();
+    /// assert_eq!(strctr.chain_ids(), vec!["A", "B"]);
+    /// assert_eq!(strctr.residues()[1], ResidueId::new("A", 70, ' '));
+    /// ```
+    pub fn merge_chains(&mut self, target: &str, source: &str) -> Result<(), PDBError> {
+        if !self.atoms.iter().any(|a| a.chain_id == target) {
+            return Err(NoSuchChain { chain_id: target.to_string() });
+        }
+        if target == source { return Ok(()); }
+        let moved: Vec<ResidueId> = self.residue_ids.iter().filter(|r| r.chain_id == source)
+            .map(|r| ResidueId::new(target, r.res_seq, r.i_code)).collect();
+        if moved.is_empty() { return Err(NoSuchChain { chain_id: source.to_string() }); }
+        let existing: HashSet<&ResidueId> = self.residue_ids.iter().filter(|r| r.chain_id == target).collect();
+        if let Some(res_id) = moved.iter().find(|r| existing.contains(r)) {
+            return Err(ResidueAlreadyExists { res_id: res_id.clone() });
+        }
+
+        let mut atoms = self.take_chain(source)?;
+        for a in atoms.iter_mut() { a.chain_id = target.to_string(); }
+        let at = self.atoms.iter().rposition(|a| a.chain_id == target).unwrap() + 1;
+        self.splice_atoms(at, atoms);
+
+        return Ok(());
+    }
+
+    /// Replaces a residue with another residue type.
+    ///
+    /// Only the atoms common to both residue types are retained. An atom is considered common when both
+    /// the residue types define it under the same name and it is connected to the root atom
+    /// (`CA` for amino acids, `C1'` for nucleotides) by the same chain of atoms. Therefore, mutation of
+    /// a leucine into isoleucine keeps only the `CB` side chain atom, while `CG` is retained when
+    /// an arginine is turned into a proline. When the original residue type hasn't been registered
+    /// in the [`MonomerManager`](crate::monomers::MonomerManager), atoms are retained by their names only.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PdbAtom, ResidueId, Structure};
+    /// let mut strctr = Structure::new("1xyz");
+    /// # strctr.push_atom(PdbAtom::from_atom_line("ATOM    514  N   SER A  69      26.532  28.200  28.365  1.00 17.85           N"));
+    /// # strctr.push_atom(PdbAtom::from_atom_line("ATOM    515  CA  SER A  69      25.790  28.757  29.513  1.00 16.12           C"));
+    /// # strctr.push_atom(PdbAtom::from_atom_line("ATOM    516  C   SER A  69      26.891  29.054  30.649  1.00 15.28           C"));
+    /// # strctr.push_atom(PdbAtom::from_atom_line("ATOM    517  O   SER A  69      26.657  29.867  31.341  1.00 20.90           O"));
+    /// # strctr.push_atom(PdbAtom::from_atom_line("ATOM    518  CB  SER A  69      25.155  27.554  29.987  1.00 21.91           C"));
+    /// # strctr.push_atom(PdbAtom::from_atom_line("ATOM    519  OG  SER A  69      24.310  27.002  29.011  1.00 21.91           O"));
+    /// // --- N, CA, C, O, CB and OG atoms of serine 69
+    /// let res_id = ResidueId::new("A", 69, ' ');
+    /// strctr.mutate_residue(&res_id, "ALA").unwrap();
+    /// assert_eq!(strctr.count_atoms(), 5);
+    /// assert_eq!(strctr.residue_type(&res_id).unwrap().code3, "ALA");
+    /// strctr.mutate_residue(&res_id, "GLY").unwrap();
+    /// assert_eq!(strctr.count_atoms(), 4);
+    /// ```
+    pub fn mutate_residue(&mut self, res_id: &ResidueId, new_res_name: &str) -> Result<(), PDBError> {
+        let i_residue = self.residue_pos(res_id)?;
+        let range = self.atoms_for_residue_id[i_residue].clone();
+        let old_res_name = self.atoms[range.start].res_name.clone();
+
+        let mut keep = vec![true; self.atoms.len()];
+        let is_standard;
+        {
+            let mgr = MonomerManager::get();
+            let new_monomer = mgr.by_code3(new_res_name)
+                .ok_or_else(|| UnknownResidueType { res_type: new_res_name.to_string() })?;
+            is_standard = new_monomer.residue_type().parent_type.code3() == new_res_name;
+            let new_paths = paths_to_root(new_monomer);
+            let old_paths = mgr.by_code3(&old_res_name).map(paths_to_root);
+            for i in range.clone() {
+                let name = self.atoms[i].name.trim();
+                keep[i] = match (new_paths.get(name), &old_paths) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    (Some(new_path), Some(old_paths)) => old_paths.get(name) == Some(new_path),
+                };
+            }
+        }
+        for a in &mut self.atoms[range] {
+            a.res_name = new_res_name.to_string();
+            a.is_hetero_atom = !is_standard;
+        }
+        self.retain_atoms(&keep);
+
+        return Ok(());
+    }
+
+    /// Inserts atoms into this [`Structure`](Structure), keeping residues and chains contiguous.
+    ///
+    /// An atom that belongs to a residue already present in this structure is placed after the last atom
+    /// of that residue. Otherwise, a new residue is created according to its number within its chain;
+    /// atoms of a new chain are appended at the end. The new atoms are also inserted into every model
+    /// of this structure with their current coordinates. Atoms are typically taken from another structure,
+    /// e.g. by [`take_residue()`](Structure::take_residue()) or [`take_chain()`](Structure::take_chain()).
+    ///
+    /// The call fails, leaving this [`Structure`](Structure) intact, when an atom with the same name
+    /// and alternate location already exists in the target residue.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{PdbAtom, ResidueId, Structure};
+    /// let mut source = Structure::new("1abc");
+    /// source.push_atom(PdbAtom::from_atom_line("ATOM    515  CA  ALA A  69      25.790  28.757  29.513  1.00 16.12           C"));
+    /// let mut target = Structure::new("1xyz");
+    /// target.push_atom(PdbAtom::from_atom_line("ATOM    515  CA  ALA A  68      25.790  28.757  29.513  1.00 16.12           C"));
+    /// target.push_atom(PdbAtom::from_atom_line("ATOM    515  CA  ALA A  70      25.790  28.757  29.513  1.00 16.12           C"));
+    /// let atoms = source.take_residue(&ResidueId::new("A", 69, ' ')).unwrap();
+    /// target.insert_atoms(atoms).unwrap();
+    /// assert_eq!(source.count_atoms(), 0);
+    /// assert_eq!(target.residues()[1], ResidueId::new("A", 69, ' '));
+    /// ```
+    pub fn insert_atoms<T>(&mut self, atoms: T) -> Result<(), PDBError>
+    where T: IntoIterator<Item = PdbAtom> {
+
+        let atoms: Vec<PdbAtom> = atoms.into_iter().collect();
+        // --- check for duplicates before anything is changed
+        for (i, a) in atoms.iter().enumerate() {
+            let res_id = ResidueId::from(a);
+            let duplicate_new = atoms[..i].iter()
+                .any(|b| ResidueId::from(b) == res_id && a.name == b.name && a.alt_loc == b.alt_loc);
+            let duplicate_old = match self.residue_pos(&res_id) {
+                Ok(pos) => self.atoms[self.atoms_for_residue_id[pos].clone()].iter()
+                    .any(|b| a.name == b.name && a.alt_loc == b.alt_loc),
+                Err(_) => false,
+            };
+            if duplicate_new || duplicate_old {
+                return Err(AtomAlreadyExists { atom_name: a.name.clone(), res_id });
+            }
+        }
+
+        // --- atoms are inserted residue by residue
+        let mut blocks: Vec<Vec<PdbAtom>> = vec![];
+        for a in atoms {
+            match blocks.last_mut() {
+                Some(block) if ResidueId::from(&block[0]) == ResidueId::from(&a) => block.push(a),
+                _ => blocks.push(vec![a]),
+            }
+        }
+        for block in blocks {
+            let res_id = ResidueId::from(&block[0]);
+            let at = if let Ok(pos) = self.residue_pos(&res_id) {
+                self.atoms_for_residue_id[pos].end
+            } else {
+                let in_chain: Vec<usize> = (0..self.residue_ids.len())
+                    .filter(|&i| self.residue_ids[i].chain_id == res_id.chain_id).collect();
+                match in_chain.iter().find(|&&i| self.residue_ids[i] > res_id) {
+                    Some(&i) => self.atoms_for_residue_id[i].start,
+                    None => match in_chain.last() {
+                        Some(&i) => self.atoms_for_residue_id[i].end,
+                        None => self.atoms.len(),
+                    }
+                }
+            };
+            self.splice_atoms(at, block);
+        }
+
+        return Ok(());
+    }
+
+    /// Moves a residue from another [`Structure`](Structure) into this one.
+    ///
+    /// This is a shortcut for [`take_residue()`](Structure::take_residue()) followed by
+    /// [`insert_atoms()`](Structure::insert_atoms()); the residue is removed from `source` only when
+    /// it can be inserted into this structure.
+    pub fn transfer_residue(&mut self, source: &mut Structure, res_id: &ResidueId) -> Result<(), PDBError> {
+        let i_residue = source.residue_pos(res_id)?;
+        let atoms = source.atoms[source.atoms_for_residue_id[i_residue].clone()].to_vec();
+        self.insert_atoms(atoms)?;
+        source.take_residue(res_id)?;
+
+        return Ok(());
+    }
+
+    /// Keeps only these atoms (and their coordinates in every model) for which `keep[i]` is `true`
+    fn retain_atoms(&mut self, keep: &[bool]) {
+        let n_atoms = self.atoms.len();
+        for model in self.model_coordinates.iter_mut().filter(|m| m.len() == n_atoms) {
+            let mut it = keep.iter();
+            model.retain(|_| *it.next().unwrap());
+        }
+        let mut it = keep.iter();
+        self.atoms.retain(|_| *it.next().unwrap());
+        self.update();
+    }
+
+    /// Removes a range of atoms (and their coordinates in every model) from this structure
+    fn drain_atoms(&mut self, range: Range<usize>) -> Vec<PdbAtom> {
+        let n_atoms = self.atoms.len();
+        for model in self.model_coordinates.iter_mut().filter(|m| m.len() == n_atoms) {
+            model.drain(range.clone());
+        }
+        let out: Vec<PdbAtom> = self.atoms.drain(range).collect();
+        self.update();
+
+        return out;
+    }
+
+    /// Inserts atoms at a given position; their coordinates are also inserted into every model
+    fn splice_atoms(&mut self, at: usize, atoms: Vec<PdbAtom>) {
+        let n_atoms = self.atoms.len();
+        for model in self.model_coordinates.iter_mut().filter(|m| m.len() == n_atoms) {
+            model.splice(at..at, atoms.iter().map(|a| a.pos));
+        }
+        self.atoms.splice(at..at, atoms);
+        self.update();
     }
 
     /// Creates a vector of [`ResidueId`](ResidueId) object for each residue found in a given vector of atoms
@@ -789,7 +1207,11 @@ impl Structure {
     ///
     /// This method should be called after any change to the atoms of this Structure
     pub(crate) fn update(&mut self) {
-        if self.atoms.is_empty() { return; }
+        if self.atoms.is_empty() {
+            self.residue_ids.clear();
+            self.atoms_for_residue_id.clear();
+            return;
+        }
 
         // --- sort atoms, just in case
         // self.sort();
@@ -803,7 +1225,8 @@ impl Structure {
         let mut first_of_res: usize = 0;
         let mut res_id = ResidueId::try_from(&self.atoms[0]).unwrap();  // never fails
         for i_atom in 1..self.atoms.len() {
-            if ! same_residue_atoms(&self.atoms[first_of_res], &self.atoms[i_atom]) {
+            // --- residues of different chains may share the number, e.g. ions assigned to separate chains
+            if ResidueId::from(&self.atoms[i_atom]) != res_id {
                 self.atoms_for_residue_id.push(first_of_res..i_atom);
                 self.residue_ids.push(res_id);
                 first_of_res = i_atom;
@@ -820,11 +1243,50 @@ impl Structure {
 
 }
 
+/// For every atom of a monomer lists names of atoms on the shortest covalent path to its root atom
+fn paths_to_root(monomer: &Monomer) -> HashMap<String, Vec<String>> {
+    let atoms = monomer.atoms();
+    let mut neighbors: Vec<Vec<usize>> = vec![vec![]; atoms.len()];
+    for b in monomer.bonds() {
+        neighbors[b.first].push(b.second);
+        neighbors[b.second].push(b.first);
+    }
+    let root = monomer.atom_index("CA").or_else(|| monomer.atom_index("C1'")).unwrap_or(0);
+    let mut parent: Vec<Option<usize>> = vec![None; atoms.len()];
+    let mut visited = vec![false; atoms.len()];
+    visited[root] = true;
+    let mut queue = std::collections::VecDeque::from([root]);
+    while let Some(i) = queue.pop_front() {
+        for &j in &neighbors[i] {
+            if visited[j] { continue }
+            visited[j] = true;
+            parent[j] = Some(i);
+            queue.push_back(j);
+        }
+    }
+
+    let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+    for i in 0..atoms.len() {
+        let mut path = vec![];
+        let mut k = parent[i];
+        while let Some(j) = k {
+            path.push(atoms[j].atom_name.clone());
+            k = parent[j];
+        }
+        paths.insert(atoms[i].atom_name.clone(), path);
+    }
+
+    return paths;
+}
+
 /// Write a given structure in the PDB format.
 ///
 /// The structure stored in a file may differ from the given object, as it may need to be adapted to the PDB file format.
 /// For example, if:
 ///  - a given structure has a chain with its name longer than a single character, all chains will be renamed
+///
+/// Atoms are preceded by `HELIX` and `SHEET` records, created from the secondary structure annotation of atoms
+/// (see [`Structure::set_residue_secondary()`]). Strands are grouped into sheets as given by [`Structure::sheets()`].
 pub fn write_pdb(strctr: &Structure, mut outstream: Box<dyn Write>) -> bool {
 
     let mut if_rename_chains = false;
@@ -835,6 +1297,21 @@ pub fn write_pdb(strctr: &Structure, mut outstream: Box<dyn Write>) -> bool {
         }
     }
     let new_chain_codes: Vec<char> = ('A'..='Z').chain('0'..='9').collect();
+    let chain_ids = strctr.chain_ids();
+    let pdb_chain_id = |chain_id: &str| -> String {
+        if !if_rename_chains { return chain_id.to_string() }
+        return new_chain_codes[chain_ids.iter().position(|c| c == chain_id).unwrap()].to_string();
+    };
+    for mut helix in PdbHelix::from_structure(strctr) {
+        helix.init_chain_id = pdb_chain_id(&helix.init_chain_id);
+        helix.end_chain_id = pdb_chain_id(&helix.end_chain_id);
+        writeln!(outstream, "{}", helix).unwrap();
+    }
+    for mut strand in strctr.sheets() {
+        strand.init_chain_id = pdb_chain_id(&strand.init_chain_id);
+        strand.end_chain_id = pdb_chain_id(&strand.end_chain_id);
+        writeln!(outstream, "{}", strand).unwrap();
+    }
     for (i, chain_id)  in strctr.chain_ids().iter().enumerate() {
         for atom in strctr.atoms_in_chain(chain_id) {
             let mut a = atom.clone();
@@ -850,4 +1327,54 @@ pub fn write_pdb(strctr: &Structure, mut outstream: Box<dyn Write>) -> bool {
     return true;
 }
 
+/// Writes the secondary structure of a given structure as mmCIF `_struct_conf` and `_struct_sheet_range` loops.
+///
+/// Helices and strands are listed exactly as by [`write_pdb()`],
+/// and can be read back by [`Deposit::from_cif_reader()`](crate::Deposit::from_cif_reader()). Residues are identified
+/// by their author-defined chain IDs, residue numbers and insertion codes. A loop is written only when the structure
+/// contains at least one helix (or strand, respectively); the loops are meant to be appended to a CIF data block.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::write_secondary_cif;
+/// let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+/// let mut out: Vec<u8> = vec![];
+/// write_secondary_cif(&mut out, &strctr)?;
+/// let text = String::from_utf8(out).unwrap();
+/// assert!(text.contains("_struct_conf.pdbx_PDB_helix_length"));
+/// assert_eq!(text.lines().filter(|l| l.starts_with("HELX_P")).count(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_secondary_cif<W: Write>(out: &mut W, strctr: &Structure) -> Result<(), PDBError> {
+    let new_loop = |category: &str, items: &[&str]| {
+        let names: Vec<String> = items.iter().map(|i| format!("{}.{}", category, i)).collect();
+        CifLoop::new(&names.iter().map(|n| n.as_str()).collect::<Vec<&str>>())
+    };
+
+    let helices = PdbHelix::from_structure(strctr);
+    if !helices.is_empty() {
+        let mut conf = new_loop("_struct_conf", &["conf_type_id", "id", "pdbx_PDB_helix_id", "beg_auth_comp_id",
+            "beg_auth_asym_id", "beg_auth_seq_id", "pdbx_beg_PDB_ins_code", "end_auth_comp_id", "end_auth_asym_id",
+            "end_auth_seq_id", "pdbx_end_PDB_ins_code", "pdbx_PDB_helix_class", "pdbx_PDB_helix_length"]);
+        for h in &helices { conf.add_data_row(h.cif_row())?; }
+        write!(out, "{}", conf)?;
+        writeln!(out, "#")?;
+    }
+
+    let strands = strctr.sheets();
+    if !strands.is_empty() {
+        let mut range = new_loop("_struct_sheet_range", &["sheet_id", "id", "beg_auth_comp_id", "beg_auth_asym_id",
+            "beg_auth_seq_id", "pdbx_beg_PDB_ins_code", "end_auth_comp_id", "end_auth_asym_id", "end_auth_seq_id",
+            "pdbx_end_PDB_ins_code"]);
+        for s in &strands { range.add_data_row(s.cif_row())?; }
+        write!(out, "{}", range)?;
+        writeln!(out, "#")?;
+    }
+
+    return Ok(());
+}
+
 
@@ -0,0 +1,169 @@
+use std::collections::HashSet;
+use crate::{is_hydrogen, PdbAtom, Structure};
+use crate::calc::{atom_radius, NeighborGrid};
+use crate::monomers::MonomerManager;
+use crate::pdb_atom_filters::{IsHBondAcceptor, IsHBondDonor, IsNotWater, PdbAtomPredicate};
+use crate::validation::geometry::{MAX_PEPTIDE_BOND, MAX_PHOSPHODIESTER_BOND};
+
+/// Two atoms overlapping by more than that (in Angstroms) are considered clashing
+pub const CLASH_OVERLAP: f64 = 0.4;
+
+/// Minimum allowed distance between a hydrogen bond donor and an acceptor atom (in Angstroms)
+pub const HBOND_MIN_DISTANCE: f64 = 2.5;
+
+/// Atoms closer than that are considered covalently bonded when no other information about bonds is available
+const MAX_COVALENT_BOND: f64 = 2.1;
+
+/// Maximum distance between two `SG` atoms forming a disulfide bond
+const MAX_DISULFIDE_BOND: f64 = 2.5;
+
+/// A pair of atoms that overlap beyond their van der Waals radii.
+///
+/// Atoms are given as indexes to the vector returned by [`Structure::atoms()`](crate::Structure::atoms())
+#[derive(Debug, Clone)]
+pub struct Clash {
+    pub first: usize,
+    pub second: usize,
+    /// distance between the two atoms
+    pub distance: f64,
+    /// sum of van der Waals radii of the two atoms minus their distance
+    pub overlap: f64,
+}
+
+/// Radius of an atom used to detect clashes.
+///
+/// Oxygen radius follows the one used by the Probe program (1.4 Å), which is more appropriate for
+/// the heavy-atom contacts; Bondi radii are used for all other elements.
+fn clash_radius(a: &PdbAtom) -> f64 {
+    match a.element.as_deref() {
+        Some("O") => 1.4,
+        _ => atom_radius(a),
+    }
+}
+
+/// Returns `true` for atoms that are checked for steric clashes: heavy atoms except water
+pub(crate) fn is_clash_candidate(a: &PdbAtom) -> bool { !is_hydrogen(a) && IsNotWater.check(a) }
+
+/// Finds pairs of heavy atoms that overlap by more than [`CLASH_OVERLAP`].
+///
+/// Atoms separated by up to three covalent bonds are not checked. Covalent bonds within standard residues are
+/// taken from the [`MonomerManager`](MonomerManager), peptide, phosphodiester and disulfide bonds are detected
+/// from the geometry. Atoms of a residue unknown to the manager are considered bonded when closer than 2.1 Å.
+/// A hydrogen bond donor and acceptor may approach each other up to [`HBOND_MIN_DISTANCE`]. Hydrogen atoms and water
+/// molecules are neglected, as well as atoms from different alternate locations.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::validation::find_clashes;
+/// let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+/// let strctr = deposit.structure()?;
+/// let clashes = find_clashes(&strctr);
+/// for c in &clashes {
+///     println!("{} {} {:.2}", strctr.atoms()[c.first], strctr.atoms()[c.second], c.overlap);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_clashes(strctr: &Structure) -> Vec<Clash> {
+
+    let atoms = strctr.atoms();
+    let bonds = covalent_bonds(strctr);
+    let selected: Vec<usize> = (0..atoms.len()).filter(|&i| is_clash_candidate(&atoms[i])).collect();
+    let radii: Vec<f64> = selected.iter().map(|&i| clash_radius(&atoms[i])).collect();
+    let max_radius = radii.iter().cloned().fold(0.0, f64::max);
+    let grid = NeighborGrid::new(&selected.iter().map(|&i| atoms[i].pos).collect::<Vec<_>>(), 2.0 * max_radius);
+
+    let mut clashes: Vec<Clash> = vec![];
+    for (k, &i) in selected.iter().enumerate() {
+        let ai = &atoms[i];
+        let mut excluded: Option<HashSet<usize>> = None;
+        let mut neighbors = grid.neighbors(&ai.pos, radii[k] + max_radius - CLASH_OVERLAP);
+        neighbors.sort();
+        for l in neighbors.into_iter().filter(|&l| l > k) {
+            let j = selected[l];
+            let aj = &atoms[j];
+            if ai.alt_loc != ' ' && aj.alt_loc != ' ' && ai.alt_loc != aj.alt_loc { continue; }
+            let distance = ai.pos.distance_to(&aj.pos);
+            let overlap = radii[k] + radii[l] - distance;
+            if overlap <= CLASH_OVERLAP { continue; }
+            if distance >= HBOND_MIN_DISTANCE && ((IsHBondDonor.check(ai) && IsHBondAcceptor.check(aj))
+                || (IsHBondAcceptor.check(ai) && IsHBondDonor.check(aj))) { continue; }
+            // --- topological neighbors are computed lazily, only when necessary
+            let close = excluded.get_or_insert_with(|| bonded_within(&bonds, i, 3));
+            if close.contains(&j) { continue; }
+            clashes.push(Clash { first: i, second: j, distance, overlap });
+        }
+    }
+
+    return clashes;
+}
+
+/// Lists covalent neighbors of every atom of a structure
+fn covalent_bonds(strctr: &Structure) -> Vec<Vec<usize>> {
+    let atoms = strctr.atoms();
+    let mut bonds: Vec<Vec<usize>> = vec![vec![]; atoms.len()];
+    let mut add_bond = |i: usize, j: usize| {
+        let (ai, aj) = (&atoms[i], &atoms[j]);
+        if ai.alt_loc != ' ' && aj.alt_loc != ' ' && ai.alt_loc != aj.alt_loc { return; }
+        bonds[i].push(j);
+        bonds[j].push(i);
+    };
+
+    // ---------- bonds within residues
+    let mut unknown: Vec<usize> = vec![];
+    {
+        let mgr = MonomerManager::get();
+        for range in &strctr.atoms_for_residue_id {
+            let Some(monomer) = mgr.by_code3(&atoms[range.start].res_name) else {
+                unknown.extend(range.clone());
+                continue
+            };
+            for b in monomer.bonds() {
+                let name_1 = &monomer.atoms()[b.first].atom_name;
+                let name_2 = &monomer.atoms()[b.second].atom_name;
+                for i in range.clone().filter(|&i| atoms[i].name.trim() == name_1) {
+                    for j in range.clone().filter(|&j| atoms[j].name.trim() == name_2) { add_bond(i, j); }
+                }
+            }
+        }
+    }
+
+    // ---------- bonds between residues: detected by distance
+    let grid = NeighborGrid::new(&atoms.iter().map(|a| a.pos).collect::<Vec<_>>(), MAX_DISULFIDE_BOND);
+    for (i, ai) in atoms.iter().enumerate() {
+        for j in grid.neighbors(&ai.pos, MAX_DISULFIDE_BOND) {
+            if j <= i { continue; }
+            let aj = &atoms[j];
+            let d = ai.pos.distance_to(&aj.pos);
+            let names = (ai.name.as_str(), aj.name.as_str());
+            let bonded = match names {
+                (" C  ", " N  ") | (" N  ", " C  ") => d < MAX_PEPTIDE_BOND,
+                (" O3'", " P  ") | (" P  ", " O3'") => d < MAX_PHOSPHODIESTER_BOND,
+                (" SG ", " SG ") => d < MAX_DISULFIDE_BOND,
+                _ => (unknown.binary_search(&i).is_ok() || unknown.binary_search(&j).is_ok()) && d < MAX_COVALENT_BOND,
+            };
+            if bonded { add_bond(i, j); }
+        }
+    }
+
+    return bonds;
+}
+
+/// Returns indexes of atoms separated from a given atom by at most `depth` covalent bonds
+fn bonded_within(bonds: &[Vec<usize>], start: usize, depth: usize) -> HashSet<usize> {
+    let mut visited: HashSet<usize> = HashSet::from([start]);
+    let mut frontier = vec![start];
+    for _ in 0..depth {
+        let mut next: Vec<usize> = vec![];
+        for i in frontier {
+            for &j in &bonds[i] {
+                if visited.insert(j) { next.push(j); }
+            }
+        }
+        frontier = next;
+    }
+
+    return visited;
+}
@@ -0,0 +1,284 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use bioshell_core::{planar_angle3, Vec3};
+use crate::{PdbAtom, Structure};
+use crate::monomers::{Monomer, MonomerManager};
+use crate::validation::{find_clashes, Clash};
+use crate::validation::clashes::is_clash_candidate;
+
+/// Standard deviation of a bond length (in Angstroms) used to compute Z-scores
+pub const BOND_SIGMA: f64 = 0.02;
+
+/// Standard deviation of a planar angle (in degrees) used to compute Z-scores
+pub const ANGLE_SIGMA: f64 = 2.0;
+
+/// Bonds and angles whose absolute Z-score exceeds this value are reported as outliers
+pub const GEOMETRY_Z_CUTOFF: f64 = 4.0;
+
+/// Maximum distance between the `C` atom of a residue and the `N` atom of the next one to consider them bonded
+pub(crate) const MAX_PEPTIDE_BOND: f64 = 2.0;
+
+/// Maximum distance between the `O3'` atom of a nucleotide and the `P` atom of the next one to consider them bonded
+pub(crate) const MAX_PHOSPHODIESTER_BOND: f64 = 2.5;
+
+/// Ideal geometry of the peptide bond after Engh & Huber (1991): bond length, its sigma and three planar angles
+/// (ideal value in degrees and its sigma): CA-C-N, O-C-N and C-N-CA
+const PEPTIDE_BOND: (f64, f64) = (1.329, 0.014);
+const PEPTIDE_ANGLES: [(f64, f64); 3] = [(117.2, 2.2), (122.7, 1.6), (121.7, 2.5)];
+
+/// Ideal length of the O3'-P bond between two nucleotides and its sigma, after Parkinson et al. (1996)
+const PHOSPHODIESTER_BOND: (f64, f64) = (1.607, 0.012);
+
+/// A bond whose length deviates from its ideal value.
+///
+/// Atoms are given as indexes to the vector returned by [`Structure::atoms()`](crate::Structure::atoms())
+#[derive(Debug, Clone)]
+pub struct BondOutlier {
+    pub atoms: [usize; 2],
+    /// bond length observed in the structure
+    pub value: f64,
+    /// ideal bond length
+    pub ideal: f64,
+    pub z_score: f64,
+}
+
+/// A planar angle whose value deviates from its ideal value.
+///
+/// Atoms are given as indexes to the vector returned by [`Structure::atoms()`](crate::Structure::atoms());
+/// the middle one is the vertex of the angle. Angles are in degrees.
+#[derive(Debug, Clone)]
+pub struct AngleOutlier {
+    pub atoms: [usize; 3],
+    /// angle observed in the structure
+    pub value: f64,
+    /// ideal value of the angle
+    pub ideal: f64,
+    pub z_score: f64,
+}
+
+/// A stereo center whose handedness differs from the one defined by the monomer, e.g. a D-amino acid.
+///
+/// The `atom` is an index to the vector returned by [`Structure::atoms()`](crate::Structure::atoms())
+#[derive(Debug, Clone)]
+pub struct ChiralityError {
+    pub atom: usize,
+    /// chiral volume observed in the structure
+    pub volume: f64,
+    /// chiral volume in the ideal monomer geometry
+    pub ideal_volume: f64,
+}
+
+/// Compares covalent geometry of a [`Structure`](crate::Structure) with ideal values.
+///
+/// Ideal bond lengths and planar angles are computed from the ideal coordinates of monomers
+/// provided by the [`MonomerManager`](MonomerManager), while the geometry of a peptide bond
+/// follows Engh & Huber (1991). The Z-score of each bond and angle is computed with
+/// [`BOND_SIGMA`] and [`ANGLE_SIGMA`] deviations, respectively (unless a specific value is known, as for
+/// the peptide bond); outliers are those exceeding [`GEOMETRY_Z_CUTOFF`]. Chirality of each stereo center
+/// is checked by comparing the sign of its chiral volume with the ideal one. Finally, the steric clashes are
+/// detected by [`find_clashes()`](find_clashes).
+///
+/// Residues that are not registered in the [`MonomerManager`](MonomerManager), or whose definition provides no coordinates,
+/// are not checked for their covalent geometry.
+///
+/// # Example
+/// ```
+/// # use bioshell_pdb::{Deposit, PDBError};
+/// # fn main() -> Result<(), PDBError> {
+/// use bioshell_pdb::validation::GeometryReport;
+/// let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+/// let strctr = deposit.structure()?;
+/// let report = GeometryReport::new(&strctr);
+/// assert!(report.bonds_checked > 400);
+/// assert_eq!(report.chirality_errors.len(), 0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct GeometryReport {
+    /// number of bonds that have been checked
+    pub bonds_checked: usize,
+    /// number of planar angles that have been checked
+    pub angles_checked: usize,
+    /// root-mean-square of bond Z-scores
+    pub bond_rmsz: f64,
+    /// root-mean-square of angle Z-scores
+    pub angle_rmsz: f64,
+    pub bond_outliers: Vec<BondOutlier>,
+    pub angle_outliers: Vec<AngleOutlier>,
+    pub chirality_errors: Vec<ChiralityError>,
+    pub clashes: Vec<Clash>,
+    /// number of heavy atoms checked for clashes
+    pub atoms_checked: usize,
+}
+
+impl GeometryReport {
+
+    /// Validates covalent geometry and steric clashes of a given structure
+    pub fn new(strctr: &Structure) -> GeometryReport {
+        let mut report = GeometryReport { bonds_checked: 0, angles_checked: 0, bond_rmsz: 0.0, angle_rmsz: 0.0,
+            bond_outliers: vec![], angle_outliers: vec![], chirality_errors: vec![], clashes: vec![], atoms_checked: 0 };
+        let mut bond_z2 = 0.0;
+        let mut angle_z2 = 0.0;
+        let atoms = strctr.atoms();
+        {
+            let mgr = MonomerManager::get();
+            for range in &strctr.atoms_for_residue_id {
+                let Some(monomer) = mgr.by_code3(&atoms[range.start].res_name) else { continue };
+                if !monomer.has_ideal_positions() { continue; }
+                let mapping = monomer_atom_map(monomer, atoms, range);
+                let neighbors = monomer_neighbors(monomer);
+                // ---------- bonds
+                for b in monomer.bonds() {
+                    if let (Some(i), Some(j)) = (mapping[b.first], mapping[b.second]) {
+                        let ideal = ideal_bond_length(monomer, &neighbors, b.first, b.second);
+                        bond_z2 += report.check_bond(atoms, [i, j], ideal, BOND_SIGMA);
+                    }
+                }
+                // ---------- angles
+                for (center, nbors) in neighbors.iter().enumerate() {
+                    let Some(j) = mapping[center] else { continue };
+                    for (n, &first) in nbors.iter().enumerate() {
+                        for &third in nbors.iter().skip(n + 1) {
+                            if let (Some(i), Some(k)) = (mapping[first], mapping[third]) {
+                                let ideal = planar_angle3(&monomer.atoms()[first].ideal_pos,
+                                      &monomer.atoms()[center].ideal_pos, &monomer.atoms()[third].ideal_pos).to_degrees();
+                                angle_z2 += report.check_angle(atoms, [i, j, k], ideal, ANGLE_SIGMA);
+                            }
+                        }
+                    }
+                }
+                // ---------- chirality
+                for (center, matom) in monomer.atoms().iter().enumerate() {
+                    if matom.stereo_config != 'R' && matom.stereo_config != 'S' { continue; }
+                    let Some(j) = mapping[center] else { continue };
+                    let heavy: Vec<usize> = neighbors[center].iter()
+                        .filter(|&&n| monomer.atoms()[n].atom_type != "H" && mapping[n].is_some())
+                        .cloned().take(3).collect();
+                    if heavy.len() < 3 { continue; }
+                    let ideal_volume = chiral_volume(&matom.ideal_pos, &monomer.atoms()[heavy[0]].ideal_pos,
+                            &monomer.atoms()[heavy[1]].ideal_pos, &monomer.atoms()[heavy[2]].ideal_pos);
+                    let volume = chiral_volume(&atoms[j].pos, &atoms[mapping[heavy[0]].unwrap()].pos,
+                            &atoms[mapping[heavy[1]].unwrap()].pos, &atoms[mapping[heavy[2]].unwrap()].pos);
+                    if volume * ideal_volume < 0.0 {
+                        report.chirality_errors.push(ChiralityError { atom: j, volume, ideal_volume });
+                    }
+                }
+            }
+        }
+
+        // ---------- bonds between residues
+        for i_res in 1..strctr.residue_ids.len() {
+            if strctr.residue_ids[i_res].chain_id != strctr.residue_ids[i_res - 1].chain_id { continue; }
+            let prev = &strctr.atoms_for_residue_id[i_res - 1];
+            let next = &strctr.atoms_for_residue_id[i_res];
+            let find = |range: &Range<usize>, name: &str| range.clone().find(|&i| atoms[i].name == name);
+            if let (Some(c), Some(n)) = (find(prev, " C  "), find(next, " N  ")) {
+                if atoms[c].pos.distance_to(&atoms[n].pos) > MAX_PEPTIDE_BOND { continue; }
+                bond_z2 += report.check_bond(atoms, [c, n], PEPTIDE_BOND.0, PEPTIDE_BOND.1);
+                let triplets = [(find(prev, " CA "), c, Some(n)), (find(prev, " O  "), c, Some(n)), (Some(c), n, find(next, " CA "))];
+                for ((first, center, third), (ideal, sigma)) in triplets.iter().zip(PEPTIDE_ANGLES.iter()) {
+                    if let (Some(i), Some(k)) = (first, third) {
+                        angle_z2 += report.check_angle(atoms, [*i, *center, *k], *ideal, *sigma);
+                    }
+                }
+            }
+            if let (Some(o3), Some(p)) = (find(prev, " O3'"), find(next, " P  ")) {
+                if atoms[o3].pos.distance_to(&atoms[p].pos) > MAX_PHOSPHODIESTER_BOND { continue; }
+                bond_z2 += report.check_bond(atoms, [o3, p], PHOSPHODIESTER_BOND.0, PHOSPHODIESTER_BOND.1);
+            }
+        }
+        if report.bonds_checked > 0 { report.bond_rmsz = (bond_z2 / report.bonds_checked as f64).sqrt(); }
+        if report.angles_checked > 0 { report.angle_rmsz = (angle_z2 / report.angles_checked as f64).sqrt(); }
+
+        // ---------- steric clashes
+        report.clashes = find_clashes(strctr);
+        report.atoms_checked = atoms.iter().filter(|a| is_clash_candidate(a)).count();
+
+        return report;
+    }
+
+    /// Number of serious clashes per 1000 atoms
+    pub fn clashscore(&self) -> f64 {
+        if self.atoms_checked == 0 { return 0.0; }
+        return 1000.0 * self.clashes.len() as f64 / self.atoms_checked as f64;
+    }
+
+    /// Checks a bond, stores it if it's an outlier; returns the squared Z-score
+    fn check_bond(&mut self, atoms: &[PdbAtom], idx: [usize; 2], ideal: f64, sigma: f64) -> f64 {
+        let value = atoms[idx[0]].pos.distance_to(&atoms[idx[1]].pos);
+        let z_score = (value - ideal) / sigma;
+        self.bonds_checked += 1;
+        if z_score.abs() > GEOMETRY_Z_CUTOFF {
+            self.bond_outliers.push(BondOutlier { atoms: idx, value, ideal, z_score });
+        }
+        return z_score * z_score;
+    }
+
+    /// Checks a planar angle, stores it if it's an outlier; returns the squared Z-score
+    fn check_angle(&mut self, atoms: &[PdbAtom], idx: [usize; 3], ideal: f64, sigma: f64) -> f64 {
+        let value = planar_angle3(&atoms[idx[0]].pos, &atoms[idx[1]].pos, &atoms[idx[2]].pos).to_degrees();
+        let z_score = (value - ideal) / sigma;
+        self.angles_checked += 1;
+        if z_score.abs() > GEOMETRY_Z_CUTOFF {
+            self.angle_outliers.push(AngleOutlier { atoms: idx, value, ideal, z_score });
+        }
+        return z_score * z_score;
+    }
+}
+
+impl Display for GeometryReport {
+    /// Prints a single-line summary of the report
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bonds: {} (rmsZ {:.2}, outliers {})  angles: {} (rmsZ {:.2}, outliers {})  chirality errors: {}  clashscore: {:.2}",
+               self.bonds_checked, self.bond_rmsz, self.bond_outliers.len(), self.angles_checked, self.angle_rmsz,
+               self.angle_outliers.len(), self.chirality_errors.len(), self.clashscore())
+    }
+}
+
+/// For every atom of a monomer finds its counterpart within a given residue.
+///
+/// Returned vector is indexed as [`Monomer::atoms()`](Monomer::atoms()) and holds indexes of atoms
+/// of a structure. When alternate locations are present, the first one is used.
+pub(crate) fn monomer_atom_map(monomer: &Monomer, atoms: &[PdbAtom], range: &Range<usize>) -> Vec<Option<usize>> {
+    monomer.atoms().iter()
+        .map(|ma| range.clone().find(|&i| atoms[i].name.trim() == ma.atom_name))
+        .collect()
+}
+
+/// Lists neighbors of every atom of a monomer
+pub(crate) fn monomer_neighbors(monomer: &Monomer) -> Vec<Vec<usize>> {
+    let mut out: Vec<Vec<usize>> = vec![vec![]; monomer.atoms().len()];
+    for b in monomer.bonds() {
+        out[b.first].push(b.second);
+        out[b.second].push(b.first);
+    }
+
+    return out;
+}
+
+/// Ideal length of a bond between two atoms of a monomer.
+///
+/// Monomers are defined in their neutral forms, where carboxyl groups are protonated. Since the two oxygens of
+/// a carboxylate group (e.g. of `ASP` or `GLU` side chain) are equivalent, the average of their C-O bond lengths
+/// is used as the ideal value for both of them.
+fn ideal_bond_length(monomer: &Monomer, neighbors: &[Vec<usize>], first: usize, second: usize) -> f64 {
+    let matoms = monomer.atoms();
+    let length = |i: usize, j: usize| matoms[i].ideal_pos.distance_to(&matoms[j].ideal_pos);
+    for (c, o) in [(first, second), (second, first)] {
+        if matoms[c].atom_type != "C" || matoms[o].atom_type != "O" { continue; }
+        let oxygens: Vec<usize> = neighbors[c].iter().cloned()
+            .filter(|&n| matoms[n].atom_type == "O" && neighbors[n].iter().all(|&k| k == c || matoms[k].atom_type == "H"))
+            .collect();
+        if oxygens.len() == 2 { return 0.5 * (length(c, oxygens[0]) + length(c, oxygens[1])); }
+    }
+
+    return length(first, second);
+}
+
+/// Signed volume of a tetrahedron spanned by a chiral center and three its neighbors
+fn chiral_volume(center: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3) -> f64 {
+    let va = Vec3::sub_s(a, center);
+    let vb = Vec3::sub_s(b, center);
+    let vc = Vec3::sub_s(c, center);
+    return Vec3::dot(&va, &Vec3::cross(&vb, &vc));
+}
@@ -0,0 +1,42 @@
+//! Checks the stereochemical quality of a [`Structure`](crate::Structure)
+//!
+//! Validation is performed for every amino acid residue; the following problems are detected:
+//!
+//!  - Ramachandran outliers, assessed separately for glycine, proline, pre-proline and all other residues;
+//!    see [`RamachandranClass`]
+//!  - side chain rotamer outliers, i.e. residues whose chi angles are far from staggered conformations
+//!  - *cis* peptide bonds and non-planar peptide bonds
+//!
+//! Results are stored per residue in [`ResidueValidation`] records; the [`ValidationReport`] combines
+//! them into an overall quality score that can be used to rank models; the clashscore term of that score
+//! comes from a [`GeometryReport`]. Note, that this score is a heuristic of this crate rather than
+//! the MolProbity score.
+//!
+//! Covalent geometry, i.e. bond lengths, planar angles and chirality, as well as steric clashes
+//! are assessed by a [`GeometryReport`].
+//!
+//! # Example
+//! ```
+//! # use bioshell_pdb::{Deposit, PDBError};
+//! # fn main() -> Result<(), PDBError> {
+//! use bioshell_pdb::validation::{GeometryReport, ValidationReport};
+//! let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+//! let strctr = deposit.structure()?;
+//! let report = ValidationReport::new(&strctr);
+//! assert_eq!(report.residues().len(), 56);
+//! assert!(report.ramachandran_favored() > 0.9);
+//! println!("score: {:.2}", report.quality_score(&GeometryReport::new(&strctr)));
+//! # Ok(())
+//! # }
+//! ```
+mod ramachandran;
+mod rotamers;
+mod residue_validation;
+mod geometry;
+mod clashes;
+
+pub use ramachandran::{ramachandran_class, ramachandran_region, RamachandranClass, RamachandranRegion};
+pub use rotamers::{is_rotamer_outlier, rotamer_chi_targets, CIS_PEPTIDE_MAX_OMEGA, NON_PLANAR_PEPTIDE_TOLERANCE, ROTAMER_TOLERANCE};
+pub use residue_validation::{ResidueValidation, ValidationReport};
+pub use geometry::{AngleOutlier, BondOutlier, ChiralityError, GeometryReport, ANGLE_SIGMA, BOND_SIGMA, GEOMETRY_Z_CUTOFF};
+pub use clashes::{find_clashes, Clash, CLASH_OVERLAP, HBOND_MIN_DISTANCE};
@@ -0,0 +1,154 @@
+use std::fmt::{Display, Formatter};
+use crate::{ResidueId, Structure};
+use crate::pdb_parsing_error::PDBError;
+
+/// Residue classes that are assessed by separate Ramachandran maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RamachandranClass {
+    /// any residue that doesn't fall into other classes
+    General,
+    /// glycine, which can access also the left-handed region of the map
+    Glycine,
+    /// proline, which has its phi angle restricted by the ring
+    Proline,
+    /// a non-glycine, non-proline residue followed by a proline
+    PrePro,
+}
+
+impl Display for RamachandranClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RamachandranClass::General => "general",
+            RamachandranClass::Glycine => "Gly",
+            RamachandranClass::Proline => "Pro",
+            RamachandranClass::PrePro => "pre-Pro",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Assessment of a residue conformation on a Ramachandran map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RamachandranRegion {
+    /// the most populated regions of the map
+    Favored,
+    /// regions observed in high-resolution structures, but not frequently
+    Allowed,
+    /// phi, psi combination very rarely observed in high-resolution structures
+    Outlier,
+}
+
+impl Display for RamachandranRegion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RamachandranRegion::Favored => "favored",
+            RamachandranRegion::Allowed => "allowed",
+            RamachandranRegion::Outlier => "OUTLIER",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Populated regions of a Ramachandran map: phi, psi of a center and its widths (all in degrees)
+type RamaCluster = (f64, f64, f64, f64);
+
+const GENERAL_CLUSTERS: [RamaCluster; 6] = [
+    (-63.0, -42.0, 22.0, 22.0),       // right-handed alpha helix
+    (-90.0, -5.0, 30.0, 25.0),        // bridge region
+    (-120.0, 130.0, 40.0, 35.0),      // beta strand
+    (-70.0, 145.0, 20.0, 25.0),       // polyproline II
+    (-150.0, 165.0, 25.0, 25.0),      // extended
+    (60.0, 40.0, 15.0, 20.0),         // left-handed alpha helix
+];
+
+const GLYCINE_CLUSTERS: [RamaCluster; 7] = [
+    (-63.0, -42.0, 25.0, 25.0),
+    (63.0, 42.0, 25.0, 25.0),
+    (-90.0, 0.0, 30.0, 30.0),
+    (90.0, 0.0, 30.0, 30.0),
+    (180.0, 180.0, 40.0, 35.0),
+    (-80.0, 170.0, 30.0, 30.0),
+    (80.0, -170.0, 30.0, 30.0),
+];
+
+const PROLINE_CLUSTERS: [RamaCluster; 3] = [
+    (-63.0, -35.0, 15.0, 25.0),
+    (-68.0, 145.0, 15.0, 25.0),
+    (-85.0, 0.0, 15.0, 25.0),
+];
+
+const PREPRO_CLUSTERS: [RamaCluster; 4] = [
+    (-120.0, 140.0, 35.0, 30.0),
+    (-70.0, 145.0, 20.0, 25.0),
+    (-75.0, -35.0, 20.0, 20.0),
+    (55.0, 45.0, 12.0, 15.0),
+];
+
+/// Conformations closer than that (in the units of cluster widths) to a cluster center are favored
+const FAVORED_DISTANCE: f64 = 1.5;
+
+/// Conformations further than that (in the units of cluster widths) from every cluster are outliers
+const ALLOWED_DISTANCE: f64 = 2.5;
+
+/// Says which Ramachandran map should be used to assess a given residue.
+///
+/// Returns an error when the residue can't be found in the given structure.
+pub fn ramachandran_class(strctr: &Structure, which_res: &ResidueId) -> Result<RamachandranClass, PDBError> {
+    let i_res = strctr.residue_pos(which_res)?;
+    let res_name = &strctr.atoms[strctr.atoms_for_residue_id[i_res].start].res_name;
+    if res_name == "GLY" { return Ok(RamachandranClass::Glycine); }
+    if res_name == "PRO" { return Ok(RamachandranClass::Proline); }
+    if i_res + 1 < strctr.residue_ids.len() && strctr.residue_ids[i_res + 1].chain_id == which_res.chain_id {
+        let next_name = &strctr.atoms[strctr.atoms_for_residue_id[i_res + 1].start].res_name;
+        if next_name == "PRO" { return Ok(RamachandranClass::PrePro); }
+    }
+
+    return Ok(RamachandranClass::General);
+}
+
+/// Assesses a residue conformation on a Ramachandran map.
+///
+/// Each residue class has its own map, given as a set of populated regions. The distance from
+/// a given (phi, psi) point to each region center is measured in the units of the region width;
+/// a residue closer than 1.5 unit to any region is *favored*, while the one that is further than 2.5
+/// units from all of them is an *outlier*.
+///
+/// The regions are a coarse approximation of the secondary structure basins, made for this crate;
+/// they are not fitted to any published reference data set, such as the Top8000 distributions used
+/// by MolProbity, so the fractions of favored and outlier residues may differ from those tools.
+///
+/// Both angles are given in degrees.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::validation::{ramachandran_region, RamachandranClass, RamachandranRegion};
+/// // --- an alpha helix
+/// assert_eq!(ramachandran_region(RamachandranClass::General, -60.0, -45.0), RamachandranRegion::Favored);
+/// // --- left-handed helix is frequently observed for glycine only
+/// assert_eq!(ramachandran_region(RamachandranClass::Glycine, 80.0, 10.0), RamachandranRegion::Favored);
+/// assert_eq!(ramachandran_region(RamachandranClass::General, 80.0, -150.0), RamachandranRegion::Outlier);
+/// // --- proline can't adopt the beta-strand conformation
+/// assert_eq!(ramachandran_region(RamachandranClass::Proline, -140.0, 140.0), RamachandranRegion::Outlier);
+/// ```
+pub fn ramachandran_region(class: RamachandranClass, phi: f64, psi: f64) -> RamachandranRegion {
+    let clusters: &[RamaCluster] = match class {
+        RamachandranClass::General => &GENERAL_CLUSTERS,
+        RamachandranClass::Glycine => &GLYCINE_CLUSTERS,
+        RamachandranClass::Proline => &PROLINE_CLUSTERS,
+        RamachandranClass::PrePro => &PREPRO_CLUSTERS,
+    };
+    let d = clusters.iter().map(|c| {
+        let dphi = angle_difference(phi, c.0) / c.2;
+        let dpsi = angle_difference(psi, c.1) / c.3;
+        (dphi * dphi + dpsi * dpsi).sqrt()
+    }).fold(f64::MAX, f64::min);
+
+    return if d <= FAVORED_DISTANCE { RamachandranRegion::Favored }
+        else if d <= ALLOWED_DISTANCE { RamachandranRegion::Allowed }
+        else { RamachandranRegion::Outlier };
+}
+
+/// Difference between two angles given in degrees, in the range [-180, 180)
+pub(crate) fn angle_difference(a: f64, b: f64) -> f64 {
+    (a - b + 180.0).rem_euclid(360.0) - 180.0
+}
@@ -0,0 +1,186 @@
+use std::fmt::{Display, Formatter};
+use crate::{ResidueId, Structure};
+use crate::calc::protein_torsions;
+use crate::residue_filters::{ArePeptideBonded, ResidueFilter2};
+use crate::validation::{is_rotamer_outlier, ramachandran_class, ramachandran_region, GeometryReport,
+                        RamachandranClass, RamachandranRegion, CIS_PEPTIDE_MAX_OMEGA, NON_PLANAR_PEPTIDE_TOLERANCE};
+
+/// Results of validation of a single amino acid residue.
+///
+/// Angles are given in degrees. The `omega` value describes the peptide bond that precedes the residue,
+/// i.e. the bond between its `N` atom and the `C` atom of the previous residue; therefore a *cis* proline
+/// is flagged on the proline residue itself.
+#[derive(Debug, Clone)]
+pub struct ResidueValidation {
+    /// identifies the residue
+    pub res_id: ResidueId,
+    /// three-letter code of the residue
+    pub res_name: String,
+    /// Ramachandran map used to assess this residue
+    pub rama_class: RamachandranClass,
+    /// phi dihedral angle, if it can be computed
+    pub phi: Option<f64>,
+    /// psi dihedral angle, if it can be computed
+    pub psi: Option<f64>,
+    /// assessment of the phi, psi conformation; `None` for terminal residues
+    pub rama_region: Option<RamachandranRegion>,
+    /// `Some(true)` for a side chain conformation far from any rotamer; `None` if it can't be assessed
+    pub rotamer_outlier: Option<bool>,
+    /// omega angle of the preceding peptide bond
+    pub omega: Option<f64>,
+    /// `true` if the preceding peptide bond is *cis*
+    pub cis_peptide: bool,
+    /// `true` if the preceding peptide bond is neither *cis* nor *trans*
+    pub non_planar_peptide: bool,
+}
+
+impl ResidueValidation {
+    /// Column names of a table created by printing [`ResidueValidation`] rows
+    pub const TABLE_HEADER: &'static str = "#res_id   res   class      phi     psi   omega rama     rotamer  peptide";
+
+    /// Returns `true` if any problem has been detected for this residue
+    pub fn has_problems(&self) -> bool {
+        self.rama_region == Some(RamachandranRegion::Outlier) || self.rotamer_outlier == Some(true)
+            || self.cis_peptide || self.non_planar_peptide
+    }
+}
+
+impl Display for ResidueValidation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let angle = |v: Option<f64>| match v { Some(a) => format!("{:7.1}", a), None => format!("{:>7}", "-") };
+        let rama = match self.rama_region { Some(r) => r.to_string(), None => "-".to_string() };
+        let rotamer = match self.rotamer_outlier { Some(true) => "OUTLIER", Some(false) => "ok", None => "-" };
+        let peptide = if self.cis_peptide { "cis" } else if self.non_planar_peptide { "NON-PLANAR" }
+            else if self.omega.is_some() { "trans" } else { "-" };
+        write!(f, "{:8} {:>4} {:8} {} {} {} {:8} {:8} {}", self.res_id, self.res_name, self.rama_class.to_string(),
+               angle(self.phi), angle(self.psi), angle(self.omega), rama, rotamer, peptide)
+    }
+}
+
+/// Validation results for a whole structure.
+///
+/// The report holds a [`ResidueValidation`] record for every amino acid residue of a structure and provides
+/// summary statistics. See the [module documentation](crate::validation) for an example.
+pub struct ValidationReport {
+    residues: Vec<ResidueValidation>,
+}
+
+impl ValidationReport {
+
+    /// Validates all amino acid residues of a given structure
+    pub fn new(strctr: &Structure) -> ValidationReport {
+        let torsions = protein_torsions(strctr);
+        let mut residues: Vec<ResidueValidation> = Vec::with_capacity(torsions.len());
+        for (i, row) in torsions.iter().enumerate() {
+            let rama_class = ramachandran_class(strctr, &row.res_id).unwrap_or(RamachandranClass::General);
+            let phi = row.phi.map(|a| a.to_degrees());
+            let psi = row.psi.map(|a| a.to_degrees());
+            let rama_region = match (phi, psi) {
+                (Some(phi), Some(psi)) => Some(ramachandran_region(rama_class, phi, psi)),
+                _ => None
+            };
+            // --- omega of the preceding peptide bond, provided that the two residues are bonded
+            let omega = if i > 0 && ArePeptideBonded.check(strctr, &torsions[i - 1].res_id, &row.res_id) {
+                torsions[i - 1].omega.map(|a| a.to_degrees())
+            } else { None };
+            let cis_peptide = omega.is_some_and(|w| w.abs() < CIS_PEPTIDE_MAX_OMEGA);
+            let non_planar_peptide = omega.is_some_and(|w| w.abs() >= CIS_PEPTIDE_MAX_OMEGA
+                && w.abs() < 180.0 - NON_PLANAR_PEPTIDE_TOLERANCE);
+            residues.push(ResidueValidation {
+                res_id: row.res_id.clone(),
+                res_name: row.res_name.clone(),
+                rama_class, phi, psi, rama_region,
+                rotamer_outlier: is_rotamer_outlier(strctr, &row.res_id).ok(),
+                omega, cis_peptide, non_planar_peptide,
+            });
+        }
+
+        return ValidationReport { residues };
+    }
+
+    /// Validation records, one for each amino acid residue
+    pub fn residues(&self) -> &Vec<ResidueValidation> { &self.residues }
+
+    /// Fraction of residues in favored regions of Ramachandran maps
+    ///
+    /// Residues whose phi and psi can't be computed are not counted
+    pub fn ramachandran_favored(&self) -> f64 {
+        self.rama_fraction(RamachandranRegion::Favored)
+    }
+
+    /// Fraction of Ramachandran outliers
+    pub fn ramachandran_outliers(&self) -> f64 {
+        self.rama_fraction(RamachandranRegion::Outlier)
+    }
+
+    /// Fraction of residues whose side chain is a rotamer outlier
+    ///
+    /// Only residues whose side chain can be assessed are counted
+    pub fn rotamer_outliers(&self) -> f64 {
+        let assessed = self.residues.iter().filter(|r| r.rotamer_outlier.is_some()).count();
+        if assessed == 0 { return 0.0; }
+        let outliers = self.residues.iter().filter(|r| r.rotamer_outlier == Some(true)).count();
+        return outliers as f64 / assessed as f64;
+    }
+
+    /// Number of *cis* peptide bonds that precede a non-proline residue
+    pub fn count_cis_nonpro(&self) -> usize {
+        self.residues.iter().filter(|r| r.cis_peptide && r.res_name != "PRO").count()
+    }
+
+    /// Number of *cis* peptide bonds
+    pub fn count_cis_peptides(&self) -> usize { self.residues.iter().filter(|r| r.cis_peptide).count() }
+
+    /// Number of non-planar peptide bonds
+    pub fn count_non_planar_peptides(&self) -> usize {
+        self.residues.iter().filter(|r| r.non_planar_peptide).count()
+    }
+
+    /// Overall quality score of a structure; the lower the better.
+    ///
+    /// This is a heuristic of BioShell, meant to rank models of the same protein; it is **not** the MolProbity score,
+    /// although it combines similar terms. The clashscore of a given [`GeometryReport`] counts clashes between
+    /// heavy atoms only, and Ramachandran regions are approximated by the clusters of [`ramachandran_region()`],
+    /// so the values can't be compared with those reported by MolProbity. The score is computed from the clashscore
+    /// and the rotamer and Ramachandran statistics of this report, with a penalty for non-proline *cis*
+    /// and non-planar peptide bonds:
+    ///
+    /// ```text
+    /// 0.426 ln(1 + clashscore) + 0.33 ln(1 + max(0, rota_out - 1)) + 0.25 ln(1 + max(0, 100 - rama_fav - 2)) + 0.5
+    ///     + ln(1 + 100 * n_pept / N)
+    /// ```
+    /// where `rota_out` and `rama_fav` are percentages of rotamer outliers and favored residues, respectively;
+    /// `n_pept` is the number of non-proline *cis* and non-planar peptide bonds in a structure of `N` residues.
+    ///
+    /// # Example
+    /// ```
+    /// # use bioshell_pdb::{Deposit, PDBError};
+    /// # fn main() -> Result<(), PDBError> {
+    /// use bioshell_pdb::validation::{GeometryReport, ValidationReport};
+    /// let strctr = Deposit::from_file("./tests/test_files/2gb1.pdb")?.structure()?;
+    /// let report = ValidationReport::new(&strctr);
+    /// let mut geometry = GeometryReport::new(&strctr);
+    /// let score = report.quality_score(&geometry);
+    /// // --- the clashscore term can only make the score worse
+    /// geometry.clashes.clear();
+    /// assert!(score >= report.quality_score(&geometry));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn quality_score(&self, geometry: &GeometryReport) -> f64 {
+        let rota_out = 100.0 * self.rotamer_outliers();
+        let rama_fav = 100.0 * self.ramachandran_favored();
+        let n = self.residues.len().max(1) as f64;
+        let n_pept = (self.count_cis_nonpro() + self.count_non_planar_peptides()) as f64;
+        return 0.426 * (1.0 + geometry.clashscore()).ln() + 0.33 * (1.0 + (rota_out - 1.0).max(0.0)).ln()
+            + 0.25 * (1.0 + (100.0 - rama_fav - 2.0).max(0.0)).ln() + 0.5
+            + (1.0 + 100.0 * n_pept / n).ln();
+    }
+
+    fn rama_fraction(&self, region: RamachandranRegion) -> f64 {
+        let assessed = self.residues.iter().filter(|r| r.rama_region.is_some()).count();
+        if assessed == 0 { return 0.0; }
+        let n = self.residues.iter().filter(|r| r.rama_region == Some(region)).count();
+        return n as f64 / assessed as f64;
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{ResidueId, Structure};
+use crate::calc::{chi, count_chi_angles};
+use crate::pdb_parsing_error::PDBError;
+use crate::validation::ramachandran::angle_difference;
+
+/// Maximum deviation (in degrees) of a chi angle from the closest rotamer value
+pub const ROTAMER_TOLERANCE: f64 = 35.0;
+
+/// A peptide bond with the absolute value of its omega angle (in degrees) below that value is *cis*
+pub const CIS_PEPTIDE_MAX_OMEGA: f64 = 30.0;
+
+/// Maximum deviation (in degrees) of an omega angle from 180 (*trans*) or 0 (*cis*) for a planar peptide bond
+pub const NON_PLANAR_PEPTIDE_TOLERANCE: f64 = 30.0;
+
+const STAGGERED: [f64; 3] = [60.0, 180.0, -60.0];
+const PERPENDICULAR: [f64; 2] = [90.0, -90.0];
+
+/// Returns values (in degrees) a given chi angle of a residue type is expected to adopt.
+///
+/// A chi angle around a bond between two sp<sup>3</sup> atoms should be staggered, i.e. close to 60, 180 or -60 degrees;
+/// aromatic rings of `PHE` and `TYR` are perpendicular to the CA-CB-CG plane. `None` is returned for
+/// chi angles that have no well-defined rotamers (e.g. those involving planar groups, such as chi2 of `ASP`)
+/// or are not defined for that residue type.
+///
+/// # Example
+/// ```
+/// use bioshell_pdb::validation::rotamer_chi_targets;
+/// assert_eq!(rotamer_chi_targets("LYS", 4).unwrap().len(), 3);
+/// assert_eq!(rotamer_chi_targets("PHE", 2).unwrap().len(), 2);
+/// assert!(rotamer_chi_targets("ASP", 2).is_none());
+/// ```
+pub fn rotamer_chi_targets(res_name: &str, k: usize) -> Option<&'static [f64]> {
+    if k == 0 || k > count_chi_angles(res_name) { return None; }
+    return match (res_name, k) {
+        ("PRO", _) => None,
+        ("ASN" | "ASP" | "HIS" | "TRP", 2) => None,
+        ("GLN" | "GLU", 3) => None,
+        ("ARG", 4) => None,
+        ("PHE" | "TYR", 2) => Some(&PERPENDICULAR),
+        _ => Some(&STAGGERED),
+    };
+}
+
+/// Returns `true` if any side chain dihedral angle of a given residue is far from its rotamer values.
+///
+/// Chi angles are compared with values given by [`rotamer_chi_targets()`]; the residue is flagged as
+/// an outlier when any chi angle deviates by more than [`ROTAMER_TOLERANCE`] from the closest target.
+/// Returns an error if any atom necessary to compute a chi angle is missing.
+pub fn is_rotamer_outlier(strctr: &Structure, which_res: &ResidueId) -> Result<bool, PDBError> {
+    let i_res = strctr.residue_pos(which_res)?;
+    let res_name = &strctr.atoms[strctr.atoms_for_residue_id[i_res].start].res_name;
+    for k in 1..=count_chi_angles(res_name) {
+        if let Some(targets) = rotamer_chi_targets(res_name, k) {
+            let chi_k = chi(strctr, which_res, k)?.to_degrees();
+            let d = targets.iter().map(|t| angle_difference(chi_k, *t).abs()).fold(f64::MAX, f64::min);
+            if d > ROTAMER_TOLERANCE { return Ok(true); }
+        }
+    }
+
+    return Ok(false);
+}
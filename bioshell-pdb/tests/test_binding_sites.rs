@@ -0,0 +1,56 @@
+use bioshell_pdb::{Deposit, PDBError, ResidueId};
+use bioshell_pdb::binding_sites::{binding_site, find_binding_sites, ligand_residues, BINDING_SITE_CUTOFF, ContactType};
+
+#[test]
+fn heme_binding_sites() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/4esa.cif")?;
+    let strctr = deposit.structure()?;
+    let ligands = ligand_residues(&deposit, &strctr);
+    // --- four hemes, four CO molecules and a glycerol; the ACE cap is a part of a polymer
+    assert_eq!(ligands.len(), 9);
+    assert!(ligands.iter().all(|r| strctr.atoms_in_residue(r).unwrap().next().unwrap().res_name != "ACE"));
+
+    let site = binding_site(&strctr, &ResidueId::new("A", 202, ' '), BINDING_SITE_CUTOFF)?;
+    assert_eq!(site.ligand_name, "HEM");
+    // --- the proximal histidine coordinates the iron
+    let metal: Vec<_> = site.contacts_of_type(ContactType::MetalCoordination).collect();
+    assert_eq!(metal.len(), 1);
+    let his = &strctr.atoms()[metal[0].partner_atom];
+    assert_eq!((his.res_name.as_str(), his.res_seq, his.name.as_str()), ("HIS", 88, " NE2"));
+    assert!((metal[0].distance - 2.12).abs() < 0.01);
+    // --- CO bound to the iron is a part of the site
+    assert!(site.residues.contains(&ResidueId::new("A", 201, ' ')));
+    assert!(site.residues.contains(&ResidueId::new("A", 88, ' ')));
+    assert!(site.count_contacts(ContactType::Hydrophobic) > 10);
+    // --- the proximal histidine is perpendicular to the heme, but not stacked
+    assert_eq!(site.count_contacts(ContactType::PiStacking), 0);
+    // --- only propionate oxygens form hydrogen bonds; nitrogens coordinate the iron
+    assert_eq!(site.count_contacts(ContactType::HydrogenBond), 3);
+    for c in site.contacts_of_type(ContactType::HydrogenBond) {
+        assert!(c.distance <= 3.5);
+        assert!(strctr.atoms()[c.ligand_atom].name.starts_with(" O"));
+    }
+    // --- the pocket holds the heme and all the site residues
+    assert_eq!(site.pocket.residues().len(), site.residues.len() + 1);
+    assert_eq!(site.pocket.count_atoms(), site.residues.iter().chain([&site.ligand])
+        .map(|r| strctr.atoms_in_residue(r).unwrap().count()).sum::<usize>());
+    Ok(())
+}
+
+#[test]
+fn all_binding_sites() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/5edw.cif")?;
+    let sites = find_binding_sites(&deposit, BINDING_SITE_CUTOFF)?;
+    assert_eq!(sites.iter().filter(|s| s.ligand_name == "TTP").count(), 1);
+    assert_eq!(sites.iter().filter(|s| s.ligand_name == "CA").count(), 3);
+    let ttp = sites.iter().find(|s| s.ligand_name == "TTP").unwrap();
+    assert!(ttp.count_contacts(ContactType::HydrogenBond) > 0);
+    // --- calcium ions coordinate the triphosphate
+    assert_eq!(ttp.count_contacts(ContactType::MetalCoordination), 4);
+    assert!(ttp.count_contacts(ContactType::PiStacking) > 0);
+
+    // --- no ligands at all
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    assert!(find_binding_sites(&deposit, BINDING_SITE_CUTOFF)?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use bioshell_pdb::{Deposit, PDBError, ResidueId};
+use bioshell_pdb::chain_mapping::MissingSegmentKind;
+
+#[test]
+fn missing_termini_from_cif() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/4esa.cif")?;
+    // --- two C-terminal residues: TYR 145 and HIS 146 are missing in chain B
+    let seq_map = deposit.chain_sequence_map("B")?;
+    assert_eq!(seq_map.len(), 146);
+    assert_eq!(seq_map.count_missing(), 2);
+    let missing = seq_map.missing_segments();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].kind, MissingSegmentKind::CTerminus);
+    assert_eq!(missing[0].first_seq_id, 145);
+    assert_eq!(missing[0].res_names, vec!["TYR", "HIS"]);
+    assert_eq!(missing[0].before, Some(ResidueId::new("B", 144, ' ')));
+    assert!(missing[0].after.is_none());
+    assert_eq!(seq_map.seq_id(&ResidueId::new("B", 144, ' ')), Some(144));
+    assert!(seq_map.residue_id(145).is_none());
+
+    // --- N-terminal GLY of chain A of 2fdo
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let seq_map = deposit.chain_sequence_map("A")?;
+    let missing = seq_map.missing_segments();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].kind, MissingSegmentKind::NTerminus);
+    assert_eq!(missing[0].len(), 1);
+    Ok(())
+}
+
+#[test]
+fn missing_loop_with_insertion_codes() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/1c5n.cif")?;
+    let seq_map = deposit.chain_sequence_map("H")?;
+    let missing = seq_map.missing_segments();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].kind, MissingSegmentKind::Loop);
+    assert_eq!(missing[0].len(), 7);
+    assert_eq!(missing[0].before, Some(ResidueId::new("H", 147, ' ')));
+    assert_eq!(missing[0].after, Some(ResidueId::new("H", 150, ' ')));
+    // --- chymotrypsin numbering of thrombin skips some residue numbers
+    let jumps = seq_map.numbering_jumps();
+    assert!(!jumps.is_empty());
+    for (a, b) in &jumps {
+        assert!(b.res_seq != a.res_seq + 1);
+    }
+    assert!(seq_map.chain_breaks(&deposit.structure()?).is_empty());
+    Ok(())
+}
+
+#[test]
+fn seqres_mapping_from_pdb() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    let seq_map = deposit.chain_sequence_map("A")?;
+    assert_eq!(seq_map.len(), 56);
+    assert_eq!(seq_map.count_missing(), 0);
+    assert!(seq_map.missing_segments().is_empty());
+    assert!(seq_map.numbering_jumps().is_empty());
+    assert!(seq_map.chain_breaks(&deposit.structure()?).is_empty());
+    Ok(())
+}
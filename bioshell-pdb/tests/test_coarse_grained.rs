@@ -0,0 +1,38 @@
+use bioshell_pdb::{Deposit, PDBError};
+use bioshell_pdb::coarse_grained::{CoarseGrainedMapping, CoarseGrainedModel};
+
+#[test]
+fn ca_models() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    let ca = CoarseGrainedMapping::load(CoarseGrainedModel::CA)?.coarse_grain(&strctr);
+    assert_eq!(ca.count_atoms(), 56);
+    assert!(ca.atoms().iter().all(|a| a.name == " CA " && a.element.as_deref() == Some("C")));
+    for (bead, atom) in ca.atoms().iter().zip(strctr.atoms().iter().filter(|a| a.name == " CA ")) {
+        assert!(bead.pos.distance_to(&atom.pos) < 1e-6);
+    }
+
+    let centroids = CoarseGrainedMapping::load(CoarseGrainedModel::CACentroid)?.coarse_grain(&strctr);
+    let n_gly = ca.atoms().iter().filter(|a| a.res_name == "GLY").count();
+    assert_eq!(centroids.count_atoms(), 2 * 56 - n_gly);
+    assert!(centroids.atoms().iter().filter(|a| a.res_name == "GLY").all(|a| a.name == " CA "));
+    Ok(())
+}
+
+#[test]
+fn nucleotide_beads() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/5edw.cif")?.structure()?;
+    let cg = CoarseGrainedMapping::load(CoarseGrainedModel::Nucleotide3Bead)?.coarse_grain(&strctr);
+    let n_sugar = cg.atoms().iter().filter(|a| a.name == " S  ").count();
+    let n_base = cg.atoms().iter().filter(|a| a.name == " B  ").count();
+    assert!(n_sugar > 0);
+    assert_eq!(n_sugar, n_base);
+    // --- amino acids are not mapped by the nucleotide model
+    assert!(cg.atoms().iter().all(|a| a.res_name.starts_with('D')));
+    Ok(())
+}
+
+#[test]
+fn unknown_model() {
+    let result = CoarseGrainedMapping::from_cif_file("./data/coarse_grained.cif", "NO_SUCH_MODEL");
+    assert!(matches!(result, Err(PDBError::UnknownCoarseGrainedModel { .. })));
+}
@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+use bioshell_pdb::{Deposit, PDBError};
+use bioshell_pdb::monomers::{ComponentDictionary, MonomerManager};
+use bioshell_seq::chemical::{MonomerType, ResidueType, ResidueTypeManager, StandardResidueType};
+
+/// Copies the test dictionary into a temporary location, so its index is not written into the source tree
+fn dictionary_copy(name: &str) -> Result<PathBuf, PDBError> {
+    let fname = std::env::temp_dir().join(name);
+    std::fs::copy("./tests/test_files/components_subset.cif", &fname)?;
+    let _ = std::fs::remove_file(format!("{}.idx", fname.to_str().unwrap()));
+    Ok(fname)
+}
+
+#[test]
+fn component_dictionary_index() -> Result<(), PDBError> {
+    let fname = dictionary_copy("components_test_index.cif")?;
+    let fname = fname.to_str().unwrap();
+    let dictionary = ComponentDictionary::open(fname)?;
+    // --- the index has been created ...
+    assert!(PathBuf::from(format!("{}.idx", fname)).exists());
+    assert_eq!(dictionary.len(), 4);
+    let sep = dictionary.entry("SEP").unwrap();
+    assert_eq!(sep.name, "PHOSPHOSERINE");
+    assert_eq!(sep.parent_type(), StandardResidueType::SER);
+    assert_eq!(sep.residue_type().chem_compound_type, MonomerType::LPeptideLinking);
+    let act = dictionary.entry("ACT").unwrap();
+    assert_eq!(act.parent, None);
+    assert_eq!(act.residue_type().chem_compound_type, MonomerType::NonPolymer);
+
+    // --- ... and it's used when the dictionary is opened again
+    let dictionary = ComponentDictionary::open(fname)?;
+    let ptr = dictionary.monomer("PTR").unwrap();
+    assert_eq!(ptr.count_residue_heavy(), 16);
+    assert!(ptr.atom_index("P").is_some());
+    assert_eq!(dictionary.monomer("ACT").unwrap().bonds().len(), 3);
+    assert!(dictionary.monomer("XYZ").is_none());
+    Ok(())
+}
+
+#[test]
+fn multiline_component_name() -> Result<(), PDBError> {
+    let text = "data_0AF
+#
+_chem_comp.id                                    0AF
+_chem_comp.name
+;(2S)-2-azanyl-3-(7-oxidanyl-1H-indol-3-yl)
+propanoic acid
+;
+_chem_comp.type
+'L-peptide linking'
+_chem_comp.mon_nstd_parent_comp_id               TRP
+#
+data_ACT
+#
+_chem_comp.id                                    ACT
+_chem_comp.name                                  \"ACETATE ION\"
+_chem_comp.type                                  NON-POLYMER
+#
+";
+    let fname = std::env::temp_dir().join("components_test_multiline.cif");
+    std::fs::write(&fname, text)?;
+    let entries = ComponentDictionary::index_entries(fname.to_str().unwrap())?;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "(2S)-2-azanyl-3-(7-oxidanyl-1H-indol-3-yl) propanoic acid");
+    assert_eq!(entries[0].monomer_type, "L-peptide linking");
+    assert_eq!(entries[0].parent_type(), StandardResidueType::TRP);
+    assert_eq!(entries[1].code, "ACT");
+    assert_eq!(entries[1].name, "ACETATE ION");
+    Ok(())
+}
+
+#[test]
+fn gzipped_component_dictionary() -> Result<(), PDBError> {
+    let fname = std::env::temp_dir().join("components_test_gz.cif.gz");
+    let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&fname)?, flate2::Compression::default());
+    std::io::copy(&mut std::fs::File::open("./tests/test_files/components_subset.cif")?, &mut encoder)?;
+    encoder.finish()?;
+    let plain = std::env::temp_dir().join("components_test_gz.cif");
+    let _ = std::fs::remove_file(&plain);
+    let dictionary = ComponentDictionary::open(fname.to_str().unwrap())?;
+    // --- the dictionary is decompressed once and read from the plain copy
+    assert_eq!(dictionary.file_name(), plain.to_str().unwrap());
+    assert_eq!(std::fs::read_to_string(&plain)?, std::fs::read_to_string("./tests/test_files/components_subset.cif")?);
+    assert_eq!(dictionary.monomer("ACT").unwrap().count_all_atoms(), 4);
+    assert_eq!(dictionary.monomer("MSE").unwrap().count_residue_heavy(), 8);
+    Ok(())
+}
+
+#[test]
+fn modified_residue_types() -> Result<(), PDBError> {
+    // --- MSE residue type is registered from the deposit with no parent type ...
+    let strctr = Deposit::from_file("./tests/test_files/2fdo.cif")?.structure()?;
+    let fname = dictionary_copy("components_test_types.cif")?;
+    // --- ... until the dictionary provides one
+    MonomerManager::get().load_components(fname.to_str().unwrap())?;
+    assert_eq!(ResidueTypeManager::get().by_code3("MSE").unwrap().parent_type, StandardResidueType::MET);
+    assert_eq!(ResidueTypeManager::get().by_code3("PTR").unwrap().parent_type, StandardResidueType::TYR);
+    assert_eq!(ResidueTypeManager::get().by_code3("ALA").unwrap().parent_type, StandardResidueType::ALA);
+    let mut n_mse = 0;
+    for res_id in strctr.residues().iter() {
+        let res_type = strctr.residue_type(res_id)?;
+        if res_type.code3 == "MSE" {
+            assert_eq!(res_type.parent_type, StandardResidueType::MET);
+            n_mse += 1;
+        }
+    }
+    assert!(n_mse > 0);
+    assert_eq!(MonomerManager::get().by_code3("MSE").map(|m| m.count_residue_heavy()), Some(8));
+    Ok(())
+}
+
+#[test]
+fn missing_ideal_coordinates() -> Result<(), PDBError> {
+    // --- ideal coordinates of CA are missing, so its model coordinates are used
+    let ala = std::fs::read_to_string("./data/monomers/ALA.cif")?;
+    let fname = std::env::temp_dir().join("components_test_coordinates.cif");
+    std::fs::write(&fname, ala.replace("13.411 0.257  0.418  0.692", "13.411 ?      ?      ?    "))?;
+    let _ = std::fs::remove_file(format!("{}.idx", fname.to_str().unwrap()));
+    let dictionary = ComponentDictionary::open(fname.to_str().unwrap())?;
+    let ca = &dictionary.monomer("ALA").unwrap().atoms()[1];
+    assert!((ca.ideal_pos.x - 1.169).abs() < 1e-6 && (ca.ideal_pos.z - 13.411).abs() < 1e-6);
+
+    // --- a component without any coordinates of an atom can't be used
+    std::fs::write(&fname, ala.replace("1.169  26.942 13.411 0.257  0.418  0.692", "?      ?      ?      ?      ?      ?    "))?;
+    let _ = std::fs::remove_file(format!("{}.idx", fname.to_str().unwrap()));
+    let dictionary = ComponentDictionary::open(fname.to_str().unwrap())?;
+    assert!(dictionary.monomer("ALA").is_none());
+    Ok(())
+}
+
+#[test]
+fn minimal_monomer_definition() -> Result<(), PDBError> {
+    // --- only the four required _chem_comp_atom items are given: no stereo configuration and no coordinates
+    let cif = "data_XGL
+loop_
+_chem_comp_atom.comp_id
+_chem_comp_atom.atom_id
+_chem_comp_atom.type_symbol
+_chem_comp_atom.pdbx_leaving_atom_flag
+_chem_comp_atom.pdbx_ordinal
+XGL N   N N 1
+XGL CA  C N 2
+XGL C   C N 3
+XGL O   O N 4
+XGL OXT O Y 5
+";
+    let fname = std::env::temp_dir().join("minimal_monomer_XGL.cif");
+    std::fs::write(&fname, cif)?;
+    ResidueTypeManager::get().register_residue_type(ResidueType::from_attrs("XGL", StandardResidueType::GLY, MonomerType::LPeptideLinking));
+    let mut mgr = MonomerManager::get();
+    mgr.load_cif_file(fname.to_str().unwrap())?;
+    let xgl = mgr.by_code3("XGL").unwrap();
+    assert_eq!(xgl.count_residue_atoms(), 4);
+    assert!(xgl.atoms().iter().all(|a| a.stereo_config == 'N'));
+    assert!(!xgl.has_ideal_positions());
+    Ok(())
+}
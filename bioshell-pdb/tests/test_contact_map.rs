@@ -0,0 +1,71 @@
+use bioshell_pdb::{Deposit, PDBError, ResidueId};
+use bioshell_pdb::calc::{contact_map, ContactMapComparison, DistanceMatrix, ResidueContact, ResidueRepresentation};
+
+#[test]
+fn residue_representations() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+    let strctr = deposit.structure()?;
+    let ca = DistanceMatrix::new(&strctr, ResidueRepresentation::CA);
+    let cb = DistanceMatrix::new(&strctr, ResidueRepresentation::CB);
+    let centroid = DistanceMatrix::new(&strctr, ResidueRepresentation::SideChainCentroid);
+    let min = DistanceMatrix::new(&strctr, ResidueRepresentation::MinHeavyAtom);
+    assert_eq!(ca.len(), cb.len());
+    assert_eq!(ca.len(), centroid.len());
+    assert_eq!(ca.len(), min.len());
+
+    // --- GLY 9: its CB and side chain centroid are placed on CA
+    let gly = ca.residues().iter().position(|r| r == &ResidueId::new("A", 9, ' ')).unwrap();
+    for j in 0..ca.len() {
+        assert!((ca.distance(gly, j) - ca.distance(j, gly)).abs() < 1e-9);
+        if j == gly { continue; }
+        assert!(min.distance(gly, j) <= ca.distance(gly, j) + 1e-9);
+    }
+    assert!((ca.distance(gly, 0) - centroid.distance(gly, 0)).abs() > 0.01);
+    Ok(())
+}
+
+#[test]
+fn sparse_contacts() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+    let strctr = deposit.structure()?;
+    for repr in [ResidueRepresentation::MinHeavyAtom, ResidueRepresentation::CA, ResidueRepresentation::SideChainCentroid] {
+        let dense = DistanceMatrix::new(&strctr, repr).contacts(5.0, 3);
+        let sparse = contact_map(&strctr, repr, 5.0, 3);
+        assert_eq!(dense.len(), sparse.len());
+        for (d, s) in dense.iter().zip(sparse.iter()) {
+            assert_eq!(d.first, s.first);
+            assert_eq!(d.second, s.second);
+            assert!(s.distance <= 5.0);
+            assert!(s.second.res_seq - s.first.res_seq >= 3);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn compare_contact_maps() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+    let strctr = deposit.structure()?;
+    let reference = contact_map(&strctr, ResidueRepresentation::CB, 8.0, 6);
+
+    let cmp = ContactMapComparison::new(&reference, &reference);
+    assert_eq!(cmp.false_positives + cmp.false_negatives, 0);
+    assert!((cmp.f1_score() - 1.0).abs() < 1e-9);
+
+    // --- half of the contacts, given in reversed order, plus a single wrong one
+    let mut predicted: Vec<(ResidueId, ResidueId)> = reference.iter().step_by(2)
+        .map(|c| (c.second.clone(), c.first.clone())).collect();
+    predicted.push((ResidueId::new("A", 1, ' '), ResidueId::new("A", 56, ' ')));
+    let cmp = ContactMapComparison::from_pairs(predicted,
+        reference.iter().map(|c: &ResidueContact| (c.first.clone(), c.second.clone())));
+    let n_half = reference.len().div_ceil(2);
+    assert_eq!(cmp.true_positives, n_half);
+    assert_eq!(cmp.false_positives, 1);
+    assert_eq!(cmp.false_negatives, reference.len() - n_half);
+    assert!((cmp.precision() - n_half as f64 / (n_half + 1) as f64).abs() < 1e-9);
+
+    let empty = ContactMapComparison::new(&[], &reference);
+    assert_eq!(empty.precision(), 0.0);
+    assert_eq!(empty.recall(), 0.0);
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::io::Cursor;
+use bioshell_pdb::{Deposit, PDBError, Structure};
+use bioshell_pdb::density_maps::{DensityMap, MapFitEvaluator, SymmetryOperator};
+
+/// Creates a big-endian CCP4 map with columns along z, rows along x and sections along y
+fn big_endian_permuted_map() -> Vec<u8> {
+    let mut bytes = vec![0u8; 1024];
+    let mut put = |i: usize, b: [u8; 4]| bytes[4 * i..4 * i + 4].copy_from_slice(&b);
+    for (i, v) in [2, 3, 4, 2, 0, 0, 0, 3, 4, 2].iter().enumerate() { put(i, (*v as i32).to_be_bytes()); }
+    for (i, v) in [3.0f32, 4.0, 2.0, 90.0, 90.0, 90.0].iter().enumerate() { put(10 + i, v.to_be_bytes()); }
+    for (i, v) in [3, 1, 2, 0, 0, 0, 3].iter().enumerate() { put(16 + i, (*v as i32).to_be_bytes()); }
+    put(23, 80i32.to_be_bytes());
+    put(53, [0x11, 0x11, 0, 0]);
+    let mut symmetry = format!("{:<80}", " X,Y,Z * -X,Y,-Z").into_bytes();
+    bytes.append(&mut symmetry);
+    for v in 0..24 { bytes.extend_from_slice(&(v as f32).to_be_bytes()); }
+
+    return bytes;
+}
+
+#[test]
+fn read_permuted_axes() -> Result<(), PDBError> {
+    let map = DensityMap::from_reader(&mut Cursor::new(big_endian_permuted_map()))?;
+    assert_eq!(map.dims(), [3, 4, 2]);
+    assert_eq!(map.space_group(), 3);
+    assert_eq!(map.symmetry_operators().len(), 2);
+    assert_eq!(map.symmetry_operators()[1].to_string(), "-X,Y,-Z");
+    for i in 0..3 {
+        for j in 0..4 {
+            for k in 0..2 {
+                // --- file index: sections (y) change the slowest, columns (z) the fastest
+                assert_eq!(map.value(i, j, k), ((j * 3 + i) * 2 + k) as f64);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn write_read_interpolate() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    let map = DensityMap::from_structure(&strctr, 4.0, 1.5);
+    let mut buffer: Vec<u8> = vec![];
+    map.write(&mut buffer)?;
+    let map_read = DensityMap::from_reader(&mut Cursor::new(buffer))?;
+    assert_eq!(map_read.dims(), map.dims());
+    assert_eq!(map_read.start(), map.start());
+    assert_eq!(map_read.labels(), map.labels());
+    let [nx, ny, nz] = map.dims();
+    for (i, j, k) in [(0, 0, 0), (nx / 2, ny / 3, nz / 4), (nx - 1, ny - 1, nz - 1)] {
+        assert_eq!(map_read.value(i, j, k), map.value(i, j, k));
+        // --- interpolation at a grid point gives the value stored at that point
+        let p = map_read.grid_position(i, j, k);
+        assert!((map_read.interpolate(&p) - map.value(i, j, k)).abs() < 1e-6);
+    }
+    // --- interpolation between two grid points along x is linear
+    let (p1, p2) = (map.grid_position(nx / 2, ny / 2, nz / 2), map.grid_position(nx / 2 + 1, ny / 2, nz / 2));
+    let mut mid = p1;
+    mid.x = (p1.x + p2.x) / 2.0;
+    let expected = (map.value(nx / 2, ny / 2, nz / 2) + map.value(nx / 2 + 1, ny / 2, nz / 2)) / 2.0;
+    assert!((map.interpolate(&mid) - expected).abs() < 1e-5);
+    Ok(())
+}
+
+#[test]
+fn expand_symmetry() -> Result<(), PDBError> {
+    // --- P2 map given for x in [0, 2] only, on a 4x4x4 grid
+    let mut map = DensityMap::new([3, 4, 4], [0, 0, 0], [4, 4, 4], [8.0, 8.0, 8.0, 90.0, 90.0, 90.0]);
+    map.set_space_group(3);
+    map.set_symmetry_operators(vec![SymmetryOperator::identity(), SymmetryOperator::from_text("-x, y, -z")?]);
+    for i in 0..3 {
+        for j in 0..4 {
+            for k in 0..4 { map.set_value(i, j, k, (i + 10 * j + 100 * k) as f64); }
+        }
+    }
+    let mut buffer: Vec<u8> = vec![];
+    map.write(&mut buffer)?;
+    let map = DensityMap::from_reader(&mut Cursor::new(buffer))?;
+    assert_eq!(map.symmetry_operators().len(), 2);
+
+    let full = map.expand_to_unit_cell();
+    assert_eq!(full.dims(), [4, 4, 4]);
+    for j in 0..4 {
+        for k in 0..4 {
+            assert_eq!(full.value(3, j, k), map.value(1, j, (4 - k) % 4));
+            assert_eq!(full.value(1, j, k), map.value(1, j, k));
+        }
+    }
+    // --- the expanded map is periodic
+    let p = full.grid_position(1, 2, 3);
+    let mut shifted = p;
+    shifted.x += 8.0;
+    assert!((full.interpolate(&shifted) - full.interpolate(&p)).abs() < 1e-6);
+    Ok(())
+}
+
+#[test]
+fn poorly_fitted_residue() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    let map = DensityMap::from_structure(&strctr, 3.0, 1.0);
+    // --- residue 20 is moved by 3 Angstroms away from its density
+    let shifted = Structure::from_iterator("2gb1", strctr.atoms().iter().cloned().map(|mut a| {
+        if a.res_seq == 20 { a.pos.x += 3.0; }
+        a
+    }));
+    let fits = MapFitEvaluator::default().evaluate(&shifted, &map);
+    let moved = fits.iter().find(|f| f.res_id.res_seq == 20).unwrap();
+    let other = fits.iter().find(|f| f.res_id.res_seq == 30).unwrap();
+    assert!(other.q_score > 0.8);
+    assert!(other.correlation > 0.95);
+    assert!(moved.q_score < other.q_score - 0.3);
+    assert!(moved.correlation < other.correlation);
+    assert!(moved.atom_inclusion < 1.0);
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use bioshell_pdb::{Deposit, PdbAtom, PDBError, Structure};
+use bioshell_pdb::validation::{find_clashes, GeometryReport};
+
+#[allow(non_upper_case_globals)]
+const two_residues: [&str; 4] = [
+    "ATOM      1  CA  ALA A   1       0.000   0.000   0.000  1.00 10.00           C",
+    "ATOM      2  CB  ALA A   1       1.530   0.000   0.000  1.00 10.00           C",
+    "ATOM      3  CA  ALA B   1       4.030   0.000   0.000  1.00 10.00           C",
+    "ATOM      4  CB  ALA B   1       5.560   0.000   0.000  1.00 10.00           C",
+];
+
+#[test]
+fn clash_between_chains() {
+    let atoms: Vec<PdbAtom> = two_residues.iter().map(|l| PdbAtom::from_atom_line(l)).collect();
+    let strctr = Structure::from_iterator("1xyz", atoms.into_iter());
+    let clashes = find_clashes(&strctr);
+    assert_eq!(clashes.len(), 1);
+    assert_eq!(clashes[0].first, 1);
+    assert_eq!(clashes[0].second, 2);
+    assert!((clashes[0].overlap - 0.9).abs() < 0.001);
+}
+
+#[test]
+fn mirror_image_has_wrong_chirality() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    let strctr = deposit.structure()?;
+    let report = GeometryReport::new(&strctr);
+    assert_eq!(report.chirality_errors.len(), 0);
+    assert!(report.bond_rmsz < 2.0);
+
+    // --- mirror image of a protein is built of D-amino acids
+    let mirrored = Structure::from_iterator("2gb1", strctr.atoms().iter().map(|a| {
+        let mut b = a.clone();
+        b.pos.x = -b.pos.x;
+        b
+    }));
+    let n_chiral = strctr.atoms().iter().filter(|a| a.name == " CA " && a.res_name != "GLY").count()
+        + strctr.atoms().iter().filter(|a| a.name == " CB " && (a.res_name == "THR" || a.res_name == "ILE")).count();
+    let report = GeometryReport::new(&mirrored);
+    assert_eq!(report.chirality_errors.len(), n_chiral);
+    // --- bond lengths and clashes are not affected by mirroring
+    assert_eq!(report.bond_outliers.len(), GeometryReport::new(&strctr).bond_outliers.len());
+
+    Ok(())
+}
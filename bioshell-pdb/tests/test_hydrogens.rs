@@ -0,0 +1,72 @@
+use bioshell_pdb::{is_hydrogen, Deposit, PDBError, ResidueId, Structure};
+use bioshell_pdb::hydrogens::{add_hydrogens, his_tautomers, HisTautomer, HydrogenPlacement};
+
+#[test]
+fn hydrogen_geometry() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    let with_h = add_hydrogens(&strctr)?;
+    assert_eq!(with_h.atoms().iter().filter(|a| !is_hydrogen(a)).count(),
+               strctr.atoms().iter().filter(|a| !is_hydrogen(a)).count());
+    for h in with_h.atoms().iter().filter(|a| is_hydrogen(a)) {
+        let res_id = ResidueId::from(h);
+        let closest = with_h.atoms_in_residue(&res_id)?.filter(|a| !is_hydrogen(a))
+            .map(|a| a.pos.distance_to(&h.pos)).fold(f64::MAX, f64::min);
+        assert!(closest > 0.9 && closest < 1.15, "wrong bond length for {}: {}", h, closest);
+    }
+    // --- charged N-terminus, no amide H on proline, charged lysine
+    let names = |strctr: &Structure, res_seq: i32| -> Result<Vec<String>, PDBError> {
+        Ok(strctr.atoms_in_residue(&ResidueId::new("A", res_seq, ' '))?
+            .filter(|a| is_hydrogen(a)).map(|a| a.name.trim().to_string()).collect())
+    };
+    let met = names(&with_h, 1)?;
+    assert!(met.contains(&"H1".to_string()) && met.contains(&"H3".to_string()) && !met.contains(&"H".to_string()));
+    assert!(names(&with_h, 4)?.iter().any(|n| n == "HZ3"));
+    Ok(())
+}
+
+#[test]
+fn nmr_hydrogens() -> Result<(), PDBError> {
+    // --- hydrogens of an NMR model are removed and placed again
+    let strctr = Deposit::from_file("./tests/test_files/2jqb.cif")?.structure()?;
+    let with_h = add_hydrogens(&strctr)?;
+    let mut n = 0;
+    for h in with_h.atoms().iter().filter(|a| a.name == " HA " || a.name == " H  ") {
+        if let Ok(nmr) = strctr.atom(&ResidueId::from(h), &h.name) {
+            assert!(nmr.pos.distance_to(&h.pos) < 0.2, "{} misplaced by {}", h, nmr.pos.distance_to(&h.pos));
+            n += 1;
+        }
+    }
+    assert!(n >= 20);
+    Ok(())
+}
+
+#[test]
+fn histidine_tautomers() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/4esa.cif")?.structure()?;
+    let tautomers = his_tautomers(&strctr)?;
+    assert!(!tautomers.is_empty());
+    // --- proximal histidines coordinate the heme iron by NE2
+    for fe in strctr.atoms().iter().filter(|a| a.name == "FE  " || a.name.trim() == "FE") {
+        let (res_id, tautomer) = tautomers.iter().find(|(r, _)| strctr.atom(r, " NE2")
+            .is_ok_and(|a| a.pos.distance_to(&fe.pos) < 2.6)).unwrap();
+        assert_eq!(*tautomer, HisTautomer::HID, "wrong tautomer for {}", res_id);
+    }
+    let hip = HydrogenPlacement { optimize_polar: false, his_tautomer: Some(HisTautomer::HIP) }.add_hydrogens(&strctr)?;
+    assert_eq!(hip.atoms().iter().filter(|a| a.name == " HD1" && a.res_name == "HIS").count(), tautomers.len());
+    assert_eq!(hip.atoms().iter().filter(|a| a.name == " HE2" && a.res_name == "HIS").count(), tautomers.len());
+    Ok(())
+}
+
+#[test]
+fn polar_hydrogen_optimization() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/4esa.cif")?.structure()?;
+    let count_hbonds = |strctr: &Structure| {
+        let oxygens: Vec<_> = strctr.atoms().iter().filter(|a| a.element.as_deref() == Some("O")).collect();
+        strctr.atoms().iter().filter(|h| (h.name == " HG " && h.res_name == "SER") || h.name == " HG1" || h.name == " HH ")
+            .filter(|h| oxygens.iter().any(|o| o.res_seq != h.res_seq && o.pos.distance_to(&h.pos) < 2.6)).count()
+    };
+    let ideal = HydrogenPlacement { optimize_polar: false, his_tautomer: None }.add_hydrogens(&strctr)?;
+    let optimized = add_hydrogens(&strctr)?;
+    assert!(count_hbonds(&optimized) > count_hbonds(&ideal));
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use bioshell_pdb::{Deposit, PdbAtom, PDBError, Structure};
+use bioshell_pdb::interfaces::{chain_interface, find_interfaces, INTERFACE_CONTACT_CUTOFF};
+
+#[allow(non_upper_case_globals)]
+const lys_asp_pair: [&str; 6] = [
+    "ATOM      1  CD  LYS A   1       0.000   0.000   0.000  1.00 10.00           C",
+    "ATOM      2  CE  LYS A   1       1.500   0.000   0.000  1.00 10.00           C",
+    "ATOM      3  NZ  LYS A   1       2.000   1.400   0.000  1.00 10.00           N",
+    "ATOM      4  CG  ASP B   1       4.500   3.400   0.000  1.00 10.00           C",
+    "ATOM      5  OD1 ASP B   1       3.300   3.600   0.000  1.00 10.00           O",
+    "ATOM      6  OD2 ASP B   1       5.300   4.300   0.000  1.00 10.00           O",
+];
+
+#[test]
+fn salt_bridge_across_interface() {
+    let atoms: Vec<PdbAtom> = lys_asp_pair.iter().map(|l| PdbAtom::from_atom_line(l)).collect();
+    let strctr = Structure::from_iterator("1xyz", atoms.iter().cloned());
+    let iface = chain_interface(&strctr, "A", "B", INTERFACE_CONTACT_CUTOFF).unwrap();
+    assert_eq!(iface.first_residues.len(), 1);
+    assert_eq!(iface.second_residues.len(), 1);
+    assert_eq!(iface.salt_bridges.len(), 1);
+    assert_eq!(iface.hydrogen_bonds.len(), 1);
+    assert_eq!(strctr.atoms()[iface.salt_bridges[0].first_atom].name, " NZ ");
+    assert_eq!(strctr.atoms()[iface.salt_bridges[0].second_atom].name, " OD1");
+    assert!(iface.buried_area > 0.0);
+
+    // --- chains too far from each other
+    assert!(chain_interface(&strctr, "A", "B", 1.0).is_none());
+}
+
+#[test]
+fn interfaces_in_deposit() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let strctr = deposit.structure()?;
+    let interfaces = find_interfaces(&strctr, INTERFACE_CONTACT_CUTOFF);
+    assert_eq!(interfaces.len(), 1);
+    let iface = &interfaces[0];
+    assert_eq!(iface.first_chain, "A");
+    assert_eq!(iface.second_chain, "B");
+    assert!(iface.first_residues.iter().all(|r| r.chain_id == "A"));
+    assert!(iface.second_residues.iter().all(|r| r.chain_id == "B"));
+    assert!(iface.contacts.iter().all(|c| c.distance <= INTERFACE_CONTACT_CUTOFF));
+    assert!(iface.buried_area > 0.0);
+
+    Ok(())
+}
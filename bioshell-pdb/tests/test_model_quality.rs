@@ -0,0 +1,81 @@
+use bioshell_core::Vec3;
+use bioshell_pdb::{Deposit, PDBError, ResidueId, Structure};
+use bioshell_pdb::calc::{residue_pairs_by_id, GDTScore, LDDTScore, Rototranslation, TMScore};
+
+#[test]
+fn rigid_body_motion() -> Result<(), PDBError> {
+    let reference = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    let rot = Rototranslation::around_axis(&Vec3::new(1.0, -2.0, 5.0), &Vec3::new(3.0, 1.0, -3.0), 2.0);
+    let model = Structure::from_iterator("2gb1", reference.atoms().iter().cloned()
+        .map(|mut a| { a.pos = rot.apply(&a.pos); a }));
+    let pairs = residue_pairs_by_id(&model, &reference);
+
+    let tm = TMScore::new(&model, &reference, &pairs);
+    assert!((tm.tm_score - 1.0).abs() < 1e-6);
+    assert!(tm.rmsd < 1e-6);
+    let a = model.atom(&ResidueId::new("A", 30, ' '), " CA ")?;
+    let b = reference.atom(&ResidueId::new("A", 30, ' '), " CA ")?;
+    assert!(tm.transformation.apply(&a.pos).distance_to(&b.pos) < 1e-6);
+
+    let gdt = GDTScore::new(&model, &reference, &pairs);
+    assert!((gdt.gdt_ha - 1.0).abs() < 1e-6);
+    let lddt = LDDTScore::new(&model, &reference, &pairs, false);
+    assert!((lddt.global - 1.0).abs() < 1e-6);
+    Ok(())
+}
+
+#[test]
+fn lddt_symmetric_side_chains() -> Result<(), PDBError> {
+    let reference = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    // --- OD1 and OD2 of every ASP swapped in the model
+    let model = Structure::from_iterator("2gb1", reference.atoms().iter().cloned().map(|mut a| {
+        if a.res_name == "ASP" && a.name == " OD1" { a.name = " OD2".to_string(); }
+        else if a.res_name == "ASP" && a.name == " OD2" { a.name = " OD1".to_string(); }
+        a
+    }));
+    let pairs = residue_pairs_by_id(&model, &reference);
+    let lddt = LDDTScore::new(&model, &reference, &pairs, false);
+    assert!((lddt.global - 1.0).abs() < 1e-6);
+    Ok(())
+}
+
+#[test]
+fn incomplete_model() -> Result<(), PDBError> {
+    let reference = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    // --- the first ten residues are missing in the model
+    let model = Structure::from_iterator("2gb1", reference.atoms().iter().filter(|a| a.res_seq > 10).cloned());
+    let pairs = residue_pairs_by_id(&model, &reference);
+    assert_eq!(pairs.len(), 46);
+
+    let tm = TMScore::new(&model, &reference, &pairs);
+    assert!(tm.tm_score < 46.0 / 56.0 + 1e-6);
+    assert!(tm.tm_score > 46.0 / 56.0 - 1e-6);
+    let gdt = GDTScore::new(&model, &reference, &pairs);
+    assert!((gdt.gdt_ts - 46.0 / 56.0).abs() < 1e-6);
+
+    let lddt = LDDTScore::new(&model, &reference, &pairs, true);
+    assert!(lddt.global < 1.0);
+    for (res_id, score) in &lddt.per_residue {
+        if res_id.res_seq <= 10 { assert_eq!(*score, 0.0); }
+    }
+    Ok(())
+}
+
+#[test]
+fn nmr_models() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2jqb.cif")?;
+    let reference = deposit.structure()?;
+    let mut model = deposit.structure()?;
+    for i in 1..model.count_models() {
+        model.set_model(i)?;
+        let pairs = residue_pairs_by_id(&model, &reference);
+        let tm = TMScore::new(&model, &reference, &pairs);
+        assert!(tm.tm_score > 0.5 && tm.tm_score < 1.0);
+        let gdt = GDTScore::new(&model, &reference, &pairs);
+        assert!(gdt.gdt_ha <= gdt.gdt_ts);
+        for k in 1..5 { assert!(gdt.fractions[k - 1] <= gdt.fractions[k]); }
+        let lddt = LDDTScore::new(&model, &reference, &pairs, false);
+        assert!(lddt.global > 0.7 && lddt.global < 1.0);
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::io::BufReader;
+use bioshell_pdb::{Deposit, ModifiedResidueCode, PDBError, ResidueId};
+use bioshell_seq::chemical::StandardResidueType;
+
+#[test]
+fn modified_residues_from_cif() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    assert!(deposit.modified_residues().iter().all(|m| m.res_name == "MSE" && m.parent_name == "MET"));
+    let strctr = deposit.structure()?;
+    for chain_id in ["A", "B"] {
+        let n_mse = deposit.modified_residues().iter().filter(|m| m.res_id.chain_id == chain_id).count();
+        assert!(n_mse > 0);
+        let parent = strctr.sequence(chain_id).to_string(1000);
+        let lower = strctr.sequence_with_modified(chain_id, ModifiedResidueCode::Lowercase).to_string(1000);
+        let unknown = strctr.sequence_with_modified(chain_id, ModifiedResidueCode::Unknown).to_string(1000);
+        assert!(!parent.contains('X'));
+        assert_eq!(lower.chars().filter(|&c| c == 'm').count(), n_mse);
+        assert_eq!(unknown.chars().filter(|&c| c == 'X').count(), n_mse);
+        assert_eq!(lower.to_uppercase(), parent);
+    }
+    Ok(())
+}
+
+#[test]
+fn modified_residues_from_pdb() -> Result<(), PDBError> {
+    let pdb_data = "HEADER    TRANSFERASE                             01-JAN-24   9ZZZ
+MODRES 9ZZZ SEP A    2  SER  PHOSPHOSERINE
+ATOM      1  CA  GLY A   1      11.104   6.134  -6.504  1.00  0.00           C
+HETATM    2  CA  SEP A   2      11.639   6.071  -5.147  1.00  0.00           C
+ATOM      3  CA  ALA A   3      12.044   7.152  -4.137  1.00  0.00           C
+TER       4      ALA A   3
+";
+    let deposit = Deposit::from_pdb_reader(BufReader::new(pdb_data.as_bytes()))?;
+    assert_eq!(deposit.modified_residues().len(), 1);
+    assert_eq!(deposit.modified_residues()[0].details.as_deref(), Some("PHOSPHOSERINE"));
+    let strctr = deposit.structure()?;
+    assert_eq!(strctr.sequence("A").to_string(10), "GSA");
+    assert_eq!(strctr.sequence_with_modified("A", ModifiedResidueCode::Lowercase).to_string(10), "GsA");
+    assert_eq!(strctr.residue_type(&ResidueId::new("A", 2, ' '))?.parent_type, StandardResidueType::SER);
+    Ok(())
+}
+
+#[test]
+fn malformed_modres_record() -> Result<(), PDBError> {
+    // --- the residue number of the first MODRES record can't be parsed; the record is skipped
+    let pdb_data = "HEADER    TRANSFERASE                             01-JAN-24   9ZZZ
+MODRES 9ZZZ SEP A    ?  SER  PHOSPHOSERINE
+MODRES 9ZZZ SEP A    2  SER  PHOSPHOSERINE
+ATOM      1  CA  GLY A   1      11.104   6.134  -6.504  1.00  0.00           C
+HETATM    2  CA  SEP A   2      11.639   6.071  -5.147  1.00  0.00           C
+TER       3      SEP A   2
+";
+    let deposit = Deposit::from_pdb_reader(BufReader::new(pdb_data.as_bytes()))?;
+    assert_eq!(deposit.modified_residues().len(), 1);
+    assert_eq!(deposit.structure()?.count_atoms(), 2);
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use bioshell_core::Vec3;
+use bioshell_pdb::{Deposit, PDBError, PdbAtom, ResidueId, Structure};
+use bioshell_pdb::calc::Rototranslation;
+use bioshell_pdb::nucleic_acids::{dot_bracket, find_base_pairs, find_base_stackings, BaseEdge, BasePairType, GlycosidicOrientation};
+
+#[test]
+fn watson_crick_pairs_5edw() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/5edw.cif")?.structure()?;
+    let pairs = find_base_pairs(&strctr);
+    assert_eq!(pairs.len(), 12);
+    assert!(pairs.iter().all(|p| p.pair_type == BasePairType::WatsonCrick && p.lw_class() == "cWW"));
+    // --- template residue 19 pairs with the first residue of the primer
+    let p = pairs.iter().find(|p| p.first == ResidueId::new("T", 19, ' ')).unwrap();
+    assert_eq!(p.second, ResidueId::new("P", 1, ' '));
+    assert_eq!((p.first_base, p.second_base), ('C', 'G'));
+    assert_eq!(p.hbonds.len(), 3);
+    let p = pairs.iter().find(|p| p.first == ResidueId::new("T", 10, ' ')).unwrap();
+    assert_eq!((p.first_base, p.second_base), ('T', 'A'));
+    assert_eq!(p.hbonds.len(), 2);
+
+    assert_eq!(dot_bracket(&strctr, &["T", "P"], &pairs), "......(.(((((((((((&))))))))))).)");
+    assert_eq!(dot_bracket(&strctr, &["T"], &pairs), "...................");
+    Ok(())
+}
+
+#[test]
+fn base_stacking_5edw() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/5edw.cif")?.structure()?;
+    let stacks = find_base_stackings(&strctr);
+    assert!(stacks.len() >= 20);
+    assert!(stacks.iter().all(|s| s.distance < 5.0 && s.angle < 30.0));
+    // --- neighbouring bases of the primer strand are stacked
+    assert!(stacks.iter().any(|s| s.first == ResidueId::new("P", 3, ' ') && s.second == ResidueId::new("P", 4, ' ')));
+    Ok(())
+}
+
+#[test]
+fn reverse_watson_crick_pair() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/5edw.cif")?.structure()?;
+    let adenine = ResidueId::new("T", 9, ' ');
+    let thymine = ResidueId::new("P", 11, ' ');
+    let ring_center = |res_id: &ResidueId| -> Result<Vec3, PDBError> {
+        let mut c = Vec3::from_float(0.0);
+        for name in [" N1 ", " C2 ", " N3 ", " C4 ", " C5 ", " C6 "] { c += &strctr.atom(res_id, name)?.pos; }
+        c /= 6.0;
+        Ok(c)
+    };
+    // --- flip the thymine around the axis connecting the two bases: the cWW pair becomes tWW
+    let rot = Rototranslation::around_axis(&ring_center(&adenine)?, &ring_center(&thymine)?, std::f64::consts::PI);
+    let mut atoms: Vec<PdbAtom> = strctr.atoms_in_residue(&adenine)?.cloned().collect();
+    for mut a in strctr.atoms_in_residue(&thymine)?.cloned() {
+        rot.apply_mut(&mut a.pos);
+        atoms.push(a);
+    }
+    let pair_strctr = Structure::from_atoms("flip", atoms);
+    let pairs = find_base_pairs(&pair_strctr);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].orientation, GlycosidicOrientation::Trans);
+    assert_eq!((pairs[0].first_edge, pairs[0].second_edge), (BaseEdge::WatsonCrick, BaseEdge::WatsonCrick));
+    assert_eq!(pairs[0].lw_class(), "tWW");
+    assert_eq!(pairs[0].pair_type, BasePairType::NonCanonical);
+    assert_eq!(dot_bracket(&pair_strctr, &["T", "P"], &pairs), ".&.");
+    Ok(())
+}
+
+#[test]
+fn rna_pseudoknot_model() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/pseudoknot_model.pdb")?.structure()?;
+    let pairs = find_base_pairs(&strctr);
+    assert_eq!(pairs.len(), 7);
+    // --- G-U wobble: U shifted towards the major groove, its N3 donates to O6 of G
+    let p = pairs.iter().find(|p| p.first == ResidueId::new("R", 4, ' ')).unwrap();
+    assert_eq!((p.first_base, p.second_base), ('U', 'G'));
+    assert_eq!(p.pair_type, BasePairType::Wobble);
+    assert_eq!(p.lw_class(), "cWW");
+    assert!(p.hbonds.iter().any(|h| h.first_atom == "N3" && h.second_atom == "O6"));
+    // --- A-U pair in the trans orientation is not canonical
+    let p = pairs.iter().find(|p| p.first == ResidueId::new("R", 7, ' ')).unwrap();
+    assert_eq!(p.second, ResidueId::new("R", 14, ' '));
+    assert_eq!(p.lw_class(), "tWW");
+    assert!(!p.is_canonical());
+    // --- the second stem, including the wobble pair, crosses the first one
+    assert_eq!(dot_bracket(&strctr, &["R"], &pairs), "((([[[.)))]]].");
+    Ok(())
+}
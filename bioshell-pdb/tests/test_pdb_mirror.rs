@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::PathBuf;
+use bioshell_pdb::{Deposit, ExperimentalMethod, PDBError, PolymerEntityType};
+use bioshell_pdb::pdb_mirror::{MirrorEntry, MirrorQuery, PdbMirror};
+
+/// Creates a small divided mirror in a temporary directory
+fn make_mirror(name: &str, pdb_ids: &[&str]) -> PathBuf {
+    let root = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&root);
+    for id in pdb_ids { copy_to_mirror(&root, id); }
+    return root;
+}
+
+fn copy_to_mirror(root: &PathBuf, pdb_id: &str) {
+    let dir = root.join(&pdb_id[1..3]);
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(format!("./tests/test_files/{}.cif", pdb_id), dir.join(format!("{}.cif", pdb_id))).unwrap();
+}
+
+#[test]
+fn build_and_query_index() -> Result<(), PDBError> {
+    let root = make_mirror("bioshell_test_mirror", &["2gb1", "2jqb", "4esa", "5edw"]);
+    let mirror = PdbMirror::build_index(root.to_str().unwrap())?;
+    assert_eq!(mirror.len(), 4);
+    let hb = mirror.entry("4esa").unwrap();
+    assert_eq!(hb.path, format!("es{}4esa.cif", std::path::MAIN_SEPARATOR));
+    assert_eq!(hb.resolution, Some(1.45));
+    assert_eq!(hb.methods, vec![ExperimentalMethod::XRay]);
+
+    let xray = MirrorQuery { method: Some(ExperimentalMethod::XRay), max_resolution: Some(2.0), ..Default::default() };
+    let found: Vec<&str> = mirror.query(&xray).iter().map(|e| e.id_code.as_str()).collect();
+    assert_eq!(found, vec!["4ESA"]);
+    let dna = MirrorQuery { polymer_type: Some(PolymerEntityType::DNA), ..Default::default() };
+    assert_eq!(mirror.query(&dna).len(), 1);
+    let nmr = MirrorQuery { method: Some(ExperimentalMethod::SolutionNMR), ..Default::default() };
+    // --- both 2GB1 and 2JQB are NMR structures
+    assert_eq!(mirror.query(&nmr).len(), 2);
+    let old = MirrorQuery { deposited_before: Some("2000-01-01".to_string()), ..Default::default() };
+    assert_eq!(mirror.query(&old)[0].id_code, "2GB1");
+
+    // --- the index saved to a file is identical when loaded
+    let index_file = root.join("mirror.idx");
+    mirror.save_index(index_file.to_str().unwrap())?;
+    let loaded = PdbMirror::load_index(root.to_str().unwrap(), index_file.to_str().unwrap())?;
+    assert_eq!(loaded.len(), mirror.len());
+    for e in mirror.entries() { assert_eq!(loaded.entry(&e.id_code), Some(e)); }
+    Ok(())
+}
+
+#[test]
+fn load_deposits() -> Result<(), PDBError> {
+    let root = make_mirror("bioshell_test_mirror_loading", &["2gb1", "4esa"]);
+    let index_file = root.join("mirror.idx");
+    let mut mirror = PdbMirror::open(root.to_str().unwrap(), index_file.to_str().unwrap())?;
+    assert!(index_file.is_file());
+
+    let deposits = mirror.load_parallel(&["4ESA", "2gb1", "1abc"]);
+    assert_eq!(deposits[0].as_ref().unwrap().count_entities(), mirror.entry("4esa").unwrap().entities.len());
+    assert_eq!(deposits[1].as_ref().unwrap().id_code, "2GB1");
+    assert!(matches!(deposits[2], Err(PDBError::DepositNotInMirror { .. })));
+
+    // --- a new deposit appears in the mirror
+    copy_to_mirror(&root, "6ins");
+    assert_eq!(mirror.update()?, 1);
+    assert!(mirror.load("6ins").is_ok());
+
+    // --- a deposit has been removed from the mirror
+    fs::remove_file(root.join("es").join("4esa.cif")).unwrap();
+    assert_eq!(mirror.update()?, 0);
+    assert_eq!(mirror.len(), 2);
+    assert!(mirror.entry("4esa").is_none());
+    assert_eq!(mirror.entry("6ins").unwrap().id_code, "6INS");
+    Ok(())
+}
+
+#[test]
+fn index_from_headers() -> Result<(), PDBError> {
+    let root = make_mirror("bioshell_test_mirror_headers", &["2fdo", "4esa", "5edw"]);
+    fs::copy("./tests/test_files/2gb1.pdb", root.join("2gb1.pdb")).unwrap();
+    let mirror = PdbMirror::build_index(root.to_str().unwrap())?;
+    assert_eq!(mirror.len(), 4);
+    // --- entries created without coordinates are the same as those of fully parsed deposits
+    for e in mirror.entries() {
+        let deposit = Deposit::from_file(root.join(&e.path))?;
+        assert_eq!(&MirrorEntry::from_deposit(&deposit, &e.path), e);
+    }
+    assert_eq!(mirror.entry("2gb1").unwrap().methods, vec![ExperimentalMethod::SolutionNMR]);
+    Ok(())
+}
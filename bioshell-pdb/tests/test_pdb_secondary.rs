@@ -44,4 +44,16 @@ mod tests {
         assert_eq!(sec_vew.helices().count(), 10);
         Ok(())
     }
+
+    #[test]
+    fn secondary_view_of_second_chain() -> Result<(), PDBError> {
+        // --- residues of a view must come from the requested chain, not from the beginning of the structure
+        let reader = BufReader::new(cif_4esa.as_bytes());
+        let strctr = Deposit::from_cif_reader(reader)?.structure()?;
+        let sec_vew = SecondaryView::new(&strctr, "B");
+        assert!(sec_vew.helices().all(|h| h.residues().iter().all(|r| r.chain_id == "B")));
+        let first_helix = sec_vew.helices().next().unwrap();
+        assert_eq!(strctr.residue_secondary(first_helix.residues()[0])?.hec_code(), b'H');
+        Ok(())
+    }
 }
\ No newline at end of file
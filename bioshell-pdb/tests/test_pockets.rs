@@ -0,0 +1,45 @@
+use bioshell_pdb::{Deposit, PDBError, ResidueId, Structure};
+use bioshell_pdb::pdb_atom_filters::{ByChain, KeepProtein, PdbAtomPredicate};
+use bioshell_pdb::pockets::{pockets_as_structure, PocketFinder};
+
+#[test]
+fn heme_pocket_of_hemoglobin() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/4esa.cif")?;
+    let strctr = deposit.structure()?;
+    let fe = strctr.atoms().iter().find(|a| a.res_name == "HEM" && a.chain_id == "A").unwrap().pos;
+    // --- the alpha chain without its heme
+    let chain_a = ByChain::new("A");
+    let apo = Structure::from_iterator("4esa", strctr.atoms().iter()
+        .filter(|a| KeepProtein.check(a) && chain_a.check(a)).cloned());
+
+    let pockets = PocketFinder::default().find_pockets(&apo);
+    assert!(!pockets.is_empty());
+    for w in pockets.windows(2) { assert!(w[0].score >= w[1].score); }
+    // --- the heme site is the best pocket
+    let best = &pockets[0];
+    assert!(best.points.iter().any(|p| p.distance_to(&fe) < 1.5));
+    assert!(best.volume > 200.0);
+    assert!(best.buriedness >= 5.0);
+    assert!(best.residues.contains(&ResidueId::new("A", 88, ' ')));
+    assert!(best.residues.contains(&ResidueId::new("A", 59, ' ')));
+    assert!(best.center().distance_to(&fe) < 5.0);
+    Ok(())
+}
+
+#[test]
+fn pocket_pseudo_atoms() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.cif")?;
+    let strctr = deposit.structure()?;
+    let finder = PocketFinder { min_volume: 10.0, ..Default::default() };
+    let pockets = finder.find_pockets(&strctr);
+    let pseudo = pockets_as_structure(&pockets);
+    assert_eq!(pseudo.residues().len(), pockets.len());
+    assert_eq!(pseudo.count_atoms(), pockets.iter().map(|p| p.points.len()).sum::<usize>());
+    for a in pseudo.atoms() {
+        assert_eq!(a.res_name, "STP");
+        assert!(a.temp_factor >= finder.min_buriedness as f64);
+    }
+
+    assert!(PocketFinder::default().find_pockets(&Structure::new("1xyz")).is_empty());
+    Ok(())
+}
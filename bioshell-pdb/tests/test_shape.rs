@@ -0,0 +1,55 @@
+use bioshell_core::assert_delta;
+use bioshell_pdb::{PDBError, PdbAtom};
+use bioshell_pdb::calc::{center_of_mass, radius_of_gyration, ShapeDescriptors};
+
+#[allow(non_upper_case_globals)]
+const octahedron: [&str; 6] = [
+    "ATOM      1  CA  GLY A   1       1.000   0.000   0.000  1.00 10.00           C",
+    "ATOM      2  CA  GLY A   2      -1.000   0.000   0.000  1.00 10.00           C",
+    "ATOM      3  CA  GLY A   3       0.000   1.000   0.000  1.00 10.00           C",
+    "ATOM      4  CA  GLY A   4       0.000  -1.000   0.000  1.00 10.00           C",
+    "ATOM      5  CA  GLY A   5       0.000   0.000   1.000  1.00 10.00           C",
+    "ATOM      6  CA  GLY A   6       0.000   0.000  -1.000  1.00 10.00           C",
+];
+
+#[allow(non_upper_case_globals)]
+const rod: [&str; 4] = [
+    "ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00 10.00           C",
+    "ATOM      2  CA  GLY A   2       1.000   1.000   1.000  1.00 10.00           C",
+    "ATOM      3  CA  GLY A   3       2.000   2.000   2.000  1.00 10.00           C",
+    "ATOM      4  CA  GLY A   4       3.000   3.000   3.000  1.00 10.00           C",
+];
+
+#[test]
+fn spherical_shape() {
+    let atoms: Vec<PdbAtom> = octahedron.iter().map(|l| PdbAtom::from_atom_line(l)).collect();
+    let shape = ShapeDescriptors::from_atoms(&atoms).unwrap();
+    assert_delta!(shape.radius_of_gyration(), 1.0, 0.0001);
+    assert_delta!(shape.asphericity(), 0.0, 0.0001);
+    assert_delta!(shape.acylindricity(), 0.0, 0.0001);
+    assert_delta!(shape.anisotropy(), 0.0, 0.0001);
+    assert_delta!(shape.center_of_mass().length(), 0.0, 0.0001);
+    for m in shape.principal_moments() { assert_delta!(m, 4.0 * 12.011, 0.001); }
+}
+
+#[test]
+fn rod_shape() {
+    let atoms: Vec<PdbAtom> = rod.iter().map(|l| PdbAtom::from_atom_line(l)).collect();
+    let shape = ShapeDescriptors::from_atoms(&atoms).unwrap();
+    // --- Rg of four points spaced by sqrt(3): sqrt(3 * 1.25)
+    assert_delta!(shape.radius_of_gyration(), 3.75_f64.sqrt(), 0.0001);
+    assert_delta!(radius_of_gyration(&atoms).unwrap(), 3.75_f64.sqrt(), 0.0001);
+    assert_delta!(shape.anisotropy(), 1.0, 0.0001);
+    // --- the longest axis is along the rod
+    let axis = shape.principal_axes()[2];
+    assert_delta!(axis.x.abs(), 1.0 / 3.0_f64.sqrt(), 0.0001);
+    let com = center_of_mass(&atoms);
+    assert_delta!(com.y, 1.5, 0.0001);
+}
+
+#[test]
+fn too_few_atoms() {
+    let atoms: Vec<PdbAtom> = rod.iter().take(1).map(|l| PdbAtom::from_atom_line(l)).collect();
+    assert!(matches!(ShapeDescriptors::from_atoms(&atoms), Err(PDBError::TooFewAtoms { required: 2, found: 1 })));
+    assert!(matches!(radius_of_gyration(&atoms[..0]), Err(PDBError::TooFewAtoms { required: 2, found: 0 })));
+}
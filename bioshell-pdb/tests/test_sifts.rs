@@ -0,0 +1,97 @@
+use std::io::BufReader;
+use bioshell_cif::read_cif_file;
+use bioshell_core::io::open_file;
+use bioshell_pdb::{Deposit, PDBError, ResidueId};
+use bioshell_pdb::sifts::{SequenceDifference, UniProtMapping};
+
+#[test]
+fn uniprot_mapping_from_cif() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let mapping = deposit.uniprot_mapping()?;
+    // --- 94 residues in each of the two chains
+    assert_eq!(mapping.len(), 188);
+    // --- GLY-HIS cloning artifact isn't present in UniProt
+    let gly = mapping.residue(&ResidueId::new("A", -1, ' ')).unwrap();
+    assert_eq!(gly.unp_pos, None);
+    assert_eq!(gly.difference, Some(SequenceDifference::CloningArtifact));
+    // --- selenomethionine in place of the initial methionine
+    let mse = mapping.residue(&ResidueId::new("B", 1, ' ')).unwrap();
+    assert_eq!(mse.unp_res, Some('M'));
+    assert_eq!(mse.difference, Some(SequenceDifference::ModifiedResidue));
+    assert_eq!(mapping.unp_position(&ResidueId::new("B", 92, ' ')), Some(("O27953", 92)));
+    assert_eq!(mapping.differences().count(), 12);
+
+    // --- a conflict: D-phenylalanine in place of PHE 67
+    let deposit = Deposit::from_file("./tests/test_files/2jqb.cif")?;
+    let mapping = deposit.uniprot_mapping()?;
+    let dpn = mapping.residue(&ResidueId::new("A", 13, ' ')).unwrap();
+    assert_eq!(dpn.res_name, "DPN");
+    assert_eq!(dpn.unp_pos, Some(67));
+    assert_eq!(dpn.unp_res, Some('F'));
+    assert_eq!(dpn.difference, Some(SequenceDifference::Conflict));
+    Ok(())
+}
+
+#[test]
+fn uniprot_mapping_requires_cif() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    assert!(deposit.uniprot_mapping().is_err());
+    Ok(())
+}
+
+#[test]
+fn uniprot_mapping_from_sifts_files() -> Result<(), PDBError> {
+    // --- the XML file has been trimmed to five residues, the last one is not observed
+    let mapping = UniProtMapping::from_sifts_xml(open_file("./tests/test_files/2fdo_sifts.xml")?)?;
+    assert_eq!(mapping.len(), 4);
+    assert_eq!(mapping.differences().count(), 3);
+    assert_eq!(mapping.unp_position(&ResidueId::new("A", 2, ' ')), Some(("O27953", 2)));
+    assert_eq!(mapping.residue(&ResidueId::new("A", 0, ' ')).unwrap().difference, Some(SequenceDifference::CloningArtifact));
+
+    // --- segment-level mapping gives the same result as the one from mmCIF, except differences
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let from_tsv = UniProtMapping::from_sifts_tsv(open_file("./tests/test_files/pdb_chain_uniprot.tsv")?, &deposit)?;
+    let from_cif = deposit.uniprot_mapping()?;
+    assert_eq!(from_tsv.len(), 184);
+    for r in from_tsv.residues() {
+        assert_eq!(from_cif.unp_position(&r.res_id), Some((r.accession.as_deref().unwrap(), r.unp_pos.unwrap())));
+    }
+    Ok(())
+}
+
+#[test]
+fn uniprot_mapping_without_references() -> Result<(), PDBError> {
+    // --- a chemical component provides no _struct_ref category
+    let cif_data = read_cif_file("./tests/test_files/ALA.cif")?;
+    assert!(UniProtMapping::from_cif_data(&cif_data[0])?.is_empty());
+
+    // --- SIFTS rows of a chain that is not present in a deposit are skipped
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let tsv = "2fdo\tZ\tO27953\t3\t94\t1\t92\t1\t92\n2fdo\tA\tO27953\t3\t94\t1\t92\t1\t92\n";
+    let mapping = UniProtMapping::from_sifts_tsv(BufReader::new(tsv.as_bytes()), &deposit)?;
+    assert_eq!(mapping.len(), 92);
+    Ok(())
+}
+
+#[test]
+fn sifts_xml_entities() -> Result<(), PDBError> {
+    let xml = r#"<entry><residue dbSource="PDBe" dbResNum="1" dbResName="MSE">
+        <crossRefDb dbSource="PDB" dbResNum="1" dbResName="MSE" dbChainId="A"/>
+        <crossRefDb dbSource="UniProt" dbAccessionId="O27953" dbResNum="1" dbResName="M"/>
+        <residueDetail dbSource="PDBe" property="Annotation">Engineered&#32;mutation</residueDetail>
+    </residue>
+    <residue dbSource="PDBe" dbResNum="2" dbResName="ALA">
+        <crossRefDb dbSource="PDB" dbResNum="2" dbResName="ALA" dbChainId="A"/>
+        <crossRefDb dbSource="UniProt" dbAccessionId="O2795&#51;" dbResNum="2" dbResName="A"/>
+        <residueDetail dbSource="PDBe" property="Annotation">Conflict &amp; Variant</residueDetail>
+    </residue></entry>"#;
+    let mapping = UniProtMapping::from_sifts_xml(BufReader::new(xml.as_bytes()))?;
+    assert_eq!(mapping.len(), 2);
+    let mse = mapping.residue(&ResidueId::new("A", 1, ' ')).unwrap();
+    assert_eq!(mse.difference, Some(SequenceDifference::EngineeredMutation));
+    // --- entities are decoded in attributes as well
+    assert_eq!(mapping.unp_position(&ResidueId::new("A", 2, ' ')), Some(("O27953", 2)));
+    // --- "Conflict & Variant" is not a sequence difference
+    assert_eq!(mapping.residue(&ResidueId::new("A", 2, ' ')).unwrap().difference, None);
+    Ok(())
+}
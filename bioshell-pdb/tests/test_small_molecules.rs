@@ -0,0 +1,140 @@
+use bioshell_pdb::{Deposit, PDBError, PdbAtom, ResidueId};
+use bioshell_pdb::small_molecules::*;
+
+#[test]
+fn sdf_v2000() -> Result<(), PDBError> {
+    let molecules = read_sdf_file("./tests/test_files/ligands.sdf")?;
+    assert_eq!(molecules.len(), 2);
+    let ethanol = &molecules[0];
+    assert_eq!(ethanol.name, "ethanol");
+    assert_eq!(ethanol.count_atoms(), 9);
+    assert_eq!(ethanol.count_bonds(), 8);
+    assert_eq!(ethanol.property("weight"), Some("46.07"));
+    // --- zwitterion: charges given by M  CHG lines
+    let glycine = &molecules[1];
+    assert_eq!(glycine.atoms[0].formal_charge, 1);
+    assert_eq!(glycine.atoms[4].formal_charge, -1);
+    assert_eq!(glycine.total_charge(), 0);
+    assert_eq!(glycine.bonds[2].order, BondOrder::Double);
+
+    // --- both versions of the format written and read back
+    for version in [MolfileVersion::V2000, MolfileVersion::V3000] {
+        let mut buffer: Vec<u8> = vec![];
+        write_sdf(&mut buffer, &molecules, version)?;
+        let copy = read_sdf(buffer.as_slice())?;
+        assert_eq!(copy.len(), 2);
+        for (m, c) in molecules.iter().zip(copy.iter()) {
+            assert_eq!(m.name, c.name);
+            assert_eq!(m.bonds, c.bonds);
+            assert_eq!(m.properties, c.properties);
+            for (a, b) in m.atoms.iter().zip(c.atoms.iter()) {
+                assert_eq!(a.element, b.element);
+                assert_eq!(a.formal_charge, b.formal_charge);
+                assert!(a.pos.distance_to(&b.pos) < 0.001);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn sdf_v3000() -> Result<(), PDBError> {
+    let molecules = read_sdf_file("./tests/test_files/benzoate_v3000.mol")?;
+    assert_eq!(molecules.len(), 1);
+    let benzoate = &molecules[0];
+    assert_eq!(benzoate.count_atoms(), 9);
+    assert_eq!(benzoate.bonds.iter().filter(|b| b.order == BondOrder::Aromatic).count(), 6);
+    // --- the charge is given in a continuation line
+    assert_eq!(benzoate.atoms[8].formal_charge, -1);
+    assert!(benzoate.is_ring_bond(0));
+    assert!(!benzoate.is_ring_bond(6));
+    Ok(())
+}
+
+#[test]
+fn mol2_read_write() -> Result<(), PDBError> {
+    let molecules = read_mol2_file("./tests/test_files/acetamide.mol2")?;
+    assert_eq!(molecules.len(), 1);
+    let acetamide = &molecules[0];
+    assert_eq!(acetamide.name, "acetamide");
+    assert_eq!(acetamide.count_atoms(), 7);
+    assert_eq!(acetamide.atoms[3].element, "N");
+    assert_eq!(acetamide.atoms[3].partial_charge, Some(-0.6));
+
+    let mut buffer: Vec<u8> = vec![];
+    write_mol2(&mut buffer, &molecules)?;
+    let text = String::from_utf8(buffer.clone()).unwrap();
+    // --- SYBYL types are assigned from elements and bonds
+    for sybyl in ["C.3", "C.2", "O.2", "N.am"] { assert!(text.contains(sybyl), "missing atom type {}", sybyl); }
+    let copy = read_mol2(buffer.as_slice())?;
+    assert_eq!(copy[0].count_bonds(), acetamide.count_bonds());
+    assert_eq!(copy[0].atoms[3].partial_charge, Some(-0.6));
+    Ok(())
+}
+
+#[test]
+fn pdbqt_read_write() -> Result<(), PDBError> {
+    let poses = read_pdbqt_file("./tests/test_files/phenol_vina.pdbqt")?;
+    assert_eq!(poses.len(), 2);
+    assert_eq!(poses[0].name, "phenol");
+    assert_eq!(poses[1].property("vina_result"), Some("-4.7      0.000      0.000"));
+    assert_eq!(poses[0].count_bonds(), 8);
+    assert_eq!(poses[0].bonds.iter().filter(|b| b.order == BondOrder::Aromatic).count(), 6);
+
+    let mut buffer: Vec<u8> = vec![];
+    write_pdbqt(&mut buffer, &poses[..1])?;
+    let text = String::from_utf8(buffer.clone()).unwrap();
+    assert!(text.contains("TORSDOF 1"));
+    assert!(text.contains("BRANCH"));
+    let copy = read_pdbqt(buffer.as_slice())?;
+    assert_eq!(copy[0].count_atoms(), 8);
+    assert_eq!(copy[0].count_bonds(), 8);
+    Ok(())
+}
+
+#[test]
+fn pdbqt_merges_nonpolar_hydrogens() -> Result<(), PDBError> {
+    let mut ethanol = read_sdf_file("./tests/test_files/ligands.sdf")?.remove(0);
+    for a in ethanol.atoms.iter_mut() { a.partial_charge = Some(if a.is_hydrogen() { 0.1 } else { -0.2 }); }
+    let mut buffer: Vec<u8> = vec![];
+    write_pdbqt(&mut buffer, &[ethanol])?;
+    let copy = read_pdbqt(buffer.as_slice())?;
+    // --- only the hydroxyl hydrogen is left; the total charge is preserved
+    assert_eq!(copy[0].count_atoms(), 4);
+    let total: f64 = copy[0].atoms.iter().filter_map(|a| a.partial_charge).sum();
+    assert!((total - 0.0).abs() < 0.01);
+    Ok(())
+}
+
+#[test]
+fn residue_to_molecule_and_back() -> Result<(), PDBError> {
+    let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    let phe = SmallMolecule::from_residue(&strctr, &ResidueId::new("A", 52, ' '))?;
+    // --- the NMR structure includes hydrogens
+    assert_eq!(phe.count_atoms(), 20);
+    assert_eq!(phe.count_bonds(), 20);
+    let mut buffer: Vec<u8> = vec![];
+    write_mol2(&mut buffer, &[phe.clone()])?;
+    let copy = read_mol2(buffer.as_slice())?.remove(0);
+    let strctr = copy.to_structure("PHE");
+    assert_eq!(strctr.count_atoms(), 20);
+    assert!(strctr.atom(&ResidueId::new("A", 1, ' '), " CZ ").is_ok());
+    Ok(())
+}
+
+#[test]
+fn molecule_from_atoms_without_elements() {
+    // --- atom names made of digits only don't tell the element
+    let mut atoms = vec![];
+    for (name, x) in [(" C1 ", 0.0), ("1", 1.5)] {
+        let mut a = PdbAtom::from_atom_line("HETATM    1  C1  LIG A   1       0.000   0.000   0.000  1.00  0.00           C  ");
+        a.name = name.to_string();
+        a.element = None;
+        a.pos.x = x;
+        atoms.push(a);
+    }
+    let mol = SmallMolecule::from_atoms(&atoms);
+    assert_eq!(mol.count_atoms(), 2);
+    assert_eq!(mol.atoms[0].element, "C");
+    assert_eq!(mol.atoms[1].element, "");
+}
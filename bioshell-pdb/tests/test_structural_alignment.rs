@@ -0,0 +1,54 @@
+use bioshell_core::Vec3;
+use bioshell_pdb::{Deposit, PDBError, Structure};
+use bioshell_pdb::calc::Rototranslation;
+use bioshell_pdb::pdb_atom_filters::{ByChain, PdbAtomPredicate};
+use bioshell_pdb::structural_alignment::{ca_trace, StructuralAligner};
+
+fn chain(strctr: &Structure, chain_id: &str) -> Structure {
+    let by_chain = ByChain::new(chain_id);
+    return Structure::from_iterator(&strctr.id_code, strctr.atoms().iter().filter(|a| by_chain.check(a)).cloned());
+}
+
+#[test]
+fn truncated_copy() -> Result<(), PDBError> {
+    let template = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    // --- the query lacks the first ten residues and is rotated
+    let rot = Rototranslation::around_axis(&Vec3::new(1.0, -2.0, 5.0), &Vec3::new(3.0, 1.0, -3.0), 2.0);
+    let query = Structure::from_iterator("2gb1", template.atoms().iter().filter(|a| a.res_seq > 10).cloned()
+        .map(|mut a| { a.pos = rot.apply(&a.pos); a }));
+    let alignment = StructuralAligner::default().align_structures(&query, &template);
+    for (i, j) in alignment.aligned_pairs() { assert_eq!(i + 10, j); }
+    assert_eq!(alignment.n_aligned, 46);
+    assert!(alignment.rmsd < 1e-6);
+    assert!((alignment.tm_score_query - 1.0).abs() < 1e-6);
+    assert!((alignment.tm_score - 46.0 / 56.0).abs() < 1e-6);
+    Ok(())
+}
+
+#[test]
+fn hemoglobin_chains() -> Result<(), PDBError> {
+    let hb = Deposit::from_file("./tests/test_files/4esa.cif")?.structure()?;
+    let (alpha, beta) = (chain(&hb, "A"), chain(&hb, "B"));
+    let alignment = StructuralAligner::default().align_structures(&alpha, &beta);
+    assert!(alignment.tm_score > 0.85);
+    assert!(alignment.n_aligned > 130);
+    assert!(alignment.rmsd < 2.0);
+
+    // --- the transformation superimposes the aligned alpha carbons
+    let (_, alpha_ca) = ca_trace(&alpha);
+    let (_, beta_ca) = ca_trace(&beta);
+    let close = alignment.aligned_pairs().iter()
+        .filter(|&&(i, j)| alignment.transformation.apply(&alpha_ca[i]).distance_to(&beta_ca[j]) < 5.0).count();
+    assert_eq!(close, alignment.n_aligned);
+    Ok(())
+}
+
+#[test]
+fn unrelated_folds() -> Result<(), PDBError> {
+    let gb1 = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    let hb = Deposit::from_file("./tests/test_files/4esa.cif")?.structure()?;
+    let alignment = StructuralAligner::default().align_structures(&gb1, &chain(&hb, "A"));
+    assert!(alignment.tm_score < 0.3);
+    assert!(alignment.tm_score_query < 0.5);
+    Ok(())
+}
@@ -48,6 +48,35 @@ fn struct_from_pushed_atoms() {
     assert_eq!(ca.serial, 515);
 }
 
+#[test]
+fn residues_of_adjacent_chains_with_equal_numbers() -> Result<(), PDBError> {
+    // --- e.g. zinc ions of 6ins, each in its own chain and numbered 30
+    let lines = ["HETATM 2001 ZN    ZN E  30      -0.002  -0.004   7.891  0.33 10.40           ZN ",
+                 "HETATM 2002 ZN    ZN F  30       0.000   0.000  -8.039  0.33 11.00           ZN "];
+    let strctr = Structure::from_iterator("6ins", lines.iter().map(|l| PdbAtom::from_atom_line(l)));
+    assert_eq!(strctr.count_residues(), 2);
+    assert_eq!(strctr.atoms_in_residue(&ResidueId::new("F", 30, ' '))?.count(), 1);
+
+    // --- the last residue of chain A and the first residue of chain B share the number
+    let lines = ["ATOM    514  N   MET A  60      26.532  28.200  28.365  1.00 17.85           N",
+                 "ATOM    515  CA  MET A  60      25.790  28.757  29.513  1.00 16.12           C",
+                 "ATOM    516  N   ALA B  60      26.891  29.054  30.649  1.00 15.28           N",
+                 "ATOM    517  CA  ALA B  60      26.657  29.867  31.341  1.00 20.90           C"];
+    let strctr = Structure::from_iterator("1xyz", lines.iter().map(|l| PdbAtom::from_atom_line(l)));
+    assert_eq!(strctr.residues(), &vec![ResidueId::new("A", 60, ' '), ResidueId::new("B", 60, ' ')]);
+    assert_eq!(strctr.residue_name(&ResidueId::new("B", 60, ' '))?, "ALA");
+    Ok(())
+}
+
+#[test]
+fn residue_name_of_multi_atom_residues() -> Result<(), PDBError> {
+    // --- the name must be taken from the first atom of a residue, not from an atom at the residue index
+    let strctr = Deposit::from_file("./tests/test_files/2gb1.cif")?.structure()?;
+    assert_eq!(strctr.residue_name(&ResidueId::new("A", 3, ' '))?, "TYR");
+    assert_eq!(strctr.residue_name(&ResidueId::new("A", 9, ' '))?, "GLY");
+    Ok(())
+}
+
 #[allow(non_upper_case_globals)]
 const pdb_2gb1:  &str = include_str!("./test_files/2gb1.pdb");
 
@@ -0,0 +1,136 @@
+use bioshell_pdb::{Deposit, is_hydrogen, PDBError, ResidueId, Structure};
+
+/// Checks that the residue index of a structure agrees with its atoms
+fn check_residue_index(strctr: &Structure) {
+    let expected = Structure::residue_ids_from_atoms(strctr.atoms().iter());
+    assert_eq!(strctr.residues(), &expected);
+    for res_id in strctr.residues() {
+        for a in strctr.atoms_in_residue(res_id).unwrap() {
+            assert_eq!(&ResidueId::from(a), res_id);
+        }
+    }
+}
+
+#[test]
+fn remove_residues_and_chains() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    let mut strctr = deposit.structure()?;
+    let n_atoms = strctr.count_atoms();
+    let n_res = strctr.residues().len();
+
+    let res_id = ResidueId::new("A", 10, ' ');
+    let n_removed = strctr.atoms_in_residue(&res_id)?.count();
+    strctr.remove_residue(&res_id)?;
+    assert_eq!(strctr.count_atoms(), n_atoms - n_removed);
+    assert_eq!(strctr.residues().len(), n_res - 1);
+    assert!(strctr.atom(&res_id, " CA ").is_err());
+    assert!(strctr.atom(&ResidueId::new("A", 11, ' '), " CA ").is_ok());
+    check_residue_index(&strctr);
+
+    assert!(strctr.remove_chain("B").is_err());
+    strctr.remove_chain("A")?;
+    assert_eq!(strctr.count_atoms(), 0);
+    assert!(strctr.residues().is_empty());
+    Ok(())
+}
+
+#[test]
+fn remove_residue_from_all_models() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2jqb.cif")?;
+    let mut strctr = deposit.structure()?;
+    strctr.remove_residue(&ResidueId::new("A", 5, ' '))?;
+    check_residue_index(&strctr);
+    for imodel in 0..strctr.count_models() {
+        strctr.set_model(imodel)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn mutate_residues() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    let mut strctr = deposit.structure()?;
+    // --- LEU 5 into ILE: CG and CD1 of leucine are not the same atoms as CG1 and CD1 of isoleucine
+    let res_id = ResidueId::new("A", 5, ' ');
+    strctr.mutate_residue(&res_id, "ILE")?;
+    let heavy: Vec<String> = strctr.atoms_in_residue(&res_id)?
+        .filter(|a| !is_hydrogen(a)).map(|a| a.name.trim().to_string()).collect();
+    assert_eq!(heavy, vec!["N", "CA", "C", "O", "CB"]);
+    assert_eq!(strctr.residue_type(&res_id)?.code3, "ILE");
+    check_residue_index(&strctr);
+
+    // --- THR 2 into SER: OG1 of threonine is not OG of serine
+    let res_id = ResidueId::new("A", 2, ' ');
+    strctr.mutate_residue(&res_id, "SER")?;
+    assert_eq!(strctr.atoms_in_residue(&res_id)?.filter(|a| !is_hydrogen(a)).count(), 5);
+    // --- GLU 15 into GLN: all the side chain heavy atoms but OE2 are kept
+    let res_id = ResidueId::new("A", 15, ' ');
+    strctr.mutate_residue(&res_id, "GLN")?;
+    assert_eq!(strctr.atoms_in_residue(&res_id)?.filter(|a| !is_hydrogen(a)).count(), 8);
+
+    assert!(strctr.mutate_residue(&res_id, "XYZ").is_err());
+    assert!(strctr.mutate_residue(&ResidueId::new("A", 100, ' '), "ALA").is_err());
+    Ok(())
+}
+
+#[test]
+fn rename_and_merge_chains() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let mut strctr = deposit.structure()?;
+    let n_atoms = strctr.count_atoms();
+    let n_res = strctr.residues().len();
+
+    strctr.rename_chain("B", "C")?;
+    assert_eq!(strctr.chain_ids(), vec!["A", "C"]);
+    assert!(strctr.rename_chain("A", "C").is_err());
+    assert!(strctr.rename_chain("B", "D").is_err());
+    check_residue_index(&strctr);
+
+    // --- both chains have the same numbering
+    assert!(strctr.merge_chains("A", "C").is_err());
+    assert_eq!(strctr.chain_ids(), vec!["A", "C"]);
+    let strctr = strctr.renumbered_structure();
+    let mut strctr = Structure::from_iterator("2fdo", strctr.atoms().iter().cloned()
+        .map(|mut a| { if a.chain_id == "C" { a.res_seq += 1000; } a }));
+    strctr.merge_chains("A", "C")?;
+    assert_eq!(strctr.chain_ids(), vec!["A"]);
+    assert_eq!(strctr.count_atoms(), n_atoms);
+    assert_eq!(strctr.residues().len(), n_res);
+    check_residue_index(&strctr);
+    Ok(())
+}
+
+#[test]
+fn transfer_atoms_between_structures() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let mut source = deposit.structure()?;
+    let n_atoms = source.count_atoms();
+    let mut target = Structure::new("2fdo");
+
+    let chain_b = source.take_chain("B")?;
+    let n_b = chain_b.len();
+    target.insert_atoms(chain_b)?;
+    assert_eq!(target.chain_ids(), vec!["B"]);
+    assert_eq!(source.count_atoms() + target.count_atoms(), n_atoms);
+
+    // --- a residue goes back to its place in the middle of the chain
+    let res_id = ResidueId::new("B", 30, ' ');
+    let residue = target.take_residue(&res_id)?;
+    let res_atoms = residue.len();
+    target.insert_atoms(residue.clone())?;
+    assert_eq!(target.count_atoms(), n_b);
+    let pos = target.residues().iter().position(|r| r == &res_id).unwrap();
+    assert_eq!(target.residues()[pos - 1].res_seq, 29);
+    assert_eq!(target.residues()[pos + 1].res_seq, 31);
+    check_residue_index(&target);
+    // --- the same atoms can't be inserted twice
+    assert!(target.insert_atoms(residue).is_err());
+    assert_eq!(target.count_atoms(), n_b);
+
+    source.transfer_residue(&mut target, &res_id)?;
+    assert_eq!(source.count_atoms(), n_atoms - n_b + res_atoms);
+    assert!(target.atom(&res_id, " CA ").is_err());
+    check_residue_index(&source);
+    check_residue_index(&target);
+    Ok(())
+}
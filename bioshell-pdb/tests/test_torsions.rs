@@ -0,0 +1,49 @@
+use bioshell_pdb::{Deposit, PDBError, ResidueId};
+use bioshell_pdb::calc::{chi, chi_nucleic, count_chi_angles, nucleic_torsions, omega, protein_torsions, sugar_pucker};
+
+#[test]
+fn protein_dihedrals() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2gb1.pdb")?;
+    let strctr = deposit.structure()?;
+    let torsions = protein_torsions(&strctr);
+    assert_eq!(torsions.len(), 56);
+    // --- the first residue has no phi, the last one neither psi nor omega
+    assert!(torsions[0].phi.is_none());
+    assert!(torsions[55].psi.is_none());
+    assert!(torsions[55].omega.is_none());
+    for row in &torsions {
+        // --- all peptide bonds in 2GB1 are trans
+        if let Some(w) = row.omega { assert!(w.to_degrees().abs() > 150.0); }
+        // --- number of chi angles computed must agree with the residue type
+        let n_chi = row.chi.iter().filter(|c| c.is_some()).count();
+        assert_eq!(n_chi, count_chi_angles(&row.res_name));
+    }
+    let res_id = ResidueId::new("A", 9, ' ');
+    assert!(omega(&strctr, &res_id).is_ok());
+    // --- GLY 9 has no side chain
+    assert!(chi(&strctr, &res_id, 1).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn nucleic_dihedrals() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/5edw.cif")?;
+    let strctr = deposit.structure()?;
+    let torsions = nucleic_torsions(&strctr);
+    assert_eq!(torsions.len(), 32);
+    // --- 5'-terminal nucleotide has no alpha, 3'-terminal has no epsilon and zeta
+    assert!(torsions[0].alpha.is_none());
+    assert!(torsions[18].epsilon.is_none());
+    assert!(torsions[18].zeta.is_none());
+
+    // --- B-DNA: anti conformation and C2'-endo sugar pucker
+    let res_id = ResidueId::new("T", 12, ' ');
+    let chi_val = chi_nucleic(&strctr, &res_id)?.to_degrees();
+    assert!(chi_val < -90.0 && chi_val > -150.0);
+    let (phase, amplitude) = sugar_pucker(&strctr, &res_id)?;
+    assert!((phase.to_degrees() - 150.9).abs() < 0.1);
+    assert!((amplitude.to_degrees() - 39.6).abs() < 0.1);
+
+    Ok(())
+}
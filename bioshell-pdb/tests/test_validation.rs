@@ -0,0 +1,53 @@
+use bioshell_pdb::{Deposit, PDBError, ResidueId};
+use bioshell_pdb::validation::{ramachandran_class, ramachandran_region, GeometryReport, RamachandranClass, RamachandranRegion, ValidationReport};
+
+#[test]
+fn ramachandran_classes() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let strctr = deposit.structure()?;
+    // --- MSE 1 is followed by PRO 2
+    assert_eq!(ramachandran_class(&strctr, &ResidueId::new("A", 1, ' '))?, RamachandranClass::PrePro);
+    assert_eq!(ramachandran_class(&strctr, &ResidueId::new("A", 2, ' '))?, RamachandranClass::Proline);
+    assert_eq!(ramachandran_class(&strctr, &ResidueId::new("A", 11, ' '))?, RamachandranClass::General);
+
+    Ok(())
+}
+
+#[test]
+fn ramachandran_regions() {
+    // --- beta strand is favored for any class but proline
+    assert_eq!(ramachandran_region(RamachandranClass::General, -120.0, 130.0), RamachandranRegion::Favored);
+    assert_eq!(ramachandran_region(RamachandranClass::PrePro, -120.0, 130.0), RamachandranRegion::Favored);
+    assert_eq!(ramachandran_region(RamachandranClass::Proline, -120.0, 130.0), RamachandranRegion::Outlier);
+    // --- the map is periodic
+    assert_eq!(ramachandran_region(RamachandranClass::Glycine, 175.0, -175.0), RamachandranRegion::Favored);
+    assert_eq!(ramachandran_region(RamachandranClass::General, -60.0, 100.0), RamachandranRegion::Allowed);
+}
+
+#[test]
+fn validation_report() -> Result<(), PDBError> {
+    let deposit = Deposit::from_file("./tests/test_files/2fdo.cif")?;
+    let strctr = deposit.structure()?;
+    let report = ValidationReport::new(&strctr);
+    assert!(report.ramachandran_favored() > 0.95);
+    assert!(report.ramachandran_outliers() < 0.01);
+    assert_eq!(report.count_cis_peptides(), 0);
+    assert_eq!(report.count_non_planar_peptides(), 0);
+    // --- the first residue of each chain has no preceding peptide bond
+    assert!(report.residues()[0].omega.is_none());
+    assert!(report.residues()[0].rama_region.is_none());
+
+    // --- NMR structure 2JQB is expected to score worse than the crystal structure 2FDO
+    let other = Deposit::from_file("./tests/test_files/2jqb.cif")?.structure()?;
+    let mut geometry = GeometryReport::new(&strctr);
+    assert!(ValidationReport::new(&other).quality_score(&GeometryReport::new(&other)) > report.quality_score(&geometry));
+
+    // --- clashes make the score worse
+    let score = report.quality_score(&geometry);
+    let had_clashes = !geometry.clashes.is_empty();
+    geometry.clashes.clear();
+    if had_clashes { assert!(score > report.quality_score(&geometry)); }
+    else { assert_eq!(score, report.quality_score(&geometry)); }
+
+    Ok(())
+}
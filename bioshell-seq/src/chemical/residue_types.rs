@@ -341,6 +341,29 @@ impl ResidueTypeManager {
         return true;
     }
 
+    /// Registers a residue type, replacing the previous definition of that monomer if it has been already registered.
+    ///
+    /// The replaced residue type keeps its index. This method should be used when a more reliable
+    /// definition of a residue type becomes available, e.g. a parent type of a modified amino acid
+    /// that has been registered earlier as a variant of ``UNK``.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bioshell_seq::chemical::{MonomerType, ResidueType, ResidueTypeManager, StandardResidueType};
+    /// let mut mgr = ResidueTypeManager::get();
+    /// mgr.register_residue_type(ResidueType::from_attrs("SEP", StandardResidueType::UNK, MonomerType::LPeptideLinking));
+    /// let idx = mgr.index(&String::from("SEP"));
+    /// mgr.update_residue_type(ResidueType::from_attrs("SEP", StandardResidueType::SER, MonomerType::LPeptideLinking));
+    /// assert_eq!(mgr.by_code3("SEP").unwrap().parent_type, StandardResidueType::SER);
+    /// assert_eq!(mgr.index(&String::from("SEP")), idx);
+    /// ```
+    pub fn update_residue_type(&mut self, res_type: ResidueType) {
+        match self.by_code_3.get(&res_type.code3) {
+            Some(&idx) => self.registered_types[idx] = res_type,
+            None => { self.register_residue_type(res_type); }
+        }
+    }
+
     /// Provides a monomer for a given three-letter code.
     ///
     /// Returns an option that contains the monomer or ``None`` if it hasn't been registered